@@ -3,7 +3,7 @@ use criterion::{
 };
 
 fn drillx_loop(nonces: u64) {
-    let mut memory = drillx::equix::SolverMemory::new();
+    let mut memory = drillx::DrillxMemory::new();
     let challenge = [255; 32];
     for nonce in 0..nonces {
         drillx::hash_with_memory(&mut memory, &challenge, &nonce.to_le_bytes()).ok();
@@ -21,5 +21,50 @@ fn different_sizes(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, different_sizes);
+fn interleave(c: &mut Criterion) {
+    let mut group = c.benchmark_group("interleave");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(100));
+    for interleave in [1, 2].iter() {
+        let mut context =
+            drillx::Context::interleaved(drillx::RuntimeOption::TryCompile, *interleave);
+        group.bench_with_input(
+            BenchmarkId::from_parameter(interleave),
+            interleave,
+            |b, _| {
+                b.iter(|| {
+                    for nonce in 0..100u64 {
+                        context.hash(&[255; 32], &nonce.to_le_bytes()).ok();
+                    }
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+fn early_exit(c: &mut Criterion) {
+    let mut group = c.benchmark_group("early_exit");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(100));
+    let challenge = [255; 32];
+    let mut memory = drillx::DrillxMemory::new();
+    group.bench_function("hash", |b| {
+        b.iter(|| {
+            for nonce in 0..100u64 {
+                drillx::hash_with_memory(&mut memory, &challenge, &nonce.to_le_bytes()).ok();
+            }
+        })
+    });
+    group.bench_function("hash_first", |b| {
+        b.iter(|| {
+            for nonce in 0..100u64 {
+                drillx::hash_first_with_memory(&mut memory, &challenge, &nonce.to_le_bytes()).ok();
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, different_sizes, interleave, early_exit);
 criterion_main!(benches);
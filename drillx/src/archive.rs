@@ -0,0 +1,259 @@
+//! Compact encoding for large batches of solutions.
+//!
+//! The format is a header followed by one record per solution:
+//!
+//! ```text
+//! header = version (u8) ‖ flags (u8) ‖ count (varint)
+//! record = nonce delta (varint) ‖ digest (16 bytes)
+//! ```
+//!
+//! Varints are unsigned LEB128 of at most 10 bytes. Nonces are read as little-endian
+//! `u64`s, and each record stores its nonce relative to the previous one, starting
+//! from zero. With [`FLAG_SORTED`] set the records are in nonce order and each delta is
+//! the plain difference; otherwise the records are in their original order and each
+//! delta is the wrapping difference, zigzag encoded. Near-sequential nonces take a
+//! single byte either way.
+//!
+//! Decoders reject a declared count that the remaining input could not hold, so they
+//! never allocate more than the input justifies.
+
+use crate::Solution;
+
+/// The only archive version so far.
+pub const VERSION: u8 = 1;
+
+/// Set when records are sorted by nonce.
+pub const FLAG_SORTED: u8 = 0b1;
+
+/// The smallest possible record: a one-byte delta and a digest.
+const MIN_RECORD_LEN: usize = 17;
+
+/// Longest varint encoding of a `u64`.
+const MAX_VARINT_LEN: usize = 10;
+
+/// Encodes solutions in their original order.
+pub fn encode(solutions: &[Solution]) -> Vec<u8> {
+    encode_records(solutions.iter(), 0)
+}
+
+/// Encodes solutions sorted by nonce, which is usually smaller than [`encode`].
+pub fn encode_sorted(solutions: &[Solution]) -> Vec<u8> {
+    let mut sorted: Vec<&Solution> = solutions.iter().collect();
+    sorted.sort_by_key(|s| u64::from_le_bytes(s.n));
+    encode_records(sorted.into_iter(), FLAG_SORTED)
+}
+
+fn encode_records<'a>(
+    solutions: impl ExactSizeIterator<Item = &'a Solution>,
+    flags: u8,
+) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(2 + MAX_VARINT_LEN + solutions.len() * MIN_RECORD_LEN);
+    bytes.push(VERSION);
+    bytes.push(flags);
+    write_varint(&mut bytes, solutions.len() as u64);
+    let mut previous = 0u64;
+    for solution in solutions {
+        let nonce = u64::from_le_bytes(solution.n);
+        let delta = nonce.wrapping_sub(previous);
+        if flags & FLAG_SORTED != 0 {
+            write_varint(&mut bytes, delta);
+        } else {
+            write_varint(&mut bytes, zigzag(delta as i64));
+        }
+        bytes.extend_from_slice(&solution.d);
+        previous = nonce;
+    }
+    bytes
+}
+
+/// Decodes an archive into a vector of solutions.
+pub fn decode(bytes: &[u8]) -> Result<Vec<Solution>, ArchiveError> {
+    let iter = iter_decode(bytes)?;
+    let mut solutions = Vec::with_capacity(iter.remaining);
+    for solution in iter {
+        solutions.push(solution?);
+    }
+    Ok(solutions)
+}
+
+/// Decodes an archive one solution at a time.
+///
+/// The header is checked up front. Once a record fails to decode, the iterator yields
+/// that error and then stops.
+pub fn iter_decode(bytes: &[u8]) -> Result<ArchiveIter<'_>, ArchiveError> {
+    let mut reader = Reader { bytes };
+    let version = reader.byte()?;
+    if version != VERSION {
+        return Err(ArchiveError::UnknownVersion(version));
+    }
+    let flags = reader.byte()?;
+    if flags & !FLAG_SORTED != 0 {
+        return Err(ArchiveError::UnknownFlags(flags));
+    }
+    let count = reader.varint()?;
+    let max = (reader.bytes.len() / MIN_RECORD_LEN) as u64;
+    if count > max {
+        return Err(ArchiveError::CountTooLarge { count, max });
+    }
+    Ok(ArchiveIter {
+        reader,
+        sorted: flags & FLAG_SORTED != 0,
+        remaining: count as usize,
+        previous: 0,
+        failed: false,
+    })
+}
+
+/// A streaming archive decoder. See [`iter_decode`].
+pub struct ArchiveIter<'a> {
+    reader: Reader<'a>,
+    sorted: bool,
+    remaining: usize,
+    previous: u64,
+    failed: bool,
+}
+
+impl ArchiveIter<'_> {
+    /// Returns true if records are sorted by nonce.
+    pub fn is_sorted_by_nonce(&self) -> bool {
+        self.sorted
+    }
+
+    fn record(&mut self) -> Result<Solution, ArchiveError> {
+        let delta = self.reader.varint()?;
+        let nonce = if self.sorted {
+            self.previous
+                .checked_add(delta)
+                .ok_or(ArchiveError::NonceOverflow)?
+        } else {
+            self.previous.wrapping_add(unzigzag(delta) as u64)
+        };
+        let digest = self.reader.take::<16>()?;
+        self.previous = nonce;
+        Ok(Solution::new(digest, nonce.to_le_bytes()))
+    }
+}
+
+impl Iterator for ArchiveIter<'_> {
+    type Item = Result<Solution, ArchiveError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let result = if self.remaining > 0 {
+            self.remaining -= 1;
+            self.record()
+        } else if !self.reader.bytes.is_empty() {
+            Err(ArchiveError::TrailingBytes(self.reader.bytes.len()))
+        } else {
+            return None;
+        };
+        self.failed = result.is_err();
+        Some(result)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.remaining + 1))
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, ArchiveError> {
+        let (&byte, rest) = self.bytes.split_first().ok_or(ArchiveError::Truncated)?;
+        self.bytes = rest;
+        Ok(byte)
+    }
+
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], ArchiveError> {
+        if self.bytes.len() < N {
+            return Err(ArchiveError::Truncated);
+        }
+        let (head, rest) = self.bytes.split_at(N);
+        self.bytes = rest;
+        Ok(head.try_into().unwrap())
+    }
+
+    fn varint(&mut self) -> Result<u64, ArchiveError> {
+        let mut value = 0u64;
+        for i in 0..MAX_VARINT_LEN {
+            let byte = self.byte()?;
+            let bits = (byte & 0x7f) as u64;
+            if i == MAX_VARINT_LEN - 1 && bits > 1 {
+                return Err(ArchiveError::VarintOverflow);
+            }
+            value |= bits << (7 * i);
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(ArchiveError::VarintOverflow)
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    (value >> 1) as i64 ^ -((value & 1) as i64)
+}
+
+/// An error decoding an archive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ArchiveError {
+    /// The input ended in the middle of the header or a record.
+    Truncated,
+    /// The version byte is not [`VERSION`].
+    UnknownVersion(u8),
+    /// The flags byte has bits other than [`FLAG_SORTED`] set.
+    UnknownFlags(u8),
+    /// A varint does not fit in a `u64`.
+    VarintOverflow,
+    /// The declared count is more than the remaining input could hold.
+    CountTooLarge { count: u64, max: u64 },
+    /// A sorted archive's nonces run past `u64::MAX`.
+    NonceOverflow,
+    /// Bytes remain after the declared number of records.
+    TrailingBytes(usize),
+}
+
+impl std::fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            ArchiveError::Truncated => write!(f, "Truncated archive"),
+            ArchiveError::UnknownVersion(version) => {
+                write!(f, "Unknown archive version {}", version)
+            }
+            ArchiveError::UnknownFlags(flags) => write!(f, "Unknown archive flags {:#04x}", flags),
+            ArchiveError::VarintOverflow => write!(f, "Varint overflows u64"),
+            ArchiveError::CountTooLarge { count, max } => {
+                write!(
+                    f,
+                    "Declared count {} exceeds the {} records the input can hold",
+                    count, max
+                )
+            }
+            ArchiveError::NonceOverflow => write!(f, "Sorted nonces overflow u64"),
+            ArchiveError::TrailingBytes(len) => write!(f, "{} trailing bytes after records", len),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
@@ -0,0 +1,354 @@
+//! Share records as Arrow record batches and Parquet files.
+//!
+//! Every batch has the [`schema`] below, which is stable: columns are only ever added
+//! at the end, and [`SCHEMA_VERSION`] in the schema metadata under [`VERSION_KEY`]
+//! changes when they are. No column is nullable.
+//!
+//! | Column       | Arrow type                    | Parquet type                     |
+//! |--------------|-------------------------------|----------------------------------|
+//! | `timestamp`  | `Timestamp(Microsecond, UTC)` | `INT64 (TIMESTAMP(MICROS,true))` |
+//! | `challenge`  | `FixedSizeBinary(32)`         | `FIXED_LEN_BYTE_ARRAY (32)`      |
+//! | `nonce`      | `FixedSizeBinary(8)`          | `FIXED_LEN_BYTE_ARRAY (8)`       |
+//! | `digest`     | `FixedSizeBinary(16)`         | `FIXED_LEN_BYTE_ARRAY (16)`      |
+//! | `difficulty` | `UInt32`                      | `INT32 (INTEGER(32,false))`      |
+//! | `accepted`   | `Boolean`                     | `BOOLEAN`                        |
+//! | `miner_id`   | `Utf8`                        | `BYTE_ARRAY (STRING)`            |
+//!
+//! The byte columns hold the same bytes as [`Solution`](crate::Solution), so a row's
+//! `nonce` and `digest` verify against its `challenge` with
+//! [`is_valid_digest`](crate::is_valid_digest). Parquet files are Snappy compressed.
+//!
+//! Reading accepts batches written elsewhere as long as the columns above are present
+//! with their types, except that the byte columns may also be variable-length
+//! `Binary`, as Spark writes them. Every byte value is checked to have the column's
+//! length, and extra columns are ignored.
+
+use std::{collections::HashMap, fs::File, io::Write, path::Path, sync::Arc};
+
+use arrow_array::{
+    builder::{
+        ArrayBuilder, BooleanBuilder, FixedSizeBinaryBuilder, StringBuilder,
+        TimestampMicrosecondBuilder, UInt32Builder,
+    },
+    Array, ArrayRef, BinaryArray, BooleanArray, FixedSizeBinaryArray, StringArray,
+    TimestampMicrosecondArray, UInt32Array,
+};
+use arrow_schema::{ArrowError, DataType, Field, Schema, SchemaRef, TimeUnit};
+use parquet::{
+    arrow::{arrow_reader::ParquetRecordBatchReaderBuilder, ArrowWriter},
+    basic::Compression,
+    errors::ParquetError,
+    file::properties::WriterProperties,
+};
+
+pub use arrow_array::RecordBatch;
+pub use parquet::file::reader::ChunkReader;
+
+/// Schema metadata key holding [`SCHEMA_VERSION`].
+pub const VERSION_KEY: &str = "drillx.share_record.version";
+
+/// The version of the share record schema.
+pub const SCHEMA_VERSION: &str = "1";
+
+/// A share and what the pool made of it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShareRecord {
+    /// Microseconds since the Unix epoch, UTC.
+    pub timestamp: i64,
+    pub challenge: [u8; 32],
+    pub nonce: [u8; 8],
+    pub digest: [u8; 16],
+    pub difficulty: u32,
+    pub accepted: bool,
+    pub miner_id: String,
+}
+
+/// The schema of every share record batch.
+pub fn schema() -> SchemaRef {
+    let fields = vec![
+        Field::new(
+            "timestamp",
+            DataType::Timestamp(TimeUnit::Microsecond, Some("UTC".into())),
+            false,
+        ),
+        Field::new("challenge", DataType::FixedSizeBinary(32), false),
+        Field::new("nonce", DataType::FixedSizeBinary(8), false),
+        Field::new("digest", DataType::FixedSizeBinary(16), false),
+        Field::new("difficulty", DataType::UInt32, false),
+        Field::new("accepted", DataType::Boolean, false),
+        Field::new("miner_id", DataType::Utf8, false),
+    ];
+    let metadata = HashMap::from([(VERSION_KEY.to_string(), SCHEMA_VERSION.to_string())]);
+    Arc::new(Schema::new_with_metadata(fields, metadata))
+}
+
+/// Accumulates share records into Arrow arrays.
+pub struct ShareRecordBatchBuilder {
+    timestamp: TimestampMicrosecondBuilder,
+    challenge: FixedSizeBinaryBuilder,
+    nonce: FixedSizeBinaryBuilder,
+    digest: FixedSizeBinaryBuilder,
+    difficulty: UInt32Builder,
+    accepted: BooleanBuilder,
+    miner_id: StringBuilder,
+}
+
+impl ShareRecordBatchBuilder {
+    pub fn new() -> Self {
+        Self::with_capacity(1024)
+    }
+
+    /// Creates a builder with room for `capacity` records.
+    pub fn with_capacity(capacity: usize) -> Self {
+        ShareRecordBatchBuilder {
+            timestamp: TimestampMicrosecondBuilder::with_capacity(capacity).with_timezone("UTC"),
+            challenge: FixedSizeBinaryBuilder::with_capacity(capacity, 32),
+            nonce: FixedSizeBinaryBuilder::with_capacity(capacity, 8),
+            digest: FixedSizeBinaryBuilder::with_capacity(capacity, 16),
+            difficulty: UInt32Builder::with_capacity(capacity),
+            accepted: BooleanBuilder::with_capacity(capacity),
+            miner_id: StringBuilder::with_capacity(capacity, 16 * capacity),
+        }
+    }
+
+    pub fn append(&mut self, record: &ShareRecord) {
+        self.timestamp.append_value(record.timestamp);
+        // The widths match the arrays, so these cannot fail.
+        self.challenge.append_value(record.challenge).unwrap();
+        self.nonce.append_value(record.nonce).unwrap();
+        self.digest.append_value(record.digest).unwrap();
+        self.difficulty.append_value(record.difficulty);
+        self.accepted.append_value(record.accepted);
+        self.miner_id.append_value(&record.miner_id);
+    }
+
+    /// Number of records appended since the last [`finish`](Self::finish).
+    pub fn len(&self) -> usize {
+        self.difficulty.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the records appended so far as a batch, and empties the builder.
+    pub fn finish(&mut self) -> RecordBatch {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(self.timestamp.finish()),
+            Arc::new(self.challenge.finish()),
+            Arc::new(self.nonce.finish()),
+            Arc::new(self.digest.finish()),
+            Arc::new(self.difficulty.finish()),
+            Arc::new(self.accepted.finish()),
+            Arc::new(self.miner_id.finish()),
+        ];
+        // The columns have the schema's types and equal lengths.
+        RecordBatch::try_new(schema(), columns).unwrap()
+    }
+}
+
+impl Default for ShareRecordBatchBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Extend<ShareRecord> for ShareRecordBatchBuilder {
+    fn extend<I: IntoIterator<Item = ShareRecord>>(&mut self, records: I) {
+        for record in records {
+            self.append(&record);
+        }
+    }
+}
+
+/// Writes batches as one Parquet file, returning the writer once the footer is written.
+pub fn write_parquet<W: Write + Send>(
+    writer: W,
+    batches: &[RecordBatch],
+) -> Result<W, ShareRecordError> {
+    let properties = WriterProperties::builder()
+        .set_compression(Compression::SNAPPY)
+        .build();
+    let mut writer = ArrowWriter::try_new(writer, schema(), Some(properties))?;
+    for batch in batches {
+        writer.write(batch)?;
+    }
+    Ok(writer.into_inner()?)
+}
+
+/// Writes batches as a Parquet file at `path`, replacing any file there.
+pub fn write_parquet_file(
+    path: impl AsRef<Path>,
+    batches: &[RecordBatch],
+) -> Result<(), ShareRecordError> {
+    let file = File::create(path).map_err(ParquetError::from)?;
+    write_parquet(file, batches)?
+        .sync_all()
+        .map_err(ParquetError::from)?;
+    Ok(())
+}
+
+/// Reads every record of a Parquet file.
+pub fn read_parquet<R: ChunkReader + 'static>(
+    reader: R,
+) -> Result<Vec<ShareRecord>, ShareRecordError> {
+    let mut records = Vec::new();
+    for batch in ParquetRecordBatchReaderBuilder::try_new(reader)?.build()? {
+        records.extend(records_from_batch(&batch?)?);
+    }
+    Ok(records)
+}
+
+/// Reads every record of the Parquet file at `path`.
+pub fn read_parquet_file(path: impl AsRef<Path>) -> Result<Vec<ShareRecord>, ShareRecordError> {
+    read_parquet(File::open(path).map_err(ParquetError::from)?)
+}
+
+/// Converts a batch back into records, checking each column's type and each value's
+/// length.
+pub fn records_from_batch(batch: &RecordBatch) -> Result<Vec<ShareRecord>, ShareRecordError> {
+    let timestamp = column::<TimestampMicrosecondArray>(batch, "timestamp")?;
+    let challenge = bytes::<32>(batch, "challenge")?;
+    let nonce = bytes::<8>(batch, "nonce")?;
+    let digest = bytes::<16>(batch, "digest")?;
+    let difficulty = column::<UInt32Array>(batch, "difficulty")?;
+    let accepted = column::<BooleanArray>(batch, "accepted")?;
+    let miner_id = column::<StringArray>(batch, "miner_id")?;
+    Ok((0..batch.num_rows())
+        .map(|row| ShareRecord {
+            timestamp: timestamp.value(row),
+            challenge: challenge[row],
+            nonce: nonce[row],
+            digest: digest[row],
+            difficulty: difficulty.value(row),
+            accepted: accepted.value(row),
+            miner_id: miner_id.value(row).to_string(),
+        })
+        .collect())
+}
+
+/// A column of the given array type, without nulls.
+fn column<'a, A: Array + 'static>(
+    batch: &'a RecordBatch,
+    name: &'static str,
+) -> Result<&'a A, ShareRecordError> {
+    let array = batch
+        .column_by_name(name)
+        .ok_or(ShareRecordError::MissingColumn(name))?;
+    let typed = array
+        .as_any()
+        .downcast_ref::<A>()
+        .ok_or_else(|| ShareRecordError::WrongType {
+            column: name,
+            actual: array.data_type().clone(),
+        })?;
+    if let Some(row) = (0..array.len()).find(|&row| array.is_null(row)) {
+        return Err(ShareRecordError::Null { column: name, row });
+    }
+    Ok(typed)
+}
+
+/// A byte column's values, each checked to be `N` bytes long.
+fn bytes<const N: usize>(
+    batch: &RecordBatch,
+    name: &'static str,
+) -> Result<Vec<[u8; N]>, ShareRecordError> {
+    let array = batch
+        .column_by_name(name)
+        .ok_or(ShareRecordError::MissingColumn(name))?;
+    let values: Vec<&[u8]> = match array.data_type() {
+        DataType::FixedSizeBinary(_) => {
+            let array = column::<FixedSizeBinaryArray>(batch, name)?;
+            (0..array.len()).map(|row| array.value(row)).collect()
+        }
+        _ => {
+            let array = column::<BinaryArray>(batch, name)?;
+            (0..array.len()).map(|row| array.value(row)).collect()
+        }
+    };
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(row, bytes)| {
+            bytes.try_into().map_err(|_| ShareRecordError::WrongLength {
+                column: name,
+                row,
+                expected: N,
+                actual: bytes.len(),
+            })
+        })
+        .collect()
+}
+
+/// Why share records could not be written or read.
+#[derive(Debug)]
+pub enum ShareRecordError {
+    /// A column of the schema is missing.
+    MissingColumn(&'static str),
+    /// A column does not have the schema's type.
+    WrongType {
+        column: &'static str,
+        actual: DataType,
+    },
+    /// A column holds a null.
+    Null { column: &'static str, row: usize },
+    /// A byte value has the wrong length.
+    WrongLength {
+        column: &'static str,
+        row: usize,
+        expected: usize,
+        actual: usize,
+    },
+    /// Arrow failed, such as when decoding a Parquet page.
+    Arrow(ArrowError),
+    /// The Parquet file or the underlying reader or writer failed.
+    Parquet(ParquetError),
+}
+
+impl From<ArrowError> for ShareRecordError {
+    fn from(err: ArrowError) -> Self {
+        ShareRecordError::Arrow(err)
+    }
+}
+
+impl From<ParquetError> for ShareRecordError {
+    fn from(err: ParquetError) -> Self {
+        ShareRecordError::Parquet(err)
+    }
+}
+
+impl std::fmt::Display for ShareRecordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ShareRecordError::MissingColumn(column) => write!(f, "Missing column {}", column),
+            ShareRecordError::WrongType { column, actual } => {
+                write!(f, "Column {} has unexpected type {}", column, actual)
+            }
+            ShareRecordError::Null { column, row } => {
+                write!(f, "Column {} is null at row {}", column, row)
+            }
+            ShareRecordError::WrongLength {
+                column,
+                row,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Column {} at row {} has {} bytes, expected {}",
+                column, row, actual, expected
+            ),
+            ShareRecordError::Arrow(err) => write!(f, "Arrow failed: {}", err),
+            ShareRecordError::Parquet(err) => write!(f, "Parquet failed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ShareRecordError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ShareRecordError::Arrow(err) => Some(err),
+            ShareRecordError::Parquet(err) => Some(err),
+            _ => None,
+        }
+    }
+}
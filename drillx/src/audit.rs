@@ -0,0 +1,205 @@
+//! Statistical checks of a solver's output.
+//!
+//! A broken kernel or port often skews the distribution of hashes long before it
+//! produces an invalid digest. [`difficulty_distribution_test`] compares difficulties
+//! against the geometric distribution honest hashes follow, where difficulty `d` has
+//! probability `2^-(d+1)`, and [`uniformity_test`] checks that every byte value of the
+//! final hashes is equally likely. Both are chi-square goodness-of-fit tests.
+
+/// Significance the tests use unless the verdict is re-evaluated with
+/// [`AuditVerdict::with_significance`].
+pub const DEFAULT_SIGNIFICANCE: f64 = 0.001;
+
+/// Smallest expected count of a bin for the chi-square approximation to hold.
+const MIN_EXPECTED: f64 = 5.0;
+
+/// The result of a goodness-of-fit test.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct AuditVerdict {
+    /// Number of values tested.
+    pub samples: usize,
+    /// The chi-square statistic.
+    pub statistic: f64,
+    pub degrees_of_freedom: u32,
+    /// Probability of a statistic at least this large from an honest source.
+    pub p_value: f64,
+    pub significance: f64,
+    /// True unless `p_value` is below `significance`.
+    pub passed: bool,
+}
+
+impl AuditVerdict {
+    fn new(samples: usize, statistic: f64, degrees_of_freedom: u32) -> Self {
+        AuditVerdict {
+            samples,
+            statistic,
+            degrees_of_freedom,
+            p_value: chi_square_p_value(statistic, degrees_of_freedom),
+            significance: DEFAULT_SIGNIFICANCE,
+            passed: true,
+        }
+        .with_significance(DEFAULT_SIGNIFICANCE)
+    }
+
+    /// Re-evaluates the verdict at another significance level.
+    pub fn with_significance(mut self, significance: f64) -> Self {
+        self.significance = significance;
+        self.passed = self.p_value >= significance;
+        self
+    }
+}
+
+/// Tests difficulties against the geometric distribution of honest hashes.
+///
+/// Difficulties are binned one per value, with the tail pooled into a last bin, so
+/// that every bin expects at least five samples. Fewer than 10 samples leave a single
+/// bin, and the test passes with no degrees of freedom.
+pub fn difficulty_distribution_test(samples: &[u32]) -> AuditVerdict {
+    let n = samples.len() as f64;
+    // Bins 0..tail hold one difficulty each and the last holds the rest, which
+    // expects `n * 2^-tail` samples.
+    let mut tail = 0;
+    while tail < 256 && n * 0.5f64.powi(tail as i32 + 1) >= MIN_EXPECTED {
+        tail += 1;
+    }
+    let mut observed = vec![0u64; tail + 1];
+    for &difficulty in samples {
+        observed[(difficulty as usize).min(tail)] += 1;
+    }
+    let statistic: f64 = observed
+        .iter()
+        .enumerate()
+        .map(|(d, &count)| {
+            let p = 0.5f64.powi(d as i32 + if d < tail { 1 } else { 0 });
+            chi_square_term(count, n * p)
+        })
+        .sum();
+    AuditVerdict::new(samples.len(), statistic, tail as u32)
+}
+
+/// Tests that every byte value is equally likely across the hashes.
+///
+/// This needs at least 40 hashes for every value to expect five occurrences; with
+/// fewer the test passes with no degrees of freedom.
+pub fn uniformity_test(hashes: &[[u8; 32]]) -> AuditVerdict {
+    let mut observed = [0u64; 256];
+    for byte in hashes.iter().flatten() {
+        observed[*byte as usize] += 1;
+    }
+    let expected = (hashes.len() * 32) as f64 / 256.0;
+    if expected < MIN_EXPECTED {
+        return AuditVerdict::new(hashes.len(), 0.0, 0);
+    }
+    let statistic = observed
+        .iter()
+        .map(|&count| chi_square_term(count, expected))
+        .sum();
+    AuditVerdict::new(hashes.len(), statistic, 255)
+}
+
+/// Hashes nonces from zero with the solver until `n` of them have solutions, and
+/// returns their difficulties.
+///
+/// Nonces without solutions are skipped. Any other solver error ends the run early,
+/// returning the samples so far.
+#[cfg(feature = "solve")]
+pub fn collect_samples(
+    challenge: &[u8; 32],
+    n: usize,
+    solver: &mut impl crate::Solver,
+) -> Vec<u32> {
+    let mut samples = Vec::with_capacity(n);
+    let mut nonce = 0u64;
+    while samples.len() < n {
+        let nonce_bytes = nonce.to_le_bytes();
+        let seed = crate::seed(challenge, &nonce_bytes);
+        match solver.solve(&seed.data, crate::RuntimeOption::TryCompile) {
+            Ok(digest) => samples.push(
+                crate::Solution::new(digest, nonce_bytes)
+                    .to_hash()
+                    .difficulty(),
+            ),
+            Err(crate::DrillxError::NoSolutions) => {}
+            Err(_) => break,
+        }
+        nonce += 1;
+    }
+    samples
+}
+
+fn chi_square_term(observed: u64, expected: f64) -> f64 {
+    let diff = observed as f64 - expected;
+    diff * diff / expected
+}
+
+/// Upper tail probability of the chi-square distribution.
+fn chi_square_p_value(statistic: f64, degrees_of_freedom: u32) -> f64 {
+    if degrees_of_freedom == 0 {
+        return 1.0;
+    }
+    upper_gamma(degrees_of_freedom as f64 / 2.0, statistic / 2.0)
+}
+
+/// The regularized upper incomplete gamma function `Q(a, x)`.
+fn upper_gamma(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+    let prefix = (a * x.ln() - x - ln_gamma(a)).exp();
+    if x < a + 1.0 {
+        // Series for the lower function, `P = 1 - Q`.
+        let (mut term, mut sum, mut k) = (1.0 / a, 1.0 / a, a);
+        while term > sum * f64::EPSILON {
+            k += 1.0;
+            term *= x / k;
+            sum += term;
+        }
+        (1.0 - prefix * sum).max(0.0)
+    } else {
+        // Continued fraction, evaluated with Lentz's method.
+        let tiny = f64::MIN_POSITIVE / f64::EPSILON;
+        let mut b = x + 1.0 - a;
+        let mut c = 1.0 / tiny;
+        let mut d = 1.0 / b;
+        let mut h = d;
+        for i in 1..1000 {
+            let an = -(i as f64) * (i as f64 - a);
+            b += 2.0;
+            d = an * d + b;
+            d = if d.abs() < tiny { tiny } else { d };
+            c = b + an / c;
+            c = if c.abs() < tiny { tiny } else { c };
+            d = 1.0 / d;
+            let delta = d * c;
+            h *= delta;
+            if (delta - 1.0).abs() < f64::EPSILON {
+                break;
+            }
+        }
+        prefix * h
+    }
+}
+
+/// The natural log of the gamma function, by the Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |sum, (i, c)| {
+            sum + c / (x + i as f64 + 1.0)
+        });
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
@@ -0,0 +1,197 @@
+//! Hashrate and energy benchmarks.
+//!
+//! Mining profitability is joules per hash as much as hashes per second. On Linux,
+//! [`benchmark`] reads the RAPL package energy counters under
+//! `/sys/class/powercap` around the run. They are usually readable only by root, and
+//! elsewhere they don't exist, in which case the report's energy is `None`.
+//!
+//! RAPL counts package energy, so the figure includes anything else running on the
+//! machine during the benchmark.
+
+use std::{
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use crate::Context;
+
+/// Where Linux exposes the RAPL counters.
+pub const POWERCAP_ROOT: &str = "/sys/class/powercap";
+
+/// How often counters are read during a run, well within the time a counter takes
+/// to wrap even on large parts.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(100);
+
+/// The result of [`benchmark`].
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct BenchReport {
+    pub threads: usize,
+    pub hashes: u64,
+    pub elapsed: Duration,
+    /// Hashes per second.
+    pub hashrate: f64,
+    /// Package energy over the run, if the RAPL counters could be read.
+    pub energy: Option<EnergyReport>,
+}
+
+/// Energy used during a benchmark.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct EnergyReport {
+    /// Number of CPU packages measured.
+    pub packages: usize,
+    /// Total package energy in joules.
+    pub joules: f64,
+    pub joules_per_hash: f64,
+    /// Average power in watts.
+    pub watts: f64,
+}
+
+/// Hashes on `threads` threads for `duration` and reports the hashrate, with the
+/// energy used where RAPL counters are readable.
+///
+/// Each thread hashes at least once, so the run overruns `duration` by up to one hash.
+pub fn benchmark(threads: usize, duration: Duration) -> BenchReport {
+    let threads = threads.max(1);
+    let mut contexts: Vec<Context> = (0..threads).map(|_| Context::default()).collect();
+    let mut meter = EnergyMeter::open();
+    let trial = thread::scope(|scope| {
+        let trial = scope.spawn(|| crate::tune::trial(&mut contexts, duration));
+        while !trial.is_finished() {
+            thread::sleep(SAMPLE_INTERVAL);
+            if let Some(meter) = &mut meter {
+                meter.sample();
+            }
+        }
+        trial.join().unwrap()
+    });
+    let energy = meter.and_then(|mut meter| {
+        if !meter.sample() {
+            return None;
+        }
+        let joules = meter.joules();
+        Some(EnergyReport {
+            packages: meter.packages(),
+            joules,
+            joules_per_hash: joules / trial.hashes as f64,
+            watts: joules / trial.elapsed.as_secs_f64(),
+        })
+    });
+    BenchReport {
+        threads: trial.threads,
+        hashes: trial.hashes,
+        elapsed: trial.elapsed,
+        hashrate: trial.hashrate,
+        energy,
+    }
+}
+
+/// Accumulates the energy of every RAPL package zone.
+///
+/// Each counter wraps to zero after its `max_energy_range_uj`, so [`sample`] must be
+/// called more often than the fastest counter wraps, which takes minutes at full load.
+///
+/// [`sample`]: EnergyMeter::sample
+#[derive(Debug)]
+pub struct EnergyMeter {
+    zones: Vec<Zone>,
+    microjoules: u64,
+}
+
+#[derive(Debug)]
+struct Zone {
+    energy: PathBuf,
+    max_range: u64,
+    last: u64,
+}
+
+impl EnergyMeter {
+    /// Opens the package zones under [`POWERCAP_ROOT`], or returns `None` if there are
+    /// none or any is unreadable.
+    pub fn open() -> Option<Self> {
+        Self::open_at(Path::new(POWERCAP_ROOT))
+    }
+
+    /// Opens the package zones of a powercap tree rooted at `root`.
+    pub fn open_at(root: &Path) -> Option<Self> {
+        let mut dirs: Vec<PathBuf> = std::fs::read_dir(root)
+            .ok()?
+            .flatten()
+            .filter(|entry| entry.file_name().to_str().is_some_and(is_package_zone))
+            .map(|entry| entry.path())
+            .collect();
+        dirs.sort();
+        let zones: Option<Vec<Zone>> = dirs
+            .into_iter()
+            .map(|dir| {
+                let read = |file: &str| {
+                    std::fs::read_to_string(dir.join(file))
+                        .ok()
+                        .and_then(|contents| parse_energy_uj(&contents))
+                };
+                Some(Zone {
+                    max_range: read("max_energy_range_uj")?,
+                    last: read("energy_uj")?,
+                    energy: dir.join("energy_uj"),
+                })
+            })
+            .collect();
+        let zones = zones.filter(|zones| !zones.is_empty())?;
+        Some(EnergyMeter {
+            zones,
+            microjoules: 0,
+        })
+    }
+
+    /// Reads every counter and adds the energy since the last read, returning false if
+    /// any counter could not be read.
+    pub fn sample(&mut self) -> bool {
+        let mut ok = true;
+        for zone in &mut self.zones {
+            let now = std::fs::read_to_string(&zone.energy)
+                .ok()
+                .and_then(|contents| parse_energy_uj(&contents));
+            match now {
+                Some(now) => {
+                    self.microjoules += energy_delta_uj(zone.last, now, zone.max_range);
+                    zone.last = now;
+                }
+                None => ok = false,
+            }
+        }
+        ok
+    }
+
+    /// Energy accumulated since the meter was opened, in joules.
+    pub fn joules(&self) -> f64 {
+        self.microjoules as f64 / 1e6
+    }
+
+    pub fn packages(&self) -> usize {
+        self.zones.len()
+    }
+}
+
+/// Parses the contents of an `energy_uj` or `max_energy_range_uj` file.
+pub fn parse_energy_uj(contents: &str) -> Option<u64> {
+    contents.trim().parse().ok()
+}
+
+/// Microjoules between two readings of a counter that wraps to zero after
+/// `max_range`, assuming it wrapped at most once.
+pub fn energy_delta_uj(before: u64, after: u64, max_range: u64) -> u64 {
+    if after >= before {
+        after - before
+    } else {
+        max_range.saturating_sub(before) + after
+    }
+}
+
+/// Whether a powercap zone name is a whole package, such as `intel-rapl:0`, rather
+/// than a subzone such as `intel-rapl:0:1` or an MMIO duplicate of the package.
+///
+/// AMD parts use the same `intel-rapl` names.
+pub fn is_package_zone(name: &str) -> bool {
+    name.strip_prefix("intel-rapl:")
+        .is_some_and(|id| !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()))
+}
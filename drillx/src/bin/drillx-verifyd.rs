@@ -0,0 +1,68 @@
+//! An HTTP sidecar answering whether shares are valid, and their difficulties.
+//!
+//! ```text
+//! drillx-verifyd [--bind 127.0.0.1:8080] [--workers N] [--max-batch 4096]
+//! ```
+//!
+//! Once listening it prints `listening on <address>` to stdout, so a bind to port zero
+//! can be found. See [`drillx::serve`] for the endpoints.
+
+use std::process::ExitCode;
+
+use drillx::serve::{serve, ServeConfig};
+
+const USAGE: &str = "usage: drillx-verifyd [--bind ADDR] [--workers N] [--max-batch N]";
+
+fn main() -> ExitCode {
+    let config = match parse(std::env::args().skip(1)) {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("{}\n{}", err, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(config) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Parses the flags, or returns `None` for `--help`.
+fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<ServeConfig>, String> {
+    let mut config = ServeConfig::default();
+    while let Some(flag) = args.next() {
+        if flag == "--help" || flag == "-h" {
+            return Ok(None);
+        }
+        let value = args
+            .next()
+            .ok_or_else(|| format!("{} needs a value", flag))?;
+        let invalid = |_| format!("invalid {} {}", flag, value);
+        match flag.as_str() {
+            "--bind" => config.bind = value.parse().map_err(|_| invalid(()))?,
+            "--workers" => config.workers = value.parse().map_err(|_| invalid(()))?,
+            "--max-batch" => config.max_batch = value.parse().map_err(|_| invalid(()))?,
+            _ => return Err(format!("unknown flag {}", flag)),
+        }
+    }
+    if config.workers == 0 {
+        return Err("--workers must be at least 1".to_string());
+    }
+    Ok(Some(config))
+}
+
+fn run(config: ServeConfig) -> std::io::Result<()> {
+    let listener = std::net::TcpListener::bind(config.bind)?;
+    println!("listening on {}", listener.local_addr()?);
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(serve(listener, &config))
+}
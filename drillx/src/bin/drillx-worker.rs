@@ -0,0 +1,27 @@
+//! A mining worker for [`drillx::isolation::IsolatedMiner`].
+//!
+//! ```text
+//! drillx-worker __drillx-worker
+//! ```
+//!
+//! It reads its assignment from stdin and reports to stdout, as laid out in
+//! [`drillx::isolation`]. Point a miner at it with
+//! [`IsolatedMiner::worker_command`](drillx::isolation::IsolatedMiner::worker_command).
+
+use std::{io, process::ExitCode};
+
+use drillx::isolation::{run_worker, WORKER_ARG};
+
+fn main() -> ExitCode {
+    if std::env::args().nth(1).as_deref() != Some(WORKER_ARG) {
+        eprintln!("usage: drillx-worker {}", WORKER_ARG);
+        return ExitCode::FAILURE;
+    }
+    match run_worker(io::stdin().lock(), io::stdout().lock()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
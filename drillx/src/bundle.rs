@@ -0,0 +1,294 @@
+//! Several solutions to one challenge, submitted as one message.
+//!
+//! The encoding is a fixed header followed by the solutions in their raw 24-byte
+//! [`Solution::to_bytes`] format:
+//!
+//! ```text
+//! bundle = version (u8) ‖ challenge id (u64 LE) ‖ authority (32 bytes) ‖ count (u16 LE)
+//!          ‖ count × (digest ‖ nonce)
+//! ```
+//!
+//! A bundle holds at most [`MAX_BUNDLE_SOLUTIONS`] solutions, none sharing a nonce.
+//! Both rules hold for every bundle, however it was built or decoded, and decoders
+//! check the count against the bound and the input length before allocating.
+
+use std::collections::HashSet;
+
+use crate::{share_weight, Solution};
+
+/// The only bundle version so far.
+pub const VERSION: u8 = 1;
+
+/// Most solutions in a bundle.
+pub const MAX_BUNDLE_SOLUTIONS: usize = 256;
+
+/// Length of the header before the solutions.
+pub const HEADER_LEN: usize = 1 + 8 + 32 + 2;
+
+/// Length of each solution.
+const SOLUTION_LEN: usize = 24;
+
+/// Solutions to one challenge from one authority.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "RawBundle")]
+pub struct SubmissionBundle {
+    challenge_id: u64,
+    authority: [u8; 32],
+    solutions: Vec<Solution>,
+}
+
+/// A bundle as deserialized, before its invariants are checked.
+#[derive(serde::Deserialize)]
+struct RawBundle {
+    challenge_id: u64,
+    authority: [u8; 32],
+    #[serde(deserialize_with = "bounded")]
+    solutions: Vec<Solution>,
+}
+
+/// Deserializes at most [`MAX_BUNDLE_SOLUTIONS`] solutions, failing on the next one
+/// rather than collecting them all first.
+fn bounded<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<Solution>, D::Error> {
+    struct Visitor;
+
+    impl<'de> serde::de::Visitor<'de> for Visitor {
+        type Value = Vec<Solution>;
+
+        fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            write!(f, "at most {} solutions", MAX_BUNDLE_SOLUTIONS)
+        }
+
+        fn visit_seq<A: serde::de::SeqAccess<'de>>(
+            self,
+            mut seq: A,
+        ) -> Result<Self::Value, A::Error> {
+            let hint = seq.size_hint().unwrap_or(0).min(MAX_BUNDLE_SOLUTIONS);
+            let mut solutions = Vec::with_capacity(hint);
+            while let Some(solution) = seq.next_element()? {
+                if solutions.len() == MAX_BUNDLE_SOLUTIONS {
+                    return Err(serde::de::Error::custom(BundleError::TooMany {
+                        count: MAX_BUNDLE_SOLUTIONS + 1,
+                    }));
+                }
+                solutions.push(solution);
+            }
+            Ok(solutions)
+        }
+    }
+
+    deserializer.deserialize_seq(Visitor)
+}
+
+impl TryFrom<RawBundle> for SubmissionBundle {
+    type Error = BundleError;
+
+    fn try_from(raw: RawBundle) -> Result<Self, BundleError> {
+        SubmissionBundle::new(raw.challenge_id, raw.authority, raw.solutions)
+    }
+}
+
+/// A value computed over the valid solutions of a bundle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Scored<T> {
+    pub value: T,
+    /// Number of invalid solutions left out.
+    pub skipped: usize,
+}
+
+impl SubmissionBundle {
+    /// Bundles solutions, rejecting more than [`MAX_BUNDLE_SOLUTIONS`] or a repeated
+    /// nonce.
+    pub fn new(
+        challenge_id: u64,
+        authority: [u8; 32],
+        solutions: Vec<Solution>,
+    ) -> Result<Self, BundleError> {
+        if solutions.len() > MAX_BUNDLE_SOLUTIONS {
+            return Err(BundleError::TooMany {
+                count: solutions.len(),
+            });
+        }
+        let mut nonces = HashSet::with_capacity(solutions.len());
+        if let Some(duplicate) = solutions.iter().find(|s| !nonces.insert(s.n)) {
+            return Err(BundleError::DuplicateNonce(duplicate.n));
+        }
+        Ok(SubmissionBundle {
+            challenge_id,
+            authority,
+            solutions,
+        })
+    }
+
+    pub fn challenge_id(&self) -> u64 {
+        self.challenge_id
+    }
+
+    pub fn authority(&self) -> &[u8; 32] {
+        &self.authority
+    }
+
+    pub fn solutions(&self) -> &[Solution] {
+        &self.solutions
+    }
+
+    pub fn len(&self) -> usize {
+        self.solutions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.solutions.is_empty()
+    }
+
+    pub fn into_solutions(self) -> Vec<Solution> {
+        self.solutions
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + self.solutions.len() * SOLUTION_LEN);
+        bytes.push(VERSION);
+        bytes.extend_from_slice(&self.challenge_id.to_le_bytes());
+        bytes.extend_from_slice(&self.authority);
+        bytes.extend_from_slice(&(self.solutions.len() as u16).to_le_bytes());
+        for solution in &self.solutions {
+            bytes.extend_from_slice(&solution.to_bytes());
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BundleError> {
+        let (&version, _) = bytes.split_first().ok_or(BundleError::Truncated {
+            expected: HEADER_LEN,
+            actual: 0,
+        })?;
+        if version != VERSION {
+            return Err(BundleError::UnknownVersion(version));
+        }
+        let header: &[u8; HEADER_LEN] = bytes
+            .get(..HEADER_LEN)
+            .and_then(|header| header.try_into().ok())
+            .ok_or(BundleError::Truncated {
+                expected: HEADER_LEN,
+                actual: bytes.len(),
+            })?;
+        let challenge_id = u64::from_le_bytes(header[1..9].try_into().unwrap());
+        let authority: [u8; 32] = header[9..41].try_into().unwrap();
+        let count = u16::from_le_bytes(header[41..43].try_into().unwrap()) as usize;
+        if count > MAX_BUNDLE_SOLUTIONS {
+            return Err(BundleError::TooMany { count });
+        }
+        let expected = HEADER_LEN + count * SOLUTION_LEN;
+        if bytes.len() < expected {
+            return Err(BundleError::Truncated {
+                expected,
+                actual: bytes.len(),
+            });
+        }
+        if bytes.len() > expected {
+            return Err(BundleError::TrailingBytes {
+                expected,
+                actual: bytes.len(),
+            });
+        }
+        let solutions = bytes[HEADER_LEN..]
+            .chunks_exact(SOLUTION_LEN)
+            .map(|chunk| Solution::from_bytes(chunk.try_into().unwrap()))
+            .collect();
+        SubmissionBundle::new(challenge_id, authority, solutions)
+    }
+
+    /// Returns the highest difficulty among the solutions valid for the challenge, if
+    /// any is.
+    pub fn best_difficulty(&self, challenge: &[u8; 32]) -> Scored<Option<u32>> {
+        let difficulties = self.difficulties(challenge);
+        Scored {
+            value: difficulties.iter().flatten().copied().max(),
+            skipped: skipped(&difficulties),
+        }
+    }
+
+    /// Returns the summed [`share_weight`] of the solutions valid for the challenge,
+    /// saturating at `u128::MAX`.
+    pub fn total_weight(&self, challenge: &[u8; 32], min_difficulty: u32) -> Scored<u128> {
+        let difficulties = self.difficulties(challenge);
+        Scored {
+            value: difficulties.iter().flatten().fold(0u128, |total, &d| {
+                total.saturating_add(share_weight(d, min_difficulty))
+            }),
+            skipped: skipped(&difficulties),
+        }
+    }
+
+    /// Verifies and scores each solution, giving the difficulty of the valid ones. With
+    /// the `rayon` feature solutions are verified in parallel on rayon's global pool.
+    fn difficulties(&self, challenge: &[u8; 32]) -> Vec<Option<u32>> {
+        let score = |solution: &Solution| {
+            solution
+                .is_valid(challenge)
+                .then(|| solution.to_hash().difficulty())
+        };
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            self.solutions.par_iter().map(score).collect()
+        }
+        #[cfg(not(feature = "rayon"))]
+        {
+            self.solutions.iter().map(score).collect()
+        }
+    }
+}
+
+fn skipped(difficulties: &[Option<u32>]) -> usize {
+    difficulties.iter().filter(|d| d.is_none()).count()
+}
+
+/// An error building or decoding a bundle.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BundleError {
+    /// The input is shorter than its header or declared count requires.
+    Truncated { expected: usize, actual: usize },
+    /// The version byte is not [`VERSION`].
+    UnknownVersion(u8),
+    /// More than [`MAX_BUNDLE_SOLUTIONS`] solutions. A deserializer stops counting at
+    /// the first one too many.
+    TooMany { count: usize },
+    /// Two solutions share this nonce.
+    DuplicateNonce([u8; 8]),
+    /// Bytes remain after the declared number of solutions.
+    TrailingBytes { expected: usize, actual: usize },
+}
+
+impl std::fmt::Display for BundleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            BundleError::Truncated { expected, actual } => write!(
+                f,
+                "Truncated bundle: expected {} bytes, got {}",
+                expected, actual
+            ),
+            BundleError::UnknownVersion(version) => {
+                write!(f, "Unknown bundle version {}", version)
+            }
+            BundleError::TooMany { count } => write!(
+                f,
+                "Bundle of {} solutions exceeds {}",
+                count, MAX_BUNDLE_SOLUTIONS
+            ),
+            BundleError::DuplicateNonce(nonce) => {
+                write!(f, "Duplicate nonce {}", u64::from_le_bytes(nonce))
+            }
+            BundleError::TrailingBytes { expected, actual } => write!(
+                f,
+                "Bundle of {} bytes has {} trailing bytes",
+                expected,
+                actual - expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BundleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
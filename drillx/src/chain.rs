@@ -0,0 +1,215 @@
+//! Chained hashing: several drillx rounds per unit of work.
+//!
+//! **Not compatible with ORE.** Chained solutions are a separate scheme, for
+//! applications that want solving to cost more relative to verifying. Nothing outside
+//! this module accepts them.
+//!
+//! A chain of `K` rounds hashes the same nonce `K` times in sequence. Round 0 is mined
+//! against the challenge, and each later round against the previous round's final
+//! hash:
+//!
+//! ```text
+//! challenge[0]     = challenge
+//! (digest[i], h[i]) = drillx hash of (challenge[i], nonce)
+//! challenge[i + 1] = h[i]
+//! ```
+//!
+//! The chain's hash and difficulty are those of the last round. Solving takes `K`
+//! equix solves, and verifying `K` equix verifications, but the verifier only needs the
+//! `K` digests and the nonce. A chain of one round is exactly a [`Solution`] and its
+//! [`Hash`](crate::Hash).
+//!
+//! A [`ChainedSolution`] encodes as
+//!
+//! ```text
+//! rounds (u8) ‖ digest[0] ‖ … ‖ digest[rounds - 1] ‖ nonce (8 bytes)
+//! ```
+//!
+//! so a one-round solution is the byte 1 followed by [`Solution::to_bytes`].
+
+use crate::{difficulty, Solution};
+#[cfg(feature = "solve")]
+use crate::{hash_with_memory, DrillxError, DrillxMemory};
+
+/// The digests and final hash of a chain.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChainedHash {
+    /// One digest per round, in order.
+    pub digests: Vec<[u8; 16]>,
+    /// The last round's hash.
+    pub h: [u8; 32],
+}
+
+impl ChainedHash {
+    /// The leading number of zeros on the last round's hash.
+    pub fn difficulty(&self) -> u32 {
+        difficulty(self.h)
+    }
+}
+
+#[cfg(feature = "solve")]
+/// Hashes `rounds` chained rounds of the nonce. Zero rounds are treated as one.
+pub fn hash_chain(
+    challenge: &[u8; 32],
+    nonce: &[u8; 8],
+    rounds: u8,
+) -> Result<ChainedHash, DrillxError> {
+    hash_chain_with_memory(&mut DrillxMemory::new(), challenge, nonce, rounds)
+}
+
+#[cfg(feature = "solve")]
+/// Hashes `rounds` chained rounds of the nonce using pre-allocated memory. Zero rounds
+/// are treated as one.
+pub fn hash_chain_with_memory(
+    memory: &mut DrillxMemory,
+    challenge: &[u8; 32],
+    nonce: &[u8; 8],
+    rounds: u8,
+) -> Result<ChainedHash, DrillxError> {
+    let mut chained = ChainedHash {
+        digests: Vec::with_capacity(rounds.max(1) as usize),
+        h: *challenge,
+    };
+    for _ in 0..rounds.max(1) {
+        let hash = hash_with_memory(memory, &chained.h, nonce)?;
+        chained.digests.push(hash.d);
+        chained.h = hash.h;
+    }
+    Ok(chained)
+}
+
+/// A chain's digests and nonce, which verify on their own.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ChainedSolution {
+    pub digests: Vec<[u8; 16]>,
+    pub n: [u8; 8],
+    /// The number of rounds, which must equal the number of digests.
+    pub rounds: u8,
+}
+
+impl ChainedSolution {
+    /// Builds a solution from a chain's digests and its nonce.
+    ///
+    /// Panics if there are more than 255 digests.
+    pub fn new(digests: Vec<[u8; 16]>, nonce: [u8; 8]) -> Self {
+        let rounds = u8::try_from(digests.len()).expect("at most 255 rounds");
+        ChainedSolution {
+            digests,
+            n: nonce,
+            rounds,
+        }
+    }
+
+    /// Verifies every round, returning the chain's difficulty.
+    ///
+    /// Rounds are checked in order, and the first that fails is reported.
+    pub fn is_valid(&self, challenge: &[u8; 32]) -> Result<u32, ChainError> {
+        if self.rounds == 0 || self.digests.len() != self.rounds as usize {
+            return Err(ChainError::RoundCount {
+                rounds: self.rounds,
+                digests: self.digests.len(),
+            });
+        }
+        let mut challenge = *challenge;
+        for (round, digest) in self.digests.iter().enumerate() {
+            let solution = Solution::new(*digest, self.n);
+            if !solution.is_valid(&challenge) {
+                return Err(ChainError::InvalidRound(round as u8));
+            }
+            challenge = solution.to_hash().h;
+        }
+        Ok(difficulty(challenge))
+    }
+
+    /// The chain's digests and final hash, without verifying them.
+    pub fn to_hash(&self) -> ChainedHash {
+        let h = self.digests.iter().fold([0; 32], |_, digest| {
+            Solution::new(*digest, self.n).to_hash().h
+        });
+        ChainedHash {
+            digests: self.digests.clone(),
+            h,
+        }
+    }
+
+    /// Encodes the solution as described in the [module docs](self).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(encoded_len(self.digests.len()));
+        bytes.push(self.digests.len() as u8);
+        for digest in &self.digests {
+            bytes.extend_from_slice(digest);
+        }
+        bytes.extend_from_slice(&self.n);
+        bytes
+    }
+
+    /// Decodes a solution, which must take exactly the bytes its round count implies.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ChainError> {
+        let Some(&rounds) = bytes.first() else {
+            return Err(ChainError::Length {
+                expected: encoded_len(0),
+                actual: 0,
+            });
+        };
+        let expected = encoded_len(rounds as usize);
+        if bytes.len() != expected {
+            return Err(ChainError::Length {
+                expected,
+                actual: bytes.len(),
+            });
+        }
+        let (digests, nonce) = bytes[1..].split_at(16 * rounds as usize);
+        Ok(ChainedSolution {
+            digests: digests
+                .chunks_exact(16)
+                .map(|digest| digest.try_into().unwrap())
+                .collect(),
+            n: nonce.try_into().unwrap(),
+            rounds,
+        })
+    }
+}
+
+impl From<Solution> for ChainedSolution {
+    /// The one-round chain of a solution.
+    fn from(solution: Solution) -> Self {
+        ChainedSolution::new(vec![solution.d], solution.n)
+    }
+}
+
+/// Bytes a solution of `rounds` rounds encodes to.
+fn encoded_len(rounds: usize) -> usize {
+    1 + 16 * rounds + 8
+}
+
+/// Why a chained solution did not verify or decode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainError {
+    /// The round count is zero or differs from the number of digests.
+    RoundCount { rounds: u8, digests: usize },
+    /// The digest of this round, counted from zero, is not a valid solution of the
+    /// round's challenge.
+    InvalidRound(u8),
+    /// The encoding is not as long as its round count implies.
+    Length { expected: usize, actual: usize },
+}
+
+impl std::fmt::Display for ChainError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ChainError::RoundCount { rounds, digests } => {
+                write!(f, "Chain of {} rounds has {} digests", rounds, digests)
+            }
+            ChainError::InvalidRound(round) => write!(f, "Round {} is invalid", round),
+            ChainError::Length { expected, actual } => {
+                write!(f, "Expected {} bytes, got {}", expected, actual)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ChainError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
@@ -0,0 +1,61 @@
+//! Commitments that hide a solution until it is revealed.
+//!
+//! A miner first submits `keccak(COMMIT_TAG ‖ solution ‖ salt)`, then reveals the
+//! solution and salt once the commitment has landed, so a solution seen in flight can
+//! no longer be submitted first by someone else. Hashing goes through the same keccak
+//! backend as the drillx hash, the syscall on-chain, so programs and clients agree.
+//!
+//! A salt must be random and never reused. Anyone who has seen a salt can check
+//! guesses against commitments made with it, and the solution space of a challenge is
+//! small enough to search.
+
+use crate::{ct_eq_hash, Solution};
+
+/// Domain-separation tag prepended to every commitment.
+pub const COMMIT_TAG: [u8; 8] = *b"DXCOMMIT";
+
+/// Commits to a solution under a salt.
+pub fn commit(solution: &Solution, salt: &[u8; 32]) -> [u8; 32] {
+    crate::keccak(&[&COMMIT_TAG, &solution.to_bytes(), salt])
+}
+
+/// Returns true if the solution and salt open the commitment.
+///
+/// The comparison takes the same time wherever the commitments differ.
+pub fn verify_reveal(commitment: &[u8; 32], solution: &Solution, salt: &[u8; 32]) -> bool {
+    ct_eq_hash(commitment, &commit(solution, salt))
+}
+
+/// A commitment to a solution. Equality is constant-time.
+#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct Commitment(pub [u8; 32]);
+
+impl Commitment {
+    pub fn new(solution: &Solution, salt: &[u8; 32]) -> Self {
+        Commitment(commit(solution, salt))
+    }
+
+    /// See [`verify_reveal`].
+    pub fn verify(&self, solution: &Solution, salt: &[u8; 32]) -> bool {
+        verify_reveal(&self.0, solution, salt)
+    }
+
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+impl From<[u8; 32]> for Commitment {
+    fn from(bytes: [u8; 32]) -> Self {
+        Commitment(bytes)
+    }
+}
+
+impl PartialEq for Commitment {
+    fn eq(&self, other: &Self) -> bool {
+        ct_eq_hash(&self.0, &other.0)
+    }
+}
+
+impl Eq for Commitment {}
@@ -0,0 +1,101 @@
+//! Confirmation of candidate nonces reported without a digest.
+//!
+//! Some miners only report promising nonces. [`confirm_candidates`] re-runs the full
+//! solve for each one, scoring the same solution [`hash`](crate::hash) returns, so a
+//! confirmed candidate can be submitted as is.
+
+use std::collections::{hash_map::Entry, HashMap};
+
+use crate::{Context, DrillxError, ScoredSolution, Solution};
+
+/// The outcome of confirming one candidate nonce.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Confirmation {
+    /// The nonce meets the minimum difficulty.
+    Confirmed(ScoredSolution),
+    /// The nonce has a solution, but below the minimum difficulty.
+    BelowDifficulty { best: u32 },
+    /// The nonce's seed has no equix solutions.
+    NoSolutions,
+    /// The nonce repeats the candidate at index `first`, whose result applies.
+    Duplicate { first: usize },
+    /// The nonce could not be solved, so it is neither confirmed nor rejected.
+    Error(DrillxError),
+}
+
+/// Confirms candidate nonces, returning one result per nonce in input order.
+///
+/// Each distinct nonce is solved once. Solver memory is reused across nonces, and with
+/// the `rayon` feature nonces are solved in parallel on rayon's global pool.
+pub fn confirm_candidates(
+    challenge: &[u8; 32],
+    nonces: &[u64],
+    min_difficulty: u32,
+) -> Vec<Confirmation> {
+    let mut first = HashMap::with_capacity(nonces.len());
+    let mut results: Vec<Option<Confirmation>> = nonces
+        .iter()
+        .enumerate()
+        .map(|(i, nonce)| match first.entry(*nonce) {
+            Entry::Occupied(entry) => Some(Confirmation::Duplicate {
+                first: *entry.get(),
+            }),
+            Entry::Vacant(entry) => {
+                entry.insert(i);
+                None
+            }
+        })
+        .collect();
+    let unique: Vec<usize> = (0..nonces.len())
+        .filter(|&i| results[i].is_none())
+        .collect();
+
+    #[cfg(feature = "rayon")]
+    let confirmed: Vec<Confirmation> = {
+        use rayon::prelude::*;
+        unique
+            .par_iter()
+            .map_init(Context::default, |context, &i| {
+                confirm(context, challenge, nonces[i], min_difficulty)
+            })
+            .collect()
+    };
+    #[cfg(not(feature = "rayon"))]
+    let confirmed: Vec<Confirmation> = {
+        let mut context = Context::default();
+        unique
+            .iter()
+            .map(|&i| confirm(&mut context, challenge, nonces[i], min_difficulty))
+            .collect()
+    };
+
+    for (i, confirmation) in unique.into_iter().zip(confirmed) {
+        results[i] = Some(confirmation);
+    }
+    results.into_iter().map(Option::unwrap).collect()
+}
+
+fn confirm(
+    context: &mut Context,
+    challenge: &[u8; 32],
+    nonce: u64,
+    min_difficulty: u32,
+) -> Confirmation {
+    let nonce = nonce.to_le_bytes();
+    match context.hash(challenge, &nonce) {
+        Ok(hash) => {
+            let difficulty = hash.difficulty();
+            if difficulty < min_difficulty {
+                return Confirmation::BelowDifficulty { best: difficulty };
+            }
+            Confirmation::Confirmed(ScoredSolution {
+                solution: Solution::new(hash.d, nonce),
+                hash: hash.h,
+                difficulty,
+            })
+        }
+        Err(DrillxError::NoSolutions) => Confirmation::NoSolutions,
+        // The default context falls back to the interpreter, so this should not happen.
+        Err(err) => Confirmation::Error(err),
+    }
+}
@@ -0,0 +1,273 @@
+//! A persistent hashing context with a compiled-runtime watchdog.
+//!
+//! A [`Context`] owns its solver memory and remembers which equix runtime to use
+//! across hashes. When asked to try the compiled runtime, it falls back to the
+//! interpreter for any hash whose program fails to compile, and after
+//! [`DEFAULT_FAILURE_THRESHOLD`] consecutive compile failures it stops trying for the
+//! rest of the session. [`Context::reset_runtime`] re-enables compilation.
+//!
+//! An [`Interleaved`] solver alternates whole solves across several memories, for
+//! [`Context::interleaved`] and [`MinerBuilder::interleave`](crate::miner::MinerBuilder::interleave).
+//! Equix solves in one call that cannot be suspended, so seeds are not truly in flight
+//! together; the hope is only that alternating memories hides some memory latency. It
+//! doesn't here: on one x86_64 core the `interleave` benchmark hashed about 12% slower
+//! with 2 memories than with 1, as the second memory only costs cache. Hashes are
+//! identical either way.
+
+#[cfg(any(feature = "tracing", feature = "metrics"))]
+use crate::telemetry;
+use std::sync::Arc;
+
+use crate::{
+    runtime, telemetry::event, DrillxError, DrillxMemory, Hash, MemoryError, MemoryPool,
+    RuntimeOption,
+};
+
+/// Consecutive compile failures after which a context stops compiling.
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 8;
+
+/// Solves the equix puzzle for a seed.
+///
+/// This is the seam between drillx and the equix solver. [`EquixSolver`] is the real
+/// implementation; other implementations can wrap it or inject failures.
+pub trait Solver: Send {
+    /// Returns the first equix solution for the seed using the given runtime.
+    ///
+    /// Never called with [`RuntimeOption::TryCompile`]; the context handles fallback.
+    /// A program that fails to compile must be reported as [`DrillxError::CompileFailed`].
+    fn solve(&mut self, seed: &[u8], runtime: RuntimeOption) -> Result<[u8; 16], DrillxError>;
+}
+
+impl<S: Solver + ?Sized> Solver for Box<S> {
+    fn solve(&mut self, seed: &[u8], runtime: RuntimeOption) -> Result<[u8; 16], DrillxError> {
+        (**self).solve(seed, runtime)
+    }
+}
+
+/// The equix solver, with its own memory.
+pub struct EquixSolver {
+    /// Only taken when dropped.
+    memory: Option<DrillxMemory>,
+    /// Where the memory goes back to when the solver is dropped.
+    pool: Option<Arc<MemoryPool>>,
+}
+
+impl EquixSolver {
+    pub fn new() -> Self {
+        EquixSolver {
+            memory: Some(DrillxMemory::new()),
+            pool: None,
+        }
+    }
+
+    /// A solver with new memory, or an error if it cannot be allocated. See
+    /// [`DrillxMemory::try_new`].
+    pub fn try_new() -> Result<Self, MemoryError> {
+        Ok(EquixSolver {
+            memory: Some(DrillxMemory::try_new()?),
+            pool: None,
+        })
+    }
+
+    /// A solver with memory from a pool, which it releases back to the pool when it is
+    /// dropped.
+    pub fn pooled(pool: &Arc<MemoryPool>) -> Result<Self, MemoryError> {
+        Ok(EquixSolver {
+            memory: Some(pool.try_acquire()?),
+            pool: Some(pool.clone()),
+        })
+    }
+}
+
+impl Default for EquixSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for EquixSolver {
+    fn drop(&mut self) {
+        if let (Some(pool), Some(memory)) = (&self.pool, self.memory.take()) {
+            pool.release(memory);
+        }
+    }
+}
+
+impl Solver for EquixSolver {
+    fn solve(&mut self, seed: &[u8], runtime: RuntimeOption) -> Result<[u8; 16], DrillxError> {
+        let equix = runtime::build(seed, runtime)?;
+        let memory = self
+            .memory
+            .as_mut()
+            .expect("solver memory is only taken on drop");
+        let solutions = equix.solve_with_memory(memory.as_equix_mut());
+        match solutions.first() {
+            Some(solution) => Ok(solution.to_bytes()),
+            None => Err(DrillxError::NoSolutions),
+        }
+    }
+}
+
+/// Solves with each of several solvers in turn, one whole solve at a time.
+pub struct Interleaved<S> {
+    solvers: Vec<S>,
+    next: usize,
+}
+
+impl<S> Interleaved<S> {
+    /// # Panics
+    ///
+    /// If `solvers` is empty.
+    pub fn new(solvers: Vec<S>) -> Self {
+        assert!(!solvers.is_empty(), "interleaving needs a solver");
+        Interleaved { solvers, next: 0 }
+    }
+
+    /// Number of solvers taking turns.
+    pub fn len(&self) -> usize {
+        self.solvers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.solvers.is_empty()
+    }
+
+    pub fn solvers_mut(&mut self) -> &mut [S] {
+        &mut self.solvers
+    }
+}
+
+impl<S: Solver> Solver for Interleaved<S> {
+    fn solve(&mut self, seed: &[u8], runtime: RuntimeOption) -> Result<[u8; 16], DrillxError> {
+        let next = self.next;
+        self.next = (next + 1) % self.solvers.len();
+        self.solvers[next].solve(seed, runtime)
+    }
+}
+
+/// A persistent hashing context.
+pub struct Context<S = EquixSolver> {
+    solver: S,
+    configured: RuntimeOption,
+    current: RuntimeOption,
+    failures: u32,
+    threshold: u32,
+    downgrades: u64,
+}
+
+impl Context {
+    /// Creates a context using the equix solver.
+    pub fn new(runtime: RuntimeOption) -> Self {
+        Context::with_solver(EquixSolver::new(), runtime)
+    }
+}
+
+impl Context<Interleaved<EquixSolver>> {
+    /// Creates a context alternating solves across `interleave` solver memories. Zero
+    /// is treated as one, which is the same as [`Context::new`].
+    pub fn interleaved(runtime: RuntimeOption, interleave: u8) -> Self {
+        let solvers = (0..interleave.max(1)).map(|_| EquixSolver::new()).collect();
+        Context::with_solver(Interleaved::new(solvers), runtime)
+    }
+}
+
+impl Default for Context {
+    fn default() -> Self {
+        Context::new(RuntimeOption::TryCompile)
+    }
+}
+
+impl<S: Solver> Context<S> {
+    /// Creates a context using the given solver.
+    ///
+    /// On interpreter-only architectures [`RuntimeOption::TryCompile`] becomes
+    /// [`RuntimeOption::InterpretOnly`].
+    pub fn with_solver(solver: S, runtime: RuntimeOption) -> Self {
+        let runtime = runtime::resolve(runtime);
+        Context {
+            solver,
+            configured: runtime,
+            current: runtime,
+            failures: 0,
+            threshold: DEFAULT_FAILURE_THRESHOLD,
+            downgrades: 0,
+        }
+    }
+
+    /// Sets the number of consecutive compile failures after which the context stops
+    /// compiling. Zero is treated as one.
+    pub fn failure_threshold(mut self, threshold: u32) -> Self {
+        self.threshold = threshold.max(1);
+        self
+    }
+
+    /// Generates a drillx hash, identical to [`hash`](crate::hash) for any runtime.
+    pub fn hash(&mut self, challenge: &[u8; 32], nonce: &[u8; 8]) -> Result<Hash, DrillxError> {
+        let seed = crate::seed(challenge, nonce);
+        let digest = self.solve(&seed.data)?;
+        Ok(Hash {
+            d: digest,
+            h: crate::hashv(&digest, nonce),
+        })
+    }
+
+    fn solve(&mut self, seed: &[u8]) -> Result<[u8; 16], DrillxError> {
+        if self.current != RuntimeOption::TryCompile {
+            return self.solver.solve(seed, self.current);
+        }
+        match self.solver.solve(seed, RuntimeOption::RequireCompile) {
+            Err(DrillxError::CompileFailed) => {
+                #[cfg(feature = "tracing")]
+                telemetry::runtime_fallback();
+                self.failures += 1;
+                if self.failures >= self.threshold {
+                    self.downgrade();
+                }
+                self.solver.solve(seed, RuntimeOption::InterpretOnly)
+            }
+            result => {
+                self.failures = 0;
+                result
+            }
+        }
+    }
+
+    /// Stops compiling for the rest of the session.
+    fn downgrade(&mut self) {
+        self.current = RuntimeOption::InterpretOnly;
+        self.downgrades += 1;
+        event!(
+            telemetry::RUNTIME_DOWNGRADE_EVENT,
+            WARN,
+            failures = self.failures,
+            "hashx compiler keeps failing, switching to the interpreter"
+        );
+        #[cfg(feature = "metrics")]
+        metrics::counter!(telemetry::RUNTIME_DOWNGRADES_METRIC).increment(1);
+    }
+
+    /// The runtime the context currently hashes with.
+    pub fn current_runtime(&self) -> RuntimeOption {
+        self.current
+    }
+
+    /// Returns true if the watchdog has switched the context to the interpreter.
+    pub fn is_downgraded(&self) -> bool {
+        self.current != self.configured
+    }
+
+    /// Number of times the watchdog has switched the context to the interpreter.
+    pub fn downgrades(&self) -> u64 {
+        self.downgrades
+    }
+
+    /// Restores the configured runtime, retrying compilation if it was abandoned.
+    pub fn reset_runtime(&mut self) {
+        self.current = self.configured;
+        self.failures = 0;
+    }
+
+    pub fn solver_mut(&mut self) -> &mut S {
+        &mut self.solver
+    }
+}
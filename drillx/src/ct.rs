@@ -0,0 +1,25 @@
+//! Constant-time comparisons.
+//!
+//! Comparing an attacker-supplied digest or hash with `==` can leak, through timing,
+//! where the first difference is. These helpers look at every byte and never branch on
+//! the data.
+
+/// Returns true if the digests are equal, in constant time.
+pub fn ct_eq_digest(a: &[u8; 16], b: &[u8; 16]) -> bool {
+    ct_eq(a, b)
+}
+
+/// Returns true if the hashes are equal, in constant time.
+pub fn ct_eq_hash(a: &[u8; 32], b: &[u8; 32]) -> bool {
+    ct_eq(a, b)
+}
+
+pub(crate) fn ct_eq<const N: usize>(a: &[u8; N], b: &[u8; N]) -> bool {
+    let mut diff = 0u8;
+    for i in 0..N {
+        // Hidden from the optimizer, which could otherwise exit early once every
+        // bit of `diff` is set.
+        diff |= std::hint::black_box(a[i] ^ b[i]);
+    }
+    diff == 0
+}
@@ -0,0 +1,68 @@
+//! Stable error codes for [`DrillxError`], for programs to return as
+//! `ProgramError::Custom` and for clients and explorers to decode.
+//!
+//! Drillx's codes take the range `0x4452_0000..=0x4452_ffff` ("DR" in the top two
+//! bytes), so they do not collide with the small codes programs number their own
+//! errors with. Each variant's code is its offset from [`BASE`]. Codes are never
+//! renumbered or reused; new variants take the next free offset. With the `solana`
+//! feature, `ProgramError::from(DrillxError)` returns `ProgramError::Custom` with that
+//! code.
+//!
+//! The reference program's `program::VerifyError` takes its codes from the same range,
+//! after the [`DrillxError`] codes, and `program::decode_verify_error` decodes them.
+
+use crate::DrillxError;
+
+/// The first code of drillx's range.
+pub const BASE: u32 = 0x4452_0000;
+
+/// The last code of drillx's range.
+pub const LAST: u32 = 0x4452_ffff;
+
+/// Code of [`DrillxError::BadEquix`].
+pub const BAD_EQUIX: u32 = BASE;
+
+/// Code of [`DrillxError::NoSolutions`].
+pub const NO_SOLUTIONS: u32 = BASE + 1;
+
+/// Code of [`DrillxError::CompileFailed`].
+pub const COMPILE_FAILED: u32 = BASE + 2;
+
+/// Code of [`DrillxError::CompilerUnsupported`].
+pub const COMPILER_UNSUPPORTED: u32 = BASE + 3;
+
+/// Code of `program::VerifyError::InvalidSolution`.
+pub const INVALID_SOLUTION: u32 = BASE + 4;
+
+/// Code of `program::VerifyError::InsufficientDifficulty`.
+pub const INSUFFICIENT_DIFFICULTY: u32 = BASE + 5;
+
+impl DrillxError {
+    /// The error's stable code.
+    pub const fn code(self) -> u32 {
+        match self {
+            DrillxError::BadEquix => BAD_EQUIX,
+            DrillxError::NoSolutions => NO_SOLUTIONS,
+            DrillxError::CompileFailed => COMPILE_FAILED,
+            DrillxError::CompilerUnsupported => COMPILER_UNSUPPORTED,
+        }
+    }
+}
+
+/// The error with the given code, or `None` if the code is not one of drillx's.
+pub fn decode_error(code: u32) -> Option<DrillxError> {
+    match code {
+        BAD_EQUIX => Some(DrillxError::BadEquix),
+        NO_SOLUTIONS => Some(DrillxError::NoSolutions),
+        COMPILE_FAILED => Some(DrillxError::CompileFailed),
+        COMPILER_UNSUPPORTED => Some(DrillxError::CompilerUnsupported),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "solana")]
+impl From<DrillxError> for solana_program::program_error::ProgramError {
+    fn from(err: DrillxError) -> Self {
+        solana_program::program_error::ProgramError::Custom(err.code())
+    }
+}
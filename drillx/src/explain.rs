@@ -0,0 +1,112 @@
+//! Human-readable reports on why a solution scores as it does.
+//!
+//! [`explain`] retraces verification and hashing step by step, for support tickets and
+//! logs where a miner and a pool disagree about a share. It never panics, and it keeps
+//! going after a failed check, so the hash and difficulty are reported even for an
+//! invalid solution. The [`Display`](std::fmt::Display) format is stable, one
+//! `name: value` line per step.
+
+use std::fmt;
+
+use crate::{difficulty, hashv, seed, sorted, telemetry::Hex, Solution};
+
+/// The step-by-step account of a solution for a challenge.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explanation {
+    /// The equix seed: the challenge followed by the nonce.
+    pub seed: [u8; 40],
+    pub equix: EquixCheck,
+    /// The digest as submitted.
+    pub digest: [u8; 16],
+    /// The digest with its eight `u16`s sorted, as hashed.
+    pub sorted_digest: [u8; 16],
+    /// The keccak input: the sorted digest followed by the nonce.
+    pub keccak_input: [u8; 24],
+    pub hash: [u8; 32],
+    pub difficulty: u32,
+}
+
+/// The outcome of equix verification.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EquixCheck {
+    Passed,
+    /// The seed makes a hash program that equix rejects, so no digest is valid.
+    ProgramConstraints,
+    /// The digest's indices break equix's ordering rules, whatever the seed.
+    Order,
+    /// The digest's hash sums are not zero where equix requires.
+    HashSum,
+    /// Any other failure, as reported by equix.
+    Other(String),
+}
+
+impl Explanation {
+    /// Returns true if equix verification passed, as [`Solution::is_valid`].
+    pub fn is_valid(&self) -> bool {
+        self.equix == EquixCheck::Passed
+    }
+
+    /// Returns true if the submitted digest was already sorted.
+    pub fn already_sorted(&self) -> bool {
+        self.digest == self.sorted_digest
+    }
+}
+
+/// Explains how `solution` verifies and scores for `challenge`.
+pub fn explain(challenge: &[u8; 32], solution: &Solution) -> Explanation {
+    let seed = seed(challenge, &solution.n).data;
+    let equix = match equix::verify_bytes(&seed, &solution.d) {
+        Ok(()) => EquixCheck::Passed,
+        Err(equix::Error::Hash(equix::HashError::ProgramConstraints)) => {
+            EquixCheck::ProgramConstraints
+        }
+        Err(equix::Error::Order) => EquixCheck::Order,
+        Err(equix::Error::HashSum) => EquixCheck::HashSum,
+        Err(error) => EquixCheck::Other(error.to_string()),
+    };
+    let sorted_digest = sorted(solution.d);
+    let mut keccak_input = [0; 24];
+    keccak_input[..16].copy_from_slice(&sorted_digest);
+    keccak_input[16..].copy_from_slice(&solution.n);
+    let hash = hashv(&solution.d, &solution.n);
+    Explanation {
+        seed,
+        equix,
+        digest: solution.d,
+        sorted_digest,
+        keccak_input,
+        hash,
+        difficulty: difficulty(hash),
+    }
+}
+
+impl fmt::Display for EquixCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EquixCheck::Passed => write!(f, "passed"),
+            EquixCheck::ProgramConstraints => {
+                write!(f, "failed: program constraints (seed has no valid digest)")
+            }
+            EquixCheck::Order => write!(f, "failed: order (digest is not well formed)"),
+            EquixCheck::HashSum => write!(f, "failed: hash sum (digest does not solve seed)"),
+            EquixCheck::Other(error) => write!(f, "failed: {}", error),
+        }
+    }
+}
+
+impl fmt::Display for Explanation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let order = if self.already_sorted() {
+            "already sorted"
+        } else {
+            "reordered"
+        };
+        writeln!(f, "seed:        {}", Hex(&self.seed))?;
+        writeln!(f, "equix:       {}", self.equix)?;
+        writeln!(f, "digest:      {}", Hex(&self.digest))?;
+        writeln!(f, "sorted:      {} ({})", Hex(&self.sorted_digest), order)?;
+        writeln!(f, "keccak in:   {}", Hex(&self.keccak_input))?;
+        writeln!(f, "keccak out:  {}", Hex(&self.hash))?;
+        write!(f, "difficulty:  {}", self.difficulty)
+    }
+}
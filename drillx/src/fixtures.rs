@@ -0,0 +1,78 @@
+//! Precomputed solutions for downstream tests.
+//!
+//! Grinding a solution at a given difficulty in every test run is slow, so
+//! [`FIXTURES`] holds valid solutions at difficulties 1, 4, 8, and 12, checked by this
+//! crate's own tests.
+//!
+//! Fixtures are public knowledge. A production program must never accept them, which
+//! it won't as long as its challenges come from real state, such as a recent
+//! blockhash, rather than from the submitter.
+
+#[cfg(feature = "solve")]
+use crate::DrillxMemory;
+use crate::{vectors::hex, Solution};
+
+/// The challenge every fixture solves: `"drillx test fixture, difficulty "` in ASCII.
+pub const FIXTURE_CHALLENGE: [u8; 32] = *b"drillx test fixture, difficulty ";
+
+/// A valid solution and its difficulty.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fixture {
+    pub challenge: [u8; 32],
+    pub nonce: u64,
+    pub digest: [u8; 16],
+    pub difficulty: u32,
+}
+
+impl Fixture {
+    pub fn solution(&self) -> Solution {
+        Solution::new(self.digest, self.nonce.to_le_bytes())
+    }
+}
+
+/// The precomputed fixtures, in increasing order of difficulty.
+pub const FIXTURES: &[Fixture] = &[
+    fixture(1, "2140d6a948c300cec088229c473c0ed7", 1),
+    fixture(8, "61ae88d5c4a023dafb585f82caaf11fb", 4),
+    fixture(5, "b9260953424ce19ea652a8933e5f60b1", 8),
+    fixture(3376, "63730a89700ec9b5e1695da24b986bbc", 12),
+];
+
+const fn fixture(nonce: u64, digest: &str, difficulty: u32) -> Fixture {
+    Fixture {
+        challenge: FIXTURE_CHALLENGE,
+        nonce,
+        digest: hex(digest),
+        difficulty,
+    }
+}
+
+/// Returns the easiest fixture with at least the given difficulty.
+///
+/// # Panics
+///
+/// If `difficulty` is above that of every fixture. Use [`find_fixture`] instead.
+pub fn fixture_at_least(difficulty: u32) -> &'static Fixture {
+    FIXTURES
+        .iter()
+        .find(|fixture| fixture.difficulty >= difficulty)
+        .unwrap_or_else(|| panic!("no fixture has difficulty {}", difficulty))
+}
+
+/// Searches nonces from zero for a solution with at least the given difficulty,
+/// hashing at most `max_attempts` nonces.
+///
+/// Each extra bit of difficulty doubles the expected number of attempts.
+#[cfg(feature = "solve")]
+pub fn find_fixture(
+    challenge: &[u8; 32],
+    min_difficulty: u32,
+    max_attempts: u64,
+) -> Option<(u64, Solution)> {
+    let mut memory = DrillxMemory::new();
+    (0..max_attempts).find_map(|nonce| {
+        let hash = crate::hash_with_memory(&mut memory, challenge, &nonce.to_le_bytes()).ok()?;
+        (hash.difficulty() >= min_difficulty)
+            .then(|| (nonce, Solution::new(hash.d, nonce.to_le_bytes())))
+    })
+}
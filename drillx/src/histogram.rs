@@ -0,0 +1,138 @@
+//! Lock-free histogram of observed difficulties.
+//!
+//! Hash difficulties follow a geometric distribution: a hash has difficulty `d` with
+//! probability `2^-(d+1)`. Comparing a [`DifficultyHistogram`] against
+//! [`DifficultyHistogram::expected_count_at`] shows whether a miner's hashes look like
+//! honest work.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Number of buckets, one per difficulty from 0 to 256.
+const BUCKETS: usize = 257;
+
+/// Counts of difficulties, one bucket per number of leading zeros.
+///
+/// Recording is a single relaxed atomic increment, so one histogram can be shared by
+/// many threads.
+#[derive(Debug)]
+pub struct DifficultyHistogram {
+    buckets: [AtomicU64; BUCKETS],
+}
+
+impl Default for DifficultyHistogram {
+    fn default() -> Self {
+        DifficultyHistogram {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+}
+
+impl DifficultyHistogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts one difficulty. Difficulties above 256 count as 256.
+    #[inline]
+    pub fn record(&self, difficulty: u32) {
+        self.buckets[(difficulty as usize).min(BUCKETS - 1)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Adds the counts of another histogram to this one.
+    pub fn merge(&self, other: &DifficultyHistogram) {
+        for (bucket, count) in self.buckets.iter().zip(&other.buckets) {
+            bucket.fetch_add(count.load(Ordering::Relaxed), Ordering::Relaxed);
+        }
+    }
+
+    /// Number of difficulties recorded at exactly `difficulty`.
+    pub fn count(&self, difficulty: u32) -> u64 {
+        self.buckets
+            .get(difficulty as usize)
+            .map_or(0, |bucket| bucket.load(Ordering::Relaxed))
+    }
+
+    pub fn total(&self) -> u64 {
+        self.buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// Returns the smallest difficulty at or below which `p` percent of the recorded
+    /// difficulties lie, or 0 if nothing was recorded. `p` is clamped to `[0, 100]`.
+    pub fn percentile(&self, p: f64) -> u32 {
+        self.snapshot().percentile(p)
+    }
+
+    /// Expected number of hashes at exactly `difficulty` among `total` uniformly random
+    /// hashes.
+    pub fn expected_count_at(difficulty: u32, total: u64) -> f64 {
+        let probability = match difficulty {
+            0..=255 => 0.5f64.powi(difficulty as i32 + 1),
+            256 => 0.5f64.powi(256),
+            _ => 0.0,
+        };
+        total as f64 * probability
+    }
+
+    /// Copies the current counts. Concurrent records may or may not be included.
+    pub fn snapshot(&self) -> HistogramSnapshot {
+        let mut counts: Vec<u64> = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.load(Ordering::Relaxed))
+            .collect();
+        while counts.last() == Some(&0) {
+            counts.pop();
+        }
+        HistogramSnapshot { counts }
+    }
+}
+
+/// A point-in-time copy of a [`DifficultyHistogram`], for serializing.
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct HistogramSnapshot {
+    /// Counts indexed by difficulty, without trailing empty buckets.
+    pub counts: Vec<u64>,
+}
+
+impl HistogramSnapshot {
+    pub fn count(&self, difficulty: u32) -> u64 {
+        self.counts.get(difficulty as usize).copied().unwrap_or(0)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Adds the counts of another snapshot to this one.
+    pub fn merge(&mut self, other: &HistogramSnapshot) {
+        if self.counts.len() < other.counts.len() {
+            self.counts.resize(other.counts.len(), 0);
+        }
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+    }
+
+    /// See [`DifficultyHistogram::percentile`].
+    pub fn percentile(&self, p: f64) -> u32 {
+        let total = self.total();
+        if total == 0 {
+            return 0;
+        }
+        let p = if p.is_nan() { 0.0 } else { p.clamp(0.0, 100.0) };
+        // Nearest rank: the smallest bucket holding the `rank`th smallest difficulty.
+        let rank = ((p / 100.0 * total as f64).ceil() as u64).clamp(1, total);
+        let mut seen = 0;
+        for (difficulty, count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                return difficulty as u32;
+            }
+        }
+        self.counts.len() as u32 - 1
+    }
+}
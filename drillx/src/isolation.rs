@@ -0,0 +1,724 @@
+//! Mining in worker processes, so that generated code never runs in the caller's.
+//!
+//! An [`IsolatedMiner`] splits the nonce range into one partition per
+//! [thread](MinerConfig::threads) and has a worker process mine each. The hashx
+//! compiler's code runs only in the workers, and a worker that crashes takes down itself
+//! alone: the parent restarts it where it left off, up to the
+//! [restart limit](MinerConfig::restart_limit).
+//!
+//! A worker is a program that calls [`run_worker`] on its stdin and stdout. By default
+//! the parent re-runs its own executable with [`WORKER_ARG`] as the only argument, so
+//! its `main` must call [`run_worker_if_requested`] before anything else. The
+//! `drillx-worker` binary built with this feature is a ready-made worker for
+//! [`IsolatedMiner::worker_command`].
+//!
+//! Parent and worker exchange frames of `length (u32 LE) ‖ payload`, where the payload
+//! is a tag byte and its fields:
+//!
+//! ```text
+//! assign   (parent to worker) = 1 ‖ challenge (32 bytes) ‖ min difficulty (u32 LE)
+//!                               ‖ start (u64 LE) ‖ last (u64 LE) ‖ runtime (u8)
+//! solution (worker to parent) = 2 ‖ nonce (u64 LE) ‖ digest (16 bytes)
+//! hashed   (worker to parent) = 3 ‖ nonce (u64 LE)
+//! ```
+//!
+//! The runtime is 0 for [`RuntimeOption::TryCompile`], 1 for
+//! [`RuntimeOption::RequireCompile`], and 2 for [`RuntimeOption::InterpretOnly`]. The
+//! parent sends a single assign frame and closes the worker's stdin. The worker hashes
+//! the nonces from start to last in order and sends a hashed frame as it finishes each.
+//! Before that frame it sends a solution frame if the hash meets the minimum difficulty
+//! or beats the worker's best, and after the last nonce it exits.
+//!
+//! Nonces a worker has reported hashed are never handed out again, so a restarted
+//! worker resumes at the nonce its predecessor died on, and solutions reported before a
+//! crash are kept and not reported twice. The parent verifies every digest it is sent
+//! with the interpreter, and treats a worker that sends an invalid one, or any frame out of order, as crashed.
+//!
+//! The run ends as a threaded one does: at the first solution meeting the minimum
+//! difficulty, or never in streaming mode, and otherwise at the deadline, on
+//! cancellation, or once every partition is exhausted. The workers are then killed.
+
+use std::{
+    collections::VecDeque,
+    ffi::OsString,
+    io::{self, BufReader, Read, Write},
+    process::{Child, ChildStdout, Command, Stdio},
+    sync::{
+        mpsc::{self, Receiver, SyncSender, TrySendError},
+        Arc, Condvar, Mutex, MutexGuard,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    is_valid_digest_with_runtime,
+    miner::{
+        JobId, JobSolution, JobState, JobStatus, MineOutcome, MinerConfig, MinerError, StopReason,
+        RESTART_WINDOW,
+    },
+    Context, RuntimeOption, ScoredSolution, Solution,
+};
+
+/// The argument a worker is started with when it is the parent's own executable.
+pub const WORKER_ARG: &str = "__drillx-worker";
+
+/// Longest frame payload either side accepts.
+const MAX_FRAME: u32 = 64;
+
+const ASSIGN: u8 = 1;
+const SOLUTION: u8 = 2;
+const HASHED: u8 = 3;
+
+/// The nonces a worker is to mine.
+#[derive(Clone, Copy)]
+struct Assignment {
+    challenge: [u8; 32],
+    min_difficulty: u32,
+    start: u64,
+    last: u64,
+    runtime: RuntimeOption,
+}
+
+enum Frame {
+    Assign(Assignment),
+    Solution { nonce: u64, digest: [u8; 16] },
+    Hashed { nonce: u64 },
+}
+
+impl Frame {
+    fn encode(&self) -> Vec<u8> {
+        let mut payload = Vec::with_capacity(MAX_FRAME as usize);
+        match self {
+            Frame::Assign(work) => {
+                payload.push(ASSIGN);
+                payload.extend_from_slice(&work.challenge);
+                payload.extend_from_slice(&work.min_difficulty.to_le_bytes());
+                payload.extend_from_slice(&work.start.to_le_bytes());
+                payload.extend_from_slice(&work.last.to_le_bytes());
+                payload.push(match work.runtime {
+                    RuntimeOption::TryCompile => 0,
+                    RuntimeOption::RequireCompile => 1,
+                    RuntimeOption::InterpretOnly => 2,
+                });
+            }
+            Frame::Solution { nonce, digest } => {
+                payload.push(SOLUTION);
+                payload.extend_from_slice(&nonce.to_le_bytes());
+                payload.extend_from_slice(digest);
+            }
+            Frame::Hashed { nonce } => {
+                payload.push(HASHED);
+                payload.extend_from_slice(&nonce.to_le_bytes());
+            }
+        }
+        let mut frame = (payload.len() as u32).to_le_bytes().to_vec();
+        frame.extend_from_slice(&payload);
+        frame
+    }
+
+    fn decode(payload: &[u8]) -> Option<Frame> {
+        let (&tag, fields) = payload.split_first()?;
+        let u64_at = |at: usize| Some(u64::from_le_bytes(fields.get(at..at + 8)?.try_into().ok()?));
+        match (tag, fields.len()) {
+            (ASSIGN, 53) => Some(Frame::Assign(Assignment {
+                challenge: fields[..32].try_into().ok()?,
+                min_difficulty: u32::from_le_bytes(fields[32..36].try_into().ok()?),
+                start: u64_at(36)?,
+                last: u64_at(44)?,
+                runtime: match fields[52] {
+                    0 => RuntimeOption::TryCompile,
+                    1 => RuntimeOption::RequireCompile,
+                    2 => RuntimeOption::InterpretOnly,
+                    _ => return None,
+                },
+            })),
+            (SOLUTION, 24) => Some(Frame::Solution {
+                nonce: u64_at(0)?,
+                digest: fields[8..].try_into().ok()?,
+            }),
+            (HASHED, 8) => Some(Frame::Hashed { nonce: u64_at(0)? }),
+            _ => None,
+        }
+    }
+
+    fn write(&self, output: &mut impl Write) -> io::Result<()> {
+        output.write_all(&self.encode())?;
+        output.flush()
+    }
+
+    /// Reads a frame, or `None` at the end of the input.
+    fn read(input: &mut impl Read) -> io::Result<Option<Frame>> {
+        let mut len = [0; 4];
+        match input.read_exact(&mut len) {
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+            result => result?,
+        }
+        let len = u32::from_le_bytes(len);
+        if len > MAX_FRAME {
+            return Err(invalid_frame());
+        }
+        let mut payload = vec![0; len as usize];
+        input.read_exact(&mut payload)?;
+        Frame::decode(&payload).map(Some).ok_or_else(invalid_frame)
+    }
+}
+
+fn invalid_frame() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "invalid drillx worker frame")
+}
+
+/// Runs a worker: reads an assign frame from `input` and mines it, reporting to
+/// `output` as laid out in the [module docs](self).
+pub fn run_worker(mut input: impl Read, mut output: impl Write) -> io::Result<()> {
+    let Some(Frame::Assign(work)) = Frame::read(&mut input)? else {
+        return Err(invalid_frame());
+    };
+    let mut context = Context::new(work.runtime);
+    let mut best = None;
+    let mut nonce = work.start;
+    loop {
+        if let Ok(hash) = context.hash(&work.challenge, &nonce.to_le_bytes()) {
+            let difficulty = hash.difficulty();
+            if difficulty >= work.min_difficulty || best.is_none_or(|best| difficulty > best) {
+                best = best.max(Some(difficulty));
+                Frame::Solution {
+                    nonce,
+                    digest: hash.d,
+                }
+                .write(&mut output)?;
+            }
+        }
+        Frame::Hashed { nonce }.write(&mut output)?;
+        if nonce >= work.last {
+            return Ok(());
+        }
+        nonce += 1;
+    }
+}
+
+/// Runs a worker on stdin and stdout and exits if the process was started as one,
+/// with [`WORKER_ARG`] as its first argument, and returns otherwise.
+pub fn run_worker_if_requested() {
+    if std::env::args_os()
+        .nth(1)
+        .is_some_and(|arg| arg == WORKER_ARG)
+    {
+        let result = run_worker(io::stdin().lock(), io::stdout().lock());
+        std::process::exit(if result.is_ok() { 0 } else { 1 });
+    }
+}
+
+/// Builds and starts a miner whose workers are processes.
+///
+/// The miner takes its worker count, minimum difficulty, deadline, nonce range,
+/// streaming capacity, runtime, and restart limit from its [`MinerConfig`], and ignores
+/// the rest.
+pub struct IsolatedMiner {
+    challenge: [u8; 32],
+    config: MinerConfig,
+    program: Option<OsString>,
+}
+
+impl IsolatedMiner {
+    pub fn new(challenge: [u8; 32], config: &MinerConfig) -> Self {
+        IsolatedMiner {
+            challenge,
+            config: config.clone(),
+            program: None,
+        }
+    }
+
+    /// Runs `program` as each worker, with [`WORKER_ARG`] as its argument, instead of
+    /// the current executable.
+    pub fn worker_command(mut self, program: impl Into<OsString>) -> Self {
+        self.program = Some(program.into());
+        self
+    }
+
+    /// Starts the workers and returns a handle to the running miner.
+    pub fn spawn(self) -> Result<IsolatedHandle, MinerError> {
+        let config = self.config;
+        let program = match self.program {
+            Some(program) => program,
+            None => std::env::current_exe()
+                .map_err(MinerError::Spawn)?
+                .into_os_string(),
+        };
+        let start = config.start_nonce;
+        let last = config.end_nonce.map_or(u64::MAX, |end| end.max(start));
+        let partitions = partition(start, last, config.threads.max(1));
+        let (stream, solutions) = match config.stream {
+            Some(capacity) => {
+                let (tx, rx) = mpsc::sync_channel(capacity);
+                (Some(tx), Some(rx))
+            }
+            None => (None, None),
+        };
+        let shared = Arc::new(Shared {
+            challenge: self.challenge,
+            min_difficulty: config.min_difficulty,
+            runtime: config.runtime,
+            program,
+            restart_limit: config.restart_limit,
+            streaming: stream.is_some(),
+            stream: Mutex::new(stream),
+            slots: partitions.iter().map(|_| Mutex::new(None)).collect(),
+            state: Mutex::new(State {
+                partitions,
+                hashes: 0,
+                solutions: 0,
+                dropped: 0,
+                restarts: 0,
+                recent_restarts: VecDeque::new(),
+                best: None,
+                reason: None,
+                error: None,
+            }),
+            signal: Condvar::new(),
+            started: Instant::now(),
+        });
+
+        let mut outputs = Vec::new();
+        for slot in 0..shared.slots.len() {
+            match shared.start(slot) {
+                Ok(output) => outputs.push(output),
+                Err(err) => {
+                    shared.stop(StopReason::Cancelled);
+                    shared.kill_all();
+                    return Err(MinerError::Spawn(err));
+                }
+            }
+        }
+        let mut supervisors = Vec::new();
+        for (slot, output) in outputs.into_iter().enumerate() {
+            let supervisor = shared.clone();
+            let spawned = thread::Builder::new()
+                .name(format!("drillx-supervisor-{}", slot))
+                .spawn(move || supervisor.supervise(slot, output));
+            match spawned {
+                Ok(handle) => supervisors.push(handle),
+                Err(err) => {
+                    shared.stop(StopReason::Cancelled);
+                    shared.kill_all();
+                    return Err(MinerError::Spawn(err));
+                }
+            }
+        }
+
+        let deadline = config.deadline.map(|d| shared.started + d);
+        let coordinator = {
+            let shared = shared.clone();
+            thread::Builder::new()
+                .name("drillx-coordinator".to_string())
+                .spawn(move || shared.coordinate(deadline, supervisors))
+        }
+        .map_err(|err| {
+            shared.stop(StopReason::Cancelled);
+            shared.kill_all();
+            MinerError::Spawn(err)
+        })?;
+
+        Ok(IsolatedHandle {
+            shared,
+            coordinator,
+            solutions,
+        })
+    }
+}
+
+/// Splits `start..=last` into up to `count` contiguous partitions of near-equal size.
+fn partition(start: u64, last: u64, count: usize) -> Vec<Partition> {
+    let total = (last - start) as u128 + 1;
+    let count = (count as u128).min(total);
+    (0..count)
+        .map(|i| {
+            let first = start + (total * i / count) as u64;
+            Partition {
+                next: Some(first),
+                last: start + (total * (i + 1) / count - 1) as u64,
+                reported: None,
+            }
+        })
+        .collect()
+}
+
+/// A handle to a running isolated miner.
+pub struct IsolatedHandle {
+    shared: Arc<Shared>,
+    coordinator: JoinHandle<Result<MineOutcome, MinerError>>,
+    solutions: Option<Receiver<JobSolution>>,
+}
+
+impl IsolatedHandle {
+    /// Ends the run and kills the workers.
+    pub fn cancel(&self) {
+        self.shared.stop(StopReason::Cancelled);
+    }
+
+    /// A snapshot of the run so far.
+    pub fn progress(&self) -> IsolatedProgress {
+        let state = self.shared.lock();
+        IsolatedProgress {
+            hashes: state.hashes,
+            solutions: state.solutions,
+            dropped: state.dropped,
+            restarts: state.restarts,
+            workers: self.worker_pids().len(),
+            elapsed: self.shared.started.elapsed(),
+            best: state.best,
+        }
+    }
+
+    /// Returns the solution channel in streaming mode. It disconnects once the run ends.
+    pub fn solutions(&self) -> Option<&Receiver<JobSolution>> {
+        self.solutions.as_ref()
+    }
+
+    /// The process ids of the running workers.
+    pub fn worker_pids(&self) -> Vec<u32> {
+        self.shared
+            .slots
+            .iter()
+            .filter_map(|slot| slot.lock().unwrap().as_ref().map(Child::id))
+            .collect()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.coordinator.is_finished()
+    }
+
+    /// Waits for the run to end.
+    ///
+    /// The outcome's single job is the challenge. Workers' runtime downgrades are not
+    /// reported.
+    pub fn join(self) -> Result<MineOutcome, MinerError> {
+        self.coordinator
+            .join()
+            .map_err(|_| MinerError::WorkerPanicked)?
+    }
+}
+
+/// A snapshot of an isolated run.
+#[derive(Clone, Copy, Debug)]
+pub struct IsolatedProgress {
+    /// Number of nonces the workers reported hashed.
+    pub hashes: u64,
+    /// Number of solutions meeting the minimum difficulty so far.
+    pub solutions: u64,
+    /// Number of solutions dropped because the stream was full.
+    pub dropped: u64,
+    /// Number of workers restarted after crashing.
+    pub restarts: u64,
+    /// Number of worker processes running.
+    pub workers: usize,
+    /// Time since the miner started.
+    pub elapsed: Duration,
+    /// Best solution seen so far.
+    pub best: Option<ScoredSolution>,
+}
+
+impl IsolatedProgress {
+    /// Average hashes per second since the start.
+    pub fn hashrate(&self) -> f64 {
+        let secs = self.elapsed.as_secs_f64();
+        if secs > 0.0 {
+            self.hashes as f64 / secs
+        } else {
+            0.0
+        }
+    }
+}
+
+/// The nonces of one worker slot.
+struct Partition {
+    /// The next nonce to hash, or `None` once the partition is exhausted.
+    next: Option<u64>,
+    last: u64,
+    /// The last nonce whose solution was recorded, so a restarted worker does not
+    /// record it again.
+    reported: Option<u64>,
+}
+
+struct State {
+    partitions: Vec<Partition>,
+    hashes: u64,
+    solutions: u64,
+    dropped: u64,
+    restarts: u64,
+    /// When workers were restarted within the restart window.
+    recent_restarts: VecDeque<Instant>,
+    best: Option<ScoredSolution>,
+    reason: Option<StopReason>,
+    error: Option<MinerError>,
+}
+
+impl State {
+    fn is_stopping(&self) -> bool {
+        self.reason.is_some() || self.error.is_some()
+    }
+}
+
+/// State shared by the supervisors, the coordinator, and the handle.
+struct Shared {
+    challenge: [u8; 32],
+    min_difficulty: u32,
+    runtime: RuntimeOption,
+    program: OsString,
+    restart_limit: u32,
+    streaming: bool,
+    stream: Mutex<Option<SyncSender<JobSolution>>>,
+    /// The worker process of each partition, while one is running.
+    slots: Vec<Mutex<Option<Child>>>,
+    state: Mutex<State>,
+    signal: Condvar,
+    started: Instant,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    fn stop(&self, reason: StopReason) {
+        let mut state = self.lock();
+        state.reason.get_or_insert(reason);
+        self.signal.notify_all();
+    }
+
+    fn fail(&self, err: MinerError) {
+        let mut state = self.lock();
+        if !state.is_stopping() {
+            state.error = Some(err);
+        }
+        self.signal.notify_all();
+    }
+
+    fn kill_all(&self) {
+        for slot in &self.slots {
+            if let Some(child) = slot.lock().unwrap().as_mut() {
+                child.kill().ok();
+            }
+        }
+    }
+
+    /// Starts a worker on the rest of a partition, returning its output.
+    fn start(&self, slot: usize) -> io::Result<ChildStdout> {
+        let work = {
+            let state = self.lock();
+            let partition = &state.partitions[slot];
+            Assignment {
+                challenge: self.challenge,
+                min_difficulty: self.min_difficulty,
+                start: partition.next.unwrap_or(partition.last),
+                last: partition.last,
+                runtime: self.runtime,
+            }
+        };
+        let mut child = Command::new(&self.program)
+            .arg(WORKER_ARG)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let mut input = child.stdin.take().unwrap();
+        let output = child.stdout.take().unwrap();
+        *self.slots[slot].lock().unwrap() = Some(child);
+        // A stop that came while the worker started may have missed it.
+        let stopping = self.lock().is_stopping();
+        if stopping {
+            self.kill_all();
+        }
+        // A worker that died already is found when its output ends.
+        Frame::Assign(work).write(&mut input).ok();
+        Ok(output)
+    }
+
+    /// Relays a slot's worker output, restarting the worker whenever it dies before its
+    /// partition is done.
+    fn supervise(&self, slot: usize, mut output: ChildStdout) {
+        loop {
+            self.relay(slot, output);
+            if let Some(mut child) = self.slots[slot].lock().unwrap().take() {
+                child.kill().ok();
+                child.wait().ok();
+            }
+            {
+                let mut state = self.lock();
+                if state.is_stopping() || state.partitions[slot].next.is_none() {
+                    self.signal.notify_all();
+                    return;
+                }
+                if let Err(err) = self.note_restart(&mut state) {
+                    drop(state);
+                    self.fail(err);
+                    return;
+                }
+            }
+            output = match self.start(slot) {
+                Ok(output) => output,
+                Err(err) => {
+                    self.fail(MinerError::Spawn(err));
+                    return;
+                }
+            };
+        }
+    }
+
+    fn note_restart(&self, state: &mut State) -> Result<(), MinerError> {
+        let now = Instant::now();
+        while state
+            .recent_restarts
+            .front()
+            .is_some_and(|&at| now.duration_since(at) >= RESTART_WINDOW)
+        {
+            state.recent_restarts.pop_front();
+        }
+        if state.recent_restarts.len() >= self.restart_limit as usize {
+            return Err(MinerError::TooManyRestarts {
+                limit: self.restart_limit,
+            });
+        }
+        state.recent_restarts.push_back(now);
+        state.restarts += 1;
+        Ok(())
+    }
+
+    /// Records a worker's frames until its output ends or it breaks the protocol.
+    fn relay(&self, slot: usize, output: ChildStdout) {
+        let mut output = BufReader::new(output);
+        while let Ok(Some(frame)) = Frame::read(&mut output) {
+            let scored = match frame {
+                Frame::Solution { nonce, digest } => {
+                    let solution = Solution::new(digest, nonce.to_le_bytes());
+                    // Checked with the interpreter, so no generated code runs here.
+                    if !is_valid_digest_with_runtime(
+                        &self.challenge,
+                        &solution.n,
+                        &solution.d,
+                        RuntimeOption::InterpretOnly,
+                    ) {
+                        return;
+                    }
+                    let hash = solution.to_hash();
+                    Some(ScoredSolution {
+                        solution,
+                        hash: hash.h,
+                        difficulty: hash.difficulty(),
+                    })
+                }
+                _ => None,
+            };
+            let mut state = self.lock();
+            if state.is_stopping() {
+                return;
+            }
+            let partition = &mut state.partitions[slot];
+            let Some(next) = partition.next else {
+                return;
+            };
+            match (frame, scored) {
+                (Frame::Solution { nonce, .. }, Some(scored)) if nonce == next => {
+                    if partition.reported != Some(nonce) {
+                        partition.reported = Some(nonce);
+                        self.record(&mut state, scored);
+                    }
+                }
+                (Frame::Hashed { nonce }, _) if nonce == next => {
+                    partition.next = (nonce < partition.last).then(|| nonce + 1);
+                    state.hashes += 1;
+                    self.signal.notify_all();
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn record(&self, state: &mut State, scored: ScoredSolution) {
+        if state
+            .best
+            .is_none_or(|best| scored.difficulty > best.difficulty)
+        {
+            state.best = Some(scored);
+        }
+        if scored.difficulty < self.min_difficulty {
+            return;
+        }
+        state.solutions += 1;
+        if !self.streaming {
+            state.reason = Some(StopReason::Found);
+            self.signal.notify_all();
+            return;
+        }
+        if let Some(stream) = self.stream.lock().unwrap().as_ref() {
+            let solution = JobSolution {
+                job: JobId(0),
+                challenge: self.challenge,
+                scored,
+            };
+            if let Err(TrySendError::Full(_)) = stream.try_send(solution) {
+                state.dropped += 1;
+            }
+        }
+    }
+
+    /// Waits for the run to end, then kills the workers and reports the outcome.
+    fn coordinate(
+        &self,
+        deadline: Option<Instant>,
+        supervisors: Vec<JoinHandle<()>>,
+    ) -> Result<MineOutcome, MinerError> {
+        {
+            let mut state = self.lock();
+            while !state.is_stopping() {
+                if state.partitions.iter().all(|p| p.next.is_none()) {
+                    state.reason = Some(StopReason::Exhausted);
+                    break;
+                }
+                state = match deadline {
+                    Some(deadline) => {
+                        let now = Instant::now();
+                        if now >= deadline {
+                            state.reason = Some(StopReason::Deadline);
+                            break;
+                        }
+                        self.signal.wait_timeout(state, deadline - now).unwrap().0
+                    }
+                    None => self.signal.wait(state).unwrap(),
+                };
+            }
+        }
+        self.kill_all();
+        for supervisor in supervisors {
+            supervisor.join().map_err(|_| MinerError::WorkerPanicked)?;
+        }
+        self.stream.lock().unwrap().take();
+
+        let mut state = self.lock();
+        if let Some(err) = state.error.take() {
+            return Err(err);
+        }
+        let reason = state.reason.unwrap_or(StopReason::Cancelled);
+        Ok(MineOutcome {
+            best: state.best,
+            hashes: state.hashes,
+            solutions: state.solutions,
+            dropped: state.dropped,
+            elapsed: self.started.elapsed(),
+            reason,
+            runtime_downgraded: false,
+            jobs: vec![JobStatus {
+                id: JobId(0),
+                challenge: self.challenge,
+                min_difficulty: self.min_difficulty,
+                state: match reason {
+                    StopReason::Found => JobState::Solved,
+                    StopReason::Exhausted => JobState::Exhausted,
+                    StopReason::Deadline | StopReason::Cancelled => JobState::Active,
+                },
+                best: state.best,
+                hashes: state.hashes,
+                solutions: state.solutions,
+            }],
+        })
+    }
+}
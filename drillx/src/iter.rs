@@ -0,0 +1,123 @@
+//! Lazy hashing over a range of nonces.
+
+use std::{
+    iter::FusedIterator,
+    ops::{Bound, RangeBounds},
+};
+
+use crate::{DrillxError, DrillxMemory, Hash};
+
+/// Returns an iterator hashing each nonce of a range in ascending order.
+///
+/// Nonces without an equix solution are skipped and counted in [`HashIter::errors`];
+/// [`HashIter::try_iter`] yields them instead. The range never wraps around: an
+/// unbounded end runs through `u64::MAX` and stops there, `..` covers every nonce,
+/// and a range whose start is past its end is empty.
+///
+/// Nothing is hashed until the iterator is advanced, and every hash reuses the same
+/// solver memory, so the iterator composes with [`Iterator::find`] and friends at no
+/// extra cost:
+///
+/// ```
+/// let (nonce, hash) = drillx::hash_iter(&[0; 32], 0..)
+///     .find(|(_, hash)| hash.difficulty() >= 4)
+///     .unwrap();
+/// assert_eq!(drillx::hash(&[0; 32], &nonce.to_le_bytes()).unwrap().h, hash.h);
+/// ```
+pub fn hash_iter(challenge: &[u8; 32], nonces: impl RangeBounds<u64>) -> HashIter {
+    let start = match nonces.start_bound() {
+        Bound::Included(&start) => Some(start),
+        Bound::Excluded(&start) => start.checked_add(1),
+        Bound::Unbounded => Some(0),
+    };
+    let end = match nonces.end_bound() {
+        Bound::Included(&end) => Some(end),
+        Bound::Excluded(&end) => end.checked_sub(1),
+        Bound::Unbounded => Some(u64::MAX),
+    };
+    let next = match (start, end) {
+        (Some(start), Some(end)) if start <= end => Some(start),
+        _ => None,
+    };
+    HashIter {
+        challenge: *challenge,
+        memory: DrillxMemory::new(),
+        next,
+        end: end.unwrap_or(0),
+        errors: 0,
+    }
+}
+
+/// An iterator over the hashes of a range of nonces, returned by [`hash_iter`].
+#[derive(Debug)]
+pub struct HashIter {
+    challenge: [u8; 32],
+    memory: DrillxMemory,
+    /// The next nonce to hash, or `None` once the range is done.
+    next: Option<u64>,
+    end: u64,
+    errors: u64,
+}
+
+impl HashIter {
+    /// Number of nonces skipped so far because they had no solution.
+    pub fn errors(&self) -> u64 {
+        self.errors
+    }
+
+    /// Yields every nonce left in the range with its result, including failures.
+    pub fn try_iter(self) -> TryHashIter {
+        TryHashIter(self)
+    }
+
+    /// Number of nonces left in the range, which may not fit in a `usize`.
+    fn remaining(&self) -> u128 {
+        self.next.map_or(0, |next| (self.end - next) as u128 + 1)
+    }
+
+    fn hash_next(&mut self) -> Option<(u64, Result<Hash, DrillxError>)> {
+        let nonce = self.next?;
+        self.next = nonce.checked_add(1).filter(|&next| next <= self.end);
+        let hash = crate::hash_with_memory(&mut self.memory, &self.challenge, &nonce.to_le_bytes());
+        Some((nonce, hash))
+    }
+}
+
+impl Iterator for HashIter {
+    type Item = (u64, Hash);
+
+    fn next(&mut self) -> Option<(u64, Hash)> {
+        loop {
+            match self.hash_next()? {
+                (nonce, Ok(hash)) => return Some((nonce, hash)),
+                (_, Err(_)) => self.errors += 1,
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, usize::try_from(self.remaining()).ok())
+    }
+}
+
+impl FusedIterator for HashIter {}
+
+/// An iterator over the results of hashing a range of nonces, returned by
+/// [`HashIter::try_iter`].
+#[derive(Debug)]
+pub struct TryHashIter(HashIter);
+
+impl Iterator for TryHashIter {
+    type Item = (u64, Result<Hash, DrillxError>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.hash_next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = usize::try_from(self.0.remaining()).ok();
+        (remaining.unwrap_or(usize::MAX), remaining)
+    }
+}
+
+impl FusedIterator for TryHashIter {}
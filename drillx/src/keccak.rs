@@ -0,0 +1,164 @@
+//! The keccak-256 behind every drillx hash.
+//!
+//! The provider is chosen at compile time, in order:
+//!
+//! 1. With `keccak-extern`, a `drillx_keccak` function that the final binary defines.
+//! 2. With `solana`, `solana_program::keccak::hashv`.
+//! 3. On `target_os = "solana"`, the raw `sol_keccak256` syscall, so programs built on
+//!    pinocchio or other lean runtimes need no `solana-program`.
+//! 4. Otherwise the `sha3` crate.
+//!
+//! Every provider computes the same hash of the concatenated parts.
+//!
+//! `drillx_keccak` has the signature of `sol_keccak256`: `vals` points to `val_len`
+//! [`KeccakPart`]s, and the 32-byte hash is written to `hash_result`. A program may
+//! simply forward to the syscall:
+//!
+//! ```ignore
+//! #[no_mangle]
+//! pub unsafe extern "C" fn drillx_keccak(vals: *const u8, val_len: u64, hash_result: *mut u8) -> u64 {
+//!     pinocchio::syscalls::sol_keccak256(vals, val_len, hash_result)
+//! }
+//! ```
+
+#[cfg(not(any(feature = "keccak-extern", feature = "solana", target_os = "solana")))]
+use sha3::Digest;
+
+/// The most parts drillx hashes at once.
+#[cfg(any(
+    feature = "keccak-extern",
+    all(target_os = "solana", not(feature = "solana"))
+))]
+const MAX_PARTS: usize = 4;
+
+/// One input to `drillx_keccak` or `sol_keccak256`: a pointer and a length in bytes,
+/// laid out as the syscall's byte slices.
+#[cfg(any(
+    feature = "keccak-extern",
+    all(target_os = "solana", not(feature = "solana"))
+))]
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct KeccakPart {
+    pub ptr: *const u8,
+    pub len: u64,
+}
+
+#[cfg(any(
+    feature = "keccak-extern",
+    all(target_os = "solana", not(feature = "solana"))
+))]
+impl KeccakPart {
+    /// Returns the parts passed to `drillx_keccak`.
+    ///
+    /// # Safety
+    ///
+    /// `vals` and `val_len` must be as received by `drillx_keccak`, and the result must
+    /// not outlive the call.
+    pub unsafe fn parts<'a>(vals: *const u8, val_len: u64) -> &'a [KeccakPart] {
+        if val_len == 0 {
+            return &[];
+        }
+        std::slice::from_raw_parts(vals as *const KeccakPart, val_len as usize)
+    }
+
+    /// Returns the bytes of the part.
+    ///
+    /// # Safety
+    ///
+    /// The part must be one of those passed to `drillx_keccak`, and the result must not
+    /// outlive the call.
+    pub unsafe fn as_slice<'a>(&self) -> &'a [u8] {
+        if self.len == 0 {
+            return &[];
+        }
+        std::slice::from_raw_parts(self.ptr, self.len as usize)
+    }
+
+    /// Lays out up to [`MAX_PARTS`] parts for the provider.
+    fn array(parts: &[&[u8]]) -> [KeccakPart; MAX_PARTS] {
+        assert!(parts.len() <= MAX_PARTS, "too many keccak parts");
+        let mut array = [KeccakPart {
+            ptr: std::ptr::null(),
+            len: 0,
+        }; MAX_PARTS];
+        for (slot, part) in array.iter_mut().zip(parts) {
+            *slot = KeccakPart {
+                ptr: part.as_ptr(),
+                len: part.len() as u64,
+            };
+        }
+        array
+    }
+}
+
+#[cfg(feature = "keccak-extern")]
+extern "C" {
+    fn drillx_keccak(vals: *const u8, val_len: u64, hash_result: *mut u8) -> u64;
+}
+
+#[cfg(all(
+    target_os = "solana",
+    not(feature = "keccak-extern"),
+    not(feature = "solana")
+))]
+extern "C" {
+    fn sol_keccak256(vals: *const u8, val_len: u64, hash_result: *mut u8) -> u64;
+}
+
+/// Returns the keccak-256 hash of the concatenated parts.
+#[cfg(feature = "keccak-extern")]
+#[inline(always)]
+pub(crate) fn keccak(parts: &[&[u8]]) -> [u8; 32] {
+    let array = KeccakPart::array(parts);
+    let mut hash = [0; 32];
+    // SAFETY: `array` holds `parts.len()` parts borrowed for the call, and `hash` has
+    // room for the result.
+    unsafe {
+        drillx_keccak(
+            array.as_ptr() as *const u8,
+            parts.len() as u64,
+            hash.as_mut_ptr(),
+        );
+    }
+    hash
+}
+
+/// Returns the keccak-256 hash of the concatenated parts, through the syscall.
+#[cfg(all(feature = "solana", not(feature = "keccak-extern")))]
+#[inline(always)]
+pub(crate) fn keccak(parts: &[&[u8]]) -> [u8; 32] {
+    solana_program::keccak::hashv(parts).to_bytes()
+}
+
+/// Returns the keccak-256 hash of the concatenated parts, through the raw syscall.
+#[cfg(all(
+    target_os = "solana",
+    not(feature = "keccak-extern"),
+    not(feature = "solana")
+))]
+#[inline(always)]
+pub(crate) fn keccak(parts: &[&[u8]]) -> [u8; 32] {
+    let array = KeccakPart::array(parts);
+    let mut hash = [0; 32];
+    // SAFETY: as for `drillx_keccak`, which has the same signature.
+    unsafe {
+        sol_keccak256(
+            array.as_ptr() as *const u8,
+            parts.len() as u64,
+            hash.as_mut_ptr(),
+        );
+    }
+    hash
+}
+
+/// Returns the keccak-256 hash of the concatenated parts.
+#[cfg(not(any(feature = "keccak-extern", feature = "solana", target_os = "solana")))]
+#[inline(always)]
+pub(crate) fn keccak(parts: &[&[u8]]) -> [u8; 32] {
+    let mut hasher = sha3::Keccak256::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize().into()
+}
@@ -0,0 +1,57 @@
+//! Drillx as it hashed before the digest was sorted, for historical data only.
+//!
+//! The first drillx programs hashed the equix digest as the solver returned it:
+//!
+//! ```text
+//! legacy hash = keccak(digest ‖ nonce)
+//! ```
+//!
+//! Reordering a digest's eight u16 items changes that hash without changing the
+//! solution, so the final hash now sorts them first. Indexers replaying transactions
+//! from before the change need the old hash to reproduce what those programs accepted.
+//!
+//! Nothing else in drillx uses these rules, and new solutions must never be checked
+//! against them. The equix check is the same under both rules; only the final hash
+//! differs.
+
+use crate::{is_valid_digest, Hash, Solution};
+
+/// Which rules a historical record verifies under.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VerifyEpoch {
+    /// The digest is valid and the hash is its current, sorted hash.
+    Current,
+    /// The digest is valid and the hash is its legacy, unsorted hash.
+    Legacy,
+    /// The digest is invalid, or the hash matches neither rule.
+    Neither,
+}
+
+/// Returns the legacy hash of a digest and nonce, with the digest unsorted.
+pub fn hashv(digest: &[u8; 16], nonce: &[u8; 8]) -> [u8; 32] {
+    crate::keccak(&[digest.as_slice(), nonce.as_slice()])
+}
+
+/// Calculates a solution's hash under the legacy rules.
+pub fn to_hash(solution: &Solution) -> Hash {
+    Hash {
+        d: solution.d,
+        h: hashv(&solution.d, &solution.n),
+    }
+}
+
+/// Checks which rules a recorded solution and the hash accepted for it verify under.
+///
+/// A digest already in sorted order hashes the same under both, and reports
+/// [`VerifyEpoch::Current`].
+pub fn verify_either(challenge: &[u8; 32], solution: &Solution, hash: &[u8; 32]) -> VerifyEpoch {
+    if !is_valid_digest(challenge, &solution.n, &solution.d) {
+        VerifyEpoch::Neither
+    } else if solution.to_hash().h == *hash {
+        VerifyEpoch::Current
+    } else if hashv(&solution.d, &solution.n) == *hash {
+        VerifyEpoch::Legacy
+    } else {
+        VerifyEpoch::Neither
+    }
+}
@@ -1,31 +1,384 @@
+//! Drillx, a proof-of-work algorithm built on Equi-X.
+//!
+//! # Features
+//!
+//! | Feature             | Default | Enables                                                  |
+//! |---------------------|---------|----------------------------------------------------------|
+//! | `solve`             | yes     | Solving: [`hash`] and its variants, memory, the miner    |
+//! | `full`              | yes     | The hashx compiler, for solving about 9x faster          |
+//! | `verify`            | no      | Nothing extra: verification and difficulty are always on |
+//! | `test-support`      | no      | Precomputed solutions for downstream tests               |
+//! | `keccak-extern`     | no      | Hashing through a `drillx_keccak` the binary defines     |
+//! | `battery`           | no      | [`throttle::BatteryAwareHook`], throttling on battery    |
+//! | `signing`           | no      | Signing and verifying [`work::WorkUnit`]s with ed25519   |
+//! | `web`               | no      | `web::WebMiner`, mining on Web Workers in browsers       |
+//! | `arrow`             | no      | `arrow::ShareRecordBatchBuilder`, shares to Parquet      |
+//! | `process-isolation` | no      | `isolation::IsolatedMiner`, mining in worker processes   |
+//!
+//! Without `solve`, drillx exposes only verification and scoring:
+//! [`is_valid_digest`], [`verify_batch`], [`Solution::is_valid`],
+//! [`Solution::to_hash`], [`difficulty`], and the formats built on them. Such builds,
+//! for programs and gateways, use `default-features = false, features = ["verify"]`.
+
+#[cfg(feature = "equix-compat")]
 pub use equix;
-#[cfg(not(feature = "solana"))]
-use sha3::Digest;
+
+pub mod archive;
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod audit;
+#[cfg(feature = "solve")]
+pub mod bench;
+pub mod bundle;
+pub mod chain;
+pub mod commit_reveal;
+#[cfg(feature = "solve")]
+mod confirm;
+#[cfg(feature = "solve")]
+mod context;
+mod ct;
+pub mod error_code;
+mod explain;
+#[cfg(feature = "test-support")]
+pub mod fixtures;
+mod histogram;
+#[cfg(feature = "process-isolation")]
+pub mod isolation;
+#[cfg(feature = "solve")]
+mod iter;
+mod keccak;
+pub mod legacy;
+#[cfg(feature = "solve")]
+mod memory;
+#[cfg(feature = "solve")]
+pub mod miner;
+mod namespace;
+mod network;
+#[cfg(feature = "offload")]
+pub mod offload;
+#[cfg(all(feature = "rayon", feature = "solve"))]
+mod par;
+pub mod payouts;
+mod permutation;
+#[cfg(feature = "sqlx-postgres")]
+pub mod postgres;
+#[cfg(feature = "program")]
+pub mod program;
+#[cfg(feature = "prost")]
+pub mod proto;
+pub mod puzzle;
+mod rank;
+#[cfg(feature = "redis")]
+pub mod redis;
+mod registry;
+#[cfg(feature = "solve")]
+mod runtime;
+#[cfg(feature = "solve")]
+mod selftest;
+#[cfg(feature = "serve")]
+pub mod serve;
+pub mod sharelog;
+pub mod sim;
+pub mod telemetry;
+#[cfg(feature = "solve")]
+pub mod throttle;
+#[cfg(feature = "solve")]
+pub mod topology;
+#[cfg(feature = "solve")]
+mod tune;
+mod vardiff;
+pub mod vectors;
+mod verify_cache;
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+pub mod web;
+mod weight;
+pub mod wire;
+pub mod work;
+
+#[cfg(feature = "solve")]
+pub use confirm::{confirm_candidates, Confirmation};
+#[cfg(feature = "solve")]
+pub use context::{Context, EquixSolver, Interleaved, Solver, DEFAULT_FAILURE_THRESHOLD};
+pub use ct::{ct_eq_digest, ct_eq_hash};
+pub use explain::{explain, EquixCheck, Explanation};
+pub use histogram::{DifficultyHistogram, HistogramSnapshot};
+#[cfg(feature = "solve")]
+pub use iter::{hash_iter, HashIter, TryHashIter};
+pub(crate) use keccak::keccak;
+#[cfg(feature = "keccak-extern")]
+pub use keccak::KeccakPart;
+#[cfg(feature = "solve")]
+pub use memory::{DrillxMemory, MemoryAllocator, MemoryError, MemoryPool, SystemAllocator};
+pub use namespace::{NamespaceError, NonceNamespace};
+pub use network::{
+    estimate_hashrate, DifficultyObservation, HashrateEstimate, HashrateTracker, OnlineEstimator,
+    HASHRATE_CONFIDENCE,
+};
+#[cfg(all(feature = "rayon", feature = "solve"))]
+pub use par::{par_hash_range, DrillxHash, ParallelHashExt};
+pub use permutation::NoncePermutation;
+pub use rank::{rank_solutions, rank_top_k, Ranked};
+pub use registry::{InsertOutcome, SolutionRegistry};
+#[cfg(feature = "solve")]
+pub use runtime::{
+    compiled_programs, runtime_info, Runtime, RuntimeInfo, RuntimeOption, COMPILER_SUPPORTED,
+};
+#[cfg(feature = "solve")]
+pub use selftest::{self_test, PathReport, SelfTestError, SelfTestReport};
+#[cfg(feature = "solve")]
+pub use tune::{autotune, autotune_within, TuneReport, TuneTrial, AUTO_TUNE_BUDGET};
+pub use vardiff::{
+    VardiffController, VARDIFF_ALPHA, VARDIFF_DEADBAND, VARDIFF_IDLE_INTERVALS, VARDIFF_MAX_STEP,
+    VARDIFF_MIN_SHARES,
+};
+pub use verify_cache::{CachedVerdict, VerifyCache};
+pub use weight::{apply_weight, share_weight, sum_weights};
+
+/// A general-purpose domain-separation tag for deployments without a tag of their own.
+///
+/// Solutions mined under any tag, including this one, are never valid untagged. The
+/// untagged functions are the ORE format.
+pub const TAG_DEFAULT: [u8; 8] = *b"DRILLX01";
+
+/// Most solutions equix can return for a single seed.
+pub const MAX_SOLUTIONS: usize = 8;
 
 /// 64-byte aligned structure for seed data
 #[repr(align(64))]
-struct AlignedSeed {
-    data: [u8; 40],
+pub struct AlignedSeed {
+    pub data: [u8; 40],
+}
+
+/// Longest challenge the `_generic` functions take.
+///
+/// Equix hashes seeds of any length, so this only bounds the seed buffer.
+pub const MAX_CHALLENGE_LEN: usize = 128;
+
+/// 64-byte aligned structure for seed data of a challenge of any supported length
+#[repr(align(64))]
+pub struct AlignedGenericSeed {
+    data: [u8; MAX_CHALLENGE_LEN + 8],
+    len: usize,
 }
 
+impl AlignedGenericSeed {
+    /// The seed, `C + 8` bytes long.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data[..self.len]
+    }
+}
+
+/// 64-byte aligned structure for tagged seed data
+#[repr(align(64))]
+pub struct AlignedTaggedSeed {
+    pub data: [u8; 48],
+}
+
+#[cfg(feature = "solve")]
 /// Generates a new drillx hash from a challenge and nonce.
+///
+/// This is [`hash_with_memory`] with transient memory, so both always agree.
 #[inline(always)]
 pub fn hash(challenge: &[u8; 32], nonce: &[u8; 8]) -> Result<Hash, DrillxError> {
-    let digest = digest(challenge, nonce)?;
+    hash_with_memory(&mut DrillxMemory::new(), challenge, nonce)
+}
+
+#[cfg(feature = "solve")]
+/// Generates a new drillx hash from a challenge and nonce using pre-allocated memory.
+#[inline(always)]
+pub fn hash_with_memory(
+    memory: &mut DrillxMemory,
+    challenge: &[u8; 32],
+    nonce: &[u8; 8],
+) -> Result<Hash, DrillxError> {
+    hash_generic_with_memory(memory, challenge, nonce)
+}
+
+#[cfg(feature = "solve")]
+/// Generates a new drillx hash from a challenge of `C` bytes and a nonce.
+///
+/// The equix seed is `challenge ‖ nonce`, `C + 8` bytes, so at `C = 32` this is
+/// [`hash`]. The keccak input does not depend on the challenge, as in [`hash`].
+/// `C` must be from 1 to [`MAX_CHALLENGE_LEN`], which is checked at compile time.
+#[inline(always)]
+pub fn hash_generic<const C: usize>(
+    challenge: &[u8; C],
+    nonce: &[u8; 8],
+) -> Result<Hash, DrillxError> {
+    hash_generic_with_memory(&mut DrillxMemory::new(), challenge, nonce)
+}
+
+#[cfg(feature = "solve")]
+/// Generates a new drillx hash from a challenge of `C` bytes using pre-allocated memory.
+#[inline(always)]
+pub fn hash_generic_with_memory<const C: usize>(
+    memory: &mut DrillxMemory,
+    challenge: &[u8; C],
+    nonce: &[u8; 8],
+) -> Result<Hash, DrillxError> {
+    let seed = seed_generic(challenge, nonce);
+    let digest = digest_with_memory(memory.as_equix_mut(), seed.as_bytes())?;
     Ok(Hash {
         d: digest,
         h: hashv(&digest, nonce),
     })
 }
 
-/// Generates a new drillx hash from a challenge and nonce using pre-allocated memory.
+/// True if [`hash_first`] stops the equix search at its first solution.
+///
+/// equix 0.1 runs every solve to completion and has no hook to stop it early, so
+/// [`hash_first`] currently falls back to the full solve.
+pub const EARLY_EXIT_SUPPORTED: bool = false;
+
+#[cfg(feature = "solve")]
+/// Generates a drillx hash from the first equix solution found for a challenge and
+/// nonce, stopping the search there where [`EARLY_EXIT_SUPPORTED`].
+///
+/// The digest always verifies with [`is_valid_digest`], but an early exit may return a
+/// solution other than the one [`hash`] returns. Anything that must reproduce that
+/// solution must use [`hash`], and [`SolutionV2`] indices come from [`hash_all`].
 #[inline(always)]
-pub fn hash_with_memory(
+pub fn hash_first(challenge: &[u8; 32], nonce: &[u8; 8]) -> Result<Hash, DrillxError> {
+    hash_first_with_memory(&mut DrillxMemory::new(), challenge, nonce)
+}
+
+#[cfg(feature = "solve")]
+/// Generates a drillx hash from the first equix solution found using pre-allocated
+/// memory. See [`hash_first`].
+#[inline(always)]
+pub fn hash_first_with_memory(
+    memory: &mut DrillxMemory,
+    challenge: &[u8; 32],
+    nonce: &[u8; 8],
+) -> Result<Hash, DrillxError> {
+    let digest = first_digest_with_memory(memory.as_equix_mut(), &seed(challenge, nonce).data)?;
+    Ok(Hash {
+        d: digest,
+        h: hashv(&digest, nonce),
+    })
+}
+
+#[cfg(feature = "solve")]
+/// Generates a drillx hash for every equix solution of a challenge and nonce.
+///
+/// Hashes are in canonical order: ascending by the digest with its u16 items sorted, as
+/// it is for the final hash, so the order depends only on the set of solutions and not
+/// on how the equix solver found them. The first is not necessarily the one [`hash`]
+/// returns.
+pub fn hash_all(challenge: &[u8; 32], nonce: &[u8; 8]) -> Result<Vec<Hash>, DrillxError> {
+    hash_all_with_memory(&mut DrillxMemory::new(), challenge, nonce)
+}
+
+#[cfg(feature = "solve")]
+/// Generates a drillx hash for every equix solution using pre-allocated memory.
+pub fn hash_all_with_memory(
+    memory: &mut DrillxMemory,
+    challenge: &[u8; 32],
+    nonce: &[u8; 8],
+) -> Result<Vec<Hash>, DrillxError> {
+    let solutions = solve_with_memory(memory.as_equix_mut(), &seed(challenge, nonce).data)?;
+    let mut hashes: Vec<Hash> = solutions
+        .iter()
+        .map(|solution| {
+            let digest = solution.to_bytes();
+            Hash {
+                d: digest,
+                h: hashv(&digest, nonce),
+            }
+        })
+        .collect();
+    hashes.sort_unstable_by_key(|hash| (sorted(hash.d), hash.d));
+    Ok(hashes)
+}
+
+#[cfg(feature = "solve")]
+/// Generates a new drillx hash from a challenge and nonce under a domain-separation tag.
+///
+/// The tag is prepended to the equix seed (`tag ‖ challenge ‖ nonce`) and to the final
+/// keccak input (`tag ‖ sorted digest ‖ nonce`), so the result never matches [`hash`]
+/// or a hash under any other tag.
+#[inline(always)]
+pub fn hash_tagged(
+    tag: &[u8; 8],
+    challenge: &[u8; 32],
+    nonce: &[u8; 8],
+) -> Result<Hash, DrillxError> {
+    hash_tagged_with_memory(&mut DrillxMemory::new(), tag, challenge, nonce)
+}
+
+#[cfg(feature = "solve")]
+/// Generates a new tagged drillx hash using pre-allocated memory.
+#[inline(always)]
+pub fn hash_tagged_with_memory(
+    memory: &mut DrillxMemory,
+    tag: &[u8; 8],
+    challenge: &[u8; 32],
+    nonce: &[u8; 8],
+) -> Result<Hash, DrillxError> {
+    let seed = tagged_seed(tag, challenge, nonce);
+    let digest = digest_with_memory(memory.as_equix_mut(), &seed.data)?;
+    Ok(Hash {
+        d: digest,
+        h: hashv_tagged(tag, &digest, nonce),
+    })
+}
+
+#[cfg(feature = "solve")]
+/// Generates a drillx v2 hash, whose final keccak also covers the challenge.
+///
+/// The digest is the one [`hash`] finds, but the hash is
+/// `keccak(HASH_V2_VERSION ‖ challenge ‖ sorted digest ‖ nonce)`, so it is bound to the
+/// challenge on its own rather than only through equix verification, and never matches
+/// a v1 or tagged hash. For protocols that store or compare bare hashes. This is
+/// opt-in: ORE consensus, [`hash`], and every other hash function use v1, which is
+/// unchanged. Unrelated to the [`SolutionV2`] encoding.
+#[inline(always)]
+pub fn hash_v2(challenge: &[u8; 32], nonce: &[u8; 8]) -> Result<Hash, DrillxError> {
+    hash_v2_with_memory(&mut DrillxMemory::new(), challenge, nonce)
+}
+
+#[cfg(feature = "solve")]
+/// Generates a drillx v2 hash using pre-allocated memory.
+#[inline(always)]
+pub fn hash_v2_with_memory(
+    memory: &mut DrillxMemory,
+    challenge: &[u8; 32],
+    nonce: &[u8; 8],
+) -> Result<Hash, DrillxError> {
+    let digest = digest_with_memory(memory.as_equix_mut(), &seed(challenge, nonce).data)?;
+    Ok(Hash {
+        d: digest,
+        h: hashv_v2(challenge, &digest, nonce),
+    })
+}
+
+#[cfg(feature = "solve")]
+/// Generates a new drillx hash from a challenge and nonce using the given equix runtime.
+#[inline(always)]
+pub fn hash_with_runtime(
+    memory: &mut DrillxMemory,
+    runtime: RuntimeOption,
+    challenge: &[u8; 32],
+    nonce: &[u8; 8],
+) -> Result<Hash, DrillxError> {
+    let solutions =
+        solve_with_runtime(memory.as_equix_mut(), runtime, &seed(challenge, nonce).data)?;
+    let digest = solutions[0].to_bytes();
+    Ok(Hash {
+        d: digest,
+        h: hashv(&digest, nonce),
+    })
+}
+
+/// Generates a new drillx hash from a challenge and nonce using raw equix solver memory.
+#[cfg(all(feature = "equix-compat", feature = "solve"))]
+#[inline(always)]
+pub fn hash_with_equix_memory(
     memory: &mut equix::SolverMemory,
     challenge: &[u8; 32],
     nonce: &[u8; 8],
 ) -> Result<Hash, DrillxError> {
-    let digest = digest_with_memory(memory, challenge, nonce)?;
+    let digest = digest_with_memory(memory, &seed(challenge, nonce).data)?;
     Ok(Hash {
         d: digest,
         h: hashv(&digest, nonce),
@@ -41,37 +394,91 @@ pub fn seed(challenge: &[u8; 32], nonce: &[u8; 8]) -> AlignedSeed {
     result
 }
 
-/// Constructs a keccak digest from a challenge and nonce using equix hashes.
+/// Rejects challenge lengths out of range at compile time. An associated const, as the
+/// SBF toolchain predates inline `const` blocks.
+struct ChallengeLen<const C: usize>;
+
+impl<const C: usize> ChallengeLen<C> {
+    const CHECK: () = assert!(
+        C >= 1 && C <= MAX_CHALLENGE_LEN,
+        "challenge length out of range"
+    );
+}
+
+/// Concatenates a challenge of `C` bytes and a nonce into a cache-aligned buffer.
+///
+/// `C` must be from 1 to [`MAX_CHALLENGE_LEN`], which is checked at compile time.
 #[inline(always)]
-fn digest(challenge: &[u8; 32], nonce: &[u8; 8]) -> Result<[u8; 16], DrillxError> {
-    let seed = seed(challenge, nonce);
-    let solutions = equix::solve(&seed.data).map_err(|_| DrillxError::BadEquix)?;
-    if solutions.is_empty() {
-        return Err(DrillxError::NoSolutions);
-    }
+pub fn seed_generic<const C: usize>(challenge: &[u8; C], nonce: &[u8; 8]) -> AlignedGenericSeed {
+    let () = ChallengeLen::<C>::CHECK;
+    let mut result = AlignedGenericSeed {
+        data: [0; MAX_CHALLENGE_LEN + 8],
+        len: C + 8,
+    };
+    result.data[..C].copy_from_slice(challenge);
+    result.data[C..C + 8].copy_from_slice(nonce);
+    result
+}
+
+/// Concatenates a tag, a challenge, and a nonce into a cache-aligned buffer.
+#[inline(always)]
+pub fn tagged_seed(tag: &[u8; 8], challenge: &[u8; 32], nonce: &[u8; 8]) -> AlignedTaggedSeed {
+    let mut result = AlignedTaggedSeed { data: [0; 48] };
+    result.data[0..8].copy_from_slice(tag);
+    result.data[8..40].copy_from_slice(challenge);
+    result.data[40..48].copy_from_slice(nonce);
+    result
+}
+
+#[cfg(feature = "solve")]
+/// Constructs a keccak digest from a seed using equix hashes and pre-allocated memory.
+#[inline(always)]
+fn digest_with_memory(
+    memory: &mut equix::SolverMemory,
+    seed: &[u8],
+) -> Result<[u8; 16], DrillxError> {
+    let solutions = solve_with_memory(memory, seed)?;
     // SAFETY: The equix solver guarantees that the first solution is always valid
     let solution = unsafe { solutions.get_unchecked(0) };
     Ok(solution.to_bytes())
 }
 
-/// Constructs a keccak digest from a challenge and nonce using equix hashes and pre-allocated memory.
+#[cfg(feature = "solve")]
+/// Constructs a digest from the first equix solution found for a seed.
 #[inline(always)]
-fn digest_with_memory(
+fn first_digest_with_memory(
     memory: &mut equix::SolverMemory,
-    challenge: &[u8; 32],
-    nonce: &[u8; 8],
+    seed: &[u8],
 ) -> Result<[u8; 16], DrillxError> {
-    let seed = seed(challenge, nonce);
-    let equix = equix::EquiXBuilder::new()
-        .runtime(equix::RuntimeOption::TryCompile)
-        .build(&seed.data)
-        .map_err(|_| DrillxError::BadEquix)?;
+    // Without an early-exit hook in equix, the first solution found is the first of a
+    // full solve.
+    digest_with_memory(memory, seed)
+}
+
+#[cfg(feature = "solve")]
+/// Returns every equix solution for a seed in solver order, failing if there are none.
+#[inline(always)]
+fn solve_with_memory(
+    memory: &mut equix::SolverMemory,
+    seed: &[u8],
+) -> Result<equix::SolutionArray, DrillxError> {
+    solve_with_runtime(memory, RuntimeOption::TryCompile, seed)
+}
+
+#[cfg(feature = "solve")]
+/// Returns every equix solution for a seed using the given runtime.
+#[inline(always)]
+fn solve_with_runtime(
+    memory: &mut equix::SolverMemory,
+    runtime: RuntimeOption,
+    seed: &[u8],
+) -> Result<equix::SolutionArray, DrillxError> {
+    let equix = runtime::build(seed, runtime)?;
     let solutions = equix.solve_with_memory(memory);
     if solutions.is_empty() {
         return Err(DrillxError::NoSolutions);
     }
-    let solution = unsafe { solutions.get_unchecked(0) };
-    Ok(solution.to_bytes())
+    Ok(solutions)
 }
 
 /// Sorts the provided digest as a list of u16 values.
@@ -86,30 +493,93 @@ fn sorted(mut digest: [u8; 16]) -> [u8; 16] {
 
 /// Returns a keccak hash of the provided digest and nonce.
 /// The digest is sorted prior to hashing to prevent malleability.
-/// Delegates the hash to a syscall if compiled for the solana runtime.
-#[cfg(feature = "solana")]
 #[inline(always)]
 fn hashv(digest: &[u8; 16], nonce: &[u8; 8]) -> [u8; 32] {
-    solana_program::keccak::hashv(&[sorted(*digest).as_slice(), &nonce.as_slice()]).to_bytes()
+    keccak(&[sorted(*digest).as_slice(), nonce.as_slice()])
 }
 
-/// Calculates a hash from the provided digest and nonce.
-/// The digest is sorted prior to hashing to prevent malleability.
-#[cfg(not(feature = "solana"))]
+/// Returns a keccak hash of the tag, the sorted digest, and the nonce.
 #[inline(always)]
-fn hashv(digest: &[u8; 16], nonce: &[u8; 8]) -> [u8; 32] {
-    let mut hasher = sha3::Keccak256::new();
-    hasher.update(&sorted(*digest));
-    hasher.update(nonce);
-    hasher.finalize().into()
+fn hashv_tagged(tag: &[u8; 8], digest: &[u8; 16], nonce: &[u8; 8]) -> [u8; 32] {
+    keccak(&[tag.as_slice(), sorted(*digest).as_slice(), nonce.as_slice()])
+}
+
+/// Version byte leading the keccak input of a [`hash_v2`].
+pub const HASH_V2_VERSION: u8 = 2;
+
+/// Returns a keccak hash of the version byte, the challenge, the sorted digest, and the
+/// nonce.
+#[inline(always)]
+fn hashv_v2(challenge: &[u8; 32], digest: &[u8; 16], nonce: &[u8; 8]) -> [u8; 32] {
+    keccak(&[
+        &[HASH_V2_VERSION],
+        challenge.as_slice(),
+        sorted(*digest).as_slice(),
+        nonce.as_slice(),
+    ])
 }
 
 /// Returns true if the digest is a valid equihash construction from the challenge and nonce.
 pub fn is_valid_digest(challenge: &[u8; 32], nonce: &[u8; 8], digest: &[u8; 16]) -> bool {
-    let seed = seed(challenge, nonce);
+    is_valid_digest_generic(challenge, nonce, digest)
+}
+
+/// Returns true if the digest is a valid equihash construction from a challenge of `C`
+/// bytes and the nonce, as mined by [`hash_generic`].
+pub fn is_valid_digest_generic<const C: usize>(
+    challenge: &[u8; C],
+    nonce: &[u8; 8],
+    digest: &[u8; 16],
+) -> bool {
+    let seed = seed_generic(challenge, nonce);
+    #[cfg(feature = "solve")]
+    return runtime::verify(seed.as_bytes(), digest, RuntimeOption::TryCompile);
+    #[cfg(not(feature = "solve"))]
+    equix::verify_bytes(seed.as_bytes(), digest).is_ok()
+}
+
+#[cfg(feature = "solve")]
+/// Returns true if the digest is a valid equihash construction from the challenge and
+/// nonce, checked with the given runtime.
+///
+/// With [`RuntimeOption::InterpretOnly`] no code is generated, so a process can check
+/// untrusted digests without running any.
+pub fn is_valid_digest_with_runtime(
+    challenge: &[u8; 32],
+    nonce: &[u8; 8],
+    digest: &[u8; 16],
+    runtime: RuntimeOption,
+) -> bool {
+    runtime::verify(&seed(challenge, nonce).data, digest, runtime)
+}
+
+/// Returns true if the digest is a valid equihash construction under the tag.
+pub fn is_valid_digest_tagged(
+    tag: &[u8; 8],
+    challenge: &[u8; 32],
+    nonce: &[u8; 8],
+    digest: &[u8; 16],
+) -> bool {
+    let seed = tagged_seed(tag, challenge, nonce);
+    #[cfg(feature = "solve")]
+    return runtime::verify(&seed.data, digest, RuntimeOption::TryCompile);
+    #[cfg(not(feature = "solve"))]
     equix::verify_bytes(&seed.data, digest).is_ok()
 }
 
+/// Returns whether each solution is valid for the challenge.
+pub fn verify_batch(challenge: &[u8; 32], solutions: &[Solution]) -> Vec<bool> {
+    let verdicts: Vec<bool> = solutions.iter().map(|s| s.is_valid(challenge)).collect();
+    #[cfg(feature = "metrics")]
+    {
+        let accepted = verdicts.iter().filter(|v| **v).count() as u64;
+        metrics::counter!(telemetry::VERIFY_ACCEPTED_METRIC).increment(accepted);
+        metrics::counter!(telemetry::VERIFY_REJECTED_METRIC, "reason" => "invalid")
+            .increment(verdicts.len() as u64 - accepted);
+    }
+    verdicts
+}
+
 /// Returns the number of leading zeros on a 32 byte buffer.
 pub fn difficulty(hash: [u8; 32]) -> u32 {
     let mut count = 0;
@@ -123,6 +593,42 @@ pub fn difficulty(hash: [u8; 32]) -> u32 {
     count
 }
 
+/// Returns a continuous difficulty, `256 - log2(H + 1)`, where `H` is the hash read as a
+/// big-endian integer.
+///
+/// The all-zero hash maps to 256.0 and the all-ones hash to 0.0. The `+ 1`
+/// makes `floor` of the result equal [`difficulty`] for every hash: each leading zero
+/// adds one, and the remaining bits order shares within a level. The result is
+/// computed from the hash's 64 most significant bits and clamped into
+/// `[difficulty, difficulty + 1)`, so it stays consistent even where `f64` cannot tell
+/// neighbouring hashes apart.
+///
+/// This is for off-chain accounting only; [`difficulty`] is authoritative on-chain.
+#[cfg(not(target_os = "solana"))]
+pub fn difficulty_fractional(hash: &[u8; 32]) -> f64 {
+    let zeros = difficulty(*hash);
+    if zeros == 256 {
+        return 256.0;
+    }
+    // The hash's top 64 bits, starting at its leading one.
+    let bits = 256 - zeros as usize;
+    let mut top = 0u64;
+    for i in 0..64.min(bits) {
+        let bit = zeros as usize + i;
+        top = top << 1 | ((hash[bit / 8] >> (7 - bit % 8)) & 1) as u64;
+    }
+    let log2 = if bits <= 64 {
+        // The hash fits in a u64 and `top` is the hash itself.
+        (top as f64 + 1.0).log2()
+    } else {
+        // The `+ 1` is below f64 precision here.
+        (bits - 64) as f64 + (top as f64).log2()
+    };
+    let floor = zeros as f64;
+    let ceil = f64::from_bits((floor + 1.0).to_bits() - 1);
+    (256.0 - log2).clamp(floor, ceil)
+}
+
 /// The result of a drillx hash
 #[derive(Default)]
 pub struct Hash {
@@ -135,10 +641,17 @@ impl Hash {
     pub fn difficulty(&self) -> u32 {
         difficulty(self.h)
     }
+
+    /// The continuous difficulty of the hash (see [`difficulty_fractional`])
+    #[cfg(not(target_os = "solana"))]
+    pub fn difficulty_fractional(&self) -> f64 {
+        difficulty_fractional(&self.h)
+    }
 }
 
 /// A drillx solution which can be efficiently validated on-chain
-#[derive(Clone, Copy, Debug, serde::Serialize, serde::Deserialize)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Solution {
     pub d: [u8; 16], // digest
     pub n: [u8; 8],  // nonce
@@ -158,15 +671,55 @@ impl Solution {
         is_valid_digest(challenge, &self.n, &self.d)
     }
 
+    /// Explains how the solution verifies and scores (see [`explain`])
+    pub fn explain(&self, challenge: &[u8; 32]) -> Explanation {
+        explain(challenge, self)
+    }
+
+    /// Returns true if the solutions are equal, in constant time
+    pub fn ct_eq(&self, other: &Solution) -> bool {
+        ct::ct_eq(&self.to_bytes(), &other.to_bytes())
+    }
+
+    /// Returns true if the solution is valid under the tag
+    pub fn is_valid_tagged(&self, tag: &[u8; 8], challenge: &[u8; 32]) -> bool {
+        is_valid_digest_tagged(tag, challenge, &self.n, &self.d)
+    }
+
     /// Calculates the result hash for a given solution
     pub fn to_hash(&self) -> Hash {
-        let mut d = self.d;
         Hash {
             d: self.d,
-            h: hashv(&mut d, &self.n),
+            h: hashv(&self.d, &self.n),
         }
     }
 
+    /// Calculates the result hash for a solution mined under the tag
+    pub fn to_hash_tagged(&self, tag: &[u8; 8]) -> Hash {
+        Hash {
+            d: self.d,
+            h: hashv_tagged(tag, &self.d, &self.n),
+        }
+    }
+
+    /// Calculates the v2 result hash, bound to the challenge (see [`hash_v2`])
+    pub fn to_hash_v2(&self, challenge: &[u8; 32]) -> Hash {
+        Hash {
+            d: self.d,
+            h: hashv_v2(challenge, &self.d, &self.n),
+        }
+    }
+
+    /// Returns true if the solution is valid and `hash` is its v1 hash
+    pub fn is_valid_hash(&self, challenge: &[u8; 32], hash: &[u8; 32]) -> bool {
+        ct_eq_hash(&self.to_hash().h, hash) && self.is_valid(challenge)
+    }
+
+    /// Returns true if the solution is valid and `hash` is its v2 hash for the challenge
+    pub fn is_valid_hash_v2(&self, challenge: &[u8; 32], hash: &[u8; 32]) -> bool {
+        ct_eq_hash(&self.to_hash_v2(challenge).h, hash) && self.is_valid(challenge)
+    }
+
     pub fn from_bytes(bytes: [u8; 24]) -> Self {
         let mut d = [0u8; 16];
         let mut n = [0u8; 8];
@@ -181,12 +734,137 @@ impl Solution {
         bytes[16..].copy_from_slice(&self.n);
         bytes
     }
+
+    /// Encodes the solution with a leading version byte
+    pub fn to_bytes_versioned(&self) -> Vec<u8> {
+        wire::VersionedSolution::V1(*self).to_bytes()
+    }
+
+    /// Decodes a solution with a leading version byte
+    pub fn from_bytes_versioned(
+        bytes: &[u8],
+    ) -> Result<wire::VersionedSolution, wire::DecodeError> {
+        wire::VersionedSolution::decode(bytes)
+    }
+}
+
+/// A solution together with its hash and difficulty
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct ScoredSolution {
+    pub solution: Solution,
+    pub hash: [u8; 32],
+    pub difficulty: u32,
+}
+
+/// A drillx solution that also identifies which of the seed's equix solutions it is
+///
+/// The index is the solution's position in canonical order (see [`hash_all`]), so
+/// converting a legacy [`Solution`] takes a solve to find it
+/// ([`SolutionV2::from_legacy`]).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct SolutionV2 {
+    pub d: [u8; 16], // digest
+    pub n: [u8; 8],  // nonce
+    pub idx: u8,     // canonical solution index
+}
+
+impl SolutionV2 {
+    pub fn new(digest: [u8; 16], nonce: [u8; 8], idx: u8) -> SolutionV2 {
+        SolutionV2 {
+            d: digest,
+            n: nonce,
+            idx,
+        }
+    }
+
+    /// Returns true if the digest is valid and the index is in range
+    ///
+    /// This is as cheap as [`Solution::is_valid`] and does not check that the digest
+    /// sits at the claimed index.
+    pub fn is_valid(&self, challenge: &[u8; 32]) -> bool {
+        (self.idx as usize) < MAX_SOLUTIONS && is_valid_digest(challenge, &self.n, &self.d)
+    }
+
+    /// Returns true if the solution is valid and the digest is at its claimed index
+    ///
+    /// This re-solves the seed and is far more expensive than [`SolutionV2::is_valid`].
+    #[cfg(feature = "solve")]
+    pub fn is_valid_strict(&self, challenge: &[u8; 32]) -> bool {
+        if !self.is_valid(challenge) {
+            return false;
+        }
+        match hash_all(challenge, &self.n) {
+            Ok(hashes) => hashes
+                .get(self.idx as usize)
+                .is_some_and(|hash| ct_eq_digest(&hash.d, &self.d)),
+            Err(_) => false,
+        }
+    }
+
+    /// Returns the legacy solution with its index, or `None` if its digest is not one
+    /// of the seed's solutions.
+    ///
+    /// Like [`SolutionV2::is_valid_strict`], this re-solves the seed.
+    #[cfg(feature = "solve")]
+    pub fn from_legacy(challenge: &[u8; 32], solution: &Solution) -> Option<SolutionV2> {
+        let idx = hash_all(challenge, &solution.n)
+            .ok()?
+            .iter()
+            .position(|hash| ct_eq_digest(&hash.d, &solution.d))?;
+        Some(SolutionV2::new(solution.d, solution.n, idx as u8))
+    }
+
+    /// Calculates the result hash for a given solution
+    pub fn to_hash(&self) -> Hash {
+        Hash {
+            d: self.d,
+            h: hashv(&self.d, &self.n),
+        }
+    }
+
+    pub fn from_bytes(bytes: [u8; 25]) -> Self {
+        let mut d = [0u8; 16];
+        let mut n = [0u8; 8];
+        d.copy_from_slice(&bytes[..16]);
+        n.copy_from_slice(&bytes[16..24]);
+        SolutionV2 {
+            d,
+            n,
+            idx: bytes[24],
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; 25] {
+        let mut bytes = [0; 25];
+        bytes[..16].copy_from_slice(&self.d);
+        bytes[16..24].copy_from_slice(&self.n);
+        bytes[24] = self.idx;
+        bytes
+    }
+
+    /// Encodes the solution with a leading version byte
+    pub fn to_bytes_versioned(&self) -> Vec<u8> {
+        wire::VersionedSolution::V2(*self).to_bytes()
+    }
+}
+
+/// Drops the index.
+impl From<SolutionV2> for Solution {
+    fn from(solution: SolutionV2) -> Self {
+        Solution::new(solution.d, solution.n)
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum DrillxError {
     BadEquix,
     NoSolutions,
+    /// The compiled runtime was required but the program failed to compile.
+    CompileFailed,
+    /// The compiled runtime was required but does not exist for this architecture.
+    CompilerUnsupported,
 }
 
 impl std::fmt::Display for DrillxError {
@@ -194,6 +872,10 @@ impl std::fmt::Display for DrillxError {
         match *self {
             DrillxError::BadEquix => write!(f, "Failed equix"),
             DrillxError::NoSolutions => write!(f, "No solutions"),
+            DrillxError::CompileFailed => write!(f, "Failed to compile equix program"),
+            DrillxError::CompilerUnsupported => {
+                write!(f, "Compiled runtime is not supported on this architecture")
+            }
         }
     }
 }
@@ -0,0 +1,175 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex,
+};
+
+/// Reusable solver memory for drillx hashing.
+///
+/// The solver needs a few megabytes of scratch space per hash. Allocating it once
+/// and passing it to [`hash_with_memory`](crate::hash_with_memory) avoids paying
+/// for that allocation on every nonce.
+pub struct DrillxMemory {
+    inner: equix::SolverMemory,
+}
+
+impl DrillxMemory {
+    /// Size of the solver memory region, in bytes.
+    pub const SIZE: usize = equix::SolverMemory::SIZE;
+
+    /// Allocates new solver memory.
+    ///
+    /// Aborts the process if the allocation fails; see [`try_new`](Self::try_new).
+    pub fn new() -> Self {
+        DrillxMemory {
+            inner: equix::SolverMemory::new(),
+        }
+    }
+
+    /// Allocates new solver memory, or fails if the allocator cannot spare it.
+    ///
+    /// Equix allocates infallibly, so this reserves [`SIZE`](Self::SIZE) bytes itself
+    /// first and hands them back just before equix asks for them. That catches a heap
+    /// already at its limit, but not one that fills in between, nor an overcommitting
+    /// kernel that only runs out when the pages are touched.
+    pub fn try_new() -> Result<Self, MemoryError> {
+        let mut probe: Vec<u8> = Vec::new();
+        probe
+            .try_reserve_exact(Self::SIZE)
+            .map_err(|_| MemoryError { size: Self::SIZE })?;
+        drop(probe);
+        Ok(Self::new())
+    }
+
+    /// Returns the wrapped equix memory.
+    #[cfg(feature = "equix-compat")]
+    pub fn into_inner(self) -> equix::SolverMemory {
+        self.inner
+    }
+
+    pub(crate) fn as_equix_mut(&mut self) -> &mut equix::SolverMemory {
+        &mut self.inner
+    }
+}
+
+impl std::fmt::Debug for DrillxMemory {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("DrillxMemory")
+            .field("size", &Self::SIZE)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for DrillxMemory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "equix-compat")]
+impl From<equix::SolverMemory> for DrillxMemory {
+    fn from(inner: equix::SolverMemory) -> Self {
+        DrillxMemory { inner }
+    }
+}
+
+/// Solver memory could not be allocated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MemoryError {
+    /// Bytes requested.
+    pub size: usize,
+}
+
+impl std::fmt::Display for MemoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Failed to allocate {} bytes of solver memory", self.size)
+    }
+}
+
+impl std::error::Error for MemoryError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+/// Allocates solver memory for a [`MemoryPool`].
+///
+/// [`SystemAllocator`] is the real implementation; others can limit or fail
+/// allocations, for tests or to stay under a budget.
+pub trait MemoryAllocator: Send + Sync {
+    fn allocate(&self) -> Result<DrillxMemory, MemoryError>;
+}
+
+/// Allocates with [`DrillxMemory::try_new`].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemAllocator;
+
+impl MemoryAllocator for SystemAllocator {
+    fn allocate(&self) -> Result<DrillxMemory, MemoryError> {
+        DrillxMemory::try_new()
+    }
+}
+
+/// Solver memory shared out to workers and kept for reuse when they are done.
+///
+/// Memory released to the pool stays allocated until the pool is dropped, so workers
+/// that come and go reuse it instead of allocating again.
+pub struct MemoryPool {
+    allocator: Box<dyn MemoryAllocator>,
+    free: Mutex<Vec<DrillxMemory>>,
+    allocated: AtomicUsize,
+}
+
+impl std::fmt::Debug for MemoryPool {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("MemoryPool")
+            .field("allocated", &self.allocated())
+            .field("idle", &self.idle())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for MemoryPool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MemoryPool {
+    /// A pool allocating with the [`SystemAllocator`].
+    pub fn new() -> Self {
+        Self::with_allocator(SystemAllocator)
+    }
+
+    pub fn with_allocator(allocator: impl MemoryAllocator + 'static) -> Self {
+        MemoryPool {
+            allocator: Box::new(allocator),
+            free: Mutex::new(Vec::new()),
+            allocated: AtomicUsize::new(0),
+        }
+    }
+
+    /// Takes memory from the pool, allocating it if none is idle.
+    pub fn try_acquire(&self) -> Result<DrillxMemory, MemoryError> {
+        if let Some(memory) = self.free.lock().unwrap().pop() {
+            return Ok(memory);
+        }
+        let memory = self.allocator.allocate()?;
+        self.allocated.fetch_add(1, Ordering::Relaxed);
+        Ok(memory)
+    }
+
+    /// Returns memory to the pool for reuse.
+    pub fn release(&self, memory: DrillxMemory) {
+        self.free.lock().unwrap().push(memory);
+    }
+
+    /// Number of allocations the pool has made.
+    pub fn allocated(&self) -> usize {
+        self.allocated.load(Ordering::Relaxed)
+    }
+
+    /// Number of allocations waiting in the pool to be reused.
+    pub fn idle(&self) -> usize {
+        self.free.lock().unwrap().len()
+    }
+}
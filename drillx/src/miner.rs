@@ -0,0 +1,2703 @@
+//! Multi-threaded nonce search.
+//!
+//! A miner works on one or more challenge jobs at once. Each job has its own nonce
+//! cursor, and workers claim disjoint chunks of nonces from it, so no nonce is ever
+//! hashed twice for the same job within a run. Workers interleave chunks across jobs
+//! in proportion to their weights, reusing the same solver memory for all of them.
+//!
+//! A job is solved once a solution meeting its minimum difficulty is found, and the
+//! search ends when every job is solved, the deadline passes, the run is cancelled, or
+//! the nonce space of every unsolved job is exhausted. The best solution seen for each
+//! job is always reported.
+//!
+//! A running miner can be paused and resumed, its thread count changed, and its
+//! challenges swapped through its handle. Paused workers park
+//! without giving up their place in the nonce space, so the search picks up exactly
+//! where it stopped.
+//!
+//! Workers are supervised. One that panics or stops making progress is replaced by a
+//! fresh worker with its own solver memory, which picks up the rest of its chunk. Too
+//! many replacements in a minute end the run with [`MinerError::TooManyRestarts`].
+//!
+//! Workers slower than the fastest one claim proportionally smaller chunks, so that a
+//! worker on an efficiency core cannot hold up the end of a search for long. On hybrid
+//! CPUs workers can also be kept on, or placed first on, performance cores; see
+//! [`MinerBuilder::core_preference`].
+//!
+//! Solver memory comes from a [`MemoryPool`]. When it runs out, the miner runs with as
+//! many workers as it could start, reports a [`MinerWarning::MemoryExhausted`] in its
+//! progress, and retries starting the rest every [`MinerBuilder::memory_retry`].
+//!
+//! Accelerators such as GPUs join a run as extra workers with their own solvers; see
+//! [`MinerBuilder::accelerator`]. They share the job cursors, best solutions, and stop
+//! conditions with the CPU workers, and claim chunks scaled up to their measured speed.
+//!
+//! A [`ThrottleHook`] can slow a running miner down or park it, as a device heats up or
+//! unplugs; see [`MinerBuilder::throttle`].
+//!
+//! In streaming mode jobs are never solved. Every solution meeting the minimum
+//! difficulty is sent to a bounded channel instead, and solutions that find the channel
+//! full are dropped and counted.
+//!
+//! Where threads cannot block, a [`SteppingMiner`] runs the same search on the
+//! caller's thread, a given number of hashes per call.
+
+use std::{
+    collections::{BTreeSet, VecDeque},
+    ops::RangeInclusive,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender, SyncSender, TrySendError},
+        Arc, Condvar, Mutex, PoisonError, RwLock,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "signing")]
+use crate::work::{SignedWorkUnit, WorkAuthError};
+use crate::{
+    telemetry::{self, event},
+    throttle::{self, ThrottleHook},
+    topology::{self, Topology},
+    Context, DifficultyHistogram, DrillxError, EquixSolver, HistogramSnapshot, Interleaved,
+    MemoryError, MemoryPool, NamespaceError, NonceNamespace, NoncePermutation, Runtime,
+    RuntimeOption, ScoredSolution, SelfTestError, SelfTestReport, Solution, Solver,
+};
+
+/// How often the coordinator wakes up to check the deadline.
+const TICK: Duration = Duration::from_millis(20);
+
+/// Workers at least this fraction of the fastest worker's speed claim full chunks.
+const FULL_CHUNK_SPEED: f64 = 0.9;
+
+/// Weight of the latest chunk in a worker's smoothed speed.
+const SPEED_SMOOTHING: f64 = 0.3;
+
+/// Accelerator chunks are at most this many times the chunk size.
+const MAX_ACCELERATOR_SCALE: u64 = 1 << 16;
+
+/// Window over which [`MinerConfig::restart_limit`] applies.
+pub(crate) const RESTART_WINDOW: Duration = Duration::from_secs(60);
+
+/// Creates the solver of each worker.
+type SolverFactory = Arc<dyn Fn() -> Result<Box<dyn Solver>, MemoryError> + Send + Sync>;
+
+/// Configuration for a mining run.
+#[derive(Clone, Debug)]
+pub struct MinerConfig {
+    /// Number of worker threads.
+    pub threads: usize,
+    /// Minimum difficulty of a solution that ends the search.
+    ///
+    /// Only used for single-challenge runs; each [`ChallengeJob`] carries its own.
+    pub min_difficulty: u32,
+    /// Wall-clock limit on the search.
+    pub deadline: Option<Duration>,
+    /// First nonce to search.
+    pub start_nonce: u64,
+    /// Last nonce to search, or `None` to search up to `u64::MAX`.
+    pub end_nonce: Option<u64>,
+    /// The order nonces are searched in. See [`MinerBuilder::nonce_order`].
+    pub nonce_order: NonceOrder,
+    /// The namespace and worker id whose counters are searched. See
+    /// [`MinerBuilder::nonce_namespace`].
+    pub nonce_namespace: Option<(NonceNamespace, u64)>,
+    /// Number of nonces a worker claims at a time.
+    pub chunk_size: u64,
+    /// Capacity of the solution channel, enabling streaming mode.
+    pub stream: Option<usize>,
+    /// Which equix runtime workers hash with.
+    pub runtime: RuntimeOption,
+    /// Returns the qualifying solution with the lowest nonce instead of the first one
+    /// found. See [`MinerBuilder::deterministic`].
+    pub deterministic: bool,
+    /// Runs [`self_test`](crate::self_test) before starting and avoids the compiled
+    /// runtime if it disagrees with the test vectors.
+    pub self_test: bool,
+    /// Pushes the deadline back by the time spent paused.
+    pub pause_extends_deadline: bool,
+    /// Which cores workers run on. See [`MinerBuilder::core_preference`].
+    pub core_preference: Prefer,
+    /// Streams solutions found for a job after it was removed. See
+    /// [`MinerBuilder::deliver_stale`].
+    pub deliver_stale: bool,
+    /// Most worker replacements allowed in any minute.
+    pub restart_limit: u32,
+    /// How long a busy worker may go without hashing before it is replaced.
+    pub stall_timeout: Option<Duration>,
+    /// Records the difficulty of every hash in [`Progress::histogram`].
+    pub histogram: bool,
+    /// How long to wait before retrying workers that failed to start for lack of memory.
+    pub memory_retry: Duration,
+    /// Solver memories each CPU worker alternates between. See [`MinerBuilder::interleave`].
+    pub interleave: u8,
+    /// How often the [`ThrottleHook`] is polled. See [`MinerBuilder::throttle_poll`].
+    pub throttle_poll: Duration,
+    /// The pool whose signed work the miner must start from. See
+    /// [`MinerBuilder::expected_pool_pubkey`].
+    #[cfg(feature = "signing")]
+    pub expected_pool_pubkey: Option<[u8; 32]>,
+}
+
+impl Default for MinerConfig {
+    fn default() -> Self {
+        MinerConfig {
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            min_difficulty: 0,
+            deadline: None,
+            start_nonce: 0,
+            end_nonce: None,
+            nonce_order: NonceOrder::Sequential,
+            nonce_namespace: None,
+            chunk_size: 64,
+            stream: None,
+            runtime: RuntimeOption::TryCompile,
+            deterministic: false,
+            self_test: false,
+            pause_extends_deadline: false,
+            core_preference: Prefer::All,
+            deliver_stale: true,
+            restart_limit: 8,
+            stall_timeout: Some(Duration::from_secs(30)),
+            histogram: false,
+            memory_retry: Duration::from_secs(5),
+            interleave: 1,
+            throttle_poll: Duration::from_millis(250),
+            #[cfg(feature = "signing")]
+            expected_pool_pubkey: None,
+        }
+    }
+}
+
+/// The order a miner searches nonces in.
+///
+/// Workers claim chunks of consecutive indices from each job's cursor as always, and
+/// hash the nonce at each index. Every order is a bijection, so no nonce is hashed twice
+/// and every nonce is reached.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub enum NonceOrder {
+    /// Nonce `i` at index `i`.
+    #[default]
+    Sequential,
+    /// The nonces of a [`NoncePermutation`] with this key, so progress through the
+    /// nonce space looks random. Workers with the same key agree on the order.
+    Permuted { key: [u8; 16] },
+}
+
+impl NonceOrder {
+    /// The nonce at index `i`.
+    pub fn nonce(&self, i: u64) -> u64 {
+        match self {
+            NonceOrder::Sequential => i,
+            NonceOrder::Permuted { key } => NoncePermutation::new(*key).permute(i),
+        }
+    }
+
+    /// The index of `nonce`.
+    pub fn index(&self, nonce: u64) -> u64 {
+        match self {
+            NonceOrder::Sequential => nonce,
+            NonceOrder::Permuted { key } => NoncePermutation::new(*key).invert(nonce),
+        }
+    }
+}
+
+/// The first and last index a run searches, once its nonce namespace is checked.
+fn index_range(config: &MinerConfig) -> Result<(u64, u64), NamespaceError> {
+    let mut last = config.end_nonce.unwrap_or(u64::MAX);
+    if let Some((namespace, worker_id)) = config.nonce_namespace {
+        if config.nonce_order != NonceOrder::Sequential {
+            return Err(NamespaceError::PermutedOrder);
+        }
+        namespace.compose(worker_id, config.start_nonce)?;
+        last = last.min(namespace.max_counter());
+    }
+    Ok((config.start_nonce, last))
+}
+
+/// The nonce a run hashes at `index`.
+fn nonce_at(order: NonceOrder, namespace: Option<(NonceNamespace, u64)>, index: u64) -> u64 {
+    match namespace {
+        Some((namespace, worker_id)) => namespace.compose_unchecked(worker_id, index),
+        None => order.nonce(index),
+    }
+}
+
+/// Which cores a miner's workers run on.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Prefer {
+    /// Pins every worker to the performance cores.
+    PerformanceOnly,
+    /// Pins one worker per performance core to the performance cores, and the rest to
+    /// the efficiency cores.
+    PerformanceFirst,
+    /// Leaves placement to the operating system.
+    #[default]
+    All,
+}
+
+/// A challenge to mine alongside others.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChallengeJob {
+    pub challenge: [u8; 32],
+    /// Minimum difficulty of a solution that solves this job.
+    pub min_difficulty: u32,
+    /// Share of the hashing time given to this job, relative to the other jobs.
+    /// A weight of zero is treated as one.
+    pub weight: u32,
+}
+
+impl ChallengeJob {
+    /// Creates a job with a weight of one.
+    pub fn new(challenge: [u8; 32], min_difficulty: u32) -> Self {
+        ChallengeJob {
+            challenge,
+            min_difficulty,
+            weight: 1,
+        }
+    }
+
+    pub fn weight(mut self, weight: u32) -> Self {
+        self.weight = weight;
+        self
+    }
+}
+
+/// Identifies a job within a mining run, in the order jobs were added.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct JobId(pub u64);
+
+/// Where a job stands.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JobState {
+    /// The job is still being searched.
+    Active,
+    /// A solution meeting the job's minimum difficulty was found.
+    Solved,
+    /// Every nonce from the start nonce to the end nonce was searched.
+    Exhausted,
+    /// The job was removed through the miner's handle.
+    Removed,
+}
+
+/// The per-job results of a mining run.
+#[derive(Clone, Debug)]
+pub struct JobStatus {
+    pub id: JobId,
+    pub challenge: [u8; 32],
+    pub min_difficulty: u32,
+    pub state: JobState,
+    /// The best solution seen for this job, which may be below its minimum difficulty.
+    ///
+    /// In deterministic mode, a solved job reports its lowest qualifying nonce instead.
+    pub best: Option<ScoredSolution>,
+    /// Number of nonces hashed for this job.
+    pub hashes: u64,
+    /// Number of solutions meeting the job's minimum difficulty.
+    pub solutions: u64,
+}
+
+/// A solution sent through the stream, labelled with the job it solves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JobSolution {
+    pub job: JobId,
+    pub challenge: [u8; 32],
+    pub scored: ScoredSolution,
+}
+
+/// A change to a running miner, sent through [`MinerHandle::events`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MinerEvent {
+    /// [`MinerHandle::set_challenge`] replaced the miner's jobs with a new one.
+    ChallengeChanged {
+        /// The new job.
+        job: JobId,
+        challenge: [u8; 32],
+        min_difficulty: u32,
+        /// The jobs it replaced, in the order they were added.
+        replaced: Vec<JobId>,
+    },
+}
+
+/// Why a mining run ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub enum StopReason {
+    /// A solution meeting the minimum difficulty was found for every job.
+    Found,
+    /// The deadline passed.
+    Deadline,
+    /// The run was cancelled through its handle.
+    Cancelled,
+    /// Every job that was not solved ran out of nonces.
+    Exhausted,
+}
+
+/// The result of a mining run.
+#[derive(Clone, Debug)]
+pub struct MineOutcome {
+    /// The best solution seen across all jobs, which may be below the minimum difficulty.
+    pub best: Option<ScoredSolution>,
+    /// Number of nonces hashed.
+    pub hashes: u64,
+    /// Number of solutions meeting the minimum difficulty.
+    pub solutions: u64,
+    /// Number of solutions dropped because the stream was full.
+    pub dropped: u64,
+    /// Wall-clock duration of the run.
+    pub elapsed: Duration,
+    /// Why the run ended.
+    pub reason: StopReason,
+    /// True if a worker stopped compiling after repeated compile failures.
+    pub runtime_downgraded: bool,
+    /// Results of each job that was not removed, in the order they were added.
+    pub jobs: Vec<JobStatus>,
+}
+
+/// The kind of hardware a worker hashes on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Backend {
+    /// A worker thread hashing on the CPU.
+    Cpu,
+    /// A worker driving an accelerator. See [`MinerBuilder::accelerator`].
+    Accelerator,
+}
+
+/// The share of a miner's work done on one backend.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BackendProgress {
+    pub backend: Backend,
+    /// Number of workers on the backend.
+    pub workers: usize,
+    /// Number of nonces the backend hashed so far.
+    pub hashes: u64,
+    /// Average hashes per second of active mining, or zero while paused.
+    pub hashrate: f64,
+}
+
+/// A problem a running miner is working around.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MinerWarning {
+    /// Solver memory ran out, so fewer CPU workers are running than requested. The
+    /// miner keeps retrying the rest.
+    MemoryExhausted {
+        requested: usize,
+        running: usize,
+        /// The last allocation that failed.
+        error: MemoryError,
+    },
+}
+
+/// A snapshot of a running miner.
+#[derive(Clone, Debug)]
+pub struct Progress {
+    /// Number of nonces hashed so far.
+    pub hashes: u64,
+    /// Number of solutions meeting the minimum difficulty so far.
+    pub solutions: u64,
+    /// Number of solutions dropped because the stream was full.
+    pub dropped: u64,
+    /// Number of solutions not streamed because their job had been removed.
+    pub stale_dropped: u64,
+    /// Number of times [`MinerHandle::set_challenge`] swapped the challenge.
+    pub challenge_changes: u64,
+    /// Number of workers replaced after panicking or stalling.
+    pub restarts: u64,
+    /// Time since the miner started.
+    pub elapsed: Duration,
+    /// Time spent mining since the miner started, excluding pauses.
+    pub active: Duration,
+    /// True while the miner is paused.
+    pub paused: bool,
+    /// The intensity workers run at, from the [`ThrottleHook`] or 1.0 without one.
+    pub intensity: f32,
+    /// Number of CPU worker threads the miner is running with.
+    pub threads: usize,
+    /// Work done on each backend with workers, CPU first.
+    pub backends: Vec<BackendProgress>,
+    /// Problems the miner is working around right now.
+    pub warnings: Vec<MinerWarning>,
+    /// Best solution seen so far across all jobs.
+    pub best: Option<ScoredSolution>,
+    /// True once a worker has stopped compiling after repeated compile failures.
+    pub runtime_downgraded: bool,
+    /// Status of each job, in the order they were added.
+    pub jobs: Vec<JobStatus>,
+    /// The core topology detected at startup.
+    pub topology: Topology,
+    /// Difficulties of every hash so far, if [`MinerBuilder::histogram`] is enabled.
+    pub histogram: Option<HistogramSnapshot>,
+}
+
+impl Progress {
+    /// Average hashes per second of active mining, or zero while paused.
+    pub fn hashrate(&self) -> f64 {
+        average_rate(self.hashes, self.active, self.paused)
+    }
+
+    /// Returns the work done on a backend, if it has workers.
+    pub fn backend(&self, backend: Backend) -> Option<&BackendProgress> {
+        self.backends.iter().find(|b| b.backend == backend)
+    }
+}
+
+fn average_rate(hashes: u64, active: Duration, paused: bool) -> f64 {
+    let secs = active.as_secs_f64();
+    if secs > 0.0 && !paused {
+        hashes as f64 / secs
+    } else {
+        0.0
+    }
+}
+
+/// Searches for a solution to the challenge, blocking until the run ends.
+pub fn mine(challenge: [u8; 32], config: &MinerConfig) -> Result<MineOutcome, MinerError> {
+    MinerBuilder::new(challenge)
+        .config(config.clone())
+        .spawn()?
+        .join()
+}
+
+/// Searches for solutions to several challenges at once, blocking until the run ends.
+///
+/// The minimum difficulty in `config` is ignored in favor of each job's own.
+pub fn mine_multi(
+    challenges: &[ChallengeJob],
+    config: &MinerConfig,
+) -> Result<MineOutcome, MinerError> {
+    MinerBuilder::multi(challenges)
+        .config(config.clone())
+        .spawn()?
+        .join()
+}
+
+/// Builds and starts a miner.
+pub struct MinerBuilder {
+    target: Target,
+    config: MinerConfig,
+    solver: Option<SolverFactory>,
+    accelerator: Option<(usize, SolverFactory)>,
+    memory: Option<Arc<MemoryPool>>,
+    throttle: Option<Arc<dyn ThrottleHook>>,
+    #[cfg(feature = "signing")]
+    work: Option<SignedWorkUnit>,
+}
+
+/// The challenges a builder starts with.
+enum Target {
+    Single([u8; 32]),
+    Multi(Vec<ChallengeJob>),
+}
+
+impl MinerBuilder {
+    pub fn new(challenge: [u8; 32]) -> Self {
+        MinerBuilder {
+            target: Target::Single(challenge),
+            config: MinerConfig::default(),
+            solver: None,
+            accelerator: None,
+            memory: None,
+            throttle: None,
+            #[cfg(feature = "signing")]
+            work: None,
+        }
+    }
+
+    /// Starts from several challenge jobs instead of a single challenge.
+    pub fn multi(challenges: &[ChallengeJob]) -> Self {
+        MinerBuilder {
+            target: Target::Multi(challenges.to_vec()),
+            config: MinerConfig::default(),
+            solver: None,
+            accelerator: None,
+            memory: None,
+            throttle: None,
+            #[cfg(feature = "signing")]
+            work: None,
+        }
+    }
+
+    /// Replaces the whole configuration.
+    pub fn config(mut self, config: MinerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.config.threads = threads;
+        self
+    }
+
+    /// Uses the thread count recommended by [`autotune`](crate::autotune).
+    ///
+    /// The first call in a process blocks for up to
+    /// [`AUTO_TUNE_BUDGET`](crate::AUTO_TUNE_BUDGET) while tuning; later calls reuse the
+    /// result.
+    pub fn threads_auto(mut self) -> Self {
+        self.config.threads = crate::tune::recommended_threads();
+        self
+    }
+
+    pub fn min_difficulty(mut self, min_difficulty: u32) -> Self {
+        self.config.min_difficulty = min_difficulty;
+        self
+    }
+
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.config.deadline = Some(deadline);
+        self
+    }
+
+    pub fn start_nonce(mut self, start_nonce: u64) -> Self {
+        self.config.start_nonce = start_nonce;
+        self
+    }
+
+    /// Stops searching each job after `end_nonce`. A job whose nonces run out is
+    /// [exhausted](JobState::Exhausted).
+    pub fn end_nonce(mut self, end_nonce: u64) -> Self {
+        self.config.end_nonce = Some(end_nonce);
+        self
+    }
+
+    /// Searches nonces in `order`. Defaults to [`NonceOrder::Sequential`].
+    ///
+    /// The start and end nonces, including a work unit's, are then indices into the
+    /// order, and in [deterministic](Self::deterministic) mode the solution is the one
+    /// earliest in the order rather than the lowest nonce.
+    pub fn nonce_order(mut self, order: NonceOrder) -> Self {
+        self.config.nonce_order = order;
+        self
+    }
+
+    /// Searches only the nonces of `worker_id` in `namespace`, so that miners sharing a
+    /// namespace with different ids never hash the same nonce.
+    ///
+    /// The start and end nonces, including a work unit's, are then counters within the
+    /// worker's space, and the end is capped at the namespace's
+    /// [highest counter](NonceNamespace::max_counter). Counters are searched in sequential
+    /// order. The miner fails to start with [`MinerError::Namespace`] if the id or start
+    /// counter does not fit, or if the nonce order is permuted.
+    pub fn nonce_namespace(mut self, namespace: NonceNamespace, worker_id: u64) -> Self {
+        self.config.nonce_namespace = Some((namespace, worker_id));
+        self
+    }
+
+    /// Mines a pool's work unit: its challenge at its minimum difficulty, over its
+    /// nonces only.
+    ///
+    /// The unit is checked when the miner starts if
+    /// [`expected_pool_pubkey`](Self::expected_pool_pubkey) is set, and trusted as it
+    /// is otherwise.
+    #[cfg(feature = "signing")]
+    pub fn work(mut self, signed: SignedWorkUnit) -> Self {
+        let work = signed.work;
+        self.target = Target::Single(work.challenge);
+        self.config.min_difficulty = work.min_difficulty;
+        self.config.start_nonce = work.start;
+        self.config.end_nonce = work.last_nonce();
+        self.work = Some(signed);
+        self
+    }
+
+    /// Refuses to start unless started from [`work`](Self::work) signed by `pool` that
+    /// has not expired by the system clock, failing with [`MinerError::WorkAuth`].
+    ///
+    /// Only the starting work is checked. Challenges later set through the handle are
+    /// the caller's to verify.
+    #[cfg(feature = "signing")]
+    pub fn expected_pool_pubkey(mut self, pool: [u8; 32]) -> Self {
+        self.config.expected_pool_pubkey = Some(pool);
+        self
+    }
+
+    pub fn chunk_size(mut self, chunk_size: u64) -> Self {
+        self.config.chunk_size = chunk_size;
+        self
+    }
+
+    /// Streams every solution through a channel of the given capacity instead of
+    /// stopping at the first one.
+    pub fn stream(mut self, capacity: usize) -> Self {
+        self.config.stream = Some(capacity);
+        self
+    }
+
+    /// Makes the result independent of thread count and scheduling.
+    ///
+    /// A job is only solved once the lowest nonce at or after the start nonce that
+    /// meets its minimum difficulty is known: workers keep searching every unfinished
+    /// chunk below the lowest qualifying nonce found so far, and the job is solved when
+    /// no such chunk remains. That solution is then reported as the job's best, whatever
+    /// the difficulties of other solutions seen. Runs that end early, by deadline or
+    /// cancellation, report the best solution seen as usual. Has no effect in streaming
+    /// mode.
+    pub fn deterministic(mut self, deterministic: bool) -> Self {
+        self.config.deterministic = deterministic;
+        self
+    }
+
+    /// Sets the runtime workers hash with. With the default solver,
+    /// [`RuntimeOption::RequireCompile`] fails to spawn with [`MinerError::Runtime`]
+    /// where the compiler is unavailable, and a compile failure mid-run ends the run with
+    /// the same error.
+    pub fn runtime(mut self, runtime: RuntimeOption) -> Self {
+        self.config.runtime = runtime;
+        self
+    }
+
+    /// Pushes the deadline back by the time spent paused.
+    pub fn pause_extends_deadline(mut self, extends: bool) -> Self {
+        self.config.pause_extends_deadline = extends;
+        self
+    }
+
+    /// Chooses which cores workers run on. Defaults to [`Prefer::All`].
+    ///
+    /// This only has an effect on Linux machines with a [hybrid](Topology::Hybrid)
+    /// topology, where workers are pinned to the chosen cores when they start.
+    /// Elsewhere workers are never pinned, but every worker still claims chunks sized to
+    /// its measured speed.
+    pub fn core_preference(mut self, prefer: Prefer) -> Self {
+        self.config.core_preference = prefer;
+        self
+    }
+
+    /// Whether to stream solutions found for a job after it was removed or replaced.
+    /// Defaults to true.
+    ///
+    /// Such solutions were being hashed when the job went away. Either way they are
+    /// labelled with the job they solve, never the one that replaced it. When not
+    /// delivered they are counted in [`Progress::stale_dropped`].
+    pub fn deliver_stale(mut self, deliver: bool) -> Self {
+        self.config.deliver_stale = deliver;
+        self
+    }
+
+    /// Sets how many times workers may be replaced in any minute before the run fails
+    /// with [`MinerError::TooManyRestarts`]. Defaults to 8; zero makes any panic or
+    /// stall fatal.
+    pub fn restart_limit(mut self, limit: u32) -> Self {
+        self.config.restart_limit = limit;
+        self
+    }
+
+    /// Sets how long a worker holding a chunk may go without finishing a hash before
+    /// it is replaced. Defaults to 30 seconds. Time spent paused does not count.
+    ///
+    /// A stalled worker cannot be stopped, only abandoned: if it ever finishes its
+    /// hash, it exits without recording it.
+    pub fn stall_timeout(mut self, timeout: Duration) -> Self {
+        self.config.stall_timeout = Some(timeout);
+        self
+    }
+
+    /// Whether to record the difficulty of every hash, including those below the
+    /// minimum, in [`Progress::histogram`]. Defaults to false.
+    ///
+    /// Comparing the counts against
+    /// [`DifficultyHistogram::expected_count_at`] shows whether the solver behaves.
+    pub fn histogram(mut self, enabled: bool) -> Self {
+        self.config.histogram = enabled;
+        self
+    }
+
+    /// Hashes with solvers made by `factory` instead of [`EquixSolver`], one per worker
+    /// and replacement worker.
+    pub fn solver<S, F>(mut self, factory: F) -> Self
+    where
+        S: Solver + 'static,
+        F: Fn() -> S + Send + Sync + 'static,
+    {
+        self.solver = Some(Arc::new(move || Ok(Box::new(factory()) as Box<dyn Solver>)));
+        self
+    }
+
+    /// Like [`solver`](Self::solver), for factories that can run out of memory, such as
+    /// ones wrapping [`EquixSolver::pooled`]. A worker whose solver fails is handled as
+    /// one that could not get memory.
+    pub fn try_solver<S, F>(mut self, factory: F) -> Self
+    where
+        S: Solver + 'static,
+        F: Fn() -> Result<S, MemoryError> + Send + Sync + 'static,
+    {
+        self.solver = Some(Arc::new(move || {
+            factory().map(|solver| Box::new(solver) as Box<dyn Solver>)
+        }));
+        self
+    }
+
+    /// Takes solver memory from `pool` instead of a pool of the miner's own. Has no
+    /// effect with a custom [`solver`](Self::solver) or [`try_solver`](Self::try_solver).
+    ///
+    /// Workers release their memory to the pool when they exit, so pools can be shared
+    /// by successive runs.
+    pub fn memory_pool(mut self, pool: Arc<MemoryPool>) -> Self {
+        self.memory = Some(pool);
+        self
+    }
+
+    /// Sets how long to wait before retrying workers that failed to start for lack of
+    /// memory. Defaults to 5 seconds.
+    pub fn memory_retry(mut self, retry: Duration) -> Self {
+        self.config.memory_retry = retry;
+        self
+    }
+
+    /// Gives each CPU worker `interleave` solver memories to alternate between, one
+    /// whole solve at a time, as an [`Interleaved`] solver. Defaults to 1; zero is
+    /// treated as one.
+    ///
+    /// Results are the same whatever the setting, but each worker takes `interleave`
+    /// times the memory. Equix solves can't be suspended, so this alternates whole
+    /// solves; it has measured slower on x86_64, so only use it where the `interleave`
+    /// benchmark shows a gain.
+    pub fn interleave(mut self, interleave: u8) -> Self {
+        self.config.interleave = interleave;
+        self
+    }
+
+    /// Throttles the miner with `hook`, polled every [`throttle_poll`](Self::throttle_poll)
+    /// from a thread of its own. See [`throttle`](crate::throttle) for how the intensity
+    /// maps onto a duty cycle.
+    ///
+    /// Throttling is not pausing: a parked miner reports itself unpaused, its deadline
+    /// keeps running, and its hashrate reflects the time spent parked or resting.
+    pub fn throttle(mut self, hook: impl ThrottleHook + 'static) -> Self {
+        self.throttle = Some(Arc::new(hook));
+        self
+    }
+
+    /// Sets how often the [`throttle`](Self::throttle) hook is polled. Defaults to 250
+    /// milliseconds.
+    pub fn throttle_poll(mut self, poll: Duration) -> Self {
+        self.config.throttle_poll = poll;
+        self
+    }
+
+    /// Adds `workers` accelerator workers, such as one per GPU, hashing with solvers
+    /// made by `factory`.
+    ///
+    /// Accelerator workers run alongside the CPU threads and are not counted in them.
+    /// Each claims chunks of [`accelerator_chunk_size`], scaled to its measured speed
+    /// relative to the fastest CPU worker, so that its chunks take about as long as the
+    /// CPU's. Deadlines, cancellation, pauses, and challenge changes apply to them as to
+    /// any worker, and their work is reported under [`Backend::Accelerator`].
+    pub fn accelerator<S, F>(mut self, workers: usize, factory: F) -> Self
+    where
+        S: Solver + 'static,
+        F: Fn() -> S + Send + Sync + 'static,
+    {
+        let factory: SolverFactory = Arc::new(move || Ok(Box::new(factory()) as Box<dyn Solver>));
+        self.accelerator = (workers > 0).then_some((workers, factory));
+        self
+    }
+
+    /// Runs the self-test before starting.
+    ///
+    /// If the compiled runtime disagrees with the test vectors, the miner falls back to
+    /// the interpreter, or refuses to start if the compiled runtime is required.
+    pub fn self_test(mut self, self_test: bool) -> Self {
+        self.config.self_test = self_test;
+        self
+    }
+
+    /// Starts the worker threads and returns a handle to the running miner.
+    pub fn spawn(self) -> Result<MinerHandle, MinerError> {
+        let mut config = self.config;
+        let (start_nonce, last_nonce) = index_range(&config).map_err(MinerError::Namespace)?;
+        #[cfg(feature = "signing")]
+        if let Some(pool) = config.expected_pool_pubkey {
+            let signed = self
+                .work
+                .as_ref()
+                .ok_or(MinerError::WorkAuth(WorkAuthError::Unsigned))?;
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs());
+            crate::work::verify_work_at(signed, &pool, now).map_err(MinerError::WorkAuth)?;
+        }
+        if config.self_test {
+            let report = crate::self_test().map_err(MinerError::SelfTest)?;
+            if report.trusted == Runtime::Interpreted && report.compiled_disagrees() {
+                if config.runtime == RuntimeOption::RequireCompile {
+                    return Err(MinerError::UntrustedCompiler(report));
+                }
+                config.runtime = RuntimeOption::InterpretOnly;
+            }
+        }
+        if config.runtime == RuntimeOption::RequireCompile
+            && self.solver.is_none()
+            && !crate::runtime_info().compiler_available
+        {
+            return Err(MinerError::Runtime(DrillxError::CompilerUnsupported));
+        }
+        let (stream, solutions) = match config.stream {
+            Some(capacity) => {
+                let (tx, rx) = mpsc::sync_channel(capacity);
+                (Some(tx), Some(rx))
+            }
+            None => (None, None),
+        };
+        let (events_tx, events) = mpsc::channel();
+        let (accelerators, accelerator) = match self.accelerator {
+            Some((workers, factory)) => (workers, Some(factory)),
+            None => (0, None),
+        };
+        let shared = Arc::new(Shared {
+            chunk_size: config.chunk_size.max(1),
+            runtime: config.runtime,
+            deterministic: config.deterministic && stream.is_none(),
+            start_nonce,
+            end_nonce: last_nonce.max(start_nonce),
+            nonce_order: config.nonce_order,
+            nonce_namespace: config.nonce_namespace,
+            jobs: RwLock::new(Vec::new()),
+            next_job: AtomicU64::new(0),
+            workers: Mutex::new(Vec::new()),
+            threads: AtomicUsize::new(0),
+            requested: AtomicUsize::new(0),
+            shortage: Mutex::new(None),
+            memory_retry: config.memory_retry,
+            interleave: config.interleave.max(1),
+            orphans: Mutex::new(Vec::new()),
+            accelerators,
+            backend_hashes: Default::default(),
+            streaming: stream.is_some(),
+            stream: Mutex::new(stream),
+            deliver_stale: config.deliver_stale,
+            events: Mutex::new(Some(events_tx)),
+            challenge_changes: AtomicU64::new(0),
+            hashes: RwLock::new(Vec::new()),
+            no_solutions: AtomicU64::new(0),
+            solutions: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            stale_dropped: AtomicU64::new(0),
+            downgraded: AtomicBool::new(false),
+            stopping: AtomicBool::new(false),
+            reason: Mutex::new(None),
+            signal: Condvar::new(),
+            paused: AtomicBool::new(false),
+            intensity: AtomicU32::new(1f32.to_bits()),
+            pause: Mutex::new(Pause::default()),
+            unpaused: Condvar::new(),
+            pause_extends_deadline: config.pause_extends_deadline,
+            topology: topology::detect(),
+            core_preference: config.core_preference,
+            fastest: AtomicU64::new(0),
+            solver: self.solver.unwrap_or_else(|| {
+                let pool = self.memory.unwrap_or_default();
+                Arc::new(move || {
+                    EquixSolver::pooled(&pool).map(|solver| Box::new(solver) as Box<dyn Solver>)
+                })
+            }),
+            accelerator,
+            restart_limit: config.restart_limit,
+            stall_timeout: config.stall_timeout,
+            restarts: AtomicU64::new(0),
+            histogram: config.histogram.then(DifficultyHistogram::new),
+            runtime_failure: Mutex::new(None),
+            started: Instant::now(),
+        });
+        event!(
+            telemetry::TOPOLOGY_EVENT,
+            INFO,
+            topology = ?shared.topology,
+            preference = ?shared.core_preference,
+        );
+        match self.target {
+            Target::Single(challenge) => {
+                shared.add(ChallengeJob::new(challenge, config.min_difficulty));
+            }
+            Target::Multi(jobs) => {
+                for job in jobs {
+                    shared.add(job);
+                }
+            }
+        }
+
+        let started = shared
+            .set_threads(config.threads)
+            .and_then(|()| shared.spawn_accelerators())
+            .and_then(|()| match *shared.shortage.lock().unwrap() {
+                Some((error, _)) if shared.threads.load(Ordering::Relaxed) == 0 => {
+                    Err(MinerError::OutOfMemory(error))
+                }
+                _ => Ok(()),
+            });
+        if let Err(err) = started {
+            shared.abandon();
+            return Err(err);
+        }
+
+        if let Some(hook) = self.throttle {
+            // Never joined, so that a hook stuck in a call cannot hold up the run's end.
+            let poller = shared.clone();
+            let poll = config.throttle_poll;
+            let spawned = thread::Builder::new()
+                .name("drillx-throttle".to_string())
+                .spawn(move || poller.poll_throttle(&*hook, poll));
+            if let Err(err) = spawned {
+                shared.abandon();
+                return Err(MinerError::Spawn(err));
+            }
+        }
+
+        let deadline = config.deadline.map(|d| shared.started + d);
+        let coordinator = {
+            let shared = shared.clone();
+            thread::Builder::new()
+                .name("drillx-coordinator".to_string())
+                .spawn(move || coordinate(&shared, deadline))
+        }
+        .map_err(|err| {
+            shared.abandon();
+            MinerError::Spawn(err)
+        })?;
+
+        Ok(MinerHandle {
+            shared,
+            coordinator,
+            solutions,
+            events,
+        })
+    }
+}
+
+/// A handle to a running miner.
+pub struct MinerHandle {
+    shared: Arc<Shared>,
+    coordinator: JoinHandle<Result<MineOutcome, MinerError>>,
+    solutions: Option<Receiver<JobSolution>>,
+    events: Receiver<MinerEvent>,
+}
+
+impl MinerHandle {
+    /// Asks the miner to stop. Workers finish the nonce they are hashing and exit, even
+    /// if paused.
+    pub fn cancel(&self) {
+        self.shared.stop(StopReason::Cancelled);
+    }
+
+    /// Pauses the miner. Workers finish the nonce they are hashing and park, keeping
+    /// their claimed chunks. Has no effect if already paused or once the run is ending.
+    pub fn pause(&self) {
+        self.shared.pause();
+    }
+
+    /// Resumes a paused miner. Has no effect if it is not paused.
+    pub fn resume(&self) {
+        self.shared.resume();
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.shared.is_paused()
+    }
+
+    /// Changes the number of worker threads. Zero is treated as one.
+    ///
+    /// New workers start claiming chunks right away, each with its own solver memory.
+    /// Surplus workers finish the chunk they are hashing and exit. Has no effect once
+    /// the run is ending.
+    ///
+    /// Workers that cannot get memory are not an error: the miner reports a
+    /// [`MinerWarning::MemoryExhausted`] and retries them later.
+    pub fn set_threads(&self, threads: usize) -> Result<(), MinerError> {
+        self.shared.set_threads(threads)
+    }
+
+    /// Returns true once the run has ended.
+    pub fn is_finished(&self) -> bool {
+        self.coordinator.is_finished()
+    }
+
+    /// Returns a snapshot of the miner's progress.
+    pub fn progress(&self) -> Progress {
+        self.shared.progress()
+    }
+
+    /// Returns the solution channel in streaming mode. It disconnects once the run ends.
+    pub fn solutions(&self) -> Option<&Receiver<JobSolution>> {
+        self.solutions.as_ref()
+    }
+
+    /// Returns the channel of changes made through the handle. It disconnects once the
+    /// run ends.
+    pub fn events(&self) -> &Receiver<MinerEvent> {
+        &self.events
+    }
+
+    /// Replaces every job of the running miner with a new challenge, returning its id.
+    ///
+    /// The swap is atomic: workers move to the new job at their next hash, with a fresh
+    /// nonce cursor and best solution. A solution finished for a replaced job is either
+    /// streamed with that job's label or dropped, as set by
+    /// [`MinerBuilder::deliver_stale`]. A [`MinerEvent::ChallengeChanged`] is sent once
+    /// the swap is done.
+    pub fn set_challenge(&self, challenge: [u8; 32], min_difficulty: u32) -> JobId {
+        self.shared
+            .set_challenge(ChallengeJob::new(challenge, min_difficulty))
+    }
+
+    /// Adds a challenge to the running miner. Has no effect once the run has ended.
+    pub fn add_challenge(&self, job: ChallengeJob) -> JobId {
+        self.shared.add(job)
+    }
+
+    /// Removes a challenge from the running miner, returning its final status.
+    ///
+    /// Workers abandon the rest of any chunk they claimed for it. Removing the last
+    /// job leaves the miner idle until a job is added or the run ends.
+    pub fn remove_challenge(&self, id: JobId) -> Option<JobStatus> {
+        self.shared.remove(id)
+    }
+
+    /// Waits for the run to end.
+    pub fn join(self) -> Result<MineOutcome, MinerError> {
+        self.coordinator
+            .join()
+            .map_err(|_| MinerError::WorkerPanicked)?
+    }
+}
+
+/// A miner that hashes a slice of nonces per call, for callers without threads.
+///
+/// [`step`](Self::step) hashes up to a given number of nonces on the calling thread
+/// and returns, so a browser's main thread, a game loop, or a single-core device can
+/// interleave mining with its other work at whatever cadence suits it. Nonces are
+/// searched one at a time in order, so the solution that ends the search is the one
+/// at the lowest index, as in [deterministic](MinerBuilder::deterministic) runs.
+///
+/// The miner takes the challenge's minimum difficulty, deadline, nonce range, nonce
+/// order, and runtime from its [`MinerConfig`], and ignores the rest. The deadline
+/// counts only time spent in `step`; without one the miner never reads the clock,
+/// which `wasm32-unknown-unknown` does not have.
+///
+/// Everything but the solver memory serializes, so a search can be saved and resumed
+/// later, by another process or on another machine. The memory is allocated on the
+/// first step.
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct SteppingMiner {
+    challenge: [u8; 32],
+    min_difficulty: u32,
+    deadline: Option<Duration>,
+    /// Time spent in `step`, if there is a deadline.
+    elapsed: Duration,
+    /// The next index to hash, or `None` once the range is exhausted.
+    next: Option<u64>,
+    last: u64,
+    nonce_order: NonceOrder,
+    nonce_namespace: Option<(NonceNamespace, u64)>,
+    runtime: RuntimeOption,
+    best: Option<ScoredSolution>,
+    hashes: u64,
+    done: Option<StopReason>,
+    failure: Option<DrillxError>,
+    #[serde(skip)]
+    context: Option<Context>,
+}
+
+/// The result of a [`SteppingMiner::step`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StepOutcome {
+    /// Nonces hashed by this step.
+    pub hashes: u32,
+    /// The best solution seen so far, which may be below the minimum difficulty.
+    pub best: Option<ScoredSolution>,
+    /// Why the search ended, or `None` if there is more to do.
+    /// [`StopReason::Cancelled`] only if the runtime failed.
+    pub done: Option<StopReason>,
+    /// The runtime failure that ended the search, as [`MinerError::Runtime`] ends a
+    /// threaded run.
+    pub failure: Option<DrillxError>,
+}
+
+impl SteppingMiner {
+    /// Fails if the config's [nonce namespace](MinerBuilder::nonce_namespace) cannot be
+    /// searched, as [`MinerBuilder::spawn`] does with [`MinerError::Namespace`].
+    pub fn new(challenge: [u8; 32], config: &MinerConfig) -> Result<Self, NamespaceError> {
+        let (start, last) = index_range(config)?;
+        Ok(SteppingMiner {
+            challenge,
+            min_difficulty: config.min_difficulty,
+            deadline: config.deadline,
+            elapsed: Duration::ZERO,
+            next: (start <= last).then_some(start),
+            last,
+            nonce_order: config.nonce_order,
+            nonce_namespace: config.nonce_namespace,
+            runtime: config.runtime,
+            best: None,
+            hashes: 0,
+            done: None,
+            failure: None,
+            context: None,
+        })
+    }
+
+    /// Hashes up to `max_hashes` nonces, stopping early once a solution meets the
+    /// minimum difficulty, the deadline passes, the nonce range runs out, or a required
+    /// compiler fails.
+    pub fn step(&mut self, max_hashes: u32) -> StepOutcome {
+        let started = self.deadline.map(|_| Instant::now());
+        let context = self
+            .context
+            .get_or_insert_with(|| Context::new(self.runtime));
+        let mut hashes = 0;
+        while self.done.is_none() && hashes < max_hashes {
+            if let (Some(deadline), Some(started)) = (self.deadline, started) {
+                if self.elapsed + started.elapsed() >= deadline {
+                    self.done = Some(StopReason::Deadline);
+                    break;
+                }
+            }
+            let Some(index) = self.next else {
+                self.done = Some(StopReason::Exhausted);
+                break;
+            };
+            self.next = index.checked_add(1).filter(|&next| next <= self.last);
+            let nonce = nonce_at(self.nonce_order, self.nonce_namespace, index);
+            let result = hash_nonce(context, &self.challenge, nonce);
+            hashes += 1;
+            self.hashes += 1;
+            let best = self.best.map(|best| best.difficulty);
+            match judge(result, best, self.min_difficulty) {
+                Verdict::Solved {
+                    scored,
+                    improved,
+                    found,
+                } => {
+                    if improved {
+                        self.best = Some(scored);
+                    }
+                    if found {
+                        self.done = Some(StopReason::Found);
+                    }
+                }
+                Verdict::Unsolved(_) => {}
+                Verdict::RuntimeFailed(err) => {
+                    self.failure = Some(err);
+                    self.done = Some(StopReason::Cancelled);
+                }
+            }
+        }
+        if self.done.is_none() && self.next.is_none() {
+            self.done = Some(StopReason::Exhausted);
+        }
+        if let Some(started) = started {
+            self.elapsed += started.elapsed();
+        }
+        StepOutcome {
+            hashes,
+            best: self.best,
+            done: self.done,
+            failure: self.failure,
+        }
+    }
+
+    /// The best solution seen so far, which may be below the minimum difficulty.
+    pub fn best(&self) -> Option<ScoredSolution> {
+        self.best
+    }
+
+    /// Nonces hashed so far, across every step.
+    pub fn hashes(&self) -> u64 {
+        self.hashes
+    }
+
+    /// Why the search ended, or `None` if there is more to do.
+    pub fn done(&self) -> Option<StopReason> {
+        self.done
+    }
+
+    /// The runtime failure that ended the search, if any.
+    pub fn failure(&self) -> Option<DrillxError> {
+        self.failure
+    }
+}
+
+/// An error that prevented a mining run from completing.
+#[derive(Debug)]
+pub enum MinerError {
+    /// A thread could not be spawned.
+    Spawn(std::io::Error),
+    /// A miner thread panicked outside a supervised hashing loop.
+    WorkerPanicked,
+    /// The self-test found no trustworthy runtime.
+    SelfTest(SelfTestError),
+    /// The compiled runtime is required but disagrees with the test vectors.
+    UntrustedCompiler(SelfTestReport),
+    /// Workers panicked or stalled more than the restart limit allows in a minute.
+    TooManyRestarts { limit: u32 },
+    /// No worker could get solver memory at startup.
+    OutOfMemory(MemoryError),
+    /// The starting work is not signed by the expected pool, or has expired.
+    #[cfg(feature = "signing")]
+    WorkAuth(WorkAuthError),
+    /// The nonce namespace cannot be searched.
+    Namespace(NamespaceError),
+    /// The compiled runtime is required but is unsupported or failed to compile.
+    Runtime(DrillxError),
+}
+
+impl std::fmt::Display for MinerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MinerError::Spawn(err) => write!(f, "Failed to spawn thread: {}", err),
+            MinerError::WorkerPanicked => write!(f, "Worker panicked"),
+            MinerError::SelfTest(err) => write!(f, "Self-test failed: {}", err),
+            MinerError::UntrustedCompiler(_) => {
+                write!(f, "Compiled runtime disagrees with the test vectors")
+            }
+            MinerError::TooManyRestarts { limit } => {
+                write!(f, "Workers restarted more than {} times in a minute", limit)
+            }
+            MinerError::OutOfMemory(err) => write!(f, "No worker could start: {}", err),
+            #[cfg(feature = "signing")]
+            MinerError::WorkAuth(err) => write!(f, "Work rejected: {}", err),
+            MinerError::Namespace(err) => write!(f, "Bad nonce namespace: {}", err),
+            MinerError::Runtime(err) => write!(f, "Runtime failed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for MinerError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MinerError::Spawn(err) => Some(err),
+            MinerError::SelfTest(err) => Some(err),
+            MinerError::OutOfMemory(err) => Some(err),
+            #[cfg(feature = "signing")]
+            MinerError::WorkAuth(err) => Some(err),
+            MinerError::Namespace(err) => Some(err),
+            MinerError::Runtime(err) => Some(err),
+            MinerError::WorkerPanicked
+            | MinerError::UntrustedCompiler(_)
+            | MinerError::TooManyRestarts { .. } => None,
+        }
+    }
+}
+
+/// State shared by the workers, the coordinator, and the handle.
+struct Shared {
+    chunk_size: u64,
+    runtime: RuntimeOption,
+    deterministic: bool,
+    start_nonce: u64,
+    /// Last nonce of every job, at or after the start.
+    end_nonce: u64,
+    nonce_order: NonceOrder,
+    nonce_namespace: Option<(NonceNamespace, u64)>,
+    /// Jobs that have not been removed, in the order they were added.
+    jobs: RwLock<Vec<Arc<Job>>>,
+    next_job: AtomicU64,
+    /// Every worker spawned and not yet joined, in spawn order.
+    workers: Mutex<Vec<Worker>>,
+    /// Number of CPU workers that have not been asked to exit.
+    threads: AtomicUsize,
+    /// Number of CPU workers last asked for, which may be more than are running.
+    requested: AtomicUsize,
+    /// The last failure to get solver memory, and when it was, while short of workers.
+    shortage: Mutex<Option<(MemoryError, Instant)>>,
+    memory_retry: Duration,
+    /// Solver memories per CPU worker.
+    interleave: u8,
+    /// Chunks of replaced workers that no replacement could take over.
+    orphans: Mutex<Vec<Claim>>,
+    /// Number of accelerator workers.
+    accelerators: usize,
+    /// Nonces hashed on each backend, indexed by [`Backend`].
+    backend_hashes: [AtomicU64; 2],
+    /// True in streaming mode.
+    streaming: bool,
+    /// Shared by the workers, and dropped when the run ends so that the stream
+    /// disconnects even if an abandoned worker is still stuck in a hash.
+    stream: Mutex<Option<SyncSender<JobSolution>>>,
+    deliver_stale: bool,
+    /// Dropped when the run ends, like `stream`.
+    events: Mutex<Option<Sender<MinerEvent>>>,
+    challenge_changes: AtomicU64,
+    /// Nonces hashed by each worker, indexed by worker id.
+    hashes: RwLock<Vec<Arc<AtomicU64>>>,
+    no_solutions: AtomicU64,
+    solutions: AtomicU64,
+    dropped: AtomicU64,
+    stale_dropped: AtomicU64,
+    /// Set once any worker's context has stopped compiling.
+    downgraded: AtomicBool,
+    stopping: AtomicBool,
+    reason: Mutex<Option<StopReason>>,
+    signal: Condvar,
+    /// Mirrors `pause.since.is_some()` for the workers' fast path.
+    paused: AtomicBool,
+    /// The throttle intensity as `f32` bits, only changed under the `pause` lock.
+    intensity: AtomicU32,
+    pause: Mutex<Pause>,
+    /// Wakes parked and resting workers on resume, stop, or a change of intensity.
+    unpaused: Condvar,
+    pause_extends_deadline: bool,
+    topology: Topology,
+    core_preference: Prefer,
+    /// Smoothed hashes per second of the fastest CPU worker so far, as `f64` bits.
+    fastest: AtomicU64,
+    solver: SolverFactory,
+    accelerator: Option<SolverFactory>,
+    restart_limit: u32,
+    stall_timeout: Option<Duration>,
+    restarts: AtomicU64,
+    histogram: Option<DifficultyHistogram>,
+    /// The runtime error that ended the run, if any.
+    runtime_failure: Mutex<Option<DrillxError>>,
+    started: Instant,
+}
+
+/// A worker thread.
+struct Worker {
+    /// Index into [`Shared::hashes`], kept by replacements.
+    id: usize,
+    /// Set to ask the worker to exit after its current chunk. Shared with replacements.
+    retire: Arc<AtomicBool>,
+    backend: Backend,
+    /// True if the worker is pinned to performance cores.
+    performance: bool,
+    cpus: Vec<usize>,
+    slot: Arc<Slot>,
+    /// The worker's hash count when last seen changing, and when that was.
+    seen: (u64, Instant),
+    handle: JoinHandle<()>,
+}
+
+/// What the supervisor needs to know about a worker.
+#[derive(Default)]
+struct Slot {
+    /// The chunk being hashed.
+    claim: Mutex<Option<Claim>>,
+    /// Set when the worker was replaced, after which it must leave its chunk alone.
+    orphaned: AtomicBool,
+    /// Message of the panic that ended the worker.
+    panic: Mutex<Option<String>>,
+}
+
+/// A claimed chunk of nonces.
+#[derive(Clone)]
+struct Claim {
+    job: Arc<Job>,
+    /// First nonce of the chunk, which identifies it to the job's cursor.
+    start: u64,
+    /// The nonces not hashed yet.
+    left: RangeInclusive<u64>,
+}
+
+/// Time spent paused.
+#[derive(Default)]
+struct Pause {
+    /// When the current pause began.
+    since: Option<Instant>,
+    /// Length of the pauses that have ended.
+    total: Duration,
+}
+
+/// A challenge being mined.
+struct Job {
+    id: JobId,
+    challenge: [u8; 32],
+    min_difficulty: u32,
+    weight: u32,
+    cursor: Mutex<Cursor>,
+    /// Set once the last chunk has been claimed.
+    drained: AtomicBool,
+    state: Mutex<JobState>,
+    /// Set once the job leaves [`JobState::Active`].
+    retired: AtomicBool,
+    hashes: AtomicU64,
+    solutions: AtomicU64,
+    best: Mutex<Option<ScoredSolution>>,
+    /// The qualifying solution earliest in the nonce order, in deterministic mode.
+    lowest: Mutex<Option<ScoredSolution>>,
+    /// Index of `lowest`, or `u64::MAX` if there is none yet.
+    lowest_index: AtomicU64,
+}
+
+/// The next unclaimed index of a job.
+struct Cursor {
+    next: u64,
+    last: u64,
+    exhausted: bool,
+    /// First indices of the chunks claimed but not yet finished.
+    pending: BTreeSet<u64>,
+}
+
+impl Job {
+    /// Claims the next chunk of indices, returning its first and last index.
+    fn claim(&self, chunk_size: u64) -> Option<(u64, u64)> {
+        let mut cursor = self.cursor.lock().unwrap();
+        if cursor.exhausted {
+            return None;
+        }
+        let start = cursor.next;
+        if start > self.lowest_index.load(Ordering::Acquire) {
+            // Nothing from here on can beat the earliest qualifying nonce.
+            self.drained.store(true, Ordering::Relaxed);
+            return None;
+        }
+        let end = start.saturating_add(chunk_size - 1).min(cursor.last);
+        if end == cursor.last {
+            cursor.exhausted = true;
+            self.drained.store(true, Ordering::Relaxed);
+        } else {
+            cursor.next = end + 1;
+        }
+        cursor.pending.insert(start);
+        Some((start, end))
+    }
+
+    /// Marks the chunk starting at `start` as finished, retiring the job once the
+    /// earliest qualifying nonce is proven or its last chunk is done.
+    fn release(&self, start: u64) -> bool {
+        let mut cursor = self.cursor.lock().unwrap();
+        cursor.pending.remove(&start);
+        let lowest = self.lowest.lock().unwrap();
+        if lowest.is_some() {
+            let index = self.lowest_index.load(Ordering::Acquire);
+            if cursor.pending.first().is_none_or(|&first| first > index) {
+                return self.retire(JobState::Solved);
+            }
+        }
+        cursor.exhausted && cursor.pending.is_empty() && self.retire(JobState::Exhausted)
+    }
+
+    /// Records a qualifying solution found at `index` in deterministic mode if it is
+    /// the earliest.
+    fn propose(&self, index: u64, candidate: ScoredSolution) {
+        let mut lowest = self.lowest.lock().unwrap();
+        if lowest.is_none() || index < self.lowest_index.load(Ordering::Acquire) {
+            *lowest = Some(candidate);
+            self.lowest_index.store(index, Ordering::Release);
+        }
+    }
+
+    /// Returns true if a qualifying nonce earlier than this index is already known.
+    fn is_beaten(&self, index: u64) -> bool {
+        index > self.lowest_index.load(Ordering::Acquire)
+    }
+
+    /// Moves an active job to the given state, returning false if it already left.
+    fn retire(&self, state: JobState) -> bool {
+        let mut current = self.state.lock().unwrap();
+        if *current != JobState::Active {
+            return false;
+        }
+        *current = state;
+        self.retired.store(true, Ordering::Release);
+        true
+    }
+
+    fn is_retired(&self) -> bool {
+        self.retired.load(Ordering::Acquire)
+    }
+
+    /// Marks the job removed, whatever state it was in.
+    fn remove(&self) {
+        if !self.retire(JobState::Removed) {
+            *self.state.lock().unwrap() = JobState::Removed;
+        }
+    }
+
+    fn is_removed(&self) -> bool {
+        *self.state.lock().unwrap() == JobState::Removed
+    }
+
+    /// Returns true if the job has chunks left to hand out.
+    fn is_open(&self) -> bool {
+        !self.is_retired() && !self.drained.load(Ordering::Relaxed)
+    }
+
+    /// Records a candidate solution if it beats the best so far.
+    fn offer(&self, candidate: ScoredSolution) {
+        let mut best = self.best.lock().unwrap();
+        if best.is_none_or(|b| b.difficulty < candidate.difficulty) {
+            *best = Some(candidate);
+        }
+    }
+
+    fn status(&self) -> JobStatus {
+        let state = *self.state.lock().unwrap();
+        let lowest = *self.lowest.lock().unwrap();
+        JobStatus {
+            id: self.id,
+            challenge: self.challenge,
+            min_difficulty: self.min_difficulty,
+            state,
+            best: match lowest {
+                Some(lowest) if state == JobState::Solved => Some(lowest),
+                _ => *self.best.lock().unwrap(),
+            },
+            hashes: self.hashes.load(Ordering::Relaxed),
+            solutions: self.solutions.load(Ordering::Relaxed),
+        }
+    }
+}
+
+impl Shared {
+    fn add(&self, job: ChallengeJob) -> JobId {
+        let job = self.job(job);
+        let id = job.id;
+        self.jobs.write().unwrap().push(job);
+        self.signal.notify_all();
+        id
+    }
+
+    /// Atomically replaces every job with a new one.
+    fn set_challenge(&self, job: ChallengeJob) -> JobId {
+        let job = self.job(job);
+        let event = MinerEvent::ChallengeChanged {
+            job: job.id,
+            challenge: job.challenge,
+            min_difficulty: job.min_difficulty,
+            replaced: {
+                let mut jobs = self.jobs.write().unwrap();
+                let replaced = std::mem::replace(&mut *jobs, vec![job.clone()]);
+                for old in &replaced {
+                    old.remove();
+                }
+                replaced.iter().map(|old| old.id).collect()
+            },
+        };
+        self.challenge_changes.fetch_add(1, Ordering::Relaxed);
+        self.signal.notify_all();
+        if let Some(events) = &*self.events.lock().unwrap() {
+            events.send(event).ok();
+        }
+        job.id
+    }
+
+    /// Creates a job with the next id.
+    fn job(&self, job: ChallengeJob) -> Arc<Job> {
+        let id = JobId(self.next_job.fetch_add(1, Ordering::Relaxed));
+        event!(
+            telemetry::CHALLENGE_EVENT,
+            INFO,
+            challenge = %telemetry::Hex(&job.challenge),
+            min_difficulty = job.min_difficulty,
+        );
+        Arc::new(Job {
+            id,
+            challenge: job.challenge,
+            min_difficulty: job.min_difficulty,
+            weight: job.weight.max(1),
+            cursor: Mutex::new(Cursor {
+                next: self.start_nonce,
+                last: self.end_nonce,
+                exhausted: false,
+                pending: BTreeSet::new(),
+            }),
+            drained: AtomicBool::new(false),
+            state: Mutex::new(JobState::Active),
+            retired: AtomicBool::new(false),
+            hashes: AtomicU64::new(0),
+            solutions: AtomicU64::new(0),
+            best: Mutex::new(None),
+            lowest: Mutex::new(None),
+            lowest_index: AtomicU64::new(u64::MAX),
+        })
+    }
+
+    fn remove(&self, id: JobId) -> Option<JobStatus> {
+        let job = {
+            let mut jobs = self.jobs.write().unwrap();
+            let index = jobs.iter().position(|job| job.id == id)?;
+            jobs.remove(index)
+        };
+        job.remove();
+        self.signal.notify_all();
+        Some(job.status())
+    }
+
+    fn jobs(&self) -> Vec<Arc<Job>> {
+        self.jobs.read().unwrap().clone()
+    }
+
+    /// Returns why the run should end on its own, if every job has been retired.
+    fn settled(&self) -> Option<StopReason> {
+        let jobs = self.jobs.read().unwrap();
+        if jobs.is_empty() || !jobs.iter().all(|job| job.is_retired()) {
+            return None;
+        }
+        let solved = jobs
+            .iter()
+            .all(|job| *job.state.lock().unwrap() == JobState::Solved);
+        Some(if solved {
+            StopReason::Found
+        } else {
+            StopReason::Exhausted
+        })
+    }
+
+    /// Ends the run. The first reason given wins.
+    fn stop(&self, reason: StopReason) {
+        let mut current = self.reason.lock().unwrap();
+        if current.is_none() {
+            *current = Some(reason);
+        }
+        self.stopping.store(true, Ordering::Release);
+        self.signal.notify_all();
+        // Taking the lock ensures no worker is between checking for a stop and parking.
+        drop(self.pause.lock().unwrap());
+        self.unpaused.notify_all();
+    }
+
+    /// Ends a run that failed to start, joining the workers already started.
+    fn abandon(&self) {
+        self.stop(StopReason::Cancelled);
+        self.stream.lock().unwrap().take();
+        for worker in self.workers.lock().unwrap().drain(..) {
+            worker.handle.join().ok();
+        }
+    }
+
+    fn is_stopping(&self) -> bool {
+        self.stopping.load(Ordering::Acquire)
+    }
+
+    fn pause(&self) {
+        let mut pause = self.pause.lock().unwrap();
+        if pause.since.is_some() || self.is_stopping() {
+            return;
+        }
+        pause.since = Some(Instant::now());
+        self.paused.store(true, Ordering::Release);
+    }
+
+    fn resume(&self) {
+        let mut pause = self.pause.lock().unwrap();
+        if let Some(since) = pause.since.take() {
+            pause.total += since.elapsed();
+            self.paused.store(false, Ordering::Release);
+            self.unpaused.notify_all();
+        }
+    }
+
+    /// Joins retired workers that have exited and replaces workers that panicked or
+    /// stalled, returning true if every worker has exited.
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    fn supervise(self: &Arc<Self>, restarts: &mut VecDeque<Instant>) -> Result<bool, MinerError> {
+        let mut workers = self.workers.lock().unwrap();
+        let mut i = 0;
+        while i < workers.len() {
+            let worker = &mut workers[i];
+            let finished = worker.handle.is_finished();
+            let panic = if finished {
+                worker.slot.panic.lock().unwrap().take()
+            } else {
+                None
+            };
+            match panic {
+                Some(payload) => {
+                    event!(
+                        telemetry::WORKER_PANIC_EVENT,
+                        ERROR,
+                        thread = worker.id,
+                        payload = %payload,
+                    );
+                }
+                None if finished && worker.retire.load(Ordering::Relaxed) => {
+                    workers.remove(i).handle.join().ok();
+                    continue;
+                }
+                None if !finished && self.is_stalled(worker) => {
+                    event!(
+                        telemetry::WORKER_STALL_EVENT,
+                        WARN,
+                        thread = worker.id,
+                        stalled_ms = worker.seen.1.elapsed().as_millis() as u64,
+                    );
+                }
+                None => {
+                    i += 1;
+                    continue;
+                }
+            }
+            if let Err(err) = self.restart(worker, restarts)? {
+                // The rest of its chunk is left to the other workers.
+                let old = workers.remove(i);
+                if old.backend == Backend::Cpu {
+                    self.threads.fetch_sub(1, Ordering::Relaxed);
+                }
+                if old.handle.is_finished() {
+                    old.handle.join().ok();
+                }
+                self.short_of_memory(err);
+                continue;
+            }
+            i += 1;
+        }
+        // With every worker lost for lack of memory, the run waits for a retry.
+        Ok(!workers.is_empty() && workers.iter().all(|worker| worker.handle.is_finished()))
+    }
+
+    /// Replaces a worker with a fresh one that inherits the rest of its chunk.
+    ///
+    /// If there is no memory for a replacement, the chunk is orphaned for any worker to
+    /// take over, and the memory error is returned for the caller to drop the worker.
+    fn restart(
+        self: &Arc<Self>,
+        worker: &mut Worker,
+        restarts: &mut VecDeque<Instant>,
+    ) -> Result<Result<(), MemoryError>, MinerError> {
+        let now = Instant::now();
+        while restarts
+            .front()
+            .is_some_and(|&at| now.duration_since(at) >= RESTART_WINDOW)
+        {
+            restarts.pop_front();
+        }
+        if restarts.len() >= self.restart_limit as usize {
+            return Err(MinerError::TooManyRestarts {
+                limit: self.restart_limit,
+            });
+        }
+        restarts.push_back(now);
+        self.restarts.fetch_add(1, Ordering::Relaxed);
+        let inherited = {
+            // The worker may have panicked while holding the lock.
+            let mut claim = worker
+                .slot
+                .claim
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            worker.slot.orphaned.store(true, Ordering::Relaxed);
+            claim.take()
+        };
+        let solver = match self.new_solver(worker.backend) {
+            Ok(solver) => solver,
+            Err(err) => {
+                self.orphans.lock().unwrap().extend(inherited);
+                return Ok(Err(err));
+            }
+        };
+        let replacement = self.spawn_worker(
+            worker.id,
+            worker.retire.clone(),
+            worker.backend,
+            solver,
+            (worker.performance, worker.cpus.clone()),
+            inherited,
+        )?;
+        let old = std::mem::replace(worker, replacement);
+        // A stalled worker may never return, so it is left detached.
+        if old.handle.is_finished() {
+            old.handle.join().ok();
+        }
+        Ok(Ok(()))
+    }
+
+    /// Returns true if a worker holding a chunk has not hashed within the stall
+    /// timeout, and updates when it was last seen hashing.
+    fn is_stalled(&self, worker: &mut Worker) -> bool {
+        let Some(timeout) = self.stall_timeout else {
+            return false;
+        };
+        let hashes = self.hashes.read().unwrap()[worker.id].load(Ordering::Relaxed);
+        let busy = worker
+            .slot
+            .claim
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .is_some();
+        let intensity = self.intensity();
+        if hashes != worker.seen.0 || !busy || self.is_paused() || intensity <= 0.0 {
+            worker.seen = (hashes, Instant::now());
+            return false;
+        }
+        // A throttled worker rests between hashes, as long as `1 / intensity` hashes.
+        worker.seen.1.elapsed() >= timeout.div_f32(intensity.min(1.0))
+    }
+
+    fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Acquire)
+    }
+
+    fn intensity(&self) -> f32 {
+        f32::from_bits(self.intensity.load(Ordering::Acquire))
+    }
+
+    /// Returns true if workers should park, because the miner is paused or throttled to
+    /// zero.
+    fn is_parked(&self) -> bool {
+        self.is_paused() || self.intensity() <= 0.0
+    }
+
+    /// Polls the throttle hook until the run ends.
+    fn poll_throttle(&self, hook: &dyn ThrottleHook, poll: Duration) {
+        while !self.is_stopping() {
+            self.set_intensity(hook.target_intensity());
+            let reason = self.reason.lock().unwrap();
+            if reason.is_none() {
+                drop(self.signal.wait_timeout(reason, poll).unwrap());
+            }
+        }
+    }
+
+    fn set_intensity(&self, intensity: f32) {
+        if intensity.is_nan() {
+            return;
+        }
+        let intensity = if intensity < throttle::MIN_INTENSITY {
+            0.0
+        } else {
+            intensity.min(1.0)
+        };
+        let _pause = self.pause.lock().unwrap();
+        let previous = f32::from_bits(self.intensity.swap(intensity.to_bits(), Ordering::AcqRel));
+        if previous != intensity {
+            event!(telemetry::THROTTLE_EVENT, INFO, previous, intensity);
+            self.unpaused.notify_all();
+        }
+    }
+
+    /// Total time spent paused, including the current pause.
+    fn paused_for(&self) -> Duration {
+        let pause = self.pause.lock().unwrap();
+        pause.total + pause.since.map_or(Duration::ZERO, |since| since.elapsed())
+    }
+
+    /// Spawns or retires CPU workers until `threads` of them are running, or as many as
+    /// there is memory for.
+    fn set_threads(self: &Arc<Self>, threads: usize) -> Result<(), MinerError> {
+        let threads = threads.max(1);
+        let mut workers = self.workers.lock().unwrap();
+        if self.is_stopping() {
+            return Ok(());
+        }
+        self.requested.store(threads, Ordering::Relaxed);
+        let active: Vec<&Worker> = workers
+            .iter()
+            .filter(|worker| worker.backend == Backend::Cpu)
+            .filter(|worker| !worker.retire.load(Ordering::Relaxed))
+            .collect();
+        let running = active.len();
+        // Retire the newest workers first.
+        for worker in active.into_iter().skip(threads) {
+            worker.retire.store(true, Ordering::Relaxed);
+        }
+        let mut running = running.min(threads);
+        self.threads.store(running, Ordering::Relaxed);
+        while running < threads {
+            let solver = match self.new_solver(Backend::Cpu) {
+                Ok(solver) => solver,
+                Err(err) => {
+                    self.short_of_memory(err);
+                    return Ok(());
+                }
+            };
+            let placement = self.placement(&workers);
+            let id = self.next_worker_id();
+            let retire = Arc::new(AtomicBool::new(false));
+            workers.push(self.spawn_worker(id, retire, Backend::Cpu, solver, placement, None)?);
+            running += 1;
+            self.threads.store(running, Ordering::Relaxed);
+        }
+        *self.shortage.lock().unwrap() = None;
+        Ok(())
+    }
+
+    /// Retries the CPU workers that could not get memory, once the retry interval has
+    /// passed since the last failure.
+    fn retry_workers(self: &Arc<Self>) -> Result<(), MinerError> {
+        let due = self
+            .shortage
+            .lock()
+            .unwrap()
+            .is_some_and(|(_, at)| at.elapsed() >= self.memory_retry);
+        if due {
+            self.set_threads(self.requested.load(Ordering::Relaxed))?;
+        }
+        Ok(())
+    }
+
+    /// Records that a worker could not get memory.
+    fn short_of_memory(&self, error: MemoryError) {
+        event!(
+            telemetry::MEMORY_EXHAUSTED_EVENT,
+            WARN,
+            requested = self.requested.load(Ordering::Relaxed),
+            running = self.threads.load(Ordering::Relaxed),
+            error = %error,
+        );
+        *self.shortage.lock().unwrap() = Some((error, Instant::now()));
+    }
+
+    /// Makes a solver for a new worker.
+    fn new_solver(&self, backend: Backend) -> Result<Box<dyn Solver>, MemoryError> {
+        match (backend, &self.accelerator) {
+            (Backend::Accelerator, Some(factory)) => factory(),
+            _ if self.interleave > 1 => {
+                // Solvers already made go back to their pool if a later one fails.
+                let solvers = (0..self.interleave)
+                    .map(|_| (self.solver)())
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Box::new(Interleaved::new(solvers)))
+            }
+            _ => (self.solver)(),
+        }
+    }
+
+    /// Spawns the accelerator workers, which are never pinned or retired.
+    fn spawn_accelerators(self: &Arc<Self>) -> Result<(), MinerError> {
+        let mut workers = self.workers.lock().unwrap();
+        for _ in 0..self.accelerators {
+            let solver = self
+                .new_solver(Backend::Accelerator)
+                .map_err(MinerError::OutOfMemory)?;
+            let id = self.next_worker_id();
+            let retire = Arc::new(AtomicBool::new(false));
+            workers.push(self.spawn_worker(
+                id,
+                retire,
+                Backend::Accelerator,
+                solver,
+                (false, vec![]),
+                None,
+            )?);
+        }
+        Ok(())
+    }
+
+    /// Allocates the hash counter of a new worker, returning its id.
+    fn next_worker_id(&self) -> usize {
+        let mut all = self.hashes.write().unwrap();
+        all.push(Arc::new(AtomicU64::new(0)));
+        all.len() - 1
+    }
+
+    /// Spawns a worker thread, which starts with the rest of the inherited chunk.
+    ///
+    /// The placement is whether the worker is on performance cores, and the CPUs to
+    /// pin it to, as returned by [`placement`](Self::placement).
+    fn spawn_worker(
+        self: &Arc<Self>,
+        id: usize,
+        retire: Arc<AtomicBool>,
+        backend: Backend,
+        solver: Box<dyn Solver>,
+        (performance, cpus): (bool, Vec<usize>),
+        inherited: Option<Claim>,
+    ) -> Result<Worker, MinerError> {
+        let slot = Arc::new(Slot::default());
+        let hashes = self.hashes.read().unwrap()[id].clone();
+        let seen = (hashes.load(Ordering::Relaxed), Instant::now());
+        let handle = {
+            let shared = self.clone();
+            let retire = retire.clone();
+            let slot = slot.clone();
+            let cpus = cpus.clone();
+            thread::Builder::new()
+                .name(format!("drillx-worker-{}", id))
+                .spawn(move || {
+                    if !cpus.is_empty() {
+                        topology::pin(&cpus);
+                    }
+                    let worker = WorkerLoop::new(id, backend, solver, &shared, &hashes, &slot);
+                    let run =
+                        panic::catch_unwind(AssertUnwindSafe(|| worker.run(&retire, inherited)));
+                    if let Err(payload) = run {
+                        *slot.panic.lock().unwrap() = Some(panic_message(&*payload));
+                    }
+                })
+                .map_err(MinerError::Spawn)?
+        };
+        Ok(Worker {
+            id,
+            retire,
+            backend,
+            performance,
+            cpus,
+            slot,
+            seen,
+            handle,
+        })
+    }
+
+    /// Returns the CPUs to pin a new worker to, which may be none, and whether they are
+    /// performance cores.
+    fn placement(&self, workers: &[Worker]) -> (bool, Vec<usize>) {
+        let Topology::Hybrid {
+            performance,
+            efficiency,
+        } = &self.topology
+        else {
+            return (false, vec![]);
+        };
+        let on_performance = workers
+            .iter()
+            .filter(|worker| worker.performance && !worker.retire.load(Ordering::Relaxed))
+            .count();
+        match self.core_preference {
+            Prefer::All => (false, vec![]),
+            Prefer::PerformanceFirst if on_performance >= performance.count => {
+                (false, efficiency.cpus.clone())
+            }
+            Prefer::PerformanceOnly | Prefer::PerformanceFirst => (true, performance.cpus.clone()),
+        }
+    }
+
+    /// Records a CPU worker's smoothed speed, returning the fastest CPU worker's.
+    fn report_speed(&self, speed: f64) -> f64 {
+        // Ordering non-negative floats by their bits orders them by value.
+        let fastest = self.fastest.fetch_max(speed.to_bits(), Ordering::Relaxed);
+        f64::from_bits(fastest).max(speed)
+    }
+
+    fn fastest_cpu(&self) -> f64 {
+        f64::from_bits(self.fastest.load(Ordering::Relaxed))
+    }
+
+    /// Blocks a worker while the miner is paused or throttled to zero, and not stopping.
+    fn park(&self) {
+        let mut pause = self.pause.lock().unwrap();
+        while (pause.since.is_some() || self.intensity() <= 0.0) && !self.is_stopping() {
+            pause = self.unpaused.wait(pause).unwrap();
+        }
+    }
+
+    /// Rests a worker after a hash that took `hashed`, returning how long it rested.
+    /// Ends early on a pause, a stop, or a change of intensity.
+    fn rest(&self, hashed: Duration) -> Duration {
+        let intensity = self.intensity.load(Ordering::Acquire);
+        let Some(rest) = throttle::rest(hashed, f32::from_bits(intensity)) else {
+            return Duration::ZERO;
+        };
+        if rest.is_zero() {
+            return rest;
+        }
+        let started = Instant::now();
+        let mut pause = self.pause.lock().unwrap();
+        while pause.since.is_none()
+            && !self.is_stopping()
+            && self.intensity.load(Ordering::Acquire) == intensity
+        {
+            let Some(left) = rest.checked_sub(started.elapsed()) else {
+                break;
+            };
+            pause = self.unpaused.wait_timeout(pause, left).unwrap().0;
+        }
+        started.elapsed()
+    }
+
+    /// Blocks a worker with nothing to do until a job changes or a tick passes.
+    fn idle(&self) {
+        let reason = self.reason.lock().unwrap();
+        if reason.is_none() {
+            drop(self.signal.wait_timeout(reason, TICK).unwrap());
+        }
+    }
+
+    fn progress(&self) -> Progress {
+        let jobs: Vec<_> = self.jobs().iter().map(|job| job.status()).collect();
+        let best = jobs
+            .iter()
+            .filter_map(|job| job.best)
+            .max_by_key(|best| best.difficulty);
+        let elapsed = self.started.elapsed();
+        let active = elapsed.saturating_sub(self.paused_for());
+        let paused = self.is_paused();
+        let threads = self.threads.load(Ordering::Relaxed);
+        let backends = [
+            (Backend::Cpu, threads),
+            (Backend::Accelerator, self.accelerators),
+        ]
+        .into_iter()
+        .filter(|(_, workers)| *workers > 0)
+        .map(|(backend, workers)| {
+            let hashes = self.backend_hashes[backend as usize].load(Ordering::Relaxed);
+            BackendProgress {
+                backend,
+                workers,
+                hashes,
+                hashrate: average_rate(hashes, active, paused),
+            }
+        })
+        .collect();
+        let warnings = match *self.shortage.lock().unwrap() {
+            Some((error, _)) => vec![MinerWarning::MemoryExhausted {
+                requested: self.requested.load(Ordering::Relaxed),
+                running: threads,
+                error,
+            }],
+            None => vec![],
+        };
+        Progress {
+            hashes: self
+                .hashes
+                .read()
+                .unwrap()
+                .iter()
+                .map(|h| h.load(Ordering::Relaxed))
+                .sum(),
+            solutions: self.solutions.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            stale_dropped: self.stale_dropped.load(Ordering::Relaxed),
+            challenge_changes: self.challenge_changes.load(Ordering::Relaxed),
+            restarts: self.restarts.load(Ordering::Relaxed),
+            elapsed,
+            active,
+            paused,
+            intensity: self.intensity(),
+            threads,
+            backends,
+            warnings,
+            best,
+            runtime_downgraded: self.downgraded.load(Ordering::Relaxed),
+            jobs,
+            topology: self.topology.clone(),
+            histogram: self.histogram.as_ref().map(DifficultyHistogram::snapshot),
+        }
+    }
+}
+
+/// Waits for the run to end and collects the outcome.
+fn coordinate(shared: &Arc<Shared>, deadline: Option<Instant>) -> Result<MineOutcome, MinerError> {
+    #[cfg(feature = "metrics")]
+    let mut metrics = Metrics::default();
+    let mut restarts = VecDeque::new();
+    let mut failure = None;
+    let mut reason = shared.reason.lock().unwrap();
+    while reason.is_none() {
+        #[cfg(feature = "metrics")]
+        metrics.flush(shared);
+        let extension = if shared.pause_extends_deadline {
+            shared.paused_for()
+        } else {
+            Duration::ZERO
+        };
+        let finished = if deadline.is_some_and(|d| Instant::now() >= d + extension) {
+            Some(StopReason::Deadline)
+        } else {
+            let supervised = shared
+                .supervise(&mut restarts)
+                .and_then(|done| shared.retry_workers().map(|()| done));
+            match supervised {
+                Ok(true) => Some(StopReason::Exhausted),
+                Ok(false) => shared.settled(),
+                Err(err) => {
+                    failure = Some(err);
+                    Some(StopReason::Cancelled)
+                }
+            }
+        };
+        if let Some(finished) = finished {
+            drop(reason);
+            shared.stop(finished);
+            reason = shared.reason.lock().unwrap();
+            break;
+        }
+        reason = shared.signal.wait_timeout(reason, TICK).unwrap().0;
+    }
+    drop(reason);
+
+    shared.stream.lock().unwrap().take();
+    shared.events.lock().unwrap().take();
+    let workers = std::mem::take(&mut *shared.workers.lock().unwrap());
+    let stopped = Instant::now();
+    let mut panicked = false;
+    for worker in workers {
+        // Give up on a worker stuck in a hash once it would count as stalled.
+        while !worker.handle.is_finished()
+            && shared
+                .stall_timeout
+                .is_none_or(|timeout| stopped.elapsed() < timeout)
+        {
+            thread::sleep(Duration::from_millis(1));
+        }
+        if worker.handle.is_finished() {
+            panicked |= worker.handle.join().is_err();
+        }
+    }
+    #[cfg(feature = "metrics")]
+    metrics.flush(shared);
+    if let Some(failure) = failure {
+        return Err(failure);
+    }
+    if let Some(err) = shared.runtime_failure.lock().unwrap().take() {
+        return Err(MinerError::Runtime(err));
+    }
+    if panicked {
+        return Err(MinerError::WorkerPanicked);
+    }
+
+    let progress = shared.progress();
+    Ok(MineOutcome {
+        best: progress.best,
+        hashes: progress.hashes,
+        solutions: progress.solutions,
+        dropped: progress.dropped,
+        elapsed: progress.elapsed,
+        reason: shared
+            .reason
+            .lock()
+            .unwrap()
+            .unwrap_or(StopReason::Exhausted),
+        runtime_downgraded: progress.runtime_downgraded,
+        jobs: progress.jobs,
+    })
+}
+
+/// Counter values already reported to the metrics recorder.
+#[cfg(feature = "metrics")]
+#[derive(Default)]
+struct Metrics {
+    hashes: Vec<u64>,
+    no_solutions: u64,
+    solutions: u64,
+    dropped: u64,
+}
+
+#[cfg(feature = "metrics")]
+impl Metrics {
+    /// Reports counter increments since the last flush.
+    fn flush(&mut self, shared: &Shared) {
+        let all = shared.hashes.read().unwrap();
+        self.hashes.resize(all.len(), 0);
+        for (id, (reported, hashes)) in self.hashes.iter_mut().zip(all.iter()).enumerate() {
+            let hashes = hashes.load(Ordering::Relaxed);
+            metrics::counter!(telemetry::HASHES_METRIC, "thread" => id.to_string())
+                .increment(hashes - *reported);
+            *reported = hashes;
+        }
+        let no_solutions = shared.no_solutions.load(Ordering::Relaxed);
+        metrics::counter!(telemetry::NO_SOLUTIONS_METRIC)
+            .increment(no_solutions - self.no_solutions);
+        self.no_solutions = no_solutions;
+        let solutions = shared.solutions.load(Ordering::Relaxed);
+        metrics::counter!(telemetry::SOLUTIONS_METRIC).increment(solutions - self.solutions);
+        self.solutions = solutions;
+        let dropped = shared.dropped.load(Ordering::Relaxed);
+        metrics::counter!(telemetry::STREAM_DROPPED_METRIC).increment(dropped - self.dropped);
+        self.dropped = dropped;
+        // Zero while there is no best, such as just after a challenge switch.
+        let best = shared.progress().best.map_or(0, |best| best.difficulty);
+        metrics::gauge!(telemetry::BEST_DIFFICULTY_METRIC).set(best as f64);
+    }
+}
+
+/// Scales a chunk size to a worker's speed relative to the fastest worker's.
+///
+/// Workers within 10% of the fastest claim full chunks. Slower ones claim chunks that
+/// take them about as long as a full chunk takes the fastest worker, and never less
+/// than one nonce.
+pub fn scaled_chunk_size(chunk_size: u64, speed: f64, fastest: f64) -> u64 {
+    if !(speed > 0.0 && fastest > 0.0) || speed >= fastest * FULL_CHUNK_SPEED {
+        return chunk_size;
+    }
+    ((chunk_size as f64 * speed / fastest).round() as u64).clamp(1, chunk_size)
+}
+
+/// Scales a chunk size up to an accelerator's speed relative to the fastest CPU
+/// worker's.
+///
+/// The chunk takes the accelerator about as long as a full chunk takes the fastest CPU
+/// worker. Until both speeds are measured, or if the accelerator is slower, it is the
+/// plain chunk size; it never exceeds 65536 times that.
+pub fn accelerator_chunk_size(chunk_size: u64, speed: f64, cpu_speed: f64) -> u64 {
+    if !(speed > 0.0 && cpu_speed > 0.0) || speed <= cpu_speed {
+        return chunk_size;
+    }
+    let max = chunk_size.saturating_mul(MAX_ACCELERATOR_SCALE);
+    let scaled = chunk_size as f64 * speed / cpu_speed;
+    if scaled >= max as f64 {
+        max
+    } else {
+        (scaled.round() as u64).clamp(chunk_size, max)
+    }
+}
+
+/// Picks jobs for one worker by smooth weighted round-robin.
+#[derive(Default)]
+struct Scheduler {
+    credits: Vec<(JobId, i64)>,
+}
+
+impl Scheduler {
+    /// Returns the open job that is furthest behind its share of chunks.
+    fn pick(&mut self, jobs: &[Arc<Job>]) -> Option<Arc<Job>> {
+        let open: Vec<_> = jobs.iter().filter(|job| job.is_open()).collect();
+        self.credits
+            .retain(|(id, _)| open.iter().any(|job| job.id == *id));
+        let mut total = 0;
+        let mut pick: Option<(usize, i64)> = None;
+        for (index, job) in open.iter().enumerate() {
+            let slot = match self.credits.iter().position(|(id, _)| *id == job.id) {
+                Some(slot) => slot,
+                None => {
+                    self.credits.push((job.id, 0));
+                    self.credits.len() - 1
+                }
+            };
+            let credit = &mut self.credits[slot].1;
+            *credit += job.weight as i64;
+            total += job.weight as i64;
+            if pick.is_none_or(|(_, best)| best < *credit) {
+                pick = Some((index, *credit));
+            }
+        }
+        let job = open[pick?.0];
+        if let Some((_, credit)) = self.credits.iter_mut().find(|(id, _)| *id == job.id) {
+            *credit -= total;
+        }
+        Some(job.clone())
+    }
+}
+
+/// Returns the message of a panic payload.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(message) => message.to_string(),
+        None => payload
+            .downcast_ref::<String>()
+            .cloned()
+            .unwrap_or_else(|| "non-string panic payload".to_string()),
+    }
+}
+
+/// Hashes a nonce and scores its solution, for every kind of miner.
+fn hash_nonce<S: Solver>(
+    context: &mut Context<S>,
+    challenge: &[u8; 32],
+    nonce: u64,
+) -> Result<ScoredSolution, DrillxError> {
+    let hash = context.hash(challenge, &nonce.to_le_bytes())?;
+    Ok(ScoredSolution {
+        solution: Solution::new(hash.d, nonce.to_le_bytes()),
+        hash: hash.h,
+        difficulty: hash.difficulty(),
+    })
+}
+
+/// What one hash means for a search, judged the same by every miner.
+enum Verdict {
+    /// The seed has no solutions, or none that can be checked, and is skipped.
+    Unsolved(DrillxError),
+    /// The runtime failed and will not recover, so the search must end.
+    RuntimeFailed(DrillxError),
+    /// A solution, whether it beats the best so far, and whether it meets the minimum
+    /// difficulty.
+    Solved {
+        scored: ScoredSolution,
+        improved: bool,
+        found: bool,
+    },
+}
+
+/// Judges a hash against the best difficulty so far and the minimum difficulty.
+fn judge(
+    result: Result<ScoredSolution, DrillxError>,
+    best: Option<u32>,
+    min_difficulty: u32,
+) -> Verdict {
+    match result {
+        Ok(scored) => Verdict::Solved {
+            scored,
+            improved: best.is_none_or(|best| scored.difficulty > best),
+            found: scored.difficulty >= min_difficulty,
+        },
+        // Only a required compiler fails like this, and it will not recover.
+        Err(err @ (DrillxError::CompileFailed | DrillxError::CompilerUnsupported)) => {
+            Verdict::RuntimeFailed(err)
+        }
+        Err(err @ (DrillxError::NoSolutions | DrillxError::BadEquix)) => Verdict::Unsolved(err),
+    }
+}
+
+/// What a worker does after recording a hash.
+enum Step {
+    Next,
+    EndChunk,
+    Exit,
+}
+
+/// A worker's hashing state.
+struct WorkerLoop<'a> {
+    id: usize,
+    backend: Backend,
+    shared: &'a Shared,
+    hashes: &'a AtomicU64,
+    slot: &'a Slot,
+    context: Context<Box<dyn Solver>>,
+    /// Best difficulty offered per job, to avoid contending on each job's lock.
+    offered: Vec<(JobId, u32)>,
+    no_solutions: u64,
+    /// Smoothed hashes per second, and the fastest CPU worker's as last seen.
+    speed: f64,
+    fastest: f64,
+}
+
+impl<'a> WorkerLoop<'a> {
+    fn new(
+        id: usize,
+        backend: Backend,
+        solver: Box<dyn Solver>,
+        shared: &'a Shared,
+        hashes: &'a AtomicU64,
+        slot: &'a Slot,
+    ) -> Self {
+        WorkerLoop {
+            id,
+            backend,
+            shared,
+            hashes,
+            slot,
+            context: Context::with_solver(solver, shared.runtime),
+            offered: Vec::new(),
+            no_solutions: 0,
+            speed: 0.0,
+            fastest: 0.0,
+        }
+    }
+
+    /// Hashes claimed chunks until the run ends, starting with an inherited chunk.
+    fn run(mut self, retire: &AtomicBool, inherited: Option<Claim>) {
+        let shared = self.shared;
+        if let Some(claim) = inherited {
+            if !self.chunk(claim) {
+                return;
+            }
+        }
+        let mut scheduler = Scheduler::default();
+        while !shared.is_stopping() && !retire.load(Ordering::Relaxed) {
+            if shared.is_parked() {
+                shared.park();
+                continue;
+            }
+            let orphan = shared.orphans.lock().unwrap().pop();
+            if let Some(claim) = orphan {
+                if !self.chunk(claim) {
+                    return;
+                }
+                continue;
+            }
+            let jobs = shared.jobs();
+            let Some(job) = scheduler.pick(&jobs) else {
+                shared.idle();
+                continue;
+            };
+            let chunk_size = match self.backend {
+                Backend::Cpu => scaled_chunk_size(shared.chunk_size, self.speed, self.fastest),
+                Backend::Accelerator => {
+                    accelerator_chunk_size(shared.chunk_size, self.speed, shared.fastest_cpu())
+                }
+            };
+            let Some((start, end)) = job.claim(chunk_size) else {
+                continue;
+            };
+            self.offered
+                .retain(|(id, _)| jobs.iter().any(|job| job.id == *id));
+            let claim = Claim {
+                job,
+                start,
+                left: start..=end,
+            };
+            if !self.chunk(claim) {
+                return;
+            }
+        }
+    }
+
+    /// Hashes the nonces left in a chunk, returning false if the worker must exit.
+    ///
+    /// Each hash is recorded under the slot's lock, and only if the worker has not been
+    /// replaced, so a replacement neither repeats nor skips a recorded nonce.
+    fn chunk(&mut self, claim: Claim) -> bool {
+        let shared = self.shared;
+        let job = claim.job.clone();
+        let start = claim.start;
+        *self.slot.claim.lock().unwrap() = Some(claim);
+        let claimed = Instant::now();
+        let mut hashed = 0u64;
+        let mut parked = false;
+        let mut rested = Duration::ZERO;
+        loop {
+            if shared.is_parked() {
+                shared.park();
+                parked = true;
+            }
+            if shared.is_stopping() {
+                return false;
+            }
+            let index = {
+                let claim = self.slot.claim.lock().unwrap();
+                if self.slot.orphaned.load(Ordering::Relaxed) {
+                    return false;
+                }
+                claim.as_ref().and_then(|claim| claim.left.clone().next())
+            };
+            let Some(index) = index else {
+                break;
+            };
+            if job.is_retired() || (shared.deterministic && job.is_beaten(index)) {
+                break;
+            }
+            let nonce = nonce_at(shared.nonce_order, shared.nonce_namespace, index);
+
+            #[cfg(feature = "tracing")]
+            let _span = nonce.is_multiple_of(telemetry::SOLVE_SPAN_SAMPLE).then(|| {
+                tracing::trace_span!(target: "drillx", telemetry::SOLVE_SPAN, thread = self.id, nonce)
+                    .entered()
+            });
+
+            let solving = Instant::now();
+            let result = hash_nonce(&mut self.context, &job.challenge, nonce);
+            let solved = solving.elapsed();
+            if self.context.is_downgraded() {
+                shared.downgraded.store(true, Ordering::Relaxed);
+            }
+            let mut claim = self.slot.claim.lock().unwrap();
+            if self.slot.orphaned.load(Ordering::Relaxed) {
+                return false;
+            }
+            hashed += 1;
+            let step = self.record(&job, index, nonce, result);
+            if let Some(claim) = claim.as_mut() {
+                claim.left.next();
+            }
+            drop(claim);
+            match step {
+                Step::Next => rested += shared.rest(solved),
+                Step::EndChunk => break,
+                Step::Exit => return false,
+            }
+        }
+        {
+            let mut claim = self.slot.claim.lock().unwrap();
+            if self.slot.orphaned.load(Ordering::Relaxed) {
+                return false;
+            }
+            claim.take();
+        }
+        if job.release(start) || job.is_retired() {
+            shared.signal.notify_all();
+        }
+        if hashed > 0 && !parked {
+            // Speeds are of hashing alone, so that throttling leaves chunk sizes be.
+            let busy = claimed.elapsed().saturating_sub(rested);
+            let rate = hashed as f64 / busy.as_secs_f64().max(1e-9);
+            self.speed = if self.speed > 0.0 {
+                self.speed + (rate - self.speed) * SPEED_SMOOTHING
+            } else {
+                rate
+            };
+            if self.backend == Backend::Cpu {
+                self.fastest = shared.report_speed(self.speed);
+            }
+        }
+        true
+    }
+
+    /// Counts a hash and reports its solution.
+    #[cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+    fn record(
+        &mut self,
+        job: &Job,
+        index: u64,
+        nonce: u64,
+        result: Result<ScoredSolution, DrillxError>,
+    ) -> Step {
+        let shared = self.shared;
+        let id = self.id;
+        self.hashes.fetch_add(1, Ordering::Relaxed);
+        shared.backend_hashes[self.backend as usize].fetch_add(1, Ordering::Relaxed);
+        job.hashes.fetch_add(1, Ordering::Relaxed);
+        let offered = self.offered.iter().position(|(id, _)| *id == job.id);
+        let best = offered.map(|i| self.offered[i].1);
+        let (scored, improved, found) = match judge(result, best, job.min_difficulty) {
+            Verdict::Solved {
+                scored,
+                improved,
+                found,
+            } => {
+                self.no_solutions = 0;
+                (scored, improved, found)
+            }
+            Verdict::Unsolved(DrillxError::NoSolutions) => {
+                shared.no_solutions.fetch_add(1, Ordering::Relaxed);
+                self.no_solutions += 1;
+                if self
+                    .no_solutions
+                    .is_multiple_of(telemetry::NO_SOLUTIONS_STREAK)
+                {
+                    event!(
+                        telemetry::NO_SOLUTIONS_STREAK_EVENT,
+                        WARN,
+                        thread = id,
+                        streak = self.no_solutions,
+                    );
+                }
+                return Step::Next;
+            }
+            Verdict::Unsolved(_) => return Step::Next,
+            Verdict::RuntimeFailed(err) => {
+                shared.runtime_failure.lock().unwrap().get_or_insert(err);
+                shared.stop(StopReason::Cancelled);
+                return Step::Exit;
+            }
+        };
+
+        let difficulty = scored.difficulty;
+        if let Some(histogram) = &shared.histogram {
+            histogram.record(difficulty);
+        }
+        if improved {
+            match offered {
+                Some(i) => self.offered[i].1 = difficulty,
+                None => self.offered.push((job.id, difficulty)),
+            }
+            job.offer(scored);
+        }
+        if !found {
+            return Step::Next;
+        }
+
+        event!(
+            telemetry::SOLUTION_EVENT,
+            INFO,
+            thread = id,
+            nonce,
+            difficulty,
+        );
+        #[cfg(feature = "metrics")]
+        metrics::histogram!(telemetry::SOLUTION_DIFFICULTY_METRIC).record(difficulty as f64);
+        shared.solutions.fetch_add(1, Ordering::Relaxed);
+        job.solutions.fetch_add(1, Ordering::Relaxed);
+        if !shared.streaming {
+            if shared.deterministic {
+                job.propose(index, scored);
+            } else {
+                job.retire(JobState::Solved);
+            }
+            return Step::EndChunk;
+        }
+        let found = JobSolution {
+            job: job.id,
+            challenge: job.challenge,
+            scored,
+        };
+        // Holding the jobs lock keeps a replaced job's solutions from being sent after
+        // `set_challenge` returns.
+        let _jobs = shared.jobs.read().unwrap();
+        if !shared.deliver_stale && job.is_removed() {
+            shared.stale_dropped.fetch_add(1, Ordering::Relaxed);
+            return Step::EndChunk;
+        }
+        let stream = shared.stream.lock().unwrap();
+        match stream.as_ref().map(|tx| tx.try_send(found)) {
+            Some(Ok(())) => Step::Next,
+            Some(Err(TrySendError::Full(_))) => {
+                shared.dropped.fetch_add(1, Ordering::Relaxed);
+                Step::Next
+            }
+            Some(Err(TrySendError::Disconnected(_))) | None => Step::Exit,
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Worker ids in the high bits of the nonce.
+//!
+//! A [`NonceNamespace`] with `B` id bits splits every nonce into a worker id in its top
+//! `B` bits and a counter in the other `64 - B`:
+//!
+//! ```text
+//! nonce = worker_id << (64 - B) | counter
+//! ```
+//!
+//! so a pool can attribute a share to a worker from its nonce alone. Workers with
+//! different ids never hash the same nonce, and each has `2^(64 - B)` nonces to itself.
+//!
+//! The namespace is a convention between a pool and its workers, not a consensus rule.
+//! Verification is unchanged: a solution is valid or not whatever its nonce's top bits
+//! hold, and nothing stops a worker from claiming another's id.
+
+/// A split of the nonce into a worker id and a counter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct NonceNamespace {
+    id_bits: u8,
+}
+
+impl NonceNamespace {
+    /// Reserves the top `id_bits` bits of the nonce for the worker id. More than 64 bits
+    /// are treated as 64.
+    pub fn new(id_bits: u8) -> Self {
+        NonceNamespace {
+            id_bits: id_bits.min(64),
+        }
+    }
+
+    /// Bits of the worker id.
+    pub fn id_bits(&self) -> u8 {
+        self.id_bits
+    }
+
+    /// Bits of the counter.
+    pub fn counter_bits(&self) -> u8 {
+        64 - self.id_bits
+    }
+
+    /// The highest worker id.
+    pub fn max_worker_id(&self) -> u64 {
+        u64::MAX
+            .checked_shr(self.counter_bits() as u32)
+            .unwrap_or(0)
+    }
+
+    /// The highest counter.
+    pub fn max_counter(&self) -> u64 {
+        u64::MAX.checked_shr(self.id_bits as u32).unwrap_or(0)
+    }
+
+    /// The nonce of a worker's counter.
+    pub fn compose(&self, worker_id: u64, counter: u64) -> Result<u64, NamespaceError> {
+        if worker_id > self.max_worker_id() {
+            return Err(NamespaceError::WorkerId {
+                worker_id,
+                id_bits: self.id_bits,
+            });
+        }
+        if counter > self.max_counter() {
+            return Err(NamespaceError::Counter {
+                counter,
+                counter_bits: self.counter_bits(),
+            });
+        }
+        Ok(self.compose_unchecked(worker_id, counter))
+    }
+
+    /// The worker id and counter of a nonce.
+    pub fn decompose(&self, nonce: u64) -> (u64, u64) {
+        let worker_id = nonce.checked_shr(self.counter_bits() as u32).unwrap_or(0);
+        (worker_id, nonce & self.max_counter())
+    }
+
+    /// [`compose`](Self::compose) for ids and counters known to fit.
+    pub(crate) fn compose_unchecked(&self, worker_id: u64, counter: u64) -> u64 {
+        worker_id
+            .checked_shl(self.counter_bits() as u32)
+            .unwrap_or(0)
+            | counter
+    }
+}
+
+/// Why a nonce could not be placed in a namespace.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NamespaceError {
+    /// The worker id does not fit in the id bits.
+    WorkerId { worker_id: u64, id_bits: u8 },
+    /// The counter does not fit in the counter bits.
+    Counter { counter: u64, counter_bits: u8 },
+    /// A miner was asked to search a namespace in permuted order. Namespaced miners
+    /// search their counters sequentially.
+    PermutedOrder,
+}
+
+impl std::fmt::Display for NamespaceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NamespaceError::WorkerId { worker_id, id_bits } => {
+                write!(
+                    f,
+                    "Worker id {} does not fit in {} bits",
+                    worker_id, id_bits
+                )
+            }
+            NamespaceError::Counter {
+                counter,
+                counter_bits,
+            } => write!(
+                f,
+                "Counter {} does not fit in {} bits",
+                counter, counter_bits
+            ),
+            NamespaceError::PermutedOrder => {
+                write!(f, "Nonce namespaces are searched in sequential order only")
+            }
+        }
+    }
+}
+
+impl std::error::Error for NamespaceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
@@ -0,0 +1,370 @@
+//! Estimation of network hashrate from the best difficulty of each challenge window,
+//! and of a miner's hashrate from its accepted shares.
+//!
+//! If the network computes hashes as a Poisson process of rate `λ`, the number of
+//! hashes of difficulty at least `d` in a window of `T` seconds is Poisson with mean
+//! `λ T 2^-d`, so the best difficulty `D` has `P(D < d) = exp(-λ T 2^-d)`. With
+//! `x = λ T 2^-(d+1)`, an observed best of `d` has likelihood `e^-x (1 - e^-x)`.
+//! [`estimate_hashrate`] maximizes the likelihood of all observations over `ln λ`,
+//! where it is concave, and takes the interval from the curvature at the maximum.
+//!
+//! Everything is computed from `ln λ`, so difficulties far beyond `f64` precision of
+//! `2^d` are fine; only the returned hashrates themselves may overflow.
+//!
+//! A [`HashrateTracker`] uses the expected-work method instead: a share accepted at
+//! difficulty `d` stands for `2^d` hashes on average, so the hashrate is the work of the
+//! shares over the time they took. Work and time are both decayed exponentially, each
+//! share and each moment weighted `2^(-age / half_life)`, and the estimate is
+//! `W / E` for decayed work `W` and decayed time `E`. Shares arrive as a Poisson
+//! process, so the variance of `W` is the decayed sum of squared work `S`, and the
+//! interval is `ln(W / E) ± z / √n` in the log domain, where `n = W² / S` is the
+//! effective number of shares.
+
+use std::{
+    collections::VecDeque,
+    f64::consts::LN_2,
+    time::{Duration, Instant},
+};
+
+/// Confidence level of [`HashrateEstimate`]'s interval.
+pub const HASHRATE_CONFIDENCE: f64 = 0.95;
+
+/// The standard normal quantile of [`HASHRATE_CONFIDENCE`].
+const Z: f64 = 1.959_963_984_540_054;
+
+/// Observations weighted below this are dropped by [`OnlineEstimator`].
+const MIN_WEIGHT: f64 = 1e-6;
+
+/// The best difficulty seen in one challenge window.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct DifficultyObservation {
+    pub best_difficulty: u32,
+    /// Length of the window in seconds.
+    pub window_seconds: f64,
+}
+
+impl DifficultyObservation {
+    pub fn new(best_difficulty: u32, window_seconds: f64) -> Self {
+        DifficultyObservation {
+            best_difficulty,
+            window_seconds,
+        }
+    }
+}
+
+/// A hashrate estimate, in hashes per second.
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct HashrateEstimate {
+    /// The maximum-likelihood estimate.
+    pub hashrate: f64,
+    /// Bounds of the [`HASHRATE_CONFIDENCE`] interval.
+    pub lower: f64,
+    pub upper: f64,
+    /// Number of observations, or their total weight for an [`OnlineEstimator`], or the
+    /// effective number of shares for a [`HashrateTracker`].
+    pub observations: f64,
+}
+
+impl HashrateEstimate {
+    /// The estimate from no observations: zero, in an unbounded interval.
+    fn none() -> Self {
+        HashrateEstimate {
+            hashrate: 0.0,
+            lower: 0.0,
+            upper: f64::INFINITY,
+            observations: 0.0,
+        }
+    }
+
+    pub fn contains(&self, hashrate: f64) -> bool {
+        (self.lower..=self.upper).contains(&hashrate)
+    }
+}
+
+/// Estimates the network hashrate from the best difficulty of each window.
+///
+/// Observations with windows that are not positive and finite are ignored. With no
+/// usable observations the estimate is zero, with an unbounded interval.
+pub fn estimate_hashrate(observations: &[DifficultyObservation]) -> HashrateEstimate {
+    let weighted: Vec<(DifficultyObservation, f64)> =
+        observations.iter().map(|&o| (o, 1.0)).collect();
+    estimate_weighted(&weighted)
+}
+
+/// A streaming estimate over an exponentially weighted window.
+///
+/// Each observation's weight halves for every `half_life` of window time pushed
+/// after it, and observations are dropped once their weight is negligible.
+#[derive(Clone, Debug)]
+pub struct OnlineEstimator {
+    half_life: f64,
+    /// Observations with the window time pushed after each, oldest first.
+    observations: VecDeque<(DifficultyObservation, f64)>,
+}
+
+impl OnlineEstimator {
+    pub fn new(half_life: Duration) -> Self {
+        OnlineEstimator {
+            half_life: half_life.as_secs_f64(),
+            observations: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, observation: DifficultyObservation) {
+        if !usable(&observation) {
+            return;
+        }
+        for (_, age) in &mut self.observations {
+            *age += observation.window_seconds;
+        }
+        self.observations.push_back((observation, 0.0));
+        while self
+            .observations
+            .front()
+            .is_some_and(|(_, age)| self.weight(*age) < MIN_WEIGHT)
+        {
+            self.observations.pop_front();
+        }
+    }
+
+    pub fn estimate(&self) -> HashrateEstimate {
+        let weighted: Vec<(DifficultyObservation, f64)> = self
+            .observations
+            .iter()
+            .map(|&(observation, age)| (observation, self.weight(age)))
+            .collect();
+        estimate_weighted(&weighted)
+    }
+
+    /// Number of observations currently weighted.
+    pub fn len(&self) -> usize {
+        self.observations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.observations.is_empty()
+    }
+
+    fn weight(&self, age: f64) -> f64 {
+        if self.half_life > 0.0 {
+            0.5f64.powf(age / self.half_life)
+        } else if age == 0.0 {
+            1.0
+        } else {
+            0.0
+        }
+    }
+}
+
+fn usable(observation: &DifficultyObservation) -> bool {
+    observation.window_seconds > 0.0 && observation.window_seconds.is_finite()
+}
+
+fn estimate_weighted(observations: &[(DifficultyObservation, f64)]) -> HashrateEstimate {
+    // `ln(T 2^-(d+1))` of each observation, so that `ln x = ln λ + offset`.
+    let terms: Vec<(f64, f64)> = observations
+        .iter()
+        .filter(|(observation, weight)| usable(observation) && *weight > 0.0)
+        .map(|(observation, weight)| {
+            let offset =
+                observation.window_seconds.ln() - (observation.best_difficulty as f64 + 1.0) * LN_2;
+            (offset, *weight)
+        })
+        .collect();
+    let total: f64 = terms.iter().map(|(_, weight)| weight).sum();
+    if terms.is_empty() {
+        return HashrateEstimate::none();
+    }
+    let score = |theta: f64| -> f64 {
+        terms
+            .iter()
+            .map(|(offset, weight)| {
+                let x = (theta + offset).exp();
+                weight * (ratio(x) - x)
+            })
+            .sum()
+    };
+
+    // The score decreases in `ln λ`. A single observation peaks at `x = ln 2`, so start
+    // from the weighted mean of those peaks and widen the bracket until it holds the
+    // root.
+    let start = LN_2.ln() - terms.iter().map(|(o, w)| o * w).sum::<f64>() / total;
+    let (mut lo, mut hi) = (start - 1.0, start + 1.0);
+    let mut step = 1.0;
+    while score(lo) < 0.0 {
+        step *= 2.0;
+        lo -= step;
+    }
+    step = 1.0;
+    while score(hi) > 0.0 {
+        step *= 2.0;
+        hi += step;
+    }
+    for _ in 0..200 {
+        let mid = 0.5 * (lo + hi);
+        if mid <= lo || mid >= hi {
+            break;
+        }
+        if score(mid) > 0.0 {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    let theta = 0.5 * (lo + hi);
+
+    let information: f64 = terms
+        .iter()
+        .map(|(offset, weight)| {
+            let x = (theta + offset).exp();
+            weight * (x - ratio_slope(x))
+        })
+        .sum();
+    let half_width = Z / information.sqrt();
+    HashrateEstimate {
+        hashrate: theta.exp(),
+        lower: (theta - half_width).exp(),
+        upper: (theta + half_width).exp(),
+        observations: total,
+    }
+}
+
+/// `x / (e^x - 1)`, which tends to 1 at zero.
+fn ratio(x: f64) -> f64 {
+    if x < 1e-8 {
+        1.0 - x / 2.0
+    } else if x.is_infinite() {
+        0.0
+    } else {
+        x / x.exp_m1()
+    }
+}
+
+/// `x` times the derivative of [`ratio`].
+fn ratio_slope(x: f64) -> f64 {
+    if x < 1e-3 {
+        -x / 2.0 + x * x / 6.0
+    } else {
+        let r = ratio(x);
+        // `e^x r` is `x / (1 - e^-x)`, which does not overflow.
+        r * (1.0 - x / -(-x).exp_m1())
+    }
+}
+
+/// A miner's hashrate, from the difficulties and times of its accepted shares.
+///
+/// Record each share at the difficulty it was accepted at, the share target, rather than
+/// the difficulty its hash happened to reach, which would overstate the work. Shares may
+/// come in bursts, at many times the same instant, and at any mix of difficulties, as
+/// when vardiff retargets in the window.
+#[derive(Clone, Debug)]
+pub struct HashrateTracker {
+    /// Decay time constant in seconds, `half_life / ln 2`.
+    tau: f64,
+    /// When the tracker started counting time.
+    start: Instant,
+    /// The time that `work` and `work_sq` are decayed to.
+    reference: Instant,
+    /// Decayed sum of `2^d`.
+    work: f64,
+    /// Decayed sum of `4^d`, with twice the decay.
+    work_sq: f64,
+}
+
+impl HashrateTracker {
+    /// A tracker whose window starts now.
+    ///
+    /// # Panics
+    ///
+    /// If `half_life` is zero.
+    pub fn new(half_life: Duration) -> Self {
+        assert!(!half_life.is_zero(), "half-life must be positive");
+        let now = Instant::now();
+        HashrateTracker {
+            tau: half_life.as_secs_f64() / LN_2,
+            start: now,
+            reference: now,
+            work: 0.0,
+            work_sq: 0.0,
+        }
+    }
+
+    /// Starts the window at `start` instead. Time before the first share counts, as the
+    /// miner was hashing toward it.
+    pub fn with_start(mut self, start: Instant) -> Self {
+        self.start = start;
+        self.reference = start;
+        self
+    }
+
+    /// Records a share accepted at `difficulty` at time `at`. Shares may be recorded out
+    /// of order.
+    pub fn record_share(&mut self, difficulty: u32, at: Instant) {
+        self.advance(at);
+        let weight = self.weight(self.reference.saturating_duration_since(at));
+        let work = 2f64.powi(difficulty as i32);
+        self.work += weight * work;
+        self.work_sq += weight * weight * work * work;
+    }
+
+    /// The estimate as of now.
+    pub fn estimate(&self) -> HashrateEstimate {
+        self.estimate_at(Instant::now())
+    }
+
+    /// The estimate as of `now`, which counts the time since the last share.
+    ///
+    /// With no shares, or no time, the estimate is zero, with an unbounded interval.
+    pub fn estimate_at(&self, now: Instant) -> HashrateEstimate {
+        let now = now.max(self.reference);
+        let decay = self.weight(now - self.reference);
+        let work = self.work * decay;
+        let work_sq = self.work_sq * decay * decay;
+        let elapsed = now.saturating_duration_since(self.start).as_secs_f64();
+        let exposure = -self.tau * (-elapsed / self.tau).exp_m1();
+        if work <= 0.0 || exposure <= 0.0 {
+            return HashrateEstimate::none();
+        }
+        let shares = work * work / work_sq;
+        let theta = work.ln() - exposure.ln();
+        let half_width = Z / shares.sqrt();
+        HashrateEstimate {
+            hashrate: theta.exp(),
+            lower: (theta - half_width).exp(),
+            upper: (theta + half_width).exp(),
+            observations: shares,
+        }
+    }
+
+    /// Adds another tracker's shares, as for an account of several workers. The window
+    /// starts at the earlier start, and both trackers must have the same half-life.
+    ///
+    /// # Panics
+    ///
+    /// If the half-lives differ.
+    pub fn merge(&mut self, other: &HashrateTracker) {
+        assert!(
+            (self.tau - other.tau).abs() <= self.tau * 1e-12,
+            "cannot merge trackers with different half-lives"
+        );
+        self.advance(other.reference);
+        let weight = self.weight(self.reference - other.reference);
+        self.work += weight * other.work;
+        self.work_sq += weight * weight * other.work_sq;
+        self.start = self.start.min(other.start);
+    }
+
+    /// Decays the sums forward to `at`, if it is later than the reference.
+    fn advance(&mut self, at: Instant) {
+        if at > self.reference {
+            let weight = self.weight(at - self.reference);
+            self.work *= weight;
+            self.work_sq *= weight * weight;
+            self.reference = at;
+        }
+    }
+
+    fn weight(&self, age: Duration) -> f64 {
+        (-age.as_secs_f64() / self.tau).exp()
+    }
+}
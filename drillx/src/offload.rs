@@ -0,0 +1,280 @@
+//! Batch verification offloaded to a pluggable device, with a CPU fallback.
+//!
+//! This is host-side plumbing only. Drillx ships no device backend: there is no GPU
+//! kernel, no on-device equix verification and no pinned host memory here, and
+//! [`default_device`] always returns `None`, so out of the box [`OffloadVerifier::new`]
+//! and [`verify_batch`] verify on the CPU, exactly as the CPU
+//! [`verify_batch`](crate::verify_batch) does. A backend, whether GPU, FPGA or a remote
+//! verifier, implements [`VerifyDevice`] and is plugged in with
+//! [`OffloadVerifier::with_device`].
+//!
+//! Verifying a share is independent of every other share, so a pool's verify farm can
+//! hand whole batches to a device. An [`OffloadVerifier`] splits a batch into launches
+//! of at most [`VerifyDevice::max_batch`] solutions, reuses one staging buffer across
+//! calls, and falls back to the CPU [`verify_batch`](crate::verify_batch) when there is no
+//! device or a launch fails.
+//!
+//! A device bug would silently accept invalid shares, so a verifier can cross-check a
+//! fraction of the device's verdicts on the CPU. On any disagreement the CPU verdict
+//! wins, [`OffloadStats::disagreements`] grows, and the `drillx.offload_disagreement`
+//! event and `drillx_offload_disagreements_total` metric fire.
+
+use std::sync::{Mutex, OnceLock};
+
+use crate::{telemetry::event, Solution};
+
+/// Fraction of device verdicts [`OffloadVerifier::new`] cross-checks on the CPU.
+pub const DEFAULT_CROSS_CHECK: f64 = 0.001;
+
+/// A device that verifies batches of solutions.
+///
+/// Implementations own their device and pinned host buffers, sized for
+/// [`max_batch`](VerifyDevice::max_batch) solutions and reused between launches.
+pub trait VerifyDevice: Send {
+    /// A name for logs, such as the device model.
+    fn name(&self) -> String;
+
+    /// Most solutions per [`verify`](VerifyDevice::verify) call.
+    fn max_batch(&self) -> usize;
+
+    /// Uploads the solutions, each laid out as [`Solution::to_bytes`], verifies them
+    /// against the challenge, and downloads one verdict per solution into `verdicts`,
+    /// which is as long as `solutions`.
+    fn verify(
+        &mut self,
+        challenge: &[u8; 32],
+        solutions: &[[u8; 24]],
+        verdicts: &mut [bool],
+    ) -> Result<(), DeviceError>;
+}
+
+/// A device launch failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeviceError(pub String);
+
+impl std::fmt::Display for DeviceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "device verification failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for DeviceError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+/// Counts of a [`OffloadVerifier`]'s work.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct OffloadStats {
+    /// Solutions verified on the device.
+    pub device_verified: u64,
+    /// Solutions verified on the CPU, for lack of a device or after a failed launch.
+    pub cpu_verified: u64,
+    /// Device verdicts checked again on the CPU.
+    pub cross_checked: u64,
+    /// Cross-checks where the device was wrong.
+    pub disagreements: u64,
+    /// Failed launches.
+    pub fallbacks: u64,
+}
+
+/// Verifies batches on a plugged-in device, falling back to the CPU.
+pub struct OffloadVerifier {
+    device: Option<Box<dyn VerifyDevice>>,
+    /// Cross-check threshold out of `u64::MAX`.
+    cross_check: u64,
+    sampler: u64,
+    staging: Vec<[u8; 24]>,
+    verdicts: Vec<bool>,
+    stats: OffloadStats,
+}
+
+impl std::fmt::Debug for OffloadVerifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("OffloadVerifier")
+            .field("device", &self.device_name())
+            .field("stats", &self.stats)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Default for OffloadVerifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OffloadVerifier {
+    /// A verifier on the [`default_device`], cross-checking [`DEFAULT_CROSS_CHECK`] of
+    /// its verdicts.
+    pub fn new() -> Self {
+        Self::build(default_device())
+    }
+
+    pub fn with_device(device: Box<dyn VerifyDevice>) -> Self {
+        Self::build(Some(device))
+    }
+
+    /// A verifier without a device, which always runs on the CPU.
+    pub fn cpu() -> Self {
+        Self::build(None)
+    }
+
+    fn build(device: Option<Box<dyn VerifyDevice>>) -> Self {
+        OffloadVerifier {
+            device,
+            cross_check: 0,
+            sampler: 0x6770_7576_6572_6966,
+            staging: Vec::new(),
+            verdicts: Vec::new(),
+            stats: OffloadStats::default(),
+        }
+        .cross_check(DEFAULT_CROSS_CHECK)
+    }
+
+    /// Sets the fraction of device verdicts checked on the CPU, clamped to `[0, 1]`.
+    /// Which verdicts are checked is chosen pseudo-randomly.
+    pub fn cross_check(mut self, fraction: f64) -> Self {
+        let fraction = if fraction.is_nan() {
+            0.0
+        } else {
+            fraction.clamp(0.0, 1.0)
+        };
+        self.cross_check = if fraction >= 1.0 {
+            u64::MAX
+        } else {
+            (fraction * u64::MAX as f64) as u64
+        };
+        self
+    }
+
+    pub fn device_name(&self) -> Option<String> {
+        self.device.as_ref().map(|device| device.name())
+    }
+
+    pub fn stats(&self) -> OffloadStats {
+        self.stats
+    }
+
+    /// Returns whether each solution is valid for the challenge, like
+    /// [`crate::verify_batch`].
+    pub fn verify_batch(&mut self, challenge: &[u8; 32], solutions: &[Solution]) -> Vec<bool> {
+        let Some(device) = &mut self.device else {
+            self.stats.cpu_verified += solutions.len() as u64;
+            return crate::verify_batch(challenge, solutions);
+        };
+        let max_batch = device.max_batch().max(1);
+        let mut verdicts = Vec::with_capacity(solutions.len());
+        for chunk in solutions.chunks(max_batch) {
+            self.staging.clear();
+            self.staging.extend(chunk.iter().map(Solution::to_bytes));
+            self.verdicts.clear();
+            self.verdicts.resize(chunk.len(), false);
+            match device.verify(challenge, &self.staging, &mut self.verdicts) {
+                Ok(()) => {
+                    self.stats.device_verified += chunk.len() as u64;
+                    let start = verdicts.len();
+                    verdicts.extend_from_slice(&self.verdicts);
+                    Self::check(
+                        &mut self.stats,
+                        &mut self.sampler,
+                        self.cross_check,
+                        challenge,
+                        chunk,
+                        &mut verdicts[start..],
+                    );
+                }
+                Err(_err) => {
+                    event!(
+                        crate::telemetry::OFFLOAD_FALLBACK_EVENT,
+                        WARN,
+                        device = device.name(),
+                        error = %_err,
+                        "device verification failed, verifying on the CPU"
+                    );
+                    #[cfg(feature = "metrics")]
+                    metrics::counter!(crate::telemetry::OFFLOAD_FALLBACKS_METRIC).increment(1);
+                    self.stats.fallbacks += 1;
+                    self.stats.cpu_verified += chunk.len() as u64;
+                    verdicts.extend(chunk.iter().map(|solution| solution.is_valid(challenge)));
+                }
+            }
+        }
+        #[cfg(feature = "metrics")]
+        {
+            let accepted = verdicts.iter().filter(|v| **v).count() as u64;
+            metrics::counter!(crate::telemetry::VERIFY_ACCEPTED_METRIC).increment(accepted);
+            metrics::counter!(crate::telemetry::VERIFY_REJECTED_METRIC, "reason" => "invalid")
+                .increment(verdicts.len() as u64 - accepted);
+        }
+        verdicts
+    }
+
+    /// Cross-checks a sample of a launch's verdicts, correcting any the device got wrong.
+    fn check(
+        stats: &mut OffloadStats,
+        sampler: &mut u64,
+        threshold: u64,
+        challenge: &[u8; 32],
+        solutions: &[Solution],
+        verdicts: &mut [bool],
+    ) {
+        if threshold == 0 {
+            return;
+        }
+        let mut disagreements = 0;
+        for (solution, verdict) in solutions.iter().zip(verdicts) {
+            if splitmix(sampler) > threshold {
+                continue;
+            }
+            stats.cross_checked += 1;
+            let cpu = solution.is_valid(challenge);
+            if cpu != *verdict {
+                disagreements += 1;
+                *verdict = cpu;
+            }
+        }
+        if disagreements > 0 {
+            stats.disagreements += disagreements;
+            event!(
+                crate::telemetry::OFFLOAD_DISAGREEMENT_EVENT,
+                ERROR,
+                disagreements,
+                "device verdicts disagree with the CPU"
+            );
+            #[cfg(feature = "metrics")]
+            metrics::counter!(crate::telemetry::OFFLOAD_DISAGREEMENTS_METRIC)
+                .increment(disagreements);
+        }
+    }
+}
+
+fn splitmix(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+/// Returns the device [`OffloadVerifier::new`] uses, if any.
+///
+/// No backend is built in, so this is always `None`.
+pub fn default_device() -> Option<Box<dyn VerifyDevice>> {
+    None
+}
+
+/// Returns whether each solution is valid for the challenge, on the
+/// [`default_device`] if there is one and on the CPU otherwise. With no backend built
+/// in, that is always the CPU.
+///
+/// Calls share one process-wide [`OffloadVerifier`] and are serialized on it.
+pub fn verify_batch(challenge: &[u8; 32], solutions: &[Solution]) -> Vec<bool> {
+    static VERIFIER: OnceLock<Mutex<OffloadVerifier>> = OnceLock::new();
+    VERIFIER
+        .get_or_init(|| Mutex::new(OffloadVerifier::new()))
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .verify_batch(challenge, solutions)
+}
@@ -0,0 +1,69 @@
+//! Rayon adapters for hashing nonces in parallel.
+//!
+//! These are data-parallel building blocks for applications with their own
+//! orchestration; the [`miner`](crate::miner) is the batteries-included alternative.
+//!
+//! The iterators run on the pool that drives them: rayon's global pool, sized to the
+//! machine, unless they are driven inside [`ThreadPool::install`] of a pool of your
+//! own. Solver memory is allocated lazily, as with [`map_init`]: rayon calls the
+//! initializer once per piece of work it splits off rather than once per nonce, so a run
+//! allocates a few buffers per thread and reuses each for many nonces. The buffers are
+//! freed when the run ends.
+//!
+//! [`ThreadPool::install`]: rayon::ThreadPool::install
+//! [`map_init`]: rayon::iter::ParallelIterator::map_init
+
+use rayon::iter::{plumbing::UnindexedConsumer, IntoParallelIterator, ParallelIterator};
+
+use crate::{DrillxMemory, Hash};
+
+/// Hashes every nonce of a range in parallel, skipping nonces without a solution.
+///
+/// The range is anything rayon can iterate over in parallel, such as `0..n` or
+/// `a..=u64::MAX`. Hashes arrive in no particular order.
+pub fn par_hash_range<R>(challenge: &[u8; 32], nonces: R) -> DrillxHash<R::Iter>
+where
+    R: IntoParallelIterator<Item = u64>,
+{
+    nonces.into_par_iter().drillx_hash(challenge)
+}
+
+/// Hashing for parallel iterators of nonces.
+pub trait ParallelHashExt: ParallelIterator<Item = u64> {
+    /// Hashes each nonce, skipping nonces without a solution, like [`par_hash_range`].
+    fn drillx_hash(self, challenge: &[u8; 32]) -> DrillxHash<Self> {
+        DrillxHash {
+            nonces: self,
+            challenge: *challenge,
+        }
+    }
+}
+
+impl<I: ParallelIterator<Item = u64>> ParallelHashExt for I {}
+
+/// A parallel iterator over the hashes of nonces, returned by [`par_hash_range`] and
+/// [`ParallelHashExt::drillx_hash`].
+#[derive(Clone, Debug)]
+pub struct DrillxHash<I> {
+    nonces: I,
+    challenge: [u8; 32],
+}
+
+impl<I: ParallelIterator<Item = u64>> ParallelIterator for DrillxHash<I> {
+    type Item = (u64, Hash);
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        let challenge = self.challenge;
+        self.nonces
+            .map_init(DrillxMemory::new, move |memory, nonce| {
+                crate::hash_with_memory(memory, &challenge, &nonce.to_le_bytes())
+                    .ok()
+                    .map(|hash| (nonce, hash))
+            })
+            .flatten_iter()
+            .drive_unindexed(consumer)
+    }
+}
@@ -0,0 +1,132 @@
+//! Pool payouts from share records.
+//!
+//! Both schemes weight shares with [`share_weight`], sum them per miner in `u128`, and
+//! split the reward by largest remainder: every miner gets the floor of its exact share,
+//! and the units left over go one each to the miners with the largest remainders, ties
+//! to the smaller miner id. Payouts therefore sum exactly to the reward, and while the
+//! weights fit in `u128`, a miner's payout never drops when its weight grows and nothing
+//! else changes.
+//!
+//! A miner's weight saturates at `u128::MAX`. If the miners' weights together overflow
+//! `u128`, every weight is shifted right by the fewest bits that make them fit, which
+//! keeps their ratios up to rounding.
+//!
+//! Results are sorted by miner id and list every miner with weight, including those whose
+//! share rounds to zero. With no weight at all nothing is paid and the result is empty.
+
+use std::collections::BTreeMap;
+
+use crate::share_weight;
+
+/// Splits `total_reward` in proportion to each miner's total share weight above
+/// `min_difficulty`. Shares below it carry no weight.
+pub fn proportional<M: Ord + Clone>(
+    shares: &[(M, u32)],
+    min_difficulty: u32,
+    total_reward: u64,
+) -> Vec<(M, u64)> {
+    let mut weights = BTreeMap::new();
+    for (miner, difficulty) in shares {
+        add(
+            &mut weights,
+            miner,
+            share_weight(*difficulty, min_difficulty),
+        );
+    }
+    split(weights, total_reward)
+}
+
+/// Splits `total_reward` over the last `n_window` units of work, PPLNS-style.
+///
+/// A share of difficulty `d` is `2^d` units, its expected hashes. Shares are walked from
+/// the newest back until the window is full, and the share that straddles its start
+/// counts only for the part inside. If all shares together are less than the window,
+/// they split the whole reward.
+pub fn pplns<M: Ord + Clone>(
+    shares_in_order: &[(M, u32)],
+    n_window: u128,
+    total_reward: u64,
+) -> Vec<(M, u64)> {
+    let mut weights = BTreeMap::new();
+    let mut left = n_window;
+    for (miner, difficulty) in shares_in_order.iter().rev() {
+        if left == 0 {
+            break;
+        }
+        let weight = share_weight(*difficulty, 0).min(left);
+        left -= weight;
+        add(&mut weights, miner, weight);
+    }
+    split(weights, total_reward)
+}
+
+fn add<M: Ord + Clone>(weights: &mut BTreeMap<M, u128>, miner: &M, weight: u128) {
+    if weight == 0 {
+        return;
+    }
+    let total = weights.entry(miner.clone()).or_insert(0);
+    *total = total.saturating_add(weight);
+}
+
+/// Splits the reward by largest remainder over the miners' weights.
+fn split<M: Ord>(weights: BTreeMap<M, u128>, total_reward: u64) -> Vec<(M, u64)> {
+    let mut shift = 0;
+    let total = loop {
+        let total = weights
+            .values()
+            .try_fold(0u128, |total, weight| total.checked_add(weight >> shift));
+        match total {
+            Some(total) => break total,
+            None => shift += 1,
+        }
+    };
+    if total == 0 {
+        return Vec::new();
+    }
+    let mut payouts: Vec<(M, u64, u128)> = weights
+        .into_iter()
+        .map(|(miner, weight)| {
+            let (payout, remainder) = mul_div(total_reward, weight >> shift, total);
+            (miner, payout, remainder)
+        })
+        .collect();
+    let paid: u64 = payouts.iter().map(|(_, payout, _)| payout).sum();
+    // Each floor loses less than one unit, so fewer units are left than miners.
+    let left = (total_reward - paid) as usize;
+    let mut order: Vec<usize> = (0..payouts.len()).collect();
+    // Stable, so equal remainders keep miner id order.
+    order.sort_by(|a, b| payouts[*b].2.cmp(&payouts[*a].2));
+    for &i in &order[..left] {
+        payouts[i].1 += 1;
+    }
+    payouts
+        .into_iter()
+        .map(|(miner, payout, _)| (miner, payout))
+        .collect()
+}
+
+/// Returns the quotient and remainder of `a * b / c`, for `b <= c` and `c > 0`, without
+/// overflowing.
+fn mul_div(a: u64, b: u128, c: u128) -> (u64, u128) {
+    // Long division over the bits of `a`, keeping `r < c` so each step subtracts `c`
+    // at most once.
+    let (mut q, mut r) = (0u64, 0u128);
+    for bit in (0..u64::BITS).rev() {
+        let (doubled, carry) = r.overflowing_add(r);
+        q <<= 1;
+        r = doubled;
+        if carry || r >= c {
+            r = r.wrapping_sub(c);
+            q += 1;
+        }
+        if a >> bit & 1 == 1 {
+            let (sum, carry) = r.overflowing_add(b);
+            r = sum;
+            if carry || r >= c {
+                r = r.wrapping_sub(c);
+                q += 1;
+            }
+        }
+    }
+    (q, r)
+}
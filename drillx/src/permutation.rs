@@ -0,0 +1,63 @@
+//! A keyed permutation of the nonce space.
+//!
+//! [`NoncePermutation`] maps indices to nonces one to one, so walking the indices in
+//! order visits every nonce exactly once in an order that looks random to anyone
+//! without the key. It is an 8-round Feistel network over the two 32-bit halves of the
+//! index, `l = i >> 32` and `r = i as u32`:
+//!
+//! ```text
+//! k[j]      = key[4j..4j + 4] as u32 LE, for j in 0..4
+//! f(x, k)   = m ^ (m >> 16), where m = (z ^ (z >> 13)) * 0xc2b2ae35
+//!                             and   z = (x ^ k) * 0x85ebca6b, all mod 2^32
+//! round n   = (l, r) -> (r, l ^ f(r, k[n % 4] ^ n * 0x9e3779b9)), for n in 0..8
+//! nonce     = l << 32 | r
+//! ```
+//!
+//! Each round is undone by `(l, r) -> (r ^ f(l, k_n), l)`, so the whole is a bijection
+//! whatever `f`. The construction is fixed: the same key gives the same nonces in every
+//! version and on every platform.
+
+/// Number of Feistel rounds.
+const ROUNDS: u32 = 8;
+
+/// A bijection of `u64` chosen by a 16-byte key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NoncePermutation {
+    keys: [u32; ROUNDS as usize],
+}
+
+impl NoncePermutation {
+    pub fn new(key: [u8; 16]) -> Self {
+        let mut keys = [0; ROUNDS as usize];
+        for (n, round_key) in keys.iter_mut().enumerate() {
+            let j = 4 * (n % 4);
+            let word = u32::from_le_bytes([key[j], key[j + 1], key[j + 2], key[j + 3]]);
+            *round_key = word ^ (n as u32).wrapping_mul(0x9e37_79b9);
+        }
+        NoncePermutation { keys }
+    }
+
+    /// The nonce at index `i`.
+    pub fn permute(&self, i: u64) -> u64 {
+        let (mut l, mut r) = ((i >> 32) as u32, i as u32);
+        for &k in &self.keys {
+            (l, r) = (r, l ^ round(r, k));
+        }
+        (l as u64) << 32 | r as u64
+    }
+
+    /// The index whose nonce is `nonce`, so that `invert(permute(i)) == i`.
+    pub fn invert(&self, nonce: u64) -> u64 {
+        let (mut l, mut r) = ((nonce >> 32) as u32, nonce as u32);
+        for &k in self.keys.iter().rev() {
+            (l, r) = (r ^ round(l, k), l);
+        }
+        (l as u64) << 32 | r as u64
+    }
+}
+
+fn round(x: u32, k: u32) -> u32 {
+    let z = (x ^ k).wrapping_mul(0x85eb_ca6b);
+    let m = (z ^ (z >> 13)).wrapping_mul(0xc2b2_ae35);
+    m ^ (m >> 16)
+}
@@ -0,0 +1,107 @@
+//! Postgres column support via sqlx.
+//!
+//! [`Solution`] is stored as a `BYTEA` of exactly 24 bytes, laid out as
+//! [`Solution::to_bytes`]. [`Hash`] is stored as a `BYTEA` of exactly 48 bytes, the
+//! digest followed by the hash. Decoding a column of any other length fails with a
+//! [`LengthError`] rather than truncating or padding.
+
+use sqlx::{
+    encode::IsNull,
+    error::BoxDynError,
+    postgres::{PgArgumentBuffer, PgHasArrayType, PgTypeInfo, PgValueRef},
+    Decode, Encode, Postgres, Type,
+};
+
+use crate::{Hash, Solution};
+
+/// A byte column has the wrong length for the type it is decoded as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LengthError {
+    pub expected: usize,
+    pub actual: usize,
+}
+
+impl std::fmt::Display for LengthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "Expected {} bytes, got {}", self.expected, self.actual)
+    }
+}
+
+impl std::error::Error for LengthError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+fn exact<const N: usize>(bytes: &[u8]) -> Result<[u8; N], LengthError> {
+    bytes.try_into().map_err(|_| LengthError {
+        expected: N,
+        actual: bytes.len(),
+    })
+}
+
+impl TryFrom<&[u8]> for Solution {
+    type Error = LengthError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        exact(bytes).map(Solution::from_bytes)
+    }
+}
+
+impl TryFrom<&[u8]> for Hash {
+    type Error = LengthError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let bytes: [u8; 48] = exact(bytes)?;
+        Ok(Hash {
+            d: bytes[..16].try_into().unwrap(),
+            h: bytes[16..].try_into().unwrap(),
+        })
+    }
+}
+
+impl Hash {
+    /// The digest followed by the hash, as stored in Postgres.
+    pub fn to_bytes(&self) -> [u8; 48] {
+        let mut bytes = [0; 48];
+        bytes[..16].copy_from_slice(&self.d);
+        bytes[16..].copy_from_slice(&self.h);
+        bytes
+    }
+}
+
+macro_rules! bytea {
+    ($ty:ty) => {
+        impl Type<Postgres> for $ty {
+            fn type_info() -> PgTypeInfo {
+                <&[u8] as Type<Postgres>>::type_info()
+            }
+
+            fn compatible(ty: &PgTypeInfo) -> bool {
+                <&[u8] as Type<Postgres>>::compatible(ty)
+            }
+        }
+
+        impl PgHasArrayType for $ty {
+            fn array_type_info() -> PgTypeInfo {
+                <&[u8] as PgHasArrayType>::array_type_info()
+            }
+        }
+
+        impl Encode<'_, Postgres> for $ty {
+            fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> Result<IsNull, BoxDynError> {
+                <&[u8] as Encode<Postgres>>::encode(&self.to_bytes()[..], buf)
+            }
+        }
+
+        impl<'r> Decode<'r, Postgres> for $ty {
+            fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+                let bytes = <&[u8] as Decode<Postgres>>::decode(value)?;
+                Ok(<$ty>::try_from(bytes)?)
+            }
+        }
+    };
+}
+
+bytea!(Solution);
+bytea!(Hash);
@@ -0,0 +1,207 @@
+//! Reference implementation of an on-chain drillx verification instruction.
+//!
+//! The instruction expects the following accounts, in order:
+//!
+//! 0. `[signer]` The miner submitting the solution.
+//! 1. `[]` The challenge account, owned by the executing program.
+//! 2. `[writable]` The result account, owned by the executing program and distinct
+//!    from the challenge account.
+//!
+//! The challenge account stores the challenge the solution must be mined against
+//! and the minimum difficulty it must meet ([`ChallengeAccount`]). The result account
+//! receives the computed hash and difficulty of an accepted solution ([`VerifyResult`]).
+//! Both layouts are little-endian and unpadded.
+//!
+//! Instruction data is the 24-byte [`Solution`] encoding (`digest ‖ nonce`). The
+//! difficulty is always derived from the solution itself, never from the instruction.
+
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{AccountMeta, Instruction},
+    program_error::ProgramError,
+    pubkey::Pubkey,
+};
+
+use crate::{error_code, Solution};
+
+/// Byte length of the verify instruction data.
+pub const VERIFY_DATA_LEN: usize = 24;
+
+/// Byte length of the challenge account data.
+pub const CHALLENGE_ACCOUNT_LEN: usize = 40;
+
+/// Byte length of the result account data.
+pub const RESULT_ACCOUNT_LEN: usize = 40;
+
+// Only on-chain, so that host builds can link it beside other programs' entrypoints.
+#[cfg(all(feature = "program-entrypoint", target_os = "solana"))]
+solana_program::entrypoint!(process_instruction);
+
+/// Processes an instruction for a program whose only instruction is [`process_verify`].
+pub fn process_instruction(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    process_verify(program_id, accounts, data)
+}
+
+/// Verifies a solution against the challenge account and records its hash in the result account.
+pub fn process_verify(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let [signer_info, challenge_info, result_info] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    if !signer_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if challenge_info.owner != program_id || result_info.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    // Writing the result into the challenge account would overwrite the challenge.
+    if challenge_info.key == result_info.key {
+        return Err(ProgramError::InvalidArgument);
+    }
+    if !result_info.is_writable {
+        return Err(ProgramError::InvalidAccountData);
+    }
+
+    // Parse the solution.
+    let bytes: [u8; VERIFY_DATA_LEN] = data
+        .try_into()
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    let solution = Solution::from_bytes(bytes);
+
+    // Prove the solution was mined against the stored challenge.
+    let challenge = ChallengeAccount::try_from_bytes(&challenge_info.try_borrow_data()?)?;
+    if !solution.is_valid(&challenge.challenge) {
+        return Err(VerifyError::InvalidSolution.into());
+    }
+
+    // Derive the difficulty from the solution rather than trusting the caller.
+    let hash = solution.to_hash();
+    let difficulty = hash.difficulty() as u64;
+    if difficulty < challenge.min_difficulty {
+        return Err(VerifyError::InsufficientDifficulty.into());
+    }
+
+    // Record the result.
+    let result = VerifyResult {
+        hash: hash.h,
+        difficulty,
+    };
+    let mut result_data = result_info.try_borrow_mut_data()?;
+    if result_data.len() < RESULT_ACCOUNT_LEN {
+        return Err(ProgramError::AccountDataTooSmall);
+    }
+    result_data[..RESULT_ACCOUNT_LEN].copy_from_slice(&result.to_bytes());
+    Ok(())
+}
+
+/// Builds a verify instruction for the given program and accounts.
+pub fn verify(
+    program_id: Pubkey,
+    signer: Pubkey,
+    challenge: Pubkey,
+    result: Pubkey,
+    solution: Solution,
+) -> Instruction {
+    Instruction {
+        program_id,
+        accounts: vec![
+            AccountMeta::new_readonly(signer, true),
+            AccountMeta::new_readonly(challenge, false),
+            AccountMeta::new(result, false),
+        ],
+        data: solution.to_bytes().to_vec(),
+    }
+}
+
+/// The challenge account layout: `challenge (32) ‖ min_difficulty (u64 LE)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChallengeAccount {
+    pub challenge: [u8; 32],
+    pub min_difficulty: u64,
+}
+
+impl ChallengeAccount {
+    pub fn to_bytes(&self) -> [u8; CHALLENGE_ACCOUNT_LEN] {
+        let mut bytes = [0; CHALLENGE_ACCOUNT_LEN];
+        bytes[..32].copy_from_slice(&self.challenge);
+        bytes[32..].copy_from_slice(&self.min_difficulty.to_le_bytes());
+        bytes
+    }
+
+    /// Parses the account data, ignoring any trailing bytes.
+    pub fn try_from_bytes(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < CHALLENGE_ACCOUNT_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut challenge = [0; 32];
+        let mut min_difficulty = [0; 8];
+        challenge.copy_from_slice(&data[..32]);
+        min_difficulty.copy_from_slice(&data[32..CHALLENGE_ACCOUNT_LEN]);
+        Ok(ChallengeAccount {
+            challenge,
+            min_difficulty: u64::from_le_bytes(min_difficulty),
+        })
+    }
+}
+
+/// The result account layout: `hash (32) ‖ difficulty (u64 LE)`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VerifyResult {
+    pub hash: [u8; 32],
+    pub difficulty: u64,
+}
+
+impl VerifyResult {
+    pub fn to_bytes(&self) -> [u8; RESULT_ACCOUNT_LEN] {
+        let mut bytes = [0; RESULT_ACCOUNT_LEN];
+        bytes[..32].copy_from_slice(&self.hash);
+        bytes[32..].copy_from_slice(&self.difficulty.to_le_bytes());
+        bytes
+    }
+
+    /// Parses the account data, ignoring any trailing bytes.
+    pub fn try_from_bytes(data: &[u8]) -> Result<Self, ProgramError> {
+        if data.len() < RESULT_ACCOUNT_LEN {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let mut hash = [0; 32];
+        let mut difficulty = [0; 8];
+        hash.copy_from_slice(&data[..32]);
+        difficulty.copy_from_slice(&data[32..RESULT_ACCOUNT_LEN]);
+        Ok(VerifyResult {
+            hash,
+            difficulty: u64::from_le_bytes(difficulty),
+        })
+    }
+}
+
+/// Errors returned by [`process_verify`] as [`ProgramError::Custom`] codes.
+///
+/// The codes are in drillx's range (see [`error_code`](crate::error_code)).
+#[repr(u32)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VerifyError {
+    /// The digest is not a valid equix solution for the stored challenge.
+    InvalidSolution = error_code::INVALID_SOLUTION,
+    /// The solution hash does not meet the stored minimum difficulty.
+    InsufficientDifficulty = error_code::INSUFFICIENT_DIFFICULTY,
+}
+
+/// The verify error with the given code, or `None` if the code is not one of its codes.
+pub fn decode_verify_error(code: u32) -> Option<VerifyError> {
+    match code {
+        error_code::INVALID_SOLUTION => Some(VerifyError::InvalidSolution),
+        error_code::INSUFFICIENT_DIFFICULTY => Some(VerifyError::InsufficientDifficulty),
+        _ => None,
+    }
+}
+
+impl From<VerifyError> for ProgramError {
+    fn from(e: VerifyError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
@@ -0,0 +1,228 @@
+//! Protobuf messages mirroring drillx's types.
+//!
+//! The messages match `proto/drillx.proto` in this crate, package `drillx.v1`, so other
+//! languages can generate compatible code from that file. Converting a drillx type to
+//! its message cannot fail; converting back checks the length of every bytes field and
+//! fails with [`ProtoError`] instead of truncating or padding.
+
+#[cfg(feature = "solve")]
+use crate::miner::{ChallengeJob as Job, JobId, JobSolution as Share};
+use crate::{ScoredSolution as Scored, Solution as Sol, SolutionV2 as SolV2};
+
+/// A message could not be converted to its drillx type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProtoError {
+    /// A bytes field has the wrong length.
+    Length {
+        field: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    /// A required message field is missing.
+    Missing { field: &'static str },
+    /// An integer field does not fit the drillx type.
+    OutOfRange { field: &'static str, value: u64 },
+}
+
+impl std::fmt::Display for ProtoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ProtoError::Length {
+                field,
+                expected,
+                actual,
+            } => write!(f, "{}: expected {} bytes, got {}", field, expected, actual),
+            ProtoError::Missing { field } => write!(f, "{}: missing", field),
+            ProtoError::OutOfRange { field, value } => {
+                write!(f, "{}: {} is out of range", field, value)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProtoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+fn exact<const N: usize>(field: &'static str, bytes: &[u8]) -> Result<[u8; N], ProtoError> {
+    bytes.try_into().map_err(|_| ProtoError::Length {
+        field,
+        expected: N,
+        actual: bytes.len(),
+    })
+}
+
+fn required<T>(field: &'static str, message: Option<T>) -> Result<T, ProtoError> {
+    message.ok_or(ProtoError::Missing { field })
+}
+
+/// `drillx.v1.Solution`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct Solution {
+    #[prost(bytes = "vec", tag = "1")]
+    pub digest: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub nonce: Vec<u8>,
+}
+
+/// `drillx.v1.SolutionV2`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct SolutionV2 {
+    #[prost(bytes = "vec", tag = "1")]
+    pub digest: Vec<u8>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub nonce: Vec<u8>,
+    #[prost(uint32, tag = "3")]
+    pub index: u32,
+}
+
+/// `drillx.v1.ScoredSolution`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ScoredSolution {
+    #[prost(message, optional, tag = "1")]
+    pub solution: Option<Solution>,
+    #[prost(bytes = "vec", tag = "2")]
+    pub hash: Vec<u8>,
+    #[prost(uint32, tag = "3")]
+    pub difficulty: u32,
+}
+
+/// `drillx.v1.ChallengeJob`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ChallengeJob {
+    #[prost(bytes = "vec", tag = "1")]
+    pub challenge: Vec<u8>,
+    #[prost(uint32, tag = "2")]
+    pub min_difficulty: u32,
+    #[prost(uint32, tag = "3")]
+    pub weight: u32,
+}
+
+/// `drillx.v1.JobSolution`
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct JobSolution {
+    #[prost(uint64, tag = "1")]
+    pub job: u64,
+    #[prost(bytes = "vec", tag = "2")]
+    pub challenge: Vec<u8>,
+    #[prost(message, optional, tag = "3")]
+    pub scored: Option<ScoredSolution>,
+}
+
+impl From<Sol> for Solution {
+    fn from(solution: Sol) -> Self {
+        Solution {
+            digest: solution.d.to_vec(),
+            nonce: solution.n.to_vec(),
+        }
+    }
+}
+
+impl TryFrom<Solution> for Sol {
+    type Error = ProtoError;
+
+    fn try_from(message: Solution) -> Result<Self, Self::Error> {
+        Ok(Sol::new(
+            exact("Solution.digest", &message.digest)?,
+            exact("Solution.nonce", &message.nonce)?,
+        ))
+    }
+}
+
+impl From<SolV2> for SolutionV2 {
+    fn from(solution: SolV2) -> Self {
+        SolutionV2 {
+            digest: solution.d.to_vec(),
+            nonce: solution.n.to_vec(),
+            index: solution.idx as u32,
+        }
+    }
+}
+
+impl TryFrom<SolutionV2> for SolV2 {
+    type Error = ProtoError;
+
+    fn try_from(message: SolutionV2) -> Result<Self, Self::Error> {
+        let index = u8::try_from(message.index).map_err(|_| ProtoError::OutOfRange {
+            field: "SolutionV2.index",
+            value: message.index as u64,
+        })?;
+        Ok(SolV2::new(
+            exact("SolutionV2.digest", &message.digest)?,
+            exact("SolutionV2.nonce", &message.nonce)?,
+            index,
+        ))
+    }
+}
+
+impl From<Scored> for ScoredSolution {
+    fn from(scored: Scored) -> Self {
+        ScoredSolution {
+            solution: Some(scored.solution.into()),
+            hash: scored.hash.to_vec(),
+            difficulty: scored.difficulty,
+        }
+    }
+}
+
+impl TryFrom<ScoredSolution> for Scored {
+    type Error = ProtoError;
+
+    fn try_from(message: ScoredSolution) -> Result<Self, Self::Error> {
+        Ok(Scored {
+            solution: required("ScoredSolution.solution", message.solution)?.try_into()?,
+            hash: exact("ScoredSolution.hash", &message.hash)?,
+            difficulty: message.difficulty,
+        })
+    }
+}
+
+#[cfg(feature = "solve")]
+impl From<Job> for ChallengeJob {
+    fn from(job: Job) -> Self {
+        ChallengeJob {
+            challenge: job.challenge.to_vec(),
+            min_difficulty: job.min_difficulty,
+            weight: job.weight,
+        }
+    }
+}
+
+#[cfg(feature = "solve")]
+impl TryFrom<ChallengeJob> for Job {
+    type Error = ProtoError;
+
+    fn try_from(message: ChallengeJob) -> Result<Self, Self::Error> {
+        Ok(Job::new(
+            exact("ChallengeJob.challenge", &message.challenge)?,
+            message.min_difficulty,
+        )
+        .weight(message.weight))
+    }
+}
+
+#[cfg(feature = "solve")]
+impl From<Share> for JobSolution {
+    fn from(share: Share) -> Self {
+        JobSolution {
+            job: share.job.0,
+            challenge: share.challenge.to_vec(),
+            scored: Some(share.scored.into()),
+        }
+    }
+}
+
+#[cfg(feature = "solve")]
+impl TryFrom<JobSolution> for Share {
+    type Error = ProtoError;
+
+    fn try_from(message: JobSolution) -> Result<Self, Self::Error> {
+        Ok(Share {
+            job: JobId(message.job),
+            challenge: exact("JobSolution.challenge", &message.challenge)?,
+            scored: required("JobSolution.scored", message.scored)?.try_into()?,
+        })
+    }
+}
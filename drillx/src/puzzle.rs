@@ -0,0 +1,299 @@
+//! Client puzzles: drillx as a proof-of-work rate limiter.
+//!
+//! A server [`issue`]s each client a puzzle of low difficulty, the client [`solve`]s
+//! it, and the server [`verify`]s the solution without having stored anything. The
+//! puzzle's challenge binds the client id, expiry, and difficulty under a server
+//! secret:
+//!
+//! ```text
+//! challenge = keccak(PUZZLE_TAG ‖ secret ‖ difficulty (u32 LE)
+//!                    ‖ expires (u64 LE, Unix seconds) ‖ client id)
+//! ```
+//!
+//! so a client cannot lower the difficulty, extend the expiry, or use a puzzle issued
+//! to someone else without the challenge no longer matching. Keccak is not open to
+//! length extension, so the keyed prefix serves as a MAC.
+//!
+//! Puzzles and solutions encode as
+//!
+//! ```text
+//! puzzle   = challenge (32 bytes) ‖ difficulty (u32 LE) ‖ expires (u64 LE)
+//! solution = puzzle ‖ digest (16 bytes) ‖ nonce (8 bytes)
+//! ```
+//!
+//! and display as that encoding in unpadded URL-safe base64, which fits in an HTTP
+//! header. Both also implement serde.
+//!
+//! Verification is stateless, so a solution can be replayed until its puzzle expires.
+//! Servers that must reject replays keep the solutions they accepted until then.
+
+use std::{fmt, str::FromStr, time::Duration};
+
+use crate::{ct_eq_hash, is_valid_digest, Solution};
+#[cfg(feature = "solve")]
+use crate::{hash_with_memory, DrillxMemory};
+
+/// Domain-separation tag prepended to every puzzle challenge.
+pub const PUZZLE_TAG: [u8; 8] = *b"DXPUZZLE";
+
+/// Length of an encoded [`Puzzle`].
+pub const PUZZLE_LEN: usize = 32 + 4 + 8;
+
+/// Length of an encoded [`PuzzleSolution`].
+pub const PUZZLE_SOLUTION_LEN: usize = PUZZLE_LEN + 16 + 8;
+
+/// A challenge issued to one client.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Puzzle {
+    pub challenge: [u8; 32],
+    /// Minimum difficulty of a solution.
+    pub difficulty: u32,
+    /// Last second the puzzle can be solved in, in Unix seconds.
+    pub expires_unix: u64,
+}
+
+/// A puzzle and the client's solution to it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PuzzleSolution {
+    pub puzzle: Puzzle,
+    pub solution: Solution,
+}
+
+/// The challenge binding a client's puzzle to the server secret.
+pub fn puzzle_challenge(
+    secret: &[u8; 32],
+    client_id: &[u8],
+    difficulty: u32,
+    expires_unix: u64,
+) -> [u8; 32] {
+    crate::keccak(&[
+        &PUZZLE_TAG,
+        secret,
+        &difficulty.to_le_bytes(),
+        &expires_unix.to_le_bytes(),
+        client_id,
+    ])
+}
+
+/// Issues `client_id` a puzzle that expires `ttl` after `now_unix`, in Unix seconds.
+pub fn issue(
+    secret: &[u8; 32],
+    client_id: &[u8],
+    difficulty: u32,
+    ttl: Duration,
+    now_unix: u64,
+) -> Puzzle {
+    let expires_unix = now_unix.saturating_add(ttl.as_secs());
+    Puzzle {
+        challenge: puzzle_challenge(secret, client_id, difficulty, expires_unix),
+        difficulty,
+        expires_unix,
+    }
+}
+
+#[cfg(feature = "solve")]
+/// Hashes nonces from zero until one meets the puzzle's difficulty, giving up after
+/// `max_attempts`.
+pub fn solve(puzzle: &Puzzle, max_attempts: u64) -> Option<PuzzleSolution> {
+    let mut memory = DrillxMemory::new();
+    (0..max_attempts).find_map(|nonce| {
+        let nonce = nonce.to_le_bytes();
+        let hash = hash_with_memory(&mut memory, &puzzle.challenge, &nonce).ok()?;
+        (hash.difficulty() >= puzzle.difficulty).then(|| PuzzleSolution {
+            puzzle: *puzzle,
+            solution: Solution::new(hash.d, nonce),
+        })
+    })
+}
+
+/// Checks that the solution's puzzle was issued to `client_id` under `secret`, has not
+/// expired by `now_unix`, and is solved at its difficulty.
+///
+/// The cheap checks run first: a forged or expired solution costs the server one keccak
+/// hash, and one below the difficulty two, before any equix verification. A puzzle is
+/// still valid in the second it expires.
+pub fn verify(
+    secret: &[u8; 32],
+    client_id: &[u8],
+    solution: &PuzzleSolution,
+    now_unix: u64,
+) -> Result<(), PuzzleError> {
+    let puzzle = &solution.puzzle;
+    let challenge = puzzle_challenge(secret, client_id, puzzle.difficulty, puzzle.expires_unix);
+    if !ct_eq_hash(&challenge, &puzzle.challenge) {
+        return Err(PuzzleError::Binding);
+    }
+    if now_unix > puzzle.expires_unix {
+        return Err(PuzzleError::Expired {
+            expires_unix: puzzle.expires_unix,
+            now_unix,
+        });
+    }
+    let actual = solution.solution.to_hash().difficulty();
+    if actual < puzzle.difficulty {
+        return Err(PuzzleError::Difficulty {
+            required: puzzle.difficulty,
+            actual,
+        });
+    }
+    if !is_valid_digest(
+        &puzzle.challenge,
+        &solution.solution.n,
+        &solution.solution.d,
+    ) {
+        return Err(PuzzleError::InvalidDigest);
+    }
+    Ok(())
+}
+
+impl Puzzle {
+    pub fn to_bytes(&self) -> [u8; PUZZLE_LEN] {
+        let mut bytes = [0; PUZZLE_LEN];
+        bytes[..32].copy_from_slice(&self.challenge);
+        bytes[32..36].copy_from_slice(&self.difficulty.to_le_bytes());
+        bytes[36..].copy_from_slice(&self.expires_unix.to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; PUZZLE_LEN]) -> Self {
+        Puzzle {
+            challenge: bytes[..32].try_into().unwrap(),
+            difficulty: u32::from_le_bytes(bytes[32..36].try_into().unwrap()),
+            expires_unix: u64::from_le_bytes(bytes[36..].try_into().unwrap()),
+        }
+    }
+}
+
+impl PuzzleSolution {
+    pub fn to_bytes(&self) -> [u8; PUZZLE_SOLUTION_LEN] {
+        let mut bytes = [0; PUZZLE_SOLUTION_LEN];
+        bytes[..PUZZLE_LEN].copy_from_slice(&self.puzzle.to_bytes());
+        bytes[PUZZLE_LEN..].copy_from_slice(&self.solution.to_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8; PUZZLE_SOLUTION_LEN]) -> Self {
+        PuzzleSolution {
+            puzzle: Puzzle::from_bytes(bytes[..PUZZLE_LEN].try_into().unwrap()),
+            solution: Solution::from_bytes(bytes[PUZZLE_LEN..].try_into().unwrap()),
+        }
+    }
+}
+
+impl fmt::Display for Puzzle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&encode_base64(&self.to_bytes()))
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = PuzzleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Puzzle::from_bytes(&decode_base64(s)?))
+    }
+}
+
+impl fmt::Display for PuzzleSolution {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&encode_base64(&self.to_bytes()))
+    }
+}
+
+impl FromStr for PuzzleSolution {
+    type Err = PuzzleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(PuzzleSolution::from_bytes(&decode_base64(s)?))
+    }
+}
+
+/// The URL-safe base64 alphabet.
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Unpadded URL-safe base64.
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let word = chunk.iter().enumerate().fold(0u32, |word, (i, &byte)| {
+            word | (byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..=chunk.len() {
+            encoded.push(BASE64[(word >> (18 - 6 * i)) as usize & 63] as char);
+        }
+    }
+    encoded
+}
+
+/// Decodes unpadded URL-safe base64 of exactly `N` bytes.
+fn decode_base64<const N: usize>(encoded: &str) -> Result<[u8; N], PuzzleError> {
+    let expected = N / 3 * 4 + [0, 2, 3][N % 3];
+    if encoded.len() != expected {
+        return Err(PuzzleError::Malformed);
+    }
+    let mut bytes = [0; N];
+    for (chunk, out) in encoded.as_bytes().chunks(4).zip(bytes.chunks_mut(3)) {
+        let mut word = 0u32;
+        for (i, &c) in chunk.iter().enumerate() {
+            let value = BASE64
+                .iter()
+                .position(|&b| b == c)
+                .ok_or(PuzzleError::Malformed)?;
+            word |= (value as u32) << (18 - 6 * i);
+        }
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = (word >> (16 - 8 * i)) as u8;
+        }
+        // Bits past the last byte must be zero, so every value has one encoding.
+        if word << (8 * out.len()) & 0xff_ffff != 0 {
+            return Err(PuzzleError::Malformed);
+        }
+    }
+    Ok(bytes)
+}
+
+/// Why a puzzle solution was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PuzzleError {
+    /// The puzzle was not issued to this client under this secret, or its difficulty or
+    /// expiry were changed.
+    Binding,
+    /// The puzzle expired before now.
+    Expired { expires_unix: u64, now_unix: u64 },
+    /// The solution's difficulty is below the puzzle's.
+    Difficulty { required: u32, actual: u32 },
+    /// The digest is not an equix solution of the puzzle.
+    InvalidDigest,
+    /// The encoding is not base64 of the right length.
+    Malformed,
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PuzzleError::Binding => write!(f, "Puzzle was not issued to this client"),
+            PuzzleError::Expired {
+                expires_unix,
+                now_unix,
+            } => write!(
+                f,
+                "Puzzle expired at {}, {} seconds ago",
+                expires_unix,
+                now_unix - expires_unix
+            ),
+            PuzzleError::Difficulty { required, actual } => write!(
+                f,
+                "Solution has difficulty {}, puzzle requires {}",
+                actual, required
+            ),
+            PuzzleError::InvalidDigest => write!(f, "Invalid digest"),
+            PuzzleError::Malformed => write!(f, "Malformed puzzle encoding"),
+        }
+    }
+}
+
+impl std::error::Error for PuzzleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
@@ -0,0 +1,123 @@
+//! Ranking claimed solutions by difficulty, without verifying them.
+//!
+//! **These functions do not validate digests.** They hash each solution as
+//! [`Solution::to_hash`] does and trust the result, so a made-up digest ranks as well
+//! as a real one. Use them only where [`Solution::is_valid`] has run upstream or will
+//! run before anything is credited, such as for leaderboards or to pick which
+//! solutions are worth verifying first.
+//!
+//! Rankings are by difficulty, highest first. Ties go to the lower hash, compared as
+//! bytes, and then to the lower index, so a ranking never depends on thread count or
+//! input order beyond the indices. With the `rayon` feature the hashes are computed in
+//! parallel on rayon's global pool.
+
+use std::{cmp::Ordering, collections::BinaryHeap};
+
+use crate::Solution;
+
+/// A solution's index in the input, its difficulty, and its hash.
+pub type Ranked = (usize, u32, [u8; 32]);
+
+/// Ranks every solution, best first. **Does not validate digests**; see the
+/// [module docs](self).
+pub fn rank_solutions(solutions: &[Solution]) -> Vec<Ranked> {
+    let rank = |(index, solution): (usize, &Solution)| {
+        let hash = solution.to_hash();
+        (index, hash.difficulty(), hash.h)
+    };
+    #[cfg(feature = "rayon")]
+    {
+        use rayon::prelude::*;
+        let mut ranked: Vec<Ranked> = solutions.par_iter().enumerate().map(rank).collect();
+        ranked.par_sort_unstable_by(order);
+        ranked
+    }
+    #[cfg(not(feature = "rayon"))]
+    {
+        let mut ranked: Vec<Ranked> = solutions.iter().enumerate().map(rank).collect();
+        ranked.sort_unstable_by(order);
+        ranked
+    }
+}
+
+/// The first `k` of [`rank_solutions`], keeping at most `k` rankings in memory per
+/// thread. **Does not validate digests**; see the [module docs](self).
+pub fn rank_top_k(solutions: &[Solution], k: usize) -> Vec<Ranked> {
+    if k == 0 {
+        return Vec::new();
+    }
+    #[cfg(feature = "rayon")]
+    let heap = {
+        use rayon::prelude::*;
+        solutions
+            .par_iter()
+            .enumerate()
+            .fold(BinaryHeap::new, |heap, entry| push(heap, entry, k))
+            .reduce(BinaryHeap::new, |a, b| {
+                let (mut big, small) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+                for entry in small {
+                    push_ranked(&mut big, entry.0, k);
+                }
+                big
+            })
+    };
+    #[cfg(not(feature = "rayon"))]
+    let heap = solutions
+        .iter()
+        .enumerate()
+        .fold(BinaryHeap::new(), |heap, entry| push(heap, entry, k));
+    let mut ranked: Vec<Ranked> = heap.into_iter().map(|entry| entry.0).collect();
+    ranked.sort_unstable_by(order);
+    ranked
+}
+
+/// Best first.
+fn order(a: &Ranked, b: &Ranked) -> Ordering {
+    b.1.cmp(&a.1).then(a.2.cmp(&b.2)).then(a.0.cmp(&b.0))
+}
+
+/// A ranking in a max-heap whose top is the worst kept so far.
+struct Worst(Ranked);
+
+impl Ord for Worst {
+    fn cmp(&self, other: &Self) -> Ordering {
+        order(&self.0, &other.0)
+    }
+}
+
+impl PartialOrd for Worst {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Worst {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Worst {}
+
+fn push(
+    mut heap: BinaryHeap<Worst>,
+    (index, solution): (usize, &Solution),
+    k: usize,
+) -> BinaryHeap<Worst> {
+    let hash = solution.to_hash();
+    push_ranked(&mut heap, (index, hash.difficulty(), hash.h), k);
+    heap
+}
+
+/// Keeps `ranked` if it is among the best `k`.
+fn push_ranked(heap: &mut BinaryHeap<Worst>, ranked: Ranked, k: usize) {
+    if heap.len() < k {
+        heap.push(Worst(ranked));
+    } else if heap
+        .peek()
+        .is_some_and(|worst| order(&ranked, &worst.0) == Ordering::Less)
+    {
+        heap.pop();
+        heap.push(Worst(ranked));
+    }
+}
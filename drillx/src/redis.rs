@@ -0,0 +1,286 @@
+//! Redis values and a share queue on Redis streams.
+//!
+//! [`Solution`] is stored as exactly 24 bytes, laid out as [`Solution::to_bytes`], and
+//! [`ScoredSolution`] as exactly 60 bytes: the solution, the hash, and the difficulty
+//! as a little-endian `u32`. Decoding a value of any other length or type fails with a
+//! [`DecodeError`] rather than truncating or padding.
+//!
+//! [`ShareQueue`] carries shares from gateways to verifier workers through a stream
+//! and a consumer group. Entries that don't decode are returned as poisoned rather
+//! than dropped, and since a group read never redelivers an entry, a poisoned entry
+//! stays pending until it is acknowledged instead of being read in a loop.
+
+use ::redis::{
+    aio::ConnectionLike,
+    streams::{StreamReadOptions, StreamReadReply},
+    AsyncCommands, ErrorKind, FromRedisValue, RedisError, RedisResult, RedisWrite, ToRedisArgs,
+    Value,
+};
+
+use crate::{ScoredSolution, Solution};
+
+/// Stream field holding a share's challenge id, in decimal.
+pub const CHALLENGE_FIELD: &str = "challenge";
+
+/// Stream field holding a share's solution.
+pub const SOLUTION_FIELD: &str = "solution";
+
+/// A Redis value or stream entry does not hold what it is decoded as.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// A byte value has the wrong length for its type.
+    Length { expected: usize, actual: usize },
+    /// The value is not a byte string.
+    NotBytes,
+    /// A stream entry lacks a field.
+    MissingField(&'static str),
+    /// The challenge id is not a decimal `u64`.
+    InvalidChallengeId,
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            DecodeError::Length { expected, actual } => {
+                write!(f, "Expected {} bytes, got {}", expected, actual)
+            }
+            DecodeError::NotBytes => write!(f, "Expected a byte string"),
+            DecodeError::MissingField(field) => write!(f, "Missing field {}", field),
+            DecodeError::InvalidChallengeId => write!(f, "Invalid challenge id"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+impl From<DecodeError> for RedisError {
+    fn from(err: DecodeError) -> Self {
+        RedisError::from((
+            ErrorKind::TypeError,
+            "invalid drillx value",
+            err.to_string(),
+        ))
+    }
+}
+
+fn bytes(value: &Value) -> Result<&[u8], DecodeError> {
+    match value {
+        Value::BulkString(bytes) => Ok(bytes),
+        Value::SimpleString(string) => Ok(string.as_bytes()),
+        _ => Err(DecodeError::NotBytes),
+    }
+}
+
+fn exact<const N: usize>(bytes: &[u8]) -> Result<[u8; N], DecodeError> {
+    bytes.try_into().map_err(|_| DecodeError::Length {
+        expected: N,
+        actual: bytes.len(),
+    })
+}
+
+impl Solution {
+    /// Decodes a solution from a Redis value.
+    pub fn from_redis(value: &Value) -> Result<Solution, DecodeError> {
+        exact(bytes(value)?).map(Solution::from_bytes)
+    }
+}
+
+impl ScoredSolution {
+    /// The solution, the hash, and the difficulty, as stored in Redis.
+    pub fn to_bytes(&self) -> [u8; 60] {
+        let mut bytes = [0; 60];
+        bytes[..24].copy_from_slice(&self.solution.to_bytes());
+        bytes[24..56].copy_from_slice(&self.hash);
+        bytes[56..].copy_from_slice(&self.difficulty.to_le_bytes());
+        bytes
+    }
+
+    pub fn from_bytes(bytes: [u8; 60]) -> Self {
+        ScoredSolution {
+            solution: Solution::from_bytes(bytes[..24].try_into().unwrap()),
+            hash: bytes[24..56].try_into().unwrap(),
+            difficulty: u32::from_le_bytes(bytes[56..].try_into().unwrap()),
+        }
+    }
+
+    /// Decodes a scored solution from a Redis value.
+    pub fn from_redis(value: &Value) -> Result<ScoredSolution, DecodeError> {
+        exact(bytes(value)?).map(ScoredSolution::from_bytes)
+    }
+}
+
+impl ToRedisArgs for Solution {
+    fn write_redis_args<W: ?Sized + RedisWrite>(&self, out: &mut W) {
+        out.write_arg(&self.to_bytes());
+    }
+}
+
+impl FromRedisValue for Solution {
+    fn from_redis_value(value: &Value) -> RedisResult<Self> {
+        Ok(Solution::from_redis(value)?)
+    }
+}
+
+impl ToRedisArgs for ScoredSolution {
+    fn write_redis_args<W: ?Sized + RedisWrite>(&self, out: &mut W) {
+        out.write_arg(&self.to_bytes());
+    }
+}
+
+impl FromRedisValue for ScoredSolution {
+    fn from_redis_value(value: &Value) -> RedisResult<Self> {
+        Ok(ScoredSolution::from_redis(value)?)
+    }
+}
+
+/// A share read from a [`ShareQueue`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QueuedShare {
+    /// The stream entry id, for [`ShareQueue::ack`].
+    pub id: String,
+    pub challenge_id: u64,
+    pub solution: Solution,
+}
+
+/// A stream entry that is not a share.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoisonedEntry {
+    pub id: String,
+    pub error: DecodeError,
+}
+
+/// The result of [`ShareQueue::pop_batch`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShareBatch {
+    pub shares: Vec<QueuedShare>,
+    /// Entries that failed to decode. They stay pending until acknowledged.
+    pub poisoned: Vec<PoisonedEntry>,
+}
+
+impl ShareBatch {
+    pub fn is_empty(&self) -> bool {
+        self.shares.is_empty() && self.poisoned.is_empty()
+    }
+
+    /// Ids of every entry in the batch, shares and poisoned alike.
+    pub fn ids(&self) -> Vec<String> {
+        let shares = self.shares.iter().map(|share| share.id.clone());
+        let poisoned = self.poisoned.iter().map(|entry| entry.id.clone());
+        shares.chain(poisoned).collect()
+    }
+}
+
+/// A queue of shares on a Redis stream, read through consumer groups.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShareQueue {
+    stream: String,
+}
+
+impl ShareQueue {
+    pub fn new(stream: impl Into<String>) -> Self {
+        ShareQueue {
+            stream: stream.into(),
+        }
+    }
+
+    pub fn stream(&self) -> &str {
+        &self.stream
+    }
+
+    /// Creates a consumer group reading new entries, and the stream if needed. A group
+    /// that already exists is left as it is.
+    pub async fn create_group<C: ConnectionLike + Send>(
+        &self,
+        conn: &mut C,
+        group: &str,
+    ) -> RedisResult<()> {
+        let created: RedisResult<()> = conn.xgroup_create_mkstream(&self.stream, group, "$").await;
+        match created {
+            Err(err) if err.code() == Some("BUSYGROUP") => Ok(()),
+            result => result,
+        }
+    }
+
+    /// Appends a share, returning its entry id.
+    pub async fn push<C: ConnectionLike + Send>(
+        &self,
+        conn: &mut C,
+        challenge_id: u64,
+        solution: &Solution,
+    ) -> RedisResult<String> {
+        conn.xadd(
+            &self.stream,
+            "*",
+            &[
+                (CHALLENGE_FIELD, challenge_id.to_string().into_bytes()),
+                (SOLUTION_FIELD, solution.to_bytes().to_vec()),
+            ],
+        )
+        .await
+    }
+
+    /// Reads up to `max` entries not yet delivered to the group, without blocking.
+    pub async fn pop_batch<C: ConnectionLike + Send>(
+        &self,
+        conn: &mut C,
+        group: &str,
+        consumer: &str,
+        max: usize,
+    ) -> RedisResult<ShareBatch> {
+        let options = StreamReadOptions::default()
+            .group(group, consumer)
+            .count(max);
+        let reply: Option<StreamReadReply> = conn
+            .xread_options(&[&self.stream], &[">"], &options)
+            .await?;
+        let mut batch = ShareBatch::default();
+        for entry in reply
+            .into_iter()
+            .flat_map(|reply| reply.keys)
+            .flat_map(|key| key.ids)
+        {
+            match decode_entry(&entry.map) {
+                Ok((challenge_id, solution)) => batch.shares.push(QueuedShare {
+                    id: entry.id,
+                    challenge_id,
+                    solution,
+                }),
+                Err(error) => batch.poisoned.push(PoisonedEntry {
+                    id: entry.id,
+                    error,
+                }),
+            }
+        }
+        Ok(batch)
+    }
+
+    /// Acknowledges entries, returning how many were pending.
+    pub async fn ack<C: ConnectionLike + Send>(
+        &self,
+        conn: &mut C,
+        group: &str,
+        ids: &[String],
+    ) -> RedisResult<usize> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+        conn.xack(&self.stream, group, ids).await
+    }
+}
+
+/// Decodes the fields of a share entry.
+pub fn decode_entry(
+    fields: &std::collections::HashMap<String, Value>,
+) -> Result<(u64, Solution), DecodeError> {
+    let field = |name: &'static str| fields.get(name).ok_or(DecodeError::MissingField(name));
+    let challenge_id = std::str::from_utf8(bytes(field(CHALLENGE_FIELD)?)?)
+        .ok()
+        .and_then(|id| id.parse().ok())
+        .ok_or(DecodeError::InvalidChallengeId)?;
+    let solution = Solution::from_redis(field(SOLUTION_FIELD)?)?;
+    Ok((challenge_id, solution))
+}
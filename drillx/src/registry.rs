@@ -0,0 +1,152 @@
+//! Bounded anti-replay tracking of accepted shares.
+//!
+//! A [`SolutionRegistry`] remembers which (challenge, nonce) pairs have been credited
+//! in a fixed amount of memory, using a sharded bloom filter. It can only err in one
+//! direction: a pair that was inserted is always reported as a duplicate, but a fresh
+//! pair is occasionally reported as a duplicate too, at roughly the configured false
+//! positive rate while no more than `capacity` pairs have been inserted.
+//!
+//! Filter positions come from randomly keyed hashes, so a spammer cannot precompute
+//! nonces that collide with other miners' shares.
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::BuildHasher,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+/// Number of independently locked shards.
+const SHARDS: usize = 16;
+
+/// The result of inserting a share.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InsertOutcome {
+    /// The share has definitely not been inserted before.
+    Fresh,
+    /// The share has probably been inserted before.
+    ProbablyDuplicate,
+}
+
+/// A fixed-size, thread-safe record of credited shares.
+pub struct SolutionRegistry {
+    shards: Vec<Mutex<Vec<u64>>>,
+    /// Bits per shard.
+    bits: u64,
+    /// Bit positions per share.
+    hashes: u32,
+    capacity: usize,
+    len: AtomicUsize,
+    challenge: Mutex<Option<[u8; 32]>>,
+    keys: [RandomState; 2],
+}
+
+impl SolutionRegistry {
+    /// Creates a registry sized for `capacity` shares at the given false positive rate.
+    ///
+    /// A zero capacity is treated as one, and the rate is clamped to `[1e-9, 0.5]`.
+    pub fn new(capacity: usize, false_positive_rate: f64) -> Self {
+        let capacity = capacity.max(1);
+        let rate = if false_positive_rate.is_nan() {
+            0.5
+        } else {
+            false_positive_rate.clamp(1e-9, 0.5)
+        };
+        let ln2 = std::f64::consts::LN_2;
+        let total = (-(capacity as f64) * rate.ln() / (ln2 * ln2)).ceil();
+        let words = ((total / SHARDS as f64 / 64.0).ceil() as usize).max(1);
+        let bits = words as u64 * 64;
+        let hashes = ((bits * SHARDS as u64) as f64 / capacity as f64 * ln2).round() as u32;
+        SolutionRegistry {
+            shards: (0..SHARDS).map(|_| Mutex::new(vec![0; words])).collect(),
+            bits,
+            hashes: hashes.clamp(1, 32),
+            capacity,
+            len: AtomicUsize::new(0),
+            challenge: Mutex::new(None),
+            keys: [RandomState::new(), RandomState::new()],
+        }
+    }
+
+    /// Records a share, returning whether it was fresh.
+    pub fn insert(&self, challenge: &[u8; 32], nonce: u64) -> InsertOutcome {
+        let (shard, positions) = self.positions(challenge, nonce);
+        let mut words = self.shards[shard].lock().unwrap();
+        let mut fresh = false;
+        for bit in positions {
+            let (word, mask) = ((bit / 64) as usize, 1 << (bit % 64));
+            fresh |= words[word] & mask == 0;
+            words[word] |= mask;
+        }
+        if fresh {
+            self.len.fetch_add(1, Ordering::Relaxed);
+            InsertOutcome::Fresh
+        } else {
+            InsertOutcome::ProbablyDuplicate
+        }
+    }
+
+    /// Returns true if the share has probably been inserted, without inserting it.
+    pub fn contains(&self, challenge: &[u8; 32], nonce: u64) -> bool {
+        let (shard, mut positions) = self.positions(challenge, nonce);
+        let words = self.shards[shard].lock().unwrap();
+        positions.all(|bit| words[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    /// Switches to a new challenge, forgetting every share if it differs from the
+    /// current one. Returns true if the registry was cleared.
+    ///
+    /// Shares for earlier challenges are forgotten too, so callers must reject stale
+    /// challenges themselves.
+    pub fn rotate(&self, challenge: [u8; 32]) -> bool {
+        let mut current = self.challenge.lock().unwrap();
+        if *current == Some(challenge) {
+            return false;
+        }
+        *current = Some(challenge);
+        for shard in &self.shards {
+            shard.lock().unwrap().fill(0);
+        }
+        self.len.store(0, Ordering::Relaxed);
+        true
+    }
+
+    /// The challenge last passed to [`rotate`](Self::rotate).
+    pub fn challenge(&self) -> Option<[u8; 32]> {
+        *self.challenge.lock().unwrap()
+    }
+
+    /// Number of shares inserted as fresh since the last clear.
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The capacity the registry was sized for.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Fraction of the capacity in use. Past 1.0 the false positive rate exceeds the
+    /// configured one and the registry should be resized.
+    pub fn saturation(&self) -> f64 {
+        self.len() as f64 / self.capacity as f64
+    }
+
+    /// Returns the shard and bit positions of a share.
+    fn positions(&self, challenge: &[u8; 32], nonce: u64) -> (usize, impl Iterator<Item = u64>) {
+        let h1 = self.keys[0].hash_one((challenge, nonce));
+        let h2 = self.keys[1].hash_one((challenge, nonce));
+        let shard = (h2 >> 60) as usize % SHARDS;
+        let step = h2 | 1;
+        let bits = self.bits;
+        let positions =
+            (0..self.hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(step)) % bits);
+        (shard, positions)
+    }
+}
@@ -0,0 +1,168 @@
+//! Selection of the equix runtime.
+//!
+//! The hashx compiler only exists for x86_64 and aarch64, and only with the
+//! `compiler` feature, which the default `full` feature enables. Everywhere else
+//! drillx is interpreter-only: [`RuntimeOption::TryCompile`] means the interpreter, and
+//! [`RuntimeOption::RequireCompile`] fails with [`DrillxError::CompilerUnsupported`].
+//!
+//! Building without default features leaves the compiler, and with it every mapping
+//! of executable memory, out of the binary entirely. The interpreter hashes roughly
+//! an order of magnitude slower, so such builds suit verifiers better than miners.
+
+use crate::DrillxError;
+
+/// True if the compiled runtime is built in for the target architecture.
+pub const COMPILER_SUPPORTED: bool = cfg!(all(
+    feature = "compiler",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+));
+
+/// Which equix runtime to hash with.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize,
+)]
+pub enum RuntimeOption {
+    /// Use the compiled runtime, falling back to the interpreter if compilation fails.
+    #[default]
+    TryCompile,
+    /// Use the compiled runtime only, failing if compilation fails.
+    RequireCompile,
+    /// Use the interpreter only.
+    InterpretOnly,
+}
+
+/// The equix runtime a hash was computed with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Runtime {
+    Compiled,
+    Interpreted,
+}
+
+impl From<RuntimeOption> for equix::RuntimeOption {
+    fn from(option: RuntimeOption) -> Self {
+        match option {
+            RuntimeOption::TryCompile => equix::RuntimeOption::TryCompile,
+            RuntimeOption::RequireCompile => equix::RuntimeOption::CompileOnly,
+            RuntimeOption::InterpretOnly => equix::RuntimeOption::InterpretOnly,
+        }
+    }
+}
+
+impl From<equix::Runtime> for Runtime {
+    fn from(runtime: equix::Runtime) -> Self {
+        match runtime {
+            equix::Runtime::Compiled => Runtime::Compiled,
+            _ => Runtime::Interpreted,
+        }
+    }
+}
+
+/// What drillx can do on this machine.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RuntimeInfo {
+    /// The target architecture drillx was built for.
+    pub arch: &'static str,
+    /// True if the compiled runtime exists for the target architecture.
+    pub compiler_supported: bool,
+    /// True if the compiled runtime also works on this machine.
+    pub compiler_available: bool,
+    /// The runtime [`RuntimeOption::TryCompile`] hashes with.
+    pub default_runtime: Runtime,
+}
+
+/// Reports which equix runtimes are usable on this machine.
+pub fn runtime_info() -> RuntimeInfo {
+    let compiler_available = compiler_available();
+    RuntimeInfo {
+        arch: std::env::consts::ARCH,
+        compiler_supported: COMPILER_SUPPORTED,
+        compiler_available,
+        default_runtime: if compiler_available {
+            Runtime::Compiled
+        } else {
+            Runtime::Interpreted
+        },
+    }
+}
+
+/// Returns true if the digest is a valid solution for the seed under the given runtime.
+#[inline(always)]
+pub(crate) fn verify(seed: &[u8], digest: &[u8; 16], option: RuntimeOption) -> bool {
+    // Item order is checked before any program is built, as in `equix::verify_bytes`.
+    let Ok(solution) = equix::Solution::try_from_bytes(digest) else {
+        return false;
+    };
+    build(seed, option).is_ok_and(|equix| equix.verify(&solution).is_ok())
+}
+
+/// Maps an option to the one actually used on the target architecture.
+#[inline(always)]
+pub(crate) fn resolve(option: RuntimeOption) -> RuntimeOption {
+    match option {
+        RuntimeOption::TryCompile if !COMPILER_SUPPORTED => RuntimeOption::InterpretOnly,
+        option => option,
+    }
+}
+
+#[cfg(all(
+    feature = "compiler",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+static COMPILED_PROGRAMS: std::sync::atomic::AtomicU64 = std::sync::atomic::AtomicU64::new(0);
+
+/// Counts the equix programs drillx has compiled in this process.
+///
+/// This stays 0 in a process that only hashes and verifies with
+/// [`RuntimeOption::InterpretOnly`], so it never ran generated code.
+pub fn compiled_programs() -> u64 {
+    #[cfg(all(
+        feature = "compiler",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    return COMPILED_PROGRAMS.load(std::sync::atomic::Ordering::Relaxed);
+    #[cfg(not(all(
+        feature = "compiler",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    )))]
+    0
+}
+
+/// Builds an equix instance for the seed with the given runtime.
+#[inline(always)]
+pub(crate) fn build(seed: &[u8], option: RuntimeOption) -> Result<equix::EquiX, DrillxError> {
+    if option == RuntimeOption::RequireCompile && !COMPILER_SUPPORTED {
+        return Err(DrillxError::CompilerUnsupported);
+    }
+    let equix = equix::EquiXBuilder::new()
+        .runtime(resolve(option).into())
+        .build(seed)
+        .map_err(|err| match err {
+            equix::Error::Hash(equix::HashError::Compiler(_)) => DrillxError::CompileFailed,
+            _ => DrillxError::BadEquix,
+        })?;
+    #[cfg(all(
+        feature = "compiler",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    if equix.runtime() == equix::Runtime::Compiled {
+        COMPILED_PROGRAMS.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    }
+    #[cfg(feature = "tracing")]
+    if equix.runtime() == equix::Runtime::Interpret && resolve(option) == RuntimeOption::TryCompile
+    {
+        crate::telemetry::runtime_fallback();
+    }
+    Ok(equix)
+}
+
+/// Returns true if the hashx compiler works on this machine.
+pub(crate) fn compiler_available() -> bool {
+    let seed = crate::seed(&[0; 32], &[0; 8]);
+    COMPILER_SUPPORTED
+        && !matches!(
+            equix::EquiXBuilder::new()
+                .runtime(equix::RuntimeOption::CompileOnly)
+                .build(&seed.data),
+            Err(equix::Error::Hash(equix::HashError::Compiler(_)))
+        )
+}
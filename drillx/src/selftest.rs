@@ -0,0 +1,118 @@
+//! Runtime self-test against the embedded test vectors.
+
+use std::time::{Duration, Instant};
+
+use crate::{
+    runtime,
+    vectors::{Vector, VECTORS},
+    DrillxMemory, Runtime, RuntimeOption,
+};
+
+/// The results of running the test vectors through one runtime.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathReport {
+    /// Number of vectors reproduced exactly.
+    pub passed: usize,
+    /// Indices into [`VECTORS`] of the vectors that were not reproduced.
+    pub failed: Vec<usize>,
+    /// Time taken to hash every vector.
+    pub elapsed: Duration,
+}
+
+impl PathReport {
+    /// Returns true if every vector was reproduced.
+    pub fn is_ok(&self) -> bool {
+        self.failed.is_empty()
+    }
+
+    /// Average time taken per vector.
+    pub fn per_vector(&self) -> Duration {
+        let count = (self.passed + self.failed.len()).max(1);
+        self.elapsed / count as u32
+    }
+}
+
+/// The results of [`self_test`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelfTestReport {
+    /// Number of vectors checked per runtime.
+    pub vectors: usize,
+    pub interpreted: PathReport,
+    /// Results of the compiled runtime, or `None` if the compiler is not available.
+    pub compiled: Option<PathReport>,
+    /// The fastest runtime that reproduced every vector.
+    pub trusted: Runtime,
+}
+
+impl SelfTestReport {
+    /// Returns true if the compiler is available but disagrees with the vectors.
+    pub fn compiled_disagrees(&self) -> bool {
+        self.compiled.as_ref().is_some_and(|c| !c.is_ok())
+    }
+}
+
+/// Runs the embedded test vectors through the interpreted and, when available, the
+/// compiled equix runtimes.
+///
+/// Fails only if the interpreter disagrees with the vectors, in which case no runtime
+/// can be trusted. A disagreeing compiler is reported through
+/// [`SelfTestReport::trusted`] instead.
+pub fn self_test() -> Result<SelfTestReport, SelfTestError> {
+    let mut memory = DrillxMemory::new();
+    let interpreted = run(&mut memory, VECTORS, RuntimeOption::InterpretOnly);
+    if !interpreted.is_ok() {
+        return Err(SelfTestError::InterpreterMismatch {
+            failed: interpreted.failed,
+        });
+    }
+    let compiled = runtime::compiler_available()
+        .then(|| run(&mut memory, VECTORS, RuntimeOption::RequireCompile));
+    let trusted = match compiled {
+        Some(ref compiled) if compiled.is_ok() => Runtime::Compiled,
+        _ => Runtime::Interpreted,
+    };
+    Ok(SelfTestReport {
+        vectors: VECTORS.len(),
+        interpreted,
+        compiled,
+        trusted,
+    })
+}
+
+fn run(memory: &mut DrillxMemory, vectors: &[Vector], runtime: RuntimeOption) -> PathReport {
+    let started = Instant::now();
+    let failed: Vec<usize> = vectors
+        .iter()
+        .enumerate()
+        .filter(|(_, vector)| !vector.check(memory, runtime))
+        .map(|(index, _)| index)
+        .collect();
+    PathReport {
+        passed: vectors.len() - failed.len(),
+        failed,
+        elapsed: started.elapsed(),
+    }
+}
+
+/// An error that leaves no equix runtime trustworthy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SelfTestError {
+    /// The interpreter did not reproduce the vectors at these indices.
+    InterpreterMismatch { failed: Vec<usize> },
+}
+
+impl std::fmt::Display for SelfTestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SelfTestError::InterpreterMismatch { failed } => {
+                write!(f, "Interpreter failed test vectors {:?}", failed)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SelfTestError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
@@ -0,0 +1,291 @@
+//! An HTTP verification service, run by the `drillx-verifyd` binary.
+//!
+//! `POST /verify` takes a challenge and a batch of solutions as hex and answers whether
+//! each is valid and what its difficulty is:
+//!
+//! ```text
+//! {"challenge": "<64 hex>", "solutions": [{"digest": "<32 hex>", "nonce": "<16 hex>"}], "min_difficulty": 8}
+//! {"results": [{"valid": true, "difficulty": 11, "accepted": true}]}
+//! ```
+//!
+//! The nonce is its 8 bytes in order, as in [`Solution::to_bytes`]. A solution is
+//! accepted if it is valid and reaches `min_difficulty`, which defaults to zero. Batches
+//! are verified in parallel on a dedicated thread pool. `GET /healthz` answers
+//! `{"status": "ok"}`.
+//!
+//! Failures are JSON too, with a status code and a stable `code`:
+//!
+//! ```text
+//! {"error": {"code": "malformed_hex", "message": "...", "field": "solutions[3].digest"}}
+//! ```
+
+use std::{net::SocketAddr, sync::Arc};
+
+use axum::{
+    extract::{rejection::JsonRejection, DefaultBodyLimit, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, post},
+    Json, Router,
+};
+use rayon::prelude::*;
+
+use crate::Solution;
+
+/// Default address [`ServeConfig`] binds to.
+pub const DEFAULT_BIND: &str = "127.0.0.1:8080";
+
+/// Default most solutions per request.
+pub const DEFAULT_MAX_BATCH: usize = 4096;
+
+/// Request body bytes allowed per solution in the batch, several times what one takes.
+const BODY_BYTES_PER_SOLUTION: usize = 128;
+
+/// Request body bytes allowed besides the solutions.
+const BODY_BYTES_BASE: usize = 1024;
+
+/// Configuration of the service.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServeConfig {
+    pub bind: SocketAddr,
+    /// Threads verifying solutions.
+    pub workers: usize,
+    /// Most solutions per request. Bodies are limited to match.
+    pub max_batch: usize,
+}
+
+impl Default for ServeConfig {
+    fn default() -> Self {
+        ServeConfig {
+            bind: DEFAULT_BIND.parse().unwrap(),
+            workers: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            max_batch: DEFAULT_MAX_BATCH,
+        }
+    }
+}
+
+impl ServeConfig {
+    /// Most request body bytes accepted.
+    pub fn max_body(&self) -> usize {
+        BODY_BYTES_BASE.saturating_add(self.max_batch.saturating_mul(BODY_BYTES_PER_SOLUTION))
+    }
+}
+
+/// The body of `POST /verify`.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct VerifyRequest {
+    pub challenge: String,
+    pub solutions: Vec<HexSolution>,
+    #[serde(default)]
+    pub min_difficulty: u32,
+}
+
+/// A solution as hex.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct HexSolution {
+    pub digest: String,
+    pub nonce: String,
+}
+
+/// The answer to `POST /verify`, one verdict per solution in order.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct VerifyResponse {
+    pub results: Vec<Verdict>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Verdict {
+    pub valid: bool,
+    /// The difficulty of a valid solution.
+    pub difficulty: Option<u32>,
+    /// Valid and at least the minimum difficulty.
+    pub accepted: bool,
+}
+
+/// A failed request, as returned in the `error` field.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ServeError {
+    /// One of `malformed_hex`, `batch_too_large`, `body_too_large`, `malformed_json`,
+    /// `invalid_request`, `unsupported_media_type`, or `internal`.
+    pub code: String,
+    pub message: String,
+    /// The offending field, such as `solutions[3].digest`.
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub field: Option<String>,
+}
+
+impl std::fmt::Display for ServeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "{}: {}", field, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for ServeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
+
+/// A [`ServeError`] with its status code.
+struct Failure(StatusCode, ServeError);
+
+impl Failure {
+    fn new(status: StatusCode, code: &str, message: String, field: Option<String>) -> Self {
+        Failure(
+            status,
+            ServeError {
+                code: code.to_string(),
+                message,
+                field,
+            },
+        )
+    }
+}
+
+impl IntoResponse for Failure {
+    fn into_response(self) -> Response {
+        #[derive(serde::Serialize)]
+        struct Body {
+            error: ServeError,
+        }
+        (self.0, Json(Body { error: self.1 })).into_response()
+    }
+}
+
+struct Shared {
+    pool: rayon::ThreadPool,
+    max_batch: usize,
+}
+
+/// The service's routes.
+pub fn router(config: &ServeConfig) -> std::io::Result<Router> {
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(config.workers.max(1))
+        .thread_name(|i| format!("drillx-verify-{}", i))
+        .build()
+        .map_err(std::io::Error::other)?;
+    let shared = Arc::new(Shared {
+        pool,
+        max_batch: config.max_batch,
+    });
+    Ok(Router::new()
+        .route("/verify", post(verify))
+        .route("/healthz", get(healthz))
+        .layer(DefaultBodyLimit::max(config.max_body()))
+        .with_state(shared))
+}
+
+/// Serves on a bound listener until the future is dropped.
+pub async fn serve(listener: std::net::TcpListener, config: &ServeConfig) -> std::io::Result<()> {
+    listener.set_nonblocking(true)?;
+    let router = router(config)?;
+    axum::Server::from_tcp(listener)
+        .map_err(std::io::Error::other)?
+        .serve(router.into_make_service())
+        .await
+        .map_err(std::io::Error::other)
+}
+
+async fn healthz() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "status": "ok" }))
+}
+
+async fn verify(
+    State(shared): State<Arc<Shared>>,
+    request: Result<Json<VerifyRequest>, JsonRejection>,
+) -> Result<Json<VerifyResponse>, Failure> {
+    let Json(request) = request.map_err(rejected)?;
+    if request.solutions.len() > shared.max_batch {
+        return Err(Failure::new(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            "batch_too_large",
+            format!(
+                "{} solutions exceed the limit of {}",
+                request.solutions.len(),
+                shared.max_batch
+            ),
+            None,
+        ));
+    }
+    let challenge: [u8; 32] = decode_hex(&request.challenge, || "challenge".to_string())?;
+    let solutions = request
+        .solutions
+        .iter()
+        .enumerate()
+        .map(|(i, solution)| {
+            let digest = decode_hex(&solution.digest, || format!("solutions[{}].digest", i))?;
+            let nonce = decode_hex(&solution.nonce, || format!("solutions[{}].nonce", i))?;
+            Ok(Solution::new(digest, nonce))
+        })
+        .collect::<Result<Vec<_>, Failure>>()?;
+
+    let min_difficulty = request.min_difficulty;
+    let (sender, receiver) = tokio::sync::oneshot::channel();
+    shared.pool.spawn(move || {
+        // Inside the pool, so the batch is split across its threads.
+        let results = solutions
+            .par_iter()
+            .map(|solution| {
+                let valid = solution.is_valid(&challenge);
+                let difficulty = valid.then(|| solution.to_hash().difficulty());
+                Verdict {
+                    valid,
+                    difficulty,
+                    accepted: difficulty.is_some_and(|d| d >= min_difficulty),
+                }
+            })
+            .collect();
+        let _ = sender.send(results);
+    });
+    let results = receiver.await.map_err(|_| {
+        Failure::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal",
+            "verification failed".to_string(),
+            None,
+        )
+    })?;
+    Ok(Json(VerifyResponse { results }))
+}
+
+fn rejected(rejection: JsonRejection) -> Failure {
+    let code = match &rejection {
+        JsonRejection::JsonSyntaxError(_) => "malformed_json",
+        JsonRejection::JsonDataError(_) => "invalid_request",
+        JsonRejection::MissingJsonContentType(_) => "unsupported_media_type",
+        _ if rejection.status() == StatusCode::PAYLOAD_TOO_LARGE => "body_too_large",
+        _ => "invalid_request",
+    };
+    Failure::new(rejection.status(), code, rejection.body_text(), None)
+}
+
+/// Decodes exactly `N` bytes of hex, naming the field on failure.
+fn decode_hex<const N: usize>(hex: &str, field: impl Fn() -> String) -> Result<[u8; N], Failure> {
+    let malformed = |message: String| {
+        Failure::new(
+            StatusCode::BAD_REQUEST,
+            "malformed_hex",
+            message,
+            Some(field()),
+        )
+    };
+    if hex.len() != 2 * N {
+        return Err(malformed(format!(
+            "expected {} hex digits, got {}",
+            2 * N,
+            hex.len()
+        )));
+    }
+    let mut bytes = [0; N];
+    for (byte, pair) in bytes.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let digit = |c: u8| (c as char).to_digit(16);
+        match (digit(pair[0]), digit(pair[1])) {
+            (Some(hi), Some(lo)) => *byte = (hi * 16 + lo) as u8,
+            _ => return Err(malformed("invalid hex digit".to_string())),
+        }
+    }
+    Ok(bytes)
+}
@@ -0,0 +1,453 @@
+//! A framed, checksummed file format for archiving raw shares.
+//!
+//! ```text
+//! header = magic "DXSL" ‖ version (u8)
+//! record = 0xa5 ‖ challenge id (8 bytes) ‖ solution (24 bytes) ‖ crc32 (u32 le)
+//! footer = 0xf0 ‖ record count (u64 le) ‖ 24 zero bytes ‖ crc32 (u32 le)
+//! ```
+//!
+//! Records and the footer are frames of the same length, each ending in the IEEE
+//! CRC-32 of the bytes before it. A reader that meets a bad frame, such as one left by
+//! a torn write, slides forward a byte at a time until the next valid frame, so the
+//! damage stays local. The footer is written by [`ShareLogWriter::finish`]; a log
+//! still being written has none.
+
+use std::io::{self, Read, Write};
+
+use crate::{DifficultyHistogram, HistogramSnapshot, Solution};
+
+/// Magic bytes at the start of every share log.
+pub const MAGIC: [u8; 4] = *b"DXSL";
+
+/// The only share log version so far.
+pub const VERSION: u8 = 1;
+
+/// Length of a record or footer frame.
+pub const FRAME_LEN: usize = 37;
+
+const RECORD_MARKER: u8 = 0xa5;
+const FOOTER_MARKER: u8 = 0xf0;
+
+/// A short identifier for the challenge a share was mined against.
+pub type ChallengeId = [u8; 8];
+
+/// A share read back from a log.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Record {
+    /// Position of the record among the log's valid records.
+    pub index: u64,
+    pub challenge_id: ChallengeId,
+    pub solution: Solution,
+}
+
+/// Appends shares to a log.
+pub struct ShareLogWriter<W: Write> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> ShareLogWriter<W> {
+    /// Starts a new log, writing its header.
+    pub fn new(mut inner: W) -> io::Result<Self> {
+        inner.write_all(&MAGIC)?;
+        inner.write_all(&[VERSION])?;
+        Ok(ShareLogWriter { inner, count: 0 })
+    }
+
+    /// Appends a share.
+    pub fn append(&mut self, challenge_id: &ChallengeId, solution: &Solution) -> io::Result<()> {
+        let mut frame = [0; FRAME_LEN];
+        frame[0] = RECORD_MARKER;
+        frame[1..9].copy_from_slice(challenge_id);
+        frame[9..33].copy_from_slice(&solution.to_bytes());
+        seal(&mut frame);
+        self.inner.write_all(&frame)?;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Number of shares appended so far.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Writes the footer, flushes, and returns the underlying writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let mut frame = [0; FRAME_LEN];
+        frame[0] = FOOTER_MARKER;
+        frame[1..9].copy_from_slice(&self.count.to_le_bytes());
+        seal(&mut frame);
+        self.inner.write_all(&frame)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+/// Reads shares from a log, one frame at a time.
+///
+/// Iterating yields each valid record. Damage is reported as an error item and reading
+/// carries on after it, so callers that only want the good records can skip errors.
+/// Iteration ends at the footer or at the end of the input.
+pub struct ShareLogReader<R: Read> {
+    inner: R,
+    frame: [u8; FRAME_LEN],
+    /// Bytes of `frame` filled so far.
+    filled: usize,
+    /// Input offset of `frame[0]`.
+    offset: u64,
+    /// Length of the damaged run currently being skipped.
+    skipped: u64,
+    pending: Option<Record>,
+    records: u64,
+    footer: Option<u64>,
+    done: bool,
+}
+
+impl<R: Read> ShareLogReader<R> {
+    /// Opens a log, checking its header.
+    pub fn new(mut inner: R) -> Result<Self, ShareLogError> {
+        let mut header = [0; 5];
+        inner
+            .read_exact(&mut header)
+            .map_err(|err| match err.kind() {
+                io::ErrorKind::UnexpectedEof => ShareLogError::BadMagic,
+                _ => ShareLogError::Io(err),
+            })?;
+        if header[..4] != MAGIC {
+            return Err(ShareLogError::BadMagic);
+        }
+        if header[4] != VERSION {
+            return Err(ShareLogError::UnknownVersion(header[4]));
+        }
+        Ok(ShareLogReader {
+            inner,
+            frame: [0; FRAME_LEN],
+            filled: 0,
+            offset: header.len() as u64,
+            skipped: 0,
+            pending: None,
+            records: 0,
+            footer: None,
+            done: false,
+        })
+    }
+
+    /// The record count from the footer, once the footer has been read.
+    pub fn footer(&self) -> Option<u64> {
+        self.footer
+    }
+
+    /// Number of valid records read so far.
+    pub fn records(&self) -> u64 {
+        self.records
+    }
+
+    /// Fills the frame buffer, returning false at the end of the input.
+    fn fill(&mut self) -> Result<bool, ShareLogError> {
+        while self.filled < FRAME_LEN {
+            match self.inner.read(&mut self.frame[self.filled..]) {
+                Ok(0) => return Ok(false),
+                Ok(n) => self.filled += n,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(ShareLogError::Io(err)),
+            }
+        }
+        Ok(true)
+    }
+
+    /// Consumes the frame buffer.
+    fn consume(&mut self) {
+        self.offset += FRAME_LEN as u64;
+        self.filled = 0;
+    }
+
+    /// Drops the first byte of the frame buffer as damaged.
+    fn slide(&mut self) {
+        self.frame.copy_within(1.., 0);
+        self.filled -= 1;
+        self.offset += 1;
+        self.skipped += 1;
+    }
+
+    /// Reports the damaged run that ends at the current offset, if any.
+    fn take_corrupt(&mut self) -> Option<ShareLogError> {
+        if self.skipped == 0 {
+            return None;
+        }
+        let len = std::mem::take(&mut self.skipped);
+        Some(ShareLogError::Corrupt {
+            offset: self.offset - len,
+            len,
+        })
+    }
+
+    fn read_next(&mut self) -> Option<Result<Record, ShareLogError>> {
+        if let Some(record) = self.pending.take() {
+            return Some(Ok(record));
+        }
+        loop {
+            if self.done {
+                return None;
+            }
+            match self.fill() {
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+                Ok(false) => {
+                    self.done = true;
+                    if self.filled == 0 {
+                        return self.take_corrupt().map(Err);
+                    }
+                    let len = self.skipped + self.filled as u64;
+                    let offset = self.offset - self.skipped;
+                    return Some(Err(if self.skipped > 0 {
+                        ShareLogError::Corrupt { offset, len }
+                    } else {
+                        ShareLogError::Truncated { offset, len }
+                    }));
+                }
+                Ok(true) => {}
+            }
+            if !is_sealed(&self.frame) {
+                self.slide();
+                continue;
+            }
+            match self.frame[0] {
+                RECORD_MARKER => {
+                    let record = Record {
+                        index: self.records,
+                        challenge_id: self.frame[1..9].try_into().unwrap(),
+                        solution: Solution::from_bytes(self.frame[9..33].try_into().unwrap()),
+                    };
+                    self.records += 1;
+                    let corrupt = self.take_corrupt();
+                    self.consume();
+                    return match corrupt {
+                        Some(err) => {
+                            self.pending = Some(record);
+                            Some(Err(err))
+                        }
+                        None => Some(Ok(record)),
+                    };
+                }
+                FOOTER_MARKER => {
+                    let declared = u64::from_le_bytes(self.frame[1..9].try_into().unwrap());
+                    let corrupt = self.take_corrupt();
+                    self.consume();
+                    self.footer = Some(declared);
+                    self.done = true;
+                    if let Some(err) = corrupt {
+                        return Some(Err(err));
+                    }
+                    if declared != self.records {
+                        return Some(Err(ShareLogError::CountMismatch {
+                            declared,
+                            read: self.records,
+                        }));
+                    }
+                    return None;
+                }
+                _ => self.slide(),
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for ShareLogReader<R> {
+    type Item = Result<Record, ShareLogError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_next()
+    }
+}
+
+/// The result of auditing a share log.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct AuditReport {
+    /// Valid records read.
+    pub records: u64,
+    /// Damaged or truncated runs skipped.
+    pub corrupt: u64,
+    /// Records selected for re-verification.
+    pub sampled: u64,
+    /// Sampled records whose solution verified.
+    pub valid: u64,
+    /// Indices of sampled records whose solution did not verify.
+    pub invalid: Vec<u64>,
+    /// Sampled records whose challenge the lookup did not know.
+    pub unknown_challenge: u64,
+    /// The footer's record count, if the log has a footer.
+    pub footer: Option<u64>,
+    /// Difficulties of the sampled records whose solution verified.
+    pub difficulties: HistogramSnapshot,
+}
+
+impl AuditReport {
+    /// Returns true if the log is intact and every sampled record verified.
+    pub fn is_clean(&self) -> bool {
+        self.corrupt == 0
+            && self.invalid.is_empty()
+            && self.unknown_challenge == 0
+            && self.footer.is_none_or(|count| count == self.records)
+    }
+}
+
+/// Re-verifies a deterministic sample of a log's records.
+///
+/// A record is sampled based on its contents, with probability `sample_rate` (clamped
+/// to `[0, 1]`), so re-running an audit checks the same records. Since the selection
+/// is predictable, audits of untrusted logs should use a rate of 1.0.
+pub fn verify_stream<R: Read>(
+    reader: R,
+    mut challenge_lookup: impl FnMut(&ChallengeId) -> Option<[u8; 32]>,
+    sample_rate: f64,
+) -> Result<AuditReport, ShareLogError> {
+    let threshold = sample_threshold(sample_rate);
+    let mut log = ShareLogReader::new(reader)?;
+    let mut report = AuditReport::default();
+    let difficulties = DifficultyHistogram::new();
+    for item in &mut log {
+        let record = match item {
+            Ok(record) => record,
+            Err(ShareLogError::Io(err)) => return Err(ShareLogError::Io(err)),
+            Err(ShareLogError::CountMismatch { .. }) => continue,
+            Err(_) => {
+                report.corrupt += 1;
+                continue;
+            }
+        };
+        if !is_sampled(&record, threshold) {
+            continue;
+        }
+        report.sampled += 1;
+        match challenge_lookup(&record.challenge_id) {
+            None => report.unknown_challenge += 1,
+            Some(challenge) if record.solution.is_valid(&challenge) => {
+                report.valid += 1;
+                difficulties.record(record.solution.to_hash().difficulty());
+            }
+            Some(_) => report.invalid.push(record.index),
+        }
+    }
+    report.records = log.records();
+    report.footer = log.footer();
+    report.difficulties = difficulties.snapshot();
+    Ok(report)
+}
+
+/// Maps a sample rate to a threshold on a record's 64-bit sample hash.
+fn sample_threshold(rate: f64) -> Option<u64> {
+    if rate.is_nan() || rate <= 0.0 {
+        None
+    } else if rate >= 1.0 {
+        Some(u64::MAX)
+    } else {
+        Some((rate * u64::MAX as f64) as u64)
+    }
+}
+
+fn is_sampled(record: &Record, threshold: Option<u64>) -> bool {
+    let Some(threshold) = threshold else {
+        return false;
+    };
+    let mut bytes = [0; 32];
+    bytes[..8].copy_from_slice(&record.challenge_id);
+    bytes[8..].copy_from_slice(&record.solution.to_bytes());
+    let mut h = 0xcbf2_9ce4_8422_2325u64;
+    for byte in bytes {
+        h = (h ^ byte as u64).wrapping_mul(0x0100_0000_01b3);
+    }
+    // Finalize so that every bit of the input affects the high bits.
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    h ^= h >> 33;
+    h <= threshold
+}
+
+/// Writes the frame's checksum into its last four bytes.
+fn seal(frame: &mut [u8; FRAME_LEN]) {
+    let crc = crc32(&frame[..FRAME_LEN - 4]);
+    frame[FRAME_LEN - 4..].copy_from_slice(&crc.to_le_bytes());
+}
+
+fn is_sealed(frame: &[u8; FRAME_LEN]) -> bool {
+    frame[FRAME_LEN - 4..] == crc32(&frame[..FRAME_LEN - 4]).to_le_bytes()
+}
+
+/// IEEE CRC-32, as used by zlib and PNG.
+fn crc32(bytes: &[u8]) -> u32 {
+    const TABLE: [u32; 256] = {
+        let mut table = [0; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u32;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 != 0 {
+                    crc >> 1 ^ 0xedb8_8320
+                } else {
+                    crc >> 1
+                };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    };
+    let mut crc = !0u32;
+    for &byte in bytes {
+        crc = crc >> 8 ^ TABLE[((crc ^ byte as u32) & 0xff) as usize];
+    }
+    !crc
+}
+
+/// An error reading a share log.
+#[derive(Debug)]
+pub enum ShareLogError {
+    /// The underlying reader failed.
+    Io(io::Error),
+    /// The input does not start with [`MAGIC`].
+    BadMagic,
+    /// The version byte is not [`VERSION`].
+    UnknownVersion(u8),
+    /// A run of bytes that are not valid frames was skipped.
+    Corrupt { offset: u64, len: u64 },
+    /// The input ends partway through a frame.
+    Truncated { offset: u64, len: u64 },
+    /// The footer's record count differs from the number of valid records read.
+    CountMismatch { declared: u64, read: u64 },
+}
+
+impl std::fmt::Display for ShareLogError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ShareLogError::Io(err) => write!(f, "Share log read failed: {}", err),
+            ShareLogError::BadMagic => write!(f, "Not a share log"),
+            ShareLogError::UnknownVersion(version) => {
+                write!(f, "Unknown share log version {}", version)
+            }
+            ShareLogError::Corrupt { offset, len } => {
+                write!(f, "{} corrupt bytes at offset {}", len, offset)
+            }
+            ShareLogError::Truncated { offset, len } => {
+                write!(f, "Truncated frame of {} bytes at offset {}", len, offset)
+            }
+            ShareLogError::CountMismatch { declared, read } => write!(
+                f,
+                "Footer declares {} records but {} were read",
+                declared, read
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ShareLogError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ShareLogError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Simulated share traffic for load-testing pools.
+//!
+//! A [`ShareSimulator`] stands in for a miner of a given hashrate without doing the
+//! work. Shares arrive as a Poisson process at `hashrate * 2^-share_difficulty` per
+//! second, and their difficulties follow the geometric distribution above the share
+//! threshold, as real shares do. The stream is deterministic in its seed.
+//!
+//! In [`SimMode::Real`], every share carries a real solution of [`SIM_CHALLENGE`] from
+//! a small embedded pool, so verification can be exercised end to end. The pool holds
+//! one solution per difficulty, so shares repeat and duplicate detection must be keyed
+//! or disabled. In [`SimMode::Synthetic`], shares carry random digests that don't
+//! verify, for throughput tests of everything before verification.
+
+use std::time::Duration;
+
+use crate::{vectors::hex, Solution};
+
+/// The challenge every real simulated share solves: `"drillx share simulator challenge"`
+/// in ASCII.
+pub const SIM_CHALLENGE: [u8; 32] = *b"drillx share simulator challenge";
+
+/// Highest difficulty of a real simulated share. Shares drawn above it are capped.
+pub const MAX_REAL_DIFFICULTY: u32 = REAL_POOL.len() as u32 - 1;
+
+/// Solutions of [`SIM_CHALLENGE`] indexed by difficulty, as `(nonce, digest)`.
+const REAL_POOL: [(u64, [u8; 16]); 16] = [
+    (0, hex("5b8c3b8d21b29ecdc3a201c3924f1be9")),
+    (1, hex("da7b5688f73713dc640c86735c5203eb")),
+    (39, hex("5c9239e7757e75e8c09c87a998353eff")),
+    (29, hex("664d40ae895b33e27c33c28d9efbadfb")),
+    (50, hex("1944f5cbf809adccb5aac3aab174f1cf")),
+    (62, hex("1140ae89463bc0ca2f6186ec47392fee")),
+    (171, hex("292abf80caa440bfc90ed369255d6af6")),
+    (46, hex("27930bccd94eabe9894981e6a8a2e1f8")),
+    (180, hex("8d15fa2da832f998ea78fbb3843e4ae3")),
+    (1016, hex("7b2094964a7efbe8b087d395e15ef4f7")),
+    (8419, hex("a92b1f518a0c95b8ce471aa49c8a33bf")),
+    (6573, hex("8d0eaf3bc444138881c81ed6591dbce5")),
+    (17829, hex("366ecf74af5430aee9415e6c8c9f4ae1")),
+    (22713, hex("986b52abfec39bc9aa0821b7680e26cb")),
+    (94621, hex("9765d871707d03bd8243358b6e461cc5")),
+    (24539, hex("3e2a56ad5f8bcecd513f255931c7c8d5")),
+];
+
+/// What simulated shares carry.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SimMode {
+    /// Real solutions of [`SIM_CHALLENGE`], capped at [`MAX_REAL_DIFFICULTY`].
+    Real,
+    /// Random digests marked synthetic, which never verify.
+    #[default]
+    Synthetic,
+}
+
+/// A simulated submission.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SimulatedShare {
+    /// Time since the simulation started.
+    pub timestamp: Duration,
+    pub challenge: [u8; 32],
+    pub solution: Solution,
+    /// The share's difficulty. For real shares, that of `solution`.
+    pub difficulty: u32,
+    /// True if `solution` is random bytes rather than a real solution.
+    pub synthetic: bool,
+}
+
+/// A deterministic stream of simulated shares.
+#[derive(Clone, Debug)]
+pub struct ShareSimulator {
+    rate: f64,
+    share_difficulty: u32,
+    mode: SimMode,
+    state: u64,
+    elapsed: f64,
+    sequence: u64,
+}
+
+impl ShareSimulator {
+    /// Simulates a miner of `hashrate` hashes per second submitting shares of at least
+    /// `share_difficulty`, in [`SimMode::Synthetic`].
+    ///
+    /// # Panics
+    ///
+    /// If `hashrate` is not positive and finite.
+    pub fn new(hashrate: f64, share_difficulty: u32, seed: u64) -> Self {
+        assert!(
+            hashrate > 0.0 && hashrate.is_finite(),
+            "hashrate must be positive, got {}",
+            hashrate
+        );
+        let share_difficulty = share_difficulty.min(256);
+        ShareSimulator {
+            rate: hashrate * 0.5f64.powi(share_difficulty as i32),
+            share_difficulty,
+            mode: SimMode::default(),
+            state: seed,
+            elapsed: 0.0,
+            sequence: 0,
+        }
+    }
+
+    /// Sets what the shares carry.
+    ///
+    /// # Panics
+    ///
+    /// If `mode` is [`SimMode::Real`] and the share difficulty is above
+    /// [`MAX_REAL_DIFFICULTY`].
+    pub fn mode(mut self, mode: SimMode) -> Self {
+        assert!(
+            mode == SimMode::Synthetic || self.share_difficulty <= MAX_REAL_DIFFICULTY,
+            "no real shares have difficulty {}",
+            self.share_difficulty
+        );
+        self.mode = mode;
+        self
+    }
+
+    /// Expected number of shares per second.
+    pub fn share_rate(&self) -> f64 {
+        self.rate
+    }
+
+    /// Returns the next share. The stream never ends.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> SimulatedShare {
+        // Inverse transform of a uniform draw in (0, 1].
+        let uniform = ((self.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64;
+        self.elapsed += -uniform.ln() / self.rate;
+        // Each extra leading zero has probability one half.
+        let mut difficulty = self.share_difficulty;
+        loop {
+            let zeros = self.next_u64().trailing_zeros();
+            difficulty = difficulty.saturating_add(zeros).min(256);
+            if zeros < 64 || difficulty == 256 {
+                break;
+            }
+        }
+        let sequence = self.sequence;
+        self.sequence += 1;
+        let (solution, difficulty) = match self.mode {
+            SimMode::Real => {
+                let difficulty = difficulty.min(MAX_REAL_DIFFICULTY);
+                let (nonce, digest) = REAL_POOL[difficulty as usize];
+                (Solution::new(digest, nonce.to_le_bytes()), difficulty)
+            }
+            SimMode::Synthetic => {
+                let digest = (self.next_u64() as u128) << 64 | self.next_u64() as u128;
+                let solution = Solution::new(digest.to_le_bytes(), sequence.to_le_bytes());
+                (solution, difficulty)
+            }
+        };
+        SimulatedShare {
+            timestamp: Duration::from_secs_f64(self.elapsed),
+            challenge: SIM_CHALLENGE,
+            solution,
+            difficulty,
+            synthetic: self.mode == SimMode::Synthetic,
+        }
+    }
+
+    /// Iterates over the shares.
+    pub fn shares(self) -> SimulatedShares {
+        SimulatedShares(self)
+    }
+
+    /// SplitMix64.
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+/// An endless iterator over simulated shares, returned by [`ShareSimulator::shares`].
+#[derive(Clone, Debug)]
+pub struct SimulatedShares(ShareSimulator);
+
+impl Iterator for SimulatedShares {
+    type Item = SimulatedShare;
+
+    fn next(&mut self) -> Option<SimulatedShare> {
+        Some(self.0.next())
+    }
+}
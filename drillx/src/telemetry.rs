@@ -0,0 +1,201 @@
+//! Instrumentation emitted by drillx.
+//!
+//! # Tracing
+//!
+//! With the `tracing` feature enabled, drillx emits the following spans and events
+//! under the `drillx` target. Their names and fields are stable.
+//!
+//! | Name                          | Kind  | Level | Fields                          |
+//! |-------------------------------|-------|-------|---------------------------------|
+//! | `drillx.solve`                | span  | TRACE | `thread`, `nonce`               |
+//! | `drillx.challenge`            | event | INFO  | `challenge`, `min_difficulty`   |
+//! | `drillx.solution`             | event | INFO  | `thread`, `nonce`, `difficulty` |
+//! | `drillx.no_solutions_streak`  | event | WARN  | `thread`, `streak`              |
+//! | `drillx.runtime_fallback`     | event | WARN  |                                 |
+//! | `drillx.runtime_downgrade`    | event | WARN  | `failures`                      |
+//! | `drillx.topology`             | event | INFO  | `topology`, `preference`        |
+//! | `drillx.worker_panic`         | event | ERROR | `thread`, `payload`             |
+//! | `drillx.worker_stall`         | event | WARN  | `thread`, `stalled_ms`          |
+//! | `drillx.memory_exhausted`     | event | WARN  | `requested`, `running`, `error` |
+//! | `drillx.offload_fallback`     | event | WARN  | `device`, `error`               |
+//! | `drillx.offload_disagreement` | event | ERROR | `disagreements`                 |
+//! | `drillx.throttle`             | event | INFO  | `previous`, `intensity`         |
+//!
+//! - `drillx.solve` wraps one in every [`SOLVE_SPAN_SAMPLE`] hashes of a miner worker.
+//! - `drillx.challenge` fires each time a challenge job is added to a miner (hex encoded).
+//! - `drillx.solution` fires when a miner worker finds a solution meeting the minimum difficulty.
+//!   The nonce is reported as a little-endian `u64`.
+//! - `drillx.no_solutions_streak` fires each time a miner worker sees another
+//!   [`NO_SOLUTIONS_STREAK`] consecutive nonces without any equix solution.
+//! - `drillx.runtime_fallback` fires once per process when the hashx compiler fails and
+//!   hashing falls back to the interpreter.
+//! - `drillx.runtime_downgrade` fires when a [`Context`](crate::Context) stops compiling
+//!   after `failures` consecutive compile failures.
+//! - `drillx.topology` fires when a miner starts, with the detected core topology and
+//!   the miner's core preference (debug formatted).
+//! - `drillx.worker_panic` fires when a miner worker panics, with the panic message,
+//!   before it is replaced.
+//! - `drillx.worker_stall` fires when a miner worker holding a chunk has not finished
+//!   a hash for `stalled_ms` milliseconds, before it is replaced.
+//! - `drillx.memory_exhausted` fires when a miner worker cannot get solver memory, so
+//!   `running` of the `requested` CPU workers are left.
+//! - `drillx.offload_fallback` fires when an offload device's launch fails and its
+//!   solutions are verified on the CPU instead.
+//! - `drillx.offload_disagreement` fires when cross-checked device verdicts of a launch
+//!   differ from the CPU's, with the number that differed.
+//! - `drillx.throttle` fires when a miner's [`ThrottleHook`](crate::throttle::ThrottleHook)
+//!   changes its intensity.
+//!
+//! With the feature disabled, none of this instrumentation is compiled.
+//!
+//! # Metrics
+//!
+//! With the `metrics` feature enabled, drillx reports the following through the
+//! [`metrics`](https://docs.rs/metrics) facade. Names and labels are stable.
+//!
+//! | Name                                 | Kind      | Labels   |
+//! |--------------------------------------|-----------|----------|
+//! | `drillx_hashes_total`                | counter   | `thread` |
+//! | `drillx_solutions_total`             | counter   |          |
+//! | `drillx_no_solutions_total`          | counter   |          |
+//! | `drillx_stream_dropped_total`        | counter   |          |
+//! | `drillx_best_difficulty`             | gauge     |          |
+//! | `drillx_solution_difficulty`         | histogram |          |
+//! | `drillx_verify_accepted_total`       | counter   |          |
+//! | `drillx_verify_rejected_total`       | counter   | `reason` |
+//! | `drillx_runtime_downgrades_total`    | counter   |          |
+//! | `drillx_offload_fallbacks_total`     | counter   |          |
+//! | `drillx_offload_disagreements_total` | counter   |          |
+//!
+//! The miner counters are published by the coordinator thread every few milliseconds
+//! rather than from the hashing loop, and `drillx_best_difficulty` tracks the best
+//! difficulty seen across the miner's current challenges. `drillx_solution_difficulty`
+//! records the difficulty of each solution meeting the minimum difficulty. The
+//! verification counters are incremented once per [`verify_batch`](crate::verify_batch)
+//! call, or offload verifier batch; the only rejection reason today is `invalid`.
+//! `drillx_runtime_downgrades_total` counts `drillx.runtime_downgrade` events, and the
+//! offload counters count failed device launches and wrong device verdicts.
+//!
+//! With the feature disabled, none of these metrics are compiled.
+
+/// Name of the sampled span around a miner solve.
+pub const SOLVE_SPAN: &str = "drillx.solve";
+
+/// Name of the event emitted when the miner starts work on a challenge.
+pub const CHALLENGE_EVENT: &str = "drillx.challenge";
+
+/// Name of the event emitted when a miner worker finds a solution.
+pub const SOLUTION_EVENT: &str = "drillx.solution";
+
+/// Name of the event emitted on a streak of nonces without solutions.
+pub const NO_SOLUTIONS_STREAK_EVENT: &str = "drillx.no_solutions_streak";
+
+/// Name of the event emitted when hashing falls back to the interpreter.
+pub const RUNTIME_FALLBACK_EVENT: &str = "drillx.runtime_fallback";
+
+/// Name of the event emitted when a context stops compiling.
+pub const RUNTIME_DOWNGRADE_EVENT: &str = "drillx.runtime_downgrade";
+
+/// Name of the event emitted with the core topology when a miner starts.
+pub const TOPOLOGY_EVENT: &str = "drillx.topology";
+
+/// Name of the event emitted when a miner worker panics.
+pub const WORKER_PANIC_EVENT: &str = "drillx.worker_panic";
+
+/// Name of the event emitted when a miner worker stalls.
+pub const WORKER_STALL_EVENT: &str = "drillx.worker_stall";
+
+/// Name of the event emitted when a miner worker cannot get solver memory.
+pub const MEMORY_EXHAUSTED_EVENT: &str = "drillx.memory_exhausted";
+
+/// Name of the event emitted when an offload device's launch fails.
+pub const OFFLOAD_FALLBACK_EVENT: &str = "drillx.offload_fallback";
+
+/// Name of the event emitted when offload device verdicts disagree with the CPU.
+pub const OFFLOAD_DISAGREEMENT_EVENT: &str = "drillx.offload_disagreement";
+
+/// Name of the event emitted when a miner's throttle intensity changes.
+pub const THROTTLE_EVENT: &str = "drillx.throttle";
+
+/// Counter of nonces hashed by the miner.
+pub const HASHES_METRIC: &str = "drillx_hashes_total";
+
+/// Counter of solutions meeting the minimum difficulty.
+pub const SOLUTIONS_METRIC: &str = "drillx_solutions_total";
+
+/// Counter of nonces without any equix solution.
+pub const NO_SOLUTIONS_METRIC: &str = "drillx_no_solutions_total";
+
+/// Counter of solutions dropped because the miner's stream was full.
+pub const STREAM_DROPPED_METRIC: &str = "drillx_stream_dropped_total";
+
+/// Gauge of the best difficulty seen across the miner's current challenges, or 0 while
+/// there is none.
+pub const BEST_DIFFICULTY_METRIC: &str = "drillx_best_difficulty";
+
+/// Histogram of the difficulty of solutions meeting the minimum difficulty.
+pub const SOLUTION_DIFFICULTY_METRIC: &str = "drillx_solution_difficulty";
+
+/// Counter of solutions accepted by batch verification.
+pub const VERIFY_ACCEPTED_METRIC: &str = "drillx_verify_accepted_total";
+
+/// Counter of solutions rejected by batch verification.
+pub const VERIFY_REJECTED_METRIC: &str = "drillx_verify_rejected_total";
+
+/// Counter of contexts switched to the interpreter by the compile-failure watchdog.
+pub const RUNTIME_DOWNGRADES_METRIC: &str = "drillx_runtime_downgrades_total";
+
+/// Counter of failed offload device launches.
+pub const OFFLOAD_FALLBACKS_METRIC: &str = "drillx_offload_fallbacks_total";
+
+/// Counter of offload device verdicts found wrong by CPU cross-checks.
+pub const OFFLOAD_DISAGREEMENTS_METRIC: &str = "drillx_offload_disagreements_total";
+
+/// One in this many miner solves is wrapped in a [`SOLVE_SPAN`].
+pub const SOLVE_SPAN_SAMPLE: u64 = 1024;
+
+/// Length of a streak of nonces without solutions that warrants an event.
+pub const NO_SOLUTIONS_STREAK: u64 = 16;
+
+/// Emits a tracing event under the `drillx` target when the `tracing` feature is enabled.
+#[cfg(any(feature = "solve", feature = "offload"))]
+macro_rules! event {
+    ($name:expr, $level:ident, $($fields:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::event!(
+            name: $name,
+            target: "drillx",
+            tracing::Level::$level,
+            $($fields)*
+        );
+    };
+}
+
+#[cfg(any(feature = "solve", feature = "offload"))]
+pub(crate) use event;
+
+/// Reports a fallback from the compiled runtime to the interpreter, once per process.
+#[cfg(all(feature = "tracing", feature = "solve"))]
+pub(crate) fn runtime_fallback() {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    static REPORTED: AtomicBool = AtomicBool::new(false);
+    if !REPORTED.swap(true, Ordering::Relaxed) {
+        event!(
+            RUNTIME_FALLBACK_EVENT,
+            WARN,
+            "hashx compiler unavailable, falling back to the interpreter"
+        );
+    }
+}
+
+/// Formats bytes as lowercase hex, in tracing fields and reports.
+pub(crate) struct Hex<'a>(pub &'a [u8]);
+
+impl std::fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
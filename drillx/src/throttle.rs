@@ -0,0 +1,164 @@
+//! Throttling a miner from outside signals such as heat or battery.
+//!
+//! An application implements [`ThrottleHook`] and hands it to
+//! [`MinerBuilder::throttle`](crate::miner::MinerBuilder::throttle). A dedicated thread
+//! polls the hook every [`MinerBuilder::throttle_poll`](crate::miner::MinerBuilder::throttle_poll),
+//! so a slow hook only delays the next change and never holds up the miner.
+//!
+//! The intensity is a duty cycle. At intensity `d` each worker rests after every hash
+//! for `(1 - d) / d` times as long as the hash took, so it hashes for a fraction `d` of
+//! the time and its hashrate drops to `d` of its full speed. At zero workers park
+//! without giving up their chunks, as when paused, until the intensity rises again.
+//! Intensities below [`MIN_INTENSITY`] count as zero, since their rests would be
+//! longer than any [`Duration`](std::time::Duration) can hold.
+//! Changes reach resting workers at once and hashing workers after their current hash.
+//!
+//! With the `battery` feature, [`BatteryAwareHook`] throttles on Linux laptops and
+//! phones running on battery.
+
+#[cfg(feature = "battery")]
+use std::path::{Path, PathBuf};
+
+/// Tells a miner how hard to work.
+pub trait ThrottleHook: Send + Sync {
+    /// The fraction of full speed to mine at, from 0.0, fully parked, to 1.0. Values
+    /// outside that range are clamped, values below [`MIN_INTENSITY`] park, and NaN
+    /// leaves the intensity unchanged.
+    fn target_intensity(&self) -> f32;
+}
+
+impl<F: Fn() -> f32 + Send + Sync> ThrottleHook for F {
+    fn target_intensity(&self) -> f32 {
+        self()
+    }
+}
+
+/// The lowest intensity a miner runs at. Anything below parks its workers.
+pub const MIN_INTENSITY: f32 = 1e-3;
+
+/// How long a worker at `intensity` rests after a hash that took `hashed`, or `None`
+/// if it should park.
+pub(crate) fn rest(hashed: std::time::Duration, intensity: f32) -> Option<std::time::Duration> {
+    if intensity < MIN_INTENSITY {
+        return None;
+    }
+    if intensity >= 1.0 {
+        return Some(std::time::Duration::ZERO);
+    }
+    Some(hashed.mul_f64((1.0 - intensity as f64) / intensity as f64))
+}
+
+/// Throttles on battery power, from the power supplies in Linux sysfs.
+///
+/// On mains power, or where no power supply can be read, the intensity is 1.0. On
+/// battery it is [`on_battery`](Self::on_battery), and at or below the low capacity it
+/// is [`low_battery`](Self::low_battery). The device counts as on mains power if any
+/// `Mains`, `USB`, or `USB_*` supply is online, and on battery otherwise if it has a
+/// `Battery` supply. The lowest capacity among batteries decides.
+#[cfg(feature = "battery")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct BatteryAwareHook {
+    root: PathBuf,
+    on_battery: f32,
+    low_capacity: u8,
+    low_battery: f32,
+}
+
+/// What [`BatteryAwareHook`] found in sysfs.
+#[cfg(feature = "battery")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PowerSource {
+    /// Plugged in, or no battery.
+    Mains,
+    /// Running on battery, with the lowest capacity in percent, if reported.
+    Battery { capacity: Option<u8> },
+}
+
+#[cfg(feature = "battery")]
+impl Default for BatteryAwareHook {
+    fn default() -> Self {
+        BatteryAwareHook::new()
+    }
+}
+
+#[cfg(feature = "battery")]
+impl BatteryAwareHook {
+    /// Mines at 0.25 on battery and parks at 20% capacity or less.
+    pub fn new() -> Self {
+        BatteryAwareHook {
+            root: PathBuf::from("/sys/class/power_supply"),
+            on_battery: 0.25,
+            low_capacity: 20,
+            low_battery: 0.0,
+        }
+    }
+
+    /// Reads power supplies under `root` instead of `/sys/class/power_supply`.
+    pub fn with_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = root.into();
+        self
+    }
+
+    /// Sets the intensity on battery.
+    pub fn on_battery(mut self, intensity: f32) -> Self {
+        self.on_battery = intensity;
+        self
+    }
+
+    /// Sets the intensity on battery at or below `capacity` percent.
+    pub fn low_battery(mut self, capacity: u8, intensity: f32) -> Self {
+        self.low_capacity = capacity;
+        self.low_battery = intensity;
+        self
+    }
+
+    /// Reads the power source now.
+    pub fn power_source(&self) -> PowerSource {
+        let read = |dir: &Path, name: &str| {
+            std::fs::read_to_string(dir.join(name))
+                .ok()
+                .map(|value| value.trim().to_string())
+        };
+        let Ok(entries) = std::fs::read_dir(&self.root) else {
+            return PowerSource::Mains;
+        };
+        let mut battery = false;
+        let mut capacity: Option<u8> = None;
+        for entry in entries.flatten() {
+            let dir = entry.path();
+            match read(&dir, "type").as_deref() {
+                Some("Mains" | "USB") => {}
+                Some(kind) if kind.starts_with("USB_") => {}
+                Some("Battery") => {
+                    battery = true;
+                    if let Some(percent) = read(&dir, "capacity").and_then(|c| c.parse().ok()) {
+                        capacity = Some(capacity.map_or(percent, |c: u8| c.min(percent)));
+                    }
+                    continue;
+                }
+                _ => continue,
+            }
+            if read(&dir, "online").as_deref() == Some("1") {
+                return PowerSource::Mains;
+            }
+        }
+        if battery {
+            PowerSource::Battery { capacity }
+        } else {
+            PowerSource::Mains
+        }
+    }
+}
+
+#[cfg(feature = "battery")]
+impl ThrottleHook for BatteryAwareHook {
+    fn target_intensity(&self) -> f32 {
+        match self.power_source() {
+            PowerSource::Mains => 1.0,
+            PowerSource::Battery { capacity: Some(c) } if c <= self.low_capacity => {
+                self.low_battery
+            }
+            PowerSource::Battery { .. } => self.on_battery,
+        }
+    }
+}
@@ -0,0 +1,241 @@
+//! Detection of performance and efficiency cores.
+//!
+//! On hybrid CPUs such as Alder Lake or Apple Silicon, an efficiency core hashes at a
+//! fraction of a performance core's speed. [`detect`] classifies the logical CPUs so
+//! the miner can schedule workers on the fast ones first; see
+//! [`MinerBuilder::core_preference`](crate::miner::MinerBuilder::core_preference).
+//!
+//! On Linux the classification comes from `cpu_capacity` in sysfs, falling back to
+//! the `cpu_core` and `cpu_atom` PMU CPU lists of Intel hybrid parts. On macOS it
+//! comes from the `hw.perflevel*` sysctls, which give counts but not CPU ids. Anywhere
+//! else, or if those sources are missing, the topology is [`Topology::Unknown`].
+//! Workers are only pinned on Linux.
+
+use std::sync::OnceLock;
+
+/// Cores whose capacity is below this fraction of the highest count as efficiency
+/// cores.
+const EFFICIENCY_CAPACITY: f64 = 0.75;
+
+/// The kinds of cores on this machine.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Topology {
+    /// The cores could not be classified.
+    Unknown,
+    /// Every logical CPU is of the same kind.
+    Uniform { cpus: usize },
+    /// A mix of performance and efficiency cores.
+    Hybrid {
+        performance: CoreGroup,
+        efficiency: CoreGroup,
+    },
+}
+
+/// The logical CPUs of one kind.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CoreGroup {
+    /// Number of logical CPUs.
+    pub count: usize,
+    /// Their CPU ids in increasing order, or empty where the platform does not expose
+    /// them.
+    pub cpus: Vec<usize>,
+}
+
+impl CoreGroup {
+    fn from_cpus(cpus: Vec<usize>) -> Self {
+        CoreGroup {
+            count: cpus.len(),
+            cpus,
+        }
+    }
+}
+
+impl Topology {
+    pub fn is_hybrid(&self) -> bool {
+        matches!(self, Topology::Hybrid { .. })
+    }
+}
+
+/// Returns the topology of this machine, detected once per process.
+pub fn detect() -> Topology {
+    static DETECTED: OnceLock<Topology> = OnceLock::new();
+    DETECTED.get_or_init(detect_uncached).clone()
+}
+
+#[cfg(target_os = "linux")]
+fn detect_uncached() -> Topology {
+    let root = std::path::Path::new("/sys/devices/system/cpu");
+    let read = |path: std::path::PathBuf| std::fs::read_to_string(path).ok();
+    let Some(online) = read(root.join("online")).and_then(|list| parse_cpulist(&list)) else {
+        return Topology::Unknown;
+    };
+    let capacities: Option<Vec<(usize, String)>> = online
+        .iter()
+        .map(|&cpu| read(root.join(format!("cpu{}", cpu)).join("cpu_capacity")).map(|c| (cpu, c)))
+        .collect();
+    if let Some(capacities) = capacities {
+        let capacities: Vec<(usize, &str)> = capacities
+            .iter()
+            .map(|(cpu, c)| (*cpu, c.as_str()))
+            .collect();
+        return parse_capacities(&capacities);
+    }
+    let pmu = |name: &str| read(format!("/sys/devices/{}/cpus", name).into());
+    if let (Some(core), Some(atom)) = (pmu("cpu_core"), pmu("cpu_atom")) {
+        return parse_pmu_cpus(&core, &atom);
+    }
+    Topology::Uniform { cpus: online.len() }
+}
+
+#[cfg(target_os = "macos")]
+fn detect_uncached() -> Topology {
+    let output = std::process::Command::new("sysctl")
+        .args([
+            "hw.logicalcpu",
+            "hw.nperflevels",
+            "hw.perflevel0.logicalcpu",
+            "hw.perflevel1.logicalcpu",
+        ])
+        .output();
+    match output {
+        // Unknown keys are reported on stderr, so stdout is usable even on failure.
+        Ok(output) => parse_sysctl(&String::from_utf8_lossy(&output.stdout)),
+        Err(_) => Topology::Unknown,
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn detect_uncached() -> Topology {
+    Topology::Unknown
+}
+
+/// Classifies CPUs from the contents of their sysfs `cpu_capacity` files, given as
+/// `(cpu, contents)` pairs.
+///
+/// Returns [`Topology::Unknown`] if any file does not hold a capacity.
+pub fn parse_capacities(files: &[(usize, &str)]) -> Topology {
+    let capacities: Option<Vec<(usize, u32)>> = files
+        .iter()
+        .map(|(cpu, contents)| contents.trim().parse().ok().map(|c| (*cpu, c)))
+        .collect();
+    let Some(mut capacities) = capacities.filter(|c| !c.is_empty()) else {
+        return Topology::Unknown;
+    };
+    capacities.sort_unstable();
+    let highest = capacities.iter().map(|(_, c)| *c).max().unwrap_or(0) as f64;
+    let (performance, efficiency): (Vec<_>, Vec<_>) = capacities
+        .iter()
+        .partition(|(_, c)| *c as f64 >= highest * EFFICIENCY_CAPACITY);
+    if efficiency.is_empty() {
+        return Topology::Uniform {
+            cpus: performance.len(),
+        };
+    }
+    Topology::Hybrid {
+        performance: CoreGroup::from_cpus(performance.iter().map(|(cpu, _)| *cpu).collect()),
+        efficiency: CoreGroup::from_cpus(efficiency.iter().map(|(cpu, _)| *cpu).collect()),
+    }
+}
+
+/// Classifies CPUs from the contents of `/sys/devices/cpu_core/cpus` and
+/// `/sys/devices/cpu_atom/cpus` on Intel hybrid parts.
+pub fn parse_pmu_cpus(core: &str, atom: &str) -> Topology {
+    let (Some(core), Some(atom)) = (parse_cpulist(core), parse_cpulist(atom)) else {
+        return Topology::Unknown;
+    };
+    match (core.is_empty(), atom.is_empty()) {
+        (false, false) => Topology::Hybrid {
+            performance: CoreGroup::from_cpus(core),
+            efficiency: CoreGroup::from_cpus(atom),
+        },
+        (true, true) => Topology::Unknown,
+        _ => Topology::Uniform {
+            cpus: core.len() + atom.len(),
+        },
+    }
+}
+
+/// Parses a sysfs CPU list such as `0-7,16,18-19` into sorted CPU ids.
+pub fn parse_cpulist(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for range in list.trim().split(',').filter(|range| !range.is_empty()) {
+        match range.split_once('-') {
+            Some((first, last)) => {
+                let (first, last): (usize, usize) = (first.parse().ok()?, last.parse().ok()?);
+                if first > last {
+                    return None;
+                }
+                cpus.extend(first..=last);
+            }
+            None => cpus.push(range.parse().ok()?),
+        }
+    }
+    cpus.sort_unstable();
+    cpus.dedup();
+    Some(cpus)
+}
+
+/// Classifies CPUs from the output of `sysctl hw.logicalcpu hw.nperflevels
+/// hw.perflevel0.logicalcpu hw.perflevel1.logicalcpu` on macOS.
+///
+/// Performance level 0 is the fastest. Any keys may be missing, as on Intel Macs.
+pub fn parse_sysctl(output: &str) -> Topology {
+    let value = |key: &str| {
+        output.lines().find_map(|line| {
+            let (name, value) = line.split_once(':')?;
+            if name.trim() != key {
+                return None;
+            }
+            value.trim().parse::<usize>().ok()
+        })
+    };
+    let levels = value("hw.nperflevels").unwrap_or(1);
+    match (
+        value("hw.perflevel0.logicalcpu"),
+        value("hw.perflevel1.logicalcpu"),
+    ) {
+        (Some(performance), Some(efficiency)) if levels >= 2 && efficiency > 0 => {
+            Topology::Hybrid {
+                performance: CoreGroup {
+                    count: performance,
+                    cpus: vec![],
+                },
+                efficiency: CoreGroup {
+                    count: efficiency,
+                    cpus: vec![],
+                },
+            }
+        }
+        _ => match value("hw.logicalcpu").or(value("hw.perflevel0.logicalcpu")) {
+            Some(cpus) if cpus > 0 => Topology::Uniform { cpus },
+            _ => Topology::Unknown,
+        },
+    }
+}
+
+/// Restricts the calling thread to the given CPUs, returning true on success.
+#[cfg(target_os = "linux")]
+pub(crate) fn pin(cpus: &[usize]) -> bool {
+    let cpus: Vec<usize> = cpus
+        .iter()
+        .copied()
+        .filter(|&cpu| cpu < libc::CPU_SETSIZE as usize)
+        .collect();
+    if cpus.is_empty() {
+        return false;
+    }
+    // SAFETY: `cpu_set_t` is plain data, every id is below `CPU_SETSIZE`, and the set
+    // outlives the call.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        for cpu in cpus {
+            libc::CPU_SET(cpu, &mut set);
+        }
+        libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) == 0
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn pin(_cpus: &[usize]) -> bool {
+    false
+}
@@ -0,0 +1,169 @@
+//! Empirical choice of a thread count.
+//!
+//! Equi-X is memory-bound, so the fastest thread count is often below the number of
+//! logical cores, especially with hyperthreading. [`autotune`] measures a few
+//! candidates and recommends the fastest.
+
+use std::{
+    sync::OnceLock,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::Context;
+
+/// Budget of the tune run by [`MinerBuilder::threads_auto`](crate::miner::MinerBuilder::threads_auto).
+pub const AUTO_TUNE_BUDGET: Duration = Duration::from_secs(2);
+
+/// Trials within this fraction of the best hashrate count as ties, which go to the
+/// smaller thread count.
+const TIE: f64 = 0.02;
+
+/// The measured hashrate at one thread count.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TuneTrial {
+    pub threads: usize,
+    pub hashes: u64,
+    pub elapsed: Duration,
+    /// Hashes per second.
+    pub hashrate: f64,
+}
+
+/// The result of [`autotune`].
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct TuneReport {
+    pub logical_cores: usize,
+    /// Number of physical cores, if it could be detected.
+    pub physical_cores: Option<usize>,
+    /// One trial per candidate thread count, in increasing order.
+    pub trials: Vec<TuneTrial>,
+    /// The recommended thread count, one of the trials'.
+    pub recommended: usize,
+    /// Wall-clock duration of the whole tune.
+    pub elapsed: Duration,
+}
+
+impl TuneReport {
+    pub fn trial(&self, threads: usize) -> Option<&TuneTrial> {
+        self.trials.iter().find(|trial| trial.threads == threads)
+    }
+}
+
+/// Benchmarks several thread counts for up to `max_seconds` and recommends one.
+///
+/// The candidates are one thread, the physical and logical core counts, and the
+/// midpoints between them. The budget is split evenly between them, and solver memory
+/// is allocated once and shared by all trials. Each thread hashes at least once, so a
+/// budget too small for even that is overrun by about one hash.
+pub fn autotune(max_seconds: u64) -> TuneReport {
+    autotune_within(Duration::from_secs(max_seconds))
+}
+
+/// Like [`autotune`], with a budget finer than whole seconds.
+pub fn autotune_within(budget: Duration) -> TuneReport {
+    let started = Instant::now();
+    let logical = thread::available_parallelism().map_or(1, |n| n.get());
+    let physical = physical_cores();
+    let candidates = candidates(logical, physical);
+    let window = budget / candidates.len() as u32;
+    let mut contexts: Vec<Context> = Vec::new();
+    let mut trials = Vec::with_capacity(candidates.len());
+    for threads in candidates {
+        contexts.resize_with(contexts.len().max(threads), Context::default);
+        trials.push(trial(&mut contexts[..threads], window));
+    }
+    let best = trials.iter().map(|t| t.hashrate).fold(0.0, f64::max);
+    let recommended = trials
+        .iter()
+        .find(|t| t.hashrate >= best * (1.0 - TIE))
+        .map_or(1, |t| t.threads);
+    TuneReport {
+        logical_cores: logical,
+        physical_cores: physical,
+        trials,
+        recommended,
+        elapsed: started.elapsed(),
+    }
+}
+
+/// Runs [`autotune`] once per process and returns its recommendation.
+pub(crate) fn recommended_threads() -> usize {
+    static TUNED: OnceLock<usize> = OnceLock::new();
+    *TUNED.get_or_init(|| autotune_within(AUTO_TUNE_BUDGET).recommended)
+}
+
+fn candidates(logical: usize, physical: Option<usize>) -> Vec<usize> {
+    let physical = physical.unwrap_or(logical).clamp(1, logical);
+    let mut candidates = vec![
+        1,
+        physical.div_ceil(2),
+        physical,
+        (physical + logical) / 2,
+        logical,
+    ];
+    candidates.sort_unstable();
+    candidates.dedup();
+    candidates
+}
+
+/// Hashes on one thread per context until the window closes.
+pub(crate) fn trial(contexts: &mut [Context], window: Duration) -> TuneTrial {
+    let started = Instant::now();
+    let deadline = started + window;
+    let hashes: u64 = thread::scope(|scope| {
+        let workers: Vec<_> = contexts
+            .iter_mut()
+            .enumerate()
+            .map(|(id, context)| {
+                scope.spawn(move || {
+                    let challenge = [0x7e; 32];
+                    let mut nonce = (id as u64) << 48;
+                    loop {
+                        context.hash(&challenge, &nonce.to_le_bytes()).ok();
+                        nonce += 1;
+                        if Instant::now() >= deadline {
+                            return nonce - ((id as u64) << 48);
+                        }
+                    }
+                })
+            })
+            .collect();
+        workers.into_iter().map(|w| w.join().unwrap()).sum()
+    });
+    let elapsed = started.elapsed();
+    TuneTrial {
+        threads: contexts.len(),
+        hashes,
+        elapsed,
+        hashrate: hashes as f64 / elapsed.as_secs_f64(),
+    }
+}
+
+/// Counts distinct (package, core) pairs in sysfs. Linux only.
+fn physical_cores() -> Option<usize> {
+    let cpus = std::fs::read_dir("/sys/devices/system/cpu").ok()?;
+    let mut cores = std::collections::BTreeSet::new();
+    for entry in cpus.flatten() {
+        let name = entry.file_name();
+        let Some(name) = name.to_str() else {
+            continue;
+        };
+        if !name
+            .strip_prefix("cpu")
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+        {
+            continue;
+        }
+        let read = |file: &str| {
+            std::fs::read_to_string(entry.path().join("topology").join(file))
+                .ok()
+                .and_then(|s| s.trim().parse::<i64>().ok())
+        };
+        // Offline or hotplugged cpus may have no topology.
+        let (Some(package), Some(core)) = (read("physical_package_id"), read("core_id")) else {
+            continue;
+        };
+        cores.insert((package, core));
+    }
+    (!cores.is_empty()).then_some(cores.len())
+}
@@ -0,0 +1,170 @@
+//! Per-connection share difficulty.
+//!
+//! A [`VardiffController`] moves one miner's share difficulty so that it submits a share
+//! about every target interval, whatever its hashrate. Raising the difficulty by one
+//! doubles the expected interval, so the controller works in steps of whole
+//! difficulties, and at the right difficulty the interval is within a factor of `√2` of
+//! the target.
+//!
+//! The algorithm, so that miners can predict it:
+//!
+//! - Intervals are measured from the previous share, or from the last retarget or
+//!   [`resume`](VardiffController::resume) for the first share after one.
+//! - The controller averages the intervals since the last retarget. The `n`-th interval
+//!   is weighted `max(1/n, VARDIFF_ALPHA)`, so the average is the plain mean until it
+//!   becomes an EWMA with smoothing [`VARDIFF_ALPHA`].
+//! - After at least [`VARDIFF_MIN_SHARES`] shares, with `r = log2(average / target)`,
+//!   the controller retargets once `|r|` exceeds [`VARDIFF_DEADBAND`]: the difficulty
+//!   moves by `round(r)`, at most [`VARDIFF_MAX_STEP`], and stays within the bounds. The
+//!   deadband is a factor of about 2.8, twice the worst the right difficulty can be
+//!   off by, so noise rarely retargets and a retarget lands well inside the deadband.
+//! - A miner that goes [`VARDIFF_IDLE_INTERVALS`] target intervals without a share is
+//!   retargeted by [`on_idle`](VardiffController::on_idle): the difficulty drops by
+//!   `floor(log2(gap / target)) - 2`, at most [`VARDIFF_MAX_STEP`]. A single gap is a
+//!   noisy sample, so this lowers the difficulty by two steps less than the gap
+//!   suggests.
+//! - Every retarget restarts the average, so intervals at different difficulties are
+//!   never mixed.
+//!
+//! Times are durations since any fixed epoch, such as the Unix epoch. A controller
+//! serializes with its average, so a reconnecting miner keeps its difficulty.
+
+use std::time::Duration;
+
+/// Weight of each new interval in the average, once it holds `1 / VARDIFF_ALPHA`
+/// intervals.
+pub const VARDIFF_ALPHA: f64 = 0.1;
+
+/// Shares after a retarget before the next one.
+pub const VARDIFF_MIN_SHARES: u32 = 8;
+
+/// How far `log2(average / target)` may stray before a retarget.
+pub const VARDIFF_DEADBAND: f64 = 1.5;
+
+/// Most difficulties a retarget moves by.
+pub const VARDIFF_MAX_STEP: u32 = 4;
+
+/// Target intervals without a share before [`VardiffController::on_idle`] retargets.
+pub const VARDIFF_IDLE_INTERVALS: u32 = 16;
+
+/// Adjusts one miner's share difficulty toward a target share interval.
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct VardiffController {
+    target: Duration,
+    min_difficulty: u32,
+    max_difficulty: u32,
+    difficulty: u32,
+    /// Average interval since the last retarget, in seconds.
+    average: f64,
+    /// Intervals in the average.
+    shares: u32,
+    /// Time of the last share, retarget, or resume.
+    since: Option<Duration>,
+}
+
+impl VardiffController {
+    /// A controller starting at `min_difficulty`.
+    ///
+    /// # Panics
+    ///
+    /// If `target_share_interval` is zero or `min_difficulty` exceeds `max_difficulty`.
+    pub fn new(target_share_interval: Duration, min_difficulty: u32, max_difficulty: u32) -> Self {
+        assert!(
+            !target_share_interval.is_zero(),
+            "target share interval must be positive"
+        );
+        assert!(
+            min_difficulty <= max_difficulty,
+            "min difficulty {} exceeds max difficulty {}",
+            min_difficulty,
+            max_difficulty
+        );
+        VardiffController {
+            target: target_share_interval,
+            min_difficulty,
+            max_difficulty,
+            difficulty: min_difficulty,
+            average: 0.0,
+            shares: 0,
+            since: None,
+        }
+    }
+
+    /// Starts at `difficulty` instead, clamped to the bounds.
+    pub fn with_difficulty(mut self, difficulty: u32) -> Self {
+        self.difficulty = difficulty.clamp(self.min_difficulty, self.max_difficulty);
+        self
+    }
+
+    /// The share difficulty the miner should be at.
+    pub fn current(&self) -> u32 {
+        self.difficulty
+    }
+
+    pub fn target_share_interval(&self) -> Duration {
+        self.target
+    }
+
+    /// The average share interval since the last retarget, if there was a share.
+    pub fn average_interval(&self) -> Option<Duration> {
+        (self.shares > 0).then(|| Duration::from_secs_f64(self.average))
+    }
+
+    /// Restarts the interval clock at `now`, as when a miner connects or reconnects, so
+    /// that time without a connection doesn't count as an interval.
+    pub fn resume(&mut self, now: Duration) {
+        self.since = Some(now);
+    }
+
+    /// Records a share at `now`, returning the new difficulty if it changes.
+    ///
+    /// The first share without a [`resume`](Self::resume) only starts the clock. A
+    /// `now` before the previous share restarts the clock.
+    pub fn on_share(&mut self, now: Duration) -> Option<u32> {
+        let interval = self.since.and_then(|since| now.checked_sub(since));
+        self.since = Some(now);
+        let interval = interval?.as_secs_f64();
+        self.shares = self.shares.saturating_add(1);
+        let weight = (1.0 / self.shares as f64).max(VARDIFF_ALPHA);
+        self.average = weight * interval + (1.0 - weight) * self.average;
+        if self.shares < VARDIFF_MIN_SHARES {
+            return None;
+        }
+        let r = (self.average / self.target.as_secs_f64()).log2();
+        if r.abs() <= VARDIFF_DEADBAND {
+            return None;
+        }
+        // An average of zero, from shares in the same instant, is as fast as can be.
+        let step = r
+            .round()
+            .clamp(-(VARDIFF_MAX_STEP as f64), VARDIFF_MAX_STEP as f64);
+        self.retarget(now, self.difficulty as i64 - step as i64)
+    }
+
+    /// Checks a miner that may have gone quiet, returning the new difficulty if it
+    /// changes. Call it periodically, such as once per target interval.
+    pub fn on_idle(&mut self, now: Duration) -> Option<u32> {
+        let gap = now.checked_sub(self.since?)?.as_secs_f64();
+        let intervals = gap / self.target.as_secs_f64();
+        if intervals < VARDIFF_IDLE_INTERVALS as f64 {
+            return None;
+        }
+        let step = (intervals.log2().floor() as i64 - 2).clamp(1, VARDIFF_MAX_STEP as i64);
+        self.retarget(now, self.difficulty as i64 - step)
+    }
+
+    /// Moves to `difficulty` within the bounds and restarts the average, unless that
+    /// leaves the difficulty where it is.
+    fn retarget(&mut self, now: Duration, difficulty: i64) -> Option<u32> {
+        let difficulty =
+            difficulty.clamp(self.min_difficulty as i64, self.max_difficulty as i64) as u32;
+        if difficulty == self.difficulty {
+            return None;
+        }
+        self.difficulty = difficulty;
+        self.average = 0.0;
+        self.shares = 0;
+        self.since = Some(now);
+        Some(difficulty)
+    }
+}
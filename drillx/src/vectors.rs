@@ -0,0 +1,226 @@
+//! Embedded test vectors.
+//!
+//! These are the cross-platform consensus check: every runtime on every target must
+//! reproduce them exactly. They were generated with the equix interpreter and cover
+//! both seeds with solutions and a seed without any. [`VECTORS_V2`] holds the same
+//! seeds' [v2 hashes](crate::hash_v2), computed with an independent keccak.
+
+#[cfg(feature = "solve")]
+use crate::{DrillxError, DrillxMemory, RuntimeOption};
+
+/// The expected result of hashing a challenge and nonce.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Vector {
+    pub challenge: [u8; 32],
+    pub nonce: [u8; 8],
+    /// The expected digest and hash, or `None` if the seed has no solutions.
+    pub output: Option<VectorOutput>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VectorOutput {
+    pub digest: [u8; 16],
+    pub hash: [u8; 32],
+}
+
+impl Vector {
+    /// Returns true if hashing with the given runtime reproduces the vector.
+    #[cfg(feature = "solve")]
+    pub fn check(&self, memory: &mut DrillxMemory, runtime: RuntimeOption) -> bool {
+        match (
+            crate::hash_with_runtime(memory, runtime, &self.challenge, &self.nonce),
+            self.output,
+        ) {
+            (Ok(hash), Some(output)) => hash.d == output.digest && hash.h == output.hash,
+            (Err(DrillxError::NoSolutions), None) => true,
+            _ => false,
+        }
+    }
+}
+
+/// The embedded test vectors.
+pub const VECTORS: &[Vector] = &[
+    vector(
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        0,
+        Some((
+            "b45a828ae35b6ec80b4a898cc60bf0d5",
+            "c1ca5f77bfe25845b1a08450066c5b946fdd05f9e95b18ec810f398cac4a04da",
+        )),
+    ),
+    vector(
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        1,
+        Some((
+            "8390c9ad882ce6e6154579baa27347fc",
+            "1e651348babc16b1f024763a4b1792e348421fa3ae129f40de82be6483a2fac6",
+        )),
+    ),
+    vector(
+        "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        0,
+        Some((
+            "583c118f4911af9e2e07b5be682bbbfd",
+            "c4486c02a210988f98e217a992945538fe99fa09a4f9f6596006575ebd070f31",
+        )),
+    ),
+    vector(
+        "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        7,
+        Some((
+            "e5836da99914f2bf9090b6c46477ead7",
+            "1e0c748a10da1175113e13ceeda97cc9fffbc74a29c06d258e393da278634d00",
+        )),
+    ),
+    vector(
+        "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        u64::MAX,
+        Some((
+            "4c08605638770d84a338b55344354ab0",
+            "8100b2841bbc6987dfeb187753cdc2fb24796aa1796cdfa49cdff99fd960c084",
+        )),
+    ),
+    vector(
+        "5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a",
+        0,
+        Some((
+            "167f9d8afb668890116022acf803d1fd",
+            "cec5bb14ca5648b68eccc0b0bac428af5dbb1db3206a6422999366fd24bf1fdf",
+        )),
+    ),
+    vector(
+        "5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a",
+        1,
+        None,
+    ),
+    vector(
+        "5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a",
+        0xdead_beef,
+        Some((
+            "235bb6b5063cbbd67d016f20eb6865e9",
+            "1ad71a3981fa67447c208cb55949efc44cc57ecf3cf06d9de22068b83c8d4a52",
+        )),
+    ),
+    vector(
+        "6472696c6c78207465737420766563746f7273206368616c6c656e6765203033",
+        0,
+        Some((
+            "bb54a1958111f3c3e52e4d5124af19f0",
+            "9888f978211055bd1611b9bac7bae6e5cda029cb8973e9d56b7996fc6722c6f9",
+        )),
+    ),
+];
+
+/// The expected [v2 hash](crate::hash_v2) of a challenge and nonce.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VectorV2 {
+    pub challenge: [u8; 32],
+    pub nonce: [u8; 8],
+    pub digest: [u8; 16],
+    pub hash: [u8; 32],
+}
+
+impl VectorV2 {
+    /// Returns true if v2 hashing reproduces the vector.
+    #[cfg(feature = "solve")]
+    pub fn check(&self, memory: &mut DrillxMemory) -> bool {
+        crate::hash_v2_with_memory(memory, &self.challenge, &self.nonce)
+            .is_ok_and(|hash| hash.d == self.digest && hash.h == self.hash)
+    }
+}
+
+/// The embedded v2 test vectors: every [`VECTORS`] entry with solutions, with the same
+/// digest and the v2 hash.
+pub const VECTORS_V2: &[VectorV2] = &[
+    vector_v2(
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        0,
+        "b45a828ae35b6ec80b4a898cc60bf0d5",
+        "19a0aa204fd0a64842f57e79a0a6495e00e41af242683e6c896db9eef0002489",
+    ),
+    vector_v2(
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        1,
+        "8390c9ad882ce6e6154579baa27347fc",
+        "bfb8af736e0b9a2a30311aae651e468bd448b6baa2402309953870995b174897",
+    ),
+    vector_v2(
+        "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        0,
+        "583c118f4911af9e2e07b5be682bbbfd",
+        "b05c0d8bffa8909b9df94a8879cb4a9fecff4dd6192387398f34085ac9a2afa0",
+    ),
+    vector_v2(
+        "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        7,
+        "e5836da99914f2bf9090b6c46477ead7",
+        "9a85439f9236a9c0716f641b22aee7fa0ec19a25f2c1c0fff316ed6c2ae45bf8",
+    ),
+    vector_v2(
+        "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        u64::MAX,
+        "4c08605638770d84a338b55344354ab0",
+        "8fe4cc5bfb7b78c532fc5914c342a64471fcb5fc1dadb86e556f36a256123142",
+    ),
+    vector_v2(
+        "5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a",
+        0,
+        "167f9d8afb668890116022acf803d1fd",
+        "adb077ab3307a9ec255337f8dd387ccfe2fef7775532bc079940ee5d62a15871",
+    ),
+    vector_v2(
+        "5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a",
+        0xdead_beef,
+        "235bb6b5063cbbd67d016f20eb6865e9",
+        "10e55245f3ddc61c372409fcebb785ead5a7080b3bfa663029e17f768482818a",
+    ),
+    vector_v2(
+        "6472696c6c78207465737420766563746f7273206368616c6c656e6765203033",
+        0,
+        "bb54a1958111f3c3e52e4d5124af19f0",
+        "0036788c3c95f47905e1fd3d4c06ea2e22292ab2b1cfd85c5dc03c699f72cc53",
+    ),
+];
+
+const fn vector(challenge: &str, nonce: u64, output: Option<(&str, &str)>) -> Vector {
+    Vector {
+        challenge: hex(challenge),
+        nonce: nonce.to_le_bytes(),
+        output: match output {
+            Some((digest, hash)) => Some(VectorOutput {
+                digest: hex(digest),
+                hash: hex(hash),
+            }),
+            None => None,
+        },
+    }
+}
+
+const fn vector_v2(challenge: &str, nonce: u64, digest: &str, hash: &str) -> VectorV2 {
+    VectorV2 {
+        challenge: hex(challenge),
+        nonce: nonce.to_le_bytes(),
+        digest: hex(digest),
+        hash: hex(hash),
+    }
+}
+
+/// Decodes a hex string at compile time.
+pub(crate) const fn hex<const N: usize>(s: &str) -> [u8; N] {
+    const fn nibble(c: u8) -> u8 {
+        match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            _ => panic!("invalid hex digit"),
+        }
+    }
+    let s = s.as_bytes();
+    assert!(s.len() == 2 * N, "wrong hex length");
+    let mut bytes = [0; N];
+    let mut i = 0;
+    while i < N {
+        bytes[i] = nibble(s[2 * i]) << 4 | nibble(s[2 * i + 1]);
+        i += 1;
+    }
+    bytes
+}
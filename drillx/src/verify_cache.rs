@@ -0,0 +1,228 @@
+//! A bounded cache of verification results.
+//!
+//! Miners on flaky connections resubmit the same share, and each resubmission would
+//! otherwise pay for a full equix verification. A [`VerifyCache`] remembers the verdict
+//! for each (challenge, nonce, digest) it has seen, evicting the least recently used
+//! once full. The whole key is compared, so a verdict is never reused for another
+//! challenge or digest.
+//!
+//! The cache is split into up to 16 independently locked shards, chosen by a randomly
+//! keyed hash, and verification runs outside the locks. Two threads missing the same
+//! share at once may both verify it.
+
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    hash::BuildHasher,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+use crate::Solution;
+
+/// Most shards a cache is split into.
+const SHARDS: usize = 16;
+
+/// Marks the end of a shard's recency list.
+const NIL: u32 = u32::MAX;
+
+/// Challenge, nonce, and digest.
+type Key = ([u8; 32], [u8; 8], [u8; 16]);
+
+/// A verification result, and whether it came from the cache.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CachedVerdict {
+    pub valid: bool,
+    /// The difficulty of a valid solution.
+    pub difficulty: Option<u32>,
+    /// The verdict was cached rather than computed by this call.
+    pub hit: bool,
+}
+
+/// A thread-safe LRU cache of solution verdicts.
+///
+/// It holds at most `capacity` entries and, beyond a fixed overhead per shard, takes at
+/// most `capacity * VerifyCache::ENTRY_BYTES` bytes.
+pub struct VerifyCache {
+    shards: Vec<Mutex<Shard>>,
+    keys: RandomState,
+    capacity: usize,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+/// One shard: a map into entries linked in order of use, most recent first.
+struct Shard {
+    map: HashMap<Key, u32>,
+    entries: Vec<Entry>,
+    capacity: usize,
+    head: u32,
+    tail: u32,
+}
+
+struct Entry {
+    key: Key,
+    difficulty: Option<u32>,
+    prev: u32,
+    next: u32,
+}
+
+impl VerifyCache {
+    /// Bytes an entry takes at most: its list entry, and three map slots with their
+    /// control bytes, since the map reserves up to that many per entry.
+    pub const ENTRY_BYTES: usize =
+        std::mem::size_of::<Entry>() + 3 * (std::mem::size_of::<(Key, u32)>() + 1);
+
+    /// Creates a cache of up to `capacity` verdicts. A zero capacity is treated as one.
+    ///
+    /// The memory for every entry is reserved up front.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.clamp(1, NIL as usize);
+        let shards = capacity.min(SHARDS);
+        VerifyCache {
+            shards: (0..shards)
+                .map(|i| {
+                    // Spread the capacity so that the shards add up to it exactly.
+                    let capacity = capacity / shards + usize::from(i < capacity % shards);
+                    Mutex::new(Shard {
+                        map: HashMap::with_capacity(capacity),
+                        entries: Vec::with_capacity(capacity),
+                        capacity,
+                        head: NIL,
+                        tail: NIL,
+                    })
+                })
+                .collect(),
+            keys: RandomState::new(),
+            capacity,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the verdict for the solution under the challenge, verifying it only if
+    /// it is not cached.
+    pub fn get_or_verify(&self, challenge: &[u8; 32], solution: &Solution) -> CachedVerdict {
+        let key = (*challenge, solution.n, solution.d);
+        let shard = &self.shards[self.keys.hash_one(key) as usize % self.shards.len()];
+        if let Some(difficulty) = shard.lock().unwrap().get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return CachedVerdict {
+                valid: difficulty.is_some(),
+                difficulty,
+                hit: true,
+            };
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let difficulty = solution
+            .is_valid(challenge)
+            .then(|| solution.to_hash().difficulty());
+        shard.lock().unwrap().insert(key, difficulty);
+        CachedVerdict {
+            valid: difficulty.is_some(),
+            difficulty,
+            hit: false,
+        }
+    }
+
+    /// Lookups answered from the cache.
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    /// Lookups that had to verify.
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    /// Number of cached verdicts.
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().map.len())
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Forgets every verdict, keeping the counters and the reserved memory.
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            let mut shard = shard.lock().unwrap();
+            shard.map.clear();
+            shard.entries.clear();
+            shard.head = NIL;
+            shard.tail = NIL;
+        }
+    }
+}
+
+impl Shard {
+    /// Returns the cached difficulty, or `None` if the key is not cached, marking the
+    /// entry as most recently used.
+    fn get(&mut self, key: &Key) -> Option<Option<u32>> {
+        let index = *self.map.get(key)?;
+        self.unlink(index);
+        self.push_front(index);
+        Some(self.entries[index as usize].difficulty)
+    }
+
+    fn insert(&mut self, key: Key, difficulty: Option<u32>) {
+        if let Some(&index) = self.map.get(&key) {
+            // Verified meanwhile by another thread.
+            self.unlink(index);
+            self.push_front(index);
+            return;
+        }
+        let index = if self.entries.len() < self.capacity {
+            self.entries.push(Entry {
+                key,
+                difficulty,
+                prev: NIL,
+                next: NIL,
+            });
+            (self.entries.len() - 1) as u32
+        } else {
+            let index = self.tail;
+            self.unlink(index);
+            let entry = &mut self.entries[index as usize];
+            self.map.remove(&entry.key);
+            entry.key = key;
+            entry.difficulty = difficulty;
+            index
+        };
+        self.map.insert(key, index);
+        self.push_front(index);
+    }
+
+    fn unlink(&mut self, index: u32) {
+        let Entry { prev, next, .. } = self.entries[index as usize];
+        match prev {
+            NIL => self.head = next,
+            prev => self.entries[prev as usize].next = next,
+        }
+        match next {
+            NIL => self.tail = prev,
+            next => self.entries[next as usize].prev = prev,
+        }
+    }
+
+    fn push_front(&mut self, index: u32) {
+        let head = self.head;
+        let entry = &mut self.entries[index as usize];
+        entry.prev = NIL;
+        entry.next = head;
+        match head {
+            NIL => self.tail = index,
+            head => self.entries[head as usize].prev = index,
+        }
+        self.head = index;
+    }
+}
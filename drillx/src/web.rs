@@ -0,0 +1,379 @@
+//! Mining in the browser, on Web Workers sharing the module's memory.
+//!
+//! The [`miner`](crate::miner) compiles for `wasm32-unknown-unknown` but cannot run
+//! there: browsers have no `std` threads or clock, and the main thread must never
+//! block. [`WebMiner`] is the browser's miner instead. Its workers are Web Workers
+//! that load the same module and memory, so each allocates its solver memory from the
+//! shared linear memory and claims chunks of nonces from one atomic cursor. The main
+//! thread only polls: on a timer it reports progress to a JavaScript callback and
+//! resolves the promise from [`WebMiner::run`] once the workers have stopped. It never
+//! takes a lock that a worker could hold, so it never waits with `Atomics.wait`.
+//!
+//! Workers need a module built with threads and a shared, imported memory that exports
+//! its thread-local storage for `wasm-bindgen`:
+//!
+//! ```sh
+//! RUSTFLAGS="-C target-feature=+atomics,+bulk-memory,+mutable-globals \
+//!     -C link-arg=--shared-memory -C link-arg=--import-memory -C link-arg=--max-memory=1073741824 \
+//!     -C link-arg=--export=__wasm_init_tls -C link-arg=--export=__tls_size \
+//!     -C link-arg=--export=__tls_align -C link-arg=--export=__tls_base -C link-arg=--export=__heap_base" \
+//!     cargo +nightly build --release --target wasm32-unknown-unknown -Z build-std=std,panic_abort
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/<crate>.wasm
+//! ```
+//!
+//! and a page that is cross-origin isolated, served with
+//! `Cross-Origin-Opener-Policy: same-origin` and
+//! `Cross-Origin-Embedder-Policy: require-corp`, so that `SharedArrayBuffer` exists.
+//! Each worker runs a small script, given as the worker URL, that initializes the
+//! module with the memory it is sent and calls [`drillx_worker_entry`]:
+//!
+//! ```js
+//! import init, { drillx_worker_entry } from "./pkg/web_miner.js";
+//! self.onmessage = async ({ data: [module, memory, task] }) => {
+//!   const wasm = await init(module, memory);
+//!   drillx_worker_entry(task);
+//!   wasm.__wbindgen_thread_destroy?.();
+//!   close();
+//! };
+//! ```
+//!
+//! Without threads, on a page that is not cross-origin isolated, or with zero
+//! workers, the miner falls back to hashing on the main thread a few nonces per timer
+//! tick, which keeps the page responsive at a fraction of the speed.
+//!
+//! Cancellation is cooperative: [`WebMiner::cancel`] sets a flag that workers check
+//! after every hash, and the workers are terminated once they have all returned.
+
+use std::sync::{
+    atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering},
+    Arc, Mutex,
+};
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+
+use crate::{Context, RuntimeOption, ScoredSolution, Solution};
+
+/// Nonces a worker claims at a time.
+const CHUNK_SIZE: u64 = 16;
+
+/// Hashes per timer tick when mining on the main thread.
+const FALLBACK_SLICE: u64 = 1;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_name = setTimeout)]
+    fn set_timeout(handler: &js_sys::Function, timeout: i32) -> JsValue;
+}
+
+/// A miner running in the browser.
+///
+/// From JavaScript:
+///
+/// ```js
+/// const miner = new WebMiner(challenge, 8, navigator.hardwareConcurrency, "worker.js");
+/// const best = await miner.run((p) => console.log(p.hashes, p.bestDifficulty));
+/// // best: { digest: Uint8Array(16), nonce: Uint8Array(8), difficulty, hashes } or null
+/// ```
+#[wasm_bindgen]
+pub struct WebMiner {
+    shared: Arc<Shared>,
+    workers: usize,
+    worker_url: String,
+    interval: i32,
+}
+
+/// State shared by the workers and the main thread.
+struct Shared {
+    challenge: [u8; 32],
+    min_difficulty: u32,
+    next: AtomicU64,
+    stopping: AtomicBool,
+    hashes: AtomicU64,
+    /// Difficulty of `best` plus one, or zero if there is none yet.
+    best_difficulty: AtomicU32,
+    /// Taken by workers to update, and only tried by the main thread.
+    best: Mutex<Option<ScoredSolution>>,
+    /// Workers that have not returned.
+    running: AtomicUsize,
+}
+
+/// What a worker is handed through its message, as a pointer into shared memory.
+struct Task {
+    shared: Arc<Shared>,
+}
+
+#[wasm_bindgen]
+impl WebMiner {
+    /// Mines `challenge` until a solution reaches `min_difficulty` or the miner is
+    /// cancelled, on `workers` Web Workers running the script at `worker_url`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        challenge: &[u8],
+        min_difficulty: u32,
+        workers: usize,
+        worker_url: String,
+    ) -> Result<WebMiner, JsError> {
+        let challenge: [u8; 32] = challenge
+            .try_into()
+            .map_err(|_| JsError::new("challenge must be 32 bytes"))?;
+        Ok(WebMiner {
+            shared: Arc::new(Shared {
+                challenge,
+                min_difficulty,
+                next: AtomicU64::new(0),
+                stopping: AtomicBool::new(false),
+                hashes: AtomicU64::new(0),
+                best_difficulty: AtomicU32::new(0),
+                best: Mutex::new(None),
+                running: AtomicUsize::new(0),
+            }),
+            workers,
+            worker_url,
+            interval: 100,
+        })
+    }
+
+    /// Sets how often progress is reported, in milliseconds. Defaults to 100.
+    #[wasm_bindgen(js_name = setInterval)]
+    pub fn set_interval(&mut self, interval: i32) {
+        self.interval = interval.max(0);
+    }
+
+    /// True if workers can run: the module was built with threads and the page is
+    /// cross-origin isolated.
+    #[wasm_bindgen(js_name = supportsThreads)]
+    pub fn supports_threads() -> bool {
+        cfg!(target_feature = "atomics")
+            && js_sys::Reflect::get(&js_sys::global(), &JsValue::from_str("crossOriginIsolated"))
+                .is_ok_and(|isolated| isolated.is_truthy())
+    }
+
+    /// Starts mining, calling `on_progress` with `{ hashes, bestDifficulty, workers }`
+    /// every interval. Resolves to the best solution found, or `null` if none was.
+    pub fn run(&self, on_progress: js_sys::Function) -> js_sys::Promise {
+        let shared = self.shared.clone();
+        let interval = self.interval;
+        let workers = if Self::supports_threads() {
+            spawn_workers(&shared, self.workers, &self.worker_url)
+        } else {
+            Ok(Vec::new())
+        };
+        wasm_bindgen_futures::future_to_promise(async move {
+            let workers = workers?;
+            let threaded = !workers.is_empty();
+            let mut context = (!threaded).then(|| Context::new(RuntimeOption::TryCompile));
+            loop {
+                if let Some(context) = context.as_mut() {
+                    for _ in 0..FALLBACK_SLICE {
+                        if shared.is_stopping() {
+                            break;
+                        }
+                        let nonce = shared.next.fetch_add(1, Ordering::Relaxed);
+                        shared.hash(context, nonce);
+                    }
+                }
+                let done = shared.is_stopping() && shared.running.load(Ordering::Acquire) == 0;
+                let progress = shared.progress(if threaded { workers.len() } else { 0 });
+                let _ = on_progress.call1(&JsValue::NULL, &progress);
+                if done {
+                    break;
+                }
+                sleep(if threaded { interval } else { 0 }).await?;
+            }
+            for worker in &workers {
+                worker.terminate();
+            }
+            Ok(shared.outcome())
+        })
+    }
+
+    /// Asks the workers to stop after their current hash.
+    pub fn cancel(&self) {
+        self.shared.stopping.store(true, Ordering::Release);
+    }
+
+    /// Nonces hashed so far.
+    pub fn hashes(&self) -> f64 {
+        self.shared.hashes.load(Ordering::Relaxed) as f64
+    }
+}
+
+impl Shared {
+    fn is_stopping(&self) -> bool {
+        self.stopping.load(Ordering::Acquire)
+    }
+
+    /// Hashes a nonce and records its solution, stopping the run once one qualifies.
+    fn hash(&self, context: &mut Context, nonce: u64) {
+        let result = context.hash(&self.challenge, &nonce.to_le_bytes());
+        self.hashes.fetch_add(1, Ordering::Relaxed);
+        let Ok(hash) = result else {
+            return;
+        };
+        let difficulty = hash.difficulty();
+        if difficulty + 1 > self.best_difficulty.load(Ordering::Relaxed) {
+            let mut best = self.best.lock().unwrap();
+            if best.is_none_or(|b| difficulty > b.difficulty) {
+                *best = Some(ScoredSolution {
+                    solution: Solution::new(hash.d, nonce.to_le_bytes()),
+                    hash: hash.h,
+                    difficulty,
+                });
+                self.best_difficulty
+                    .store(difficulty + 1, Ordering::Relaxed);
+            }
+        }
+        if difficulty >= self.min_difficulty {
+            self.stopping.store(true, Ordering::Release);
+        }
+    }
+
+    fn progress(&self, workers: usize) -> JsValue {
+        let progress = js_sys::Object::new();
+        let best = self.best_difficulty.load(Ordering::Relaxed);
+        let fields = [
+            (
+                "hashes",
+                JsValue::from(self.hashes.load(Ordering::Relaxed) as f64),
+            ),
+            (
+                "bestDifficulty",
+                best.checked_sub(1).map_or(JsValue::NULL, JsValue::from),
+            ),
+            ("workers", JsValue::from(workers as u32)),
+        ];
+        for (key, value) in fields {
+            let _ = js_sys::Reflect::set(&progress, &JsValue::from_str(key), &value);
+        }
+        progress.into()
+    }
+
+    /// The best solution as a JavaScript object, once every worker has returned.
+    fn outcome(&self) -> JsValue {
+        // No worker is left to hold the lock.
+        let Some(best) = *self.best.try_lock().unwrap() else {
+            return JsValue::NULL;
+        };
+        let outcome = js_sys::Object::new();
+        let fields = [
+            (
+                "digest",
+                js_sys::Uint8Array::from(&best.solution.d[..]).into(),
+            ),
+            (
+                "nonce",
+                js_sys::Uint8Array::from(&best.solution.n[..]).into(),
+            ),
+            ("difficulty", JsValue::from(best.difficulty)),
+            (
+                "hashes",
+                JsValue::from(self.hashes.load(Ordering::Relaxed) as f64),
+            ),
+        ];
+        for (key, value) in fields {
+            let _ = js_sys::Reflect::set(&outcome, &JsValue::from_str(key), &value);
+        }
+        outcome.into()
+    }
+
+    fn work(&self, context: &mut Context) {
+        while !self.is_stopping() {
+            let start = self.next.fetch_add(CHUNK_SIZE, Ordering::Relaxed);
+            for nonce in start..start + CHUNK_SIZE {
+                if self.is_stopping() {
+                    break;
+                }
+                self.hash(context, nonce);
+            }
+        }
+    }
+}
+
+/// Starts the workers, each with a [`Task`] it takes ownership of.
+///
+/// If any worker fails to start, the run is stopped and every worker already started
+/// is terminated, so none mines on after the error.
+fn spawn_workers(
+    shared: &Arc<Shared>,
+    count: usize,
+    url: &str,
+) -> Result<Vec<web_sys::Worker>, JsValue> {
+    let mut options = web_sys::WorkerOptions::new();
+    options.type_(web_sys::WorkerType::Module);
+    let mut workers = Vec::with_capacity(count);
+    for _ in 0..count {
+        match spawn_worker(shared, url, &options) {
+            Ok(worker) => workers.push(worker),
+            Err(err) => {
+                shared.stopping.store(true, Ordering::Release);
+                for worker in workers {
+                    worker.terminate();
+                }
+                return Err(err);
+            }
+        }
+    }
+    Ok(workers)
+}
+
+/// Starts one worker and posts it its task.
+fn spawn_worker(
+    shared: &Arc<Shared>,
+    url: &str,
+    options: &web_sys::WorkerOptions,
+) -> Result<web_sys::Worker, JsValue> {
+    let worker = web_sys::Worker::new_with_options(url, options)?;
+    shared.running.fetch_add(1, Ordering::AcqRel);
+    let task = Box::into_raw(Box::new(Task {
+        shared: shared.clone(),
+    }));
+    let message = js_sys::Array::of3(
+        &worker_module(),
+        &wasm_bindgen::memory(),
+        &JsValue::from(task as usize as u32),
+    );
+    if let Err(err) = worker.post_message(&message) {
+        // The worker never got the task, so it is still ours to free.
+        drop(unsafe { Box::from_raw(task) });
+        shared.running.fetch_sub(1, Ordering::AcqRel);
+        worker.terminate();
+        return Err(err);
+    }
+    Ok(worker)
+}
+
+/// The module workers initialize. `wasm_bindgen::module` only binds with `--target web`,
+/// so builds without threads, which never start workers, leave it out.
+fn worker_module() -> JsValue {
+    #[cfg(target_feature = "atomics")]
+    {
+        wasm_bindgen::module()
+    }
+    #[cfg(not(target_feature = "atomics"))]
+    {
+        JsValue::UNDEFINED
+    }
+}
+
+/// Runs a worker's share of the mining. Called by the worker script with the task it
+/// was sent, once per task.
+#[wasm_bindgen]
+pub fn drillx_worker_entry(task: u32) {
+    // Safety: the pointer came from `Box::into_raw` in `spawn_worker`, and each task
+    // is posted to exactly one worker.
+    let task = unsafe { Box::from_raw(task as usize as *mut Task) };
+    let shared = task.shared;
+    let mut context = Context::new(RuntimeOption::TryCompile);
+    shared.work(&mut context);
+    drop(context);
+    shared.running.fetch_sub(1, Ordering::AcqRel);
+}
+
+/// Resolves after `ms` milliseconds on the event loop.
+async fn sleep(ms: i32) -> Result<(), JsValue> {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        set_timeout(&resolve, ms);
+    });
+    JsFuture::from(promise).await.map(|_| ())
+}
@@ -0,0 +1,54 @@
+//! Integer reward weighting.
+//!
+//! ORE-style rewards double with each difficulty level above a minimum. These helpers
+//! compute that weight with integer arithmetic only, so on-chain programs and off-chain
+//! accounting agree exactly. All of them saturate instead of overflowing.
+
+/// Returns the weight of a share, `2^(difficulty - min_difficulty)`.
+///
+/// The weight is zero below the minimum difficulty and saturates at `u128::MAX` once
+/// the difficulty exceeds the minimum by 128 or more.
+pub const fn share_weight(difficulty: u32, min_difficulty: u32) -> u128 {
+    if difficulty < min_difficulty {
+        return 0;
+    }
+    let exp = difficulty - min_difficulty;
+    if exp >= u128::BITS {
+        return u128::MAX;
+    }
+    1 << exp
+}
+
+/// Scales a base reward by the share weight, `base_reward * 2^(difficulty - min_difficulty)`.
+///
+/// The reward is zero below the minimum difficulty or when the base reward is zero, and
+/// saturates at `u64::MAX` when the product does not fit.
+pub const fn apply_weight(base_reward: u64, difficulty: u32, min_difficulty: u32) -> u64 {
+    if difficulty < min_difficulty || base_reward == 0 {
+        return 0;
+    }
+    let exp = difficulty - min_difficulty;
+    if exp >= u64::BITS {
+        return u64::MAX;
+    }
+    // Cannot overflow: both factors are below 2^64.
+    let reward = (base_reward as u128) << exp;
+    if reward > u64::MAX as u128 {
+        u64::MAX
+    } else {
+        reward as u64
+    }
+}
+
+/// Sums the share weights of a set of difficulties, saturating at `u128::MAX`.
+///
+/// An empty slice sums to zero.
+pub const fn sum_weights(difficulties: &[u32], min_difficulty: u32) -> u128 {
+    let mut total: u128 = 0;
+    let mut i = 0;
+    while i < difficulties.len() {
+        total = total.saturating_add(share_weight(difficulties[i], min_difficulty));
+        i += 1;
+    }
+    total
+}
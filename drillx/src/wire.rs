@@ -0,0 +1,153 @@
+//! Versioned solution encoding.
+//!
+//! A versioned encoding is a single version byte followed by the body for that
+//! version. Version 1 is the 24-byte [`Solution`] body (`digest ‖ nonce`), and version 2
+//! is the 25-byte [`SolutionV2`] body (`digest ‖ nonce ‖ idx`). The raw
+//! [`Solution::to_bytes`] format has no version byte and stays the on-chain format.
+
+use crate::{Solution, SolutionV2};
+
+/// Version byte of the 24-byte [`Solution`] body.
+pub const VERSION_1: u8 = 1;
+
+/// Version byte of the 25-byte [`SolutionV2`] body.
+pub const VERSION_2: u8 = 2;
+
+/// Upper bound on the length of any versioned encoding, present or future.
+///
+/// Decoders reject longer input before looking at it.
+pub const MAX_VERSIONED_LEN: usize = 128;
+
+/// A decoded solution of any known version.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VersionedSolution {
+    V1(Solution),
+    V2(SolutionV2),
+}
+
+impl VersionedSolution {
+    /// Returns the version byte of the encoding.
+    pub fn version(&self) -> u8 {
+        match self {
+            VersionedSolution::V1(_) => VERSION_1,
+            VersionedSolution::V2(_) => VERSION_2,
+        }
+    }
+
+    /// Encodes the solution with its leading version byte.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![self.version()];
+        match self {
+            VersionedSolution::V1(solution) => bytes.extend_from_slice(&solution.to_bytes()),
+            VersionedSolution::V2(solution) => bytes.extend_from_slice(&solution.to_bytes()),
+        }
+        bytes
+    }
+
+    /// Decodes a solution, rejecting unknown versions and bodies of the wrong length.
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        if bytes.len() > MAX_VERSIONED_LEN {
+            return Err(DecodeError::TooLong { len: bytes.len() });
+        }
+        let (&version, body) = bytes.split_first().ok_or(DecodeError::Empty)?;
+        match version {
+            VERSION_1 => {
+                let body = exact::<24>(version, body)?;
+                Ok(VersionedSolution::V1(Solution::from_bytes(body)))
+            }
+            VERSION_2 => {
+                let body = exact::<25>(version, body)?;
+                Ok(VersionedSolution::V2(SolutionV2::from_bytes(body)))
+            }
+            version => Err(DecodeError::UnknownVersion(version)),
+        }
+    }
+}
+
+impl From<Solution> for VersionedSolution {
+    fn from(solution: Solution) -> Self {
+        VersionedSolution::V1(solution)
+    }
+}
+
+impl From<SolutionV2> for VersionedSolution {
+    fn from(solution: SolutionV2) -> Self {
+        VersionedSolution::V2(solution)
+    }
+}
+
+/// Checks that a body has exactly `N` bytes.
+fn exact<const N: usize>(version: u8, body: &[u8]) -> Result<[u8; N], DecodeError> {
+    if body.len() < N {
+        return Err(DecodeError::Truncated {
+            version,
+            expected: N,
+            actual: body.len(),
+        });
+    }
+    body.try_into().map_err(|_| DecodeError::TrailingBytes {
+        version,
+        expected: N,
+        actual: body.len(),
+    })
+}
+
+/// An error decoding a versioned solution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    /// The input has no version byte.
+    Empty,
+    /// The input is longer than [`MAX_VERSIONED_LEN`].
+    TooLong { len: usize },
+    /// The version byte is not a known version.
+    UnknownVersion(u8),
+    /// The body is shorter than its version requires.
+    Truncated {
+        version: u8,
+        expected: usize,
+        actual: usize,
+    },
+    /// The body is longer than its version requires.
+    TrailingBytes {
+        version: u8,
+        expected: usize,
+        actual: usize,
+    },
+}
+
+impl std::fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            DecodeError::Empty => write!(f, "Missing version byte"),
+            DecodeError::TooLong { len } => {
+                write!(f, "Encoding of {} bytes exceeds {}", len, MAX_VERSIONED_LEN)
+            }
+            DecodeError::UnknownVersion(version) => write!(f, "Unknown version {}", version),
+            DecodeError::Truncated {
+                version,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Truncated v{} body: expected {} bytes, got {}",
+                version, expected, actual
+            ),
+            DecodeError::TrailingBytes {
+                version,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Trailing bytes after v{} body: expected {} bytes, got {}",
+                version, expected, actual
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
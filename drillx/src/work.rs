@@ -0,0 +1,397 @@
+//! Work units handed out by pools, and signatures over them.
+//!
+//! A pool gives each miner a [`WorkUnit`]: a challenge, the authority the solutions pay,
+//! a range of nonces, a minimum difficulty, and an expiry. With the `signing` feature
+//! the pool signs it with ed25519 using [`sign_work`], and miners check it with
+//! [`verify_work`] before hashing, so a compromised relay cannot point their
+//! hashpower at another challenge or authority. The signature covers
+//! [`WorkUnit::signing_message`]:
+//!
+//! ```text
+//! message = "drillx-work-v1\0" (15 bytes) ‖ id (u64 LE) ‖ challenge (32 bytes)
+//!           ‖ authority (32 bytes) ‖ start (u64 LE) ‖ count (u64 LE)
+//!           ‖ min difficulty (u32 LE) ‖ expires (u64 LE, Unix seconds)
+//! ```
+//!
+//! The tag keeps these signatures from being replayed as anything else the pool key
+//! signs, such as transactions.
+//!
+//! [`audit_coverage`] checks the units a coordinator handed out for nonces assigned
+//! twice and nonces left unassigned.
+
+#[cfg(feature = "signing")]
+pub use ed25519_dalek::Keypair;
+
+/// Tag a [`WorkUnit::signing_message`] starts with.
+pub const WORK_TAG: &[u8; 15] = b"drillx-work-v1\0";
+
+/// Length of a [`WorkUnit::signing_message`].
+pub const SIGNING_MESSAGE_LEN: usize = WORK_TAG.len() + 8 + 32 + 32 + 8 + 8 + 4 + 8;
+
+/// A range of nonces to search on one challenge, for one authority.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct WorkUnit {
+    /// The pool's id for the unit.
+    pub id: u64,
+    pub challenge: [u8; 32],
+    /// The account solutions are credited to.
+    pub authority: [u8; 32],
+    /// First nonce to search.
+    pub start: u64,
+    /// Number of nonces to search from `start`.
+    pub count: u64,
+    pub min_difficulty: u32,
+    /// Unix time in seconds after which the unit is no longer valid.
+    pub expires_unix: u64,
+}
+
+impl WorkUnit {
+    /// The last nonce of the unit, or `None` if it has none. A range that would run
+    /// past `u64::MAX` ends there.
+    pub fn last_nonce(&self) -> Option<u64> {
+        let span = self.count.checked_sub(1)?;
+        Some(self.start.saturating_add(span))
+    }
+
+    /// The bytes a pool signs, as laid out in the [module docs](self).
+    pub fn signing_message(&self) -> [u8; SIGNING_MESSAGE_LEN] {
+        let mut message = [0; SIGNING_MESSAGE_LEN];
+        let fields: [&[u8]; 8] = [
+            WORK_TAG,
+            &self.id.to_le_bytes(),
+            &self.challenge,
+            &self.authority,
+            &self.start.to_le_bytes(),
+            &self.count.to_le_bytes(),
+            &self.min_difficulty.to_le_bytes(),
+            &self.expires_unix.to_le_bytes(),
+        ];
+        let mut offset = 0;
+        for field in fields {
+            message[offset..offset + field.len()].copy_from_slice(field);
+            offset += field.len();
+        }
+        message
+    }
+}
+
+/// The result of [`audit_coverage`].
+#[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct CoverageReport {
+    /// One entry per challenge, in the order the challenges first appear.
+    pub challenges: Vec<ChallengeCoverage>,
+    /// Ids given to more than one unit, in ascending order.
+    pub duplicate_ids: Vec<u64>,
+}
+
+impl CoverageReport {
+    /// True if no challenge has overlaps or gaps and no id is reused.
+    pub fn is_clean(&self) -> bool {
+        self.duplicate_ids.is_empty()
+            && self
+                .challenges
+                .iter()
+                .all(|c| c.overlaps.is_empty() && c.gaps.is_empty())
+    }
+}
+
+/// How the units of one challenge cover its nonces.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct ChallengeCoverage {
+    pub challenge: [u8; 32],
+    /// Number of units on the challenge, empty ones included.
+    pub units: usize,
+    /// The lowest and highest nonce assigned, or `None` if every unit is empty.
+    pub span: Option<NonceRange>,
+    /// Number of distinct nonces assigned.
+    pub covered: u128,
+    /// Number of nonces assigned counting repeats, so `assigned - covered` were
+    /// assigned more than once.
+    pub assigned: u128,
+    /// Every pair of units sharing nonces, ordered by where the overlap starts.
+    pub overlaps: Vec<Overlap>,
+    /// The unassigned ranges within the span, in ascending order.
+    pub gaps: Vec<NonceRange>,
+    /// Units whose range runs past `u64::MAX`, which end there as in
+    /// [`WorkUnit::last_nonce`].
+    pub truncated: Vec<u64>,
+    /// Units with no nonces.
+    pub empty: Vec<u64>,
+}
+
+/// Nonces shared by two units.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct Overlap {
+    /// The unit starting first, or listed first if both start together.
+    pub first_id: u64,
+    pub second_id: u64,
+    pub range: NonceRange,
+}
+
+/// An inclusive range of nonces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct NonceRange {
+    pub start: u64,
+    pub last: u64,
+}
+
+impl NonceRange {
+    /// Number of nonces in the range, up to `2^64`.
+    pub fn size(&self) -> u128 {
+        (self.last - self.start) as u128 + 1
+    }
+}
+
+/// Checks how a set of work units covers each challenge's nonces.
+///
+/// Per challenge, the units' ranges are merged to find the nonces assigned more than
+/// once and the gaps between the lowest and highest nonce assigned. Only range
+/// endpoints are compared, so units of any size cost the same. Overlaps are reported
+/// per pair of units, so a nonce shared by `k` units appears in `k(k - 1)/2` of them.
+pub fn audit_coverage(units: &[WorkUnit]) -> CoverageReport {
+    let mut report = CoverageReport::default();
+    let mut index = std::collections::HashMap::new();
+    let mut ranges: Vec<Vec<(NonceRange, u64)>> = Vec::new();
+    for unit in units {
+        let i = *index.entry(unit.challenge).or_insert_with(|| {
+            report.challenges.push(ChallengeCoverage {
+                challenge: unit.challenge,
+                units: 0,
+                span: None,
+                covered: 0,
+                assigned: 0,
+                overlaps: Vec::new(),
+                gaps: Vec::new(),
+                truncated: Vec::new(),
+                empty: Vec::new(),
+            });
+            ranges.push(Vec::new());
+            ranges.len() - 1
+        });
+        let coverage = &mut report.challenges[i];
+        coverage.units += 1;
+        let Some(last) = unit.last_nonce() else {
+            coverage.empty.push(unit.id);
+            continue;
+        };
+        if unit.start.checked_add(unit.count - 1).is_none() {
+            coverage.truncated.push(unit.id);
+        }
+        ranges[i].push((
+            NonceRange {
+                start: unit.start,
+                last,
+            },
+            unit.id,
+        ));
+    }
+    for (coverage, mut ranges) in report.challenges.iter_mut().zip(ranges) {
+        // Stable, so units starting together keep their order.
+        ranges.sort_by_key(|(range, _)| range.start);
+        let mut merged: Option<NonceRange> = None;
+        // Units that may still overlap a later one.
+        let mut active: Vec<(NonceRange, u64)> = Vec::new();
+        for &(range, id) in &ranges {
+            coverage.assigned += range.size();
+            active.retain(|(other, _)| other.last >= range.start);
+            for &(other, other_id) in &active {
+                coverage.overlaps.push(Overlap {
+                    first_id: other_id,
+                    second_id: id,
+                    range: NonceRange {
+                        start: range.start,
+                        last: range.last.min(other.last),
+                    },
+                });
+            }
+            active.push((range, id));
+            merged = Some(match merged {
+                Some(current) if range.start <= current.last.saturating_add(1) => NonceRange {
+                    start: current.start,
+                    last: current.last.max(range.last),
+                },
+                Some(current) => {
+                    coverage.covered += current.size();
+                    coverage.gaps.push(NonceRange {
+                        start: current.last + 1,
+                        last: range.start - 1,
+                    });
+                    range
+                }
+                None => range,
+            });
+        }
+        if let Some(current) = merged {
+            coverage.covered += current.size();
+        }
+        coverage.span = ranges.first().map(|(first, _)| NonceRange {
+            start: first.start,
+            last: ranges.iter().map(|(range, _)| range.last).max().unwrap(),
+        });
+        coverage.overlaps.sort_by_key(|overlap| overlap.range.start);
+    }
+    let mut ids: Vec<u64> = units.iter().map(|unit| unit.id).collect();
+    ids.sort_unstable();
+    report.duplicate_ids = ids
+        .windows(2)
+        .filter(|w| w[0] == w[1])
+        .map(|w| w[0])
+        .collect();
+    report.duplicate_ids.dedup();
+    report
+}
+
+/// A work unit with the pool's signature over it.
+#[cfg(feature = "signing")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct SignedWorkUnit {
+    pub work: WorkUnit,
+    pub pool_pubkey: [u8; 32],
+    #[serde(with = "signature")]
+    pub signature: [u8; 64],
+}
+
+/// Serde for 64-byte signatures, as a tuple of bytes like shorter arrays.
+#[cfg(feature = "signing")]
+mod signature {
+    use serde::ser::SerializeTuple;
+
+    pub fn serialize<S: serde::Serializer>(
+        signature: &[u8; 64],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(64)?;
+        for byte in signature {
+            tuple.serialize_element(byte)?;
+        }
+        tuple.end()
+    }
+
+    pub fn deserialize<'de, D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<[u8; 64], D::Error> {
+        struct Visitor;
+
+        impl<'de> serde::de::Visitor<'de> for Visitor {
+            type Value = [u8; 64];
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "64 bytes")
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<Self::Value, A::Error> {
+                let mut signature = [0; 64];
+                for (i, byte) in signature.iter_mut().enumerate() {
+                    *byte = seq
+                        .next_element()?
+                        .ok_or_else(|| serde::de::Error::invalid_length(i, &self))?;
+                }
+                Ok(signature)
+            }
+        }
+
+        deserializer.deserialize_tuple(64, Visitor)
+    }
+}
+
+/// Signs a work unit with the pool's keypair.
+#[cfg(feature = "signing")]
+pub fn sign_work(work: &WorkUnit, keypair: &Keypair) -> SignedWorkUnit {
+    use ed25519_dalek::Signer;
+
+    SignedWorkUnit {
+        work: *work,
+        pool_pubkey: keypair.public.to_bytes(),
+        signature: keypair.sign(&work.signing_message()).to_bytes(),
+    }
+}
+
+/// Checks that a work unit was signed by `expected_pool`, whatever its expiry.
+///
+/// The key in the unit must be the expected one; a unit correctly signed by any other
+/// key is [`WorkAuthError::WrongPool`]. Signatures are checked strictly, rejecting
+/// malleable encodings and weak keys.
+#[cfg(feature = "signing")]
+pub fn verify_work(signed: &SignedWorkUnit, expected_pool: &[u8; 32]) -> Result<(), WorkAuthError> {
+    if signed.pool_pubkey != *expected_pool {
+        return Err(WorkAuthError::WrongPool);
+    }
+    let key = ed25519_dalek::PublicKey::from_bytes(expected_pool)
+        .map_err(|_| WorkAuthError::InvalidPublicKey)?;
+    let signature = ed25519_dalek::Signature::from_bytes(&signed.signature)
+        .map_err(|_| WorkAuthError::BadSignature)?;
+    key.verify_strict(&signed.work.signing_message(), &signature)
+        .map_err(|_| WorkAuthError::BadSignature)?;
+    if signed.work.count == 0 {
+        return Err(WorkAuthError::Empty);
+    }
+    Ok(())
+}
+
+/// Like [`verify_work`], also rejecting a unit that expired before `now_unix`, in Unix
+/// seconds. A unit is still valid in the second it expires.
+#[cfg(feature = "signing")]
+pub fn verify_work_at(
+    signed: &SignedWorkUnit,
+    expected_pool: &[u8; 32],
+    now_unix: u64,
+) -> Result<(), WorkAuthError> {
+    verify_work(signed, expected_pool)?;
+    if now_unix > signed.work.expires_unix {
+        return Err(WorkAuthError::Expired {
+            expires_unix: signed.work.expires_unix,
+            now_unix,
+        });
+    }
+    Ok(())
+}
+
+/// Why a work unit was not accepted.
+#[cfg(feature = "signing")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WorkAuthError {
+    /// The unit names a different pool key than expected.
+    WrongPool,
+    /// The expected pool key is not a valid ed25519 key.
+    InvalidPublicKey,
+    /// The signature does not match the unit and key.
+    BadSignature,
+    /// The unit expired before now.
+    Expired { expires_unix: u64, now_unix: u64 },
+    /// The unit has no nonces to search.
+    Empty,
+    /// A pool key is expected but the work is not signed.
+    Unsigned,
+}
+
+#[cfg(feature = "signing")]
+impl std::fmt::Display for WorkAuthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match *self {
+            WorkAuthError::WrongPool => write!(f, "Work unit is from another pool"),
+            WorkAuthError::InvalidPublicKey => write!(f, "Invalid pool public key"),
+            WorkAuthError::BadSignature => write!(f, "Bad work unit signature"),
+            WorkAuthError::Expired {
+                expires_unix,
+                now_unix,
+            } => write!(
+                f,
+                "Work unit expired at {}, {} seconds ago",
+                expires_unix,
+                now_unix - expires_unix
+            ),
+            WorkAuthError::Empty => write!(f, "Work unit has no nonces"),
+            WorkAuthError::Unsigned => write!(f, "Work is not signed by the expected pool"),
+        }
+    }
+}
+
+#[cfg(feature = "signing")]
+impl std::error::Error for WorkAuthError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        None
+    }
+}
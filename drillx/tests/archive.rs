@@ -0,0 +1,186 @@
+use drillx::{
+    archive::{self, ArchiveError, FLAG_SORTED, VERSION},
+    Solution,
+};
+
+/// A small deterministic generator so failures reproduce.
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn solution(&mut self, nonce: u64) -> Solution {
+        let mut digest = [0; 16];
+        digest[..8].copy_from_slice(&self.next().to_le_bytes());
+        digest[8..].copy_from_slice(&self.next().to_le_bytes());
+        Solution::new(digest, nonce.to_le_bytes())
+    }
+}
+
+fn random(rng: &mut SplitMix, len: usize) -> Vec<Solution> {
+    (0..len)
+        .map(|_| {
+            let nonce = rng.next();
+            rng.solution(nonce)
+        })
+        .collect()
+}
+
+fn sorted(solutions: &[Solution]) -> Vec<Solution> {
+    let mut solutions = solutions.to_vec();
+    solutions.sort_by_key(|s| u64::from_le_bytes(s.n));
+    solutions
+}
+
+#[test]
+fn test_round_trip_random() {
+    let mut rng = SplitMix(1);
+    for len in [0, 1, 2, 17, 500] {
+        let solutions = random(&mut rng, len);
+        assert_eq!(
+            archive::decode(&archive::encode(&solutions)),
+            Ok(solutions.clone())
+        );
+        assert_eq!(
+            archive::decode(&archive::encode_sorted(&solutions)),
+            Ok(sorted(&solutions))
+        );
+    }
+}
+
+#[test]
+fn test_round_trip_extremes() {
+    let mut rng = SplitMix(2);
+    let solutions: Vec<Solution> = [u64::MAX, 0, u64::MAX, 1, u64::MAX - 1, 0, 0]
+        .into_iter()
+        .map(|nonce| rng.solution(nonce))
+        .collect();
+    assert_eq!(
+        archive::decode(&archive::encode(&solutions)),
+        Ok(solutions.clone())
+    );
+    assert_eq!(
+        archive::decode(&archive::encode_sorted(&solutions)),
+        Ok(sorted(&solutions))
+    );
+}
+
+#[test]
+fn test_iter_decode() {
+    let mut rng = SplitMix(3);
+    let solutions = random(&mut rng, 20);
+    let bytes = archive::encode_sorted(&solutions);
+    let iter = archive::iter_decode(&bytes).unwrap();
+    assert!(iter.is_sorted_by_nonce());
+    let decoded: Vec<Solution> = iter.map(Result::unwrap).collect();
+    assert_eq!(decoded, sorted(&solutions));
+}
+
+#[test]
+fn test_size_sequential() {
+    let mut rng = SplitMix(4);
+    let solutions: Vec<Solution> = (1_000_000..1_001_000).map(|n| rng.solution(n)).collect();
+    let bytes = archive::encode(&solutions);
+    // Header, a 3-byte first delta, then a one-byte delta per record.
+    assert_eq!(bytes.len(), 2 + 2 + 3 + 16 + 999 * 17);
+    assert!(bytes.len() * 4 < solutions.len() * 24 * 3);
+    assert_eq!(archive::encode_sorted(&solutions).len(), bytes.len());
+}
+
+#[test]
+fn test_rejects_header() {
+    assert_eq!(archive::decode(&[]), Err(ArchiveError::Truncated));
+    assert_eq!(archive::decode(&[VERSION]), Err(ArchiveError::Truncated));
+    assert_eq!(archive::decode(&[VERSION, 0]), Err(ArchiveError::Truncated));
+    assert_eq!(
+        archive::decode(&[9, 0, 0]),
+        Err(ArchiveError::UnknownVersion(9))
+    );
+    assert_eq!(
+        archive::decode(&[VERSION, 0b10, 0]),
+        Err(ArchiveError::UnknownFlags(0b10))
+    );
+    assert_eq!(archive::decode(&[VERSION, 0, 0]), Ok(vec![]));
+}
+
+#[test]
+fn test_rejects_huge_count() {
+    // A declared count of u64::MAX must not allocate.
+    let mut bytes = vec![VERSION, 0];
+    bytes.extend_from_slice(&[0xff; 9]);
+    bytes.push(0x01);
+    bytes.extend_from_slice(&[0; 34]);
+    assert_eq!(
+        archive::decode(&bytes),
+        Err(ArchiveError::CountTooLarge {
+            count: u64::MAX,
+            max: 2
+        })
+    );
+    assert!(archive::iter_decode(&bytes).is_err());
+}
+
+#[test]
+fn test_rejects_varint_overflow() {
+    let mut bytes = vec![VERSION, 0];
+    bytes.extend_from_slice(&[0xff; 9]);
+    bytes.push(0x02);
+    assert_eq!(archive::decode(&bytes), Err(ArchiveError::VarintOverflow));
+    let mut bytes = vec![VERSION, 0];
+    bytes.extend_from_slice(&[0x80; 11]);
+    assert_eq!(archive::decode(&bytes), Err(ArchiveError::VarintOverflow));
+}
+
+#[test]
+fn test_rejects_sorted_overflow() {
+    let mut rng = SplitMix(5);
+    let mut bytes = archive::encode_sorted(&[rng.solution(u64::MAX)]);
+    bytes[2] = 2;
+    bytes.push(0x01);
+    bytes.extend_from_slice(&[0; 16]);
+    assert_eq!(archive::decode(&bytes), Err(ArchiveError::NonceOverflow));
+    assert_eq!(bytes[1], FLAG_SORTED);
+}
+
+#[test]
+fn test_rejects_truncated_and_trailing() {
+    let mut rng = SplitMix(6);
+    let solutions = random(&mut rng, 5);
+    let bytes = archive::encode(&solutions);
+    for len in 0..bytes.len() {
+        assert!(archive::decode(&bytes[..len]).is_err(), "len {}", len);
+    }
+    let mut long = bytes.clone();
+    long.push(0);
+    assert_eq!(archive::decode(&long), Err(ArchiveError::TrailingBytes(1)));
+    let mut iter = archive::iter_decode(&long).unwrap();
+    for solution in &solutions {
+        assert_eq!(iter.next(), Some(Ok(*solution)));
+    }
+    assert_eq!(iter.next(), Some(Err(ArchiveError::TrailingBytes(1))));
+    assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_garbage_never_panics() {
+    let mut rng = SplitMix(7);
+    for _ in 0..2000 {
+        let len = (rng.next() % 96) as usize;
+        let mut bytes: Vec<u8> = (0..len).map(|_| rng.next() as u8).collect();
+        if let Some(first) = bytes.first_mut() {
+            *first = VERSION;
+        }
+        if bytes.len() > 1 {
+            bytes[1] &= FLAG_SORTED;
+        }
+        if let Ok(solutions) = archive::decode(&bytes) {
+            assert!(solutions.len() * 17 <= bytes.len());
+        }
+    }
+}
@@ -0,0 +1,241 @@
+#![cfg(feature = "arrow")]
+
+use std::{fs::File, path::PathBuf, sync::Arc};
+
+use arrow_array::{ArrayRef, BinaryArray, FixedSizeBinaryArray};
+use drillx::{
+    arrow::{
+        read_parquet, read_parquet_file, records_from_batch, schema, write_parquet,
+        write_parquet_file, RecordBatch, ShareRecord, ShareRecordBatchBuilder, ShareRecordError,
+    },
+    is_valid_digest,
+    vectors::VECTORS,
+    Solution,
+};
+use parquet::file::reader::{FileReader, SerializedFileReader};
+
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn bytes<const N: usize>(&mut self) -> [u8; N] {
+        let mut bytes = [0; N];
+        for chunk in bytes.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next().to_le_bytes()[..chunk.len()]);
+        }
+        bytes
+    }
+}
+
+fn temp_path(test: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("drillx-{}-{}.parquet", test, std::process::id()))
+}
+
+/// Records with random bytes, and every hundredth one a real solution from the vectors.
+fn records(count: usize) -> Vec<ShareRecord> {
+    let solved: Vec<_> = VECTORS
+        .iter()
+        .filter_map(|v| Some((v.challenge, v.nonce, v.output?.digest)))
+        .collect();
+    let mut rng = SplitMix(161);
+    (0..count)
+        .map(|i| {
+            let (challenge, nonce, digest) = if i % 100 == 0 {
+                solved[i / 100 % solved.len()]
+            } else {
+                (rng.bytes(), rng.bytes(), rng.bytes())
+            };
+            ShareRecord {
+                timestamp: 1_700_000_000_000_000 + i as i64 * 1_500,
+                challenge,
+                nonce,
+                digest,
+                difficulty: Solution::new(digest, nonce).to_hash().difficulty(),
+                accepted: i % 3 != 0,
+                miner_id: format!("miner-{}", rng.next() % 50),
+            }
+        })
+        .collect()
+}
+
+fn batches(records: &[ShareRecord], batch_len: usize) -> Vec<RecordBatch> {
+    let mut builder = ShareRecordBatchBuilder::with_capacity(batch_len);
+    records
+        .chunks(batch_len)
+        .map(|chunk| {
+            builder.extend(chunk.iter().cloned());
+            assert_eq!(builder.len(), chunk.len());
+            let batch = builder.finish();
+            assert!(builder.is_empty());
+            batch
+        })
+        .collect()
+}
+
+#[test]
+fn test_share_record_schema_snapshot() {
+    let schema = schema();
+    let mut arrow = String::new();
+    for field in schema.fields() {
+        arrow += &format!(
+            "{}: {:?} nullable={}\n",
+            field.name(),
+            field.data_type(),
+            field.is_nullable()
+        );
+    }
+    for (key, value) in schema.metadata() {
+        arrow += &format!("{} = {}\n", key, value);
+    }
+    assert_eq!(arrow, include_str!("schema/share_record_arrow.txt"));
+
+    let path = temp_path("arrow-schema");
+    write_parquet_file(&path, &[]).unwrap();
+    let reader = SerializedFileReader::new(File::open(&path).unwrap()).unwrap();
+    let mut parquet = Vec::new();
+    parquet::schema::printer::print_schema(
+        &mut parquet,
+        reader.metadata().file_metadata().schema(),
+    );
+    std::fs::remove_file(&path).ok();
+    assert_eq!(
+        String::from_utf8(parquet).unwrap(),
+        include_str!("schema/share_record_parquet.txt")
+    );
+}
+
+#[test]
+fn test_share_records_round_trip() {
+    let records = records(5000);
+    let path = temp_path("arrow-round-trip");
+    write_parquet_file(&path, &batches(&records, 1024)).unwrap();
+    let read = read_parquet_file(&path).unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(read, records);
+
+    // The real solutions still verify, so no byte column was swapped or shifted.
+    for record in read.iter().step_by(100) {
+        assert!(is_valid_digest(
+            &record.challenge,
+            &record.nonce,
+            &record.digest
+        ));
+        let hash = Solution::new(record.digest, record.nonce).to_hash();
+        assert_eq!(hash.difficulty(), record.difficulty);
+    }
+    assert!(!is_valid_digest(
+        &read[1].challenge,
+        &read[1].nonce,
+        &read[1].digest
+    ));
+}
+
+#[test]
+fn test_write_parquet_to_writer() {
+    let records = records(300);
+    let bytes = write_parquet(Vec::new(), &batches(&records, 128)).unwrap();
+    assert_eq!(&bytes[..4], b"PAR1");
+    assert_eq!(&bytes[bytes.len() - 4..], b"PAR1");
+
+    let path = temp_path("arrow-writer");
+    std::fs::write(&path, &bytes).unwrap();
+    let read = read_parquet(File::open(&path).unwrap()).unwrap();
+    std::fs::remove_file(&path).ok();
+    assert_eq!(read, records);
+}
+
+/// A batch of `records` with one column replaced.
+fn with_column(records: &[ShareRecord], name: &str, column: ArrayRef) -> RecordBatch {
+    let batch = batches(records, records.len()).remove(0);
+    let index = batch.schema().index_of(name).unwrap();
+    let mut columns = batch.columns().to_vec();
+    let mut fields: Vec<_> = batch.schema().fields().iter().cloned().collect();
+    fields[index] = Arc::new(
+        fields[index]
+            .as_ref()
+            .clone()
+            .with_data_type(column.data_type().clone())
+            .with_nullable(true),
+    );
+    columns[index] = column;
+    RecordBatch::try_new(Arc::new(arrow_schema::Schema::new(fields)), columns).unwrap()
+}
+
+#[test]
+fn test_records_from_batch_validates_lengths() {
+    let records = records(4);
+
+    // Variable-length binary is read when every value has the right length.
+    let nonces = BinaryArray::from_iter_values(records.iter().map(|r| r.nonce));
+    let batch = with_column(&records, "nonce", Arc::new(nonces));
+    assert_eq!(records_from_batch(&batch).unwrap(), records);
+
+    let nonces = BinaryArray::from_iter_values(records.iter().enumerate().map(|(i, r)| {
+        if i == 2 {
+            &r.nonce[..7]
+        } else {
+            &r.nonce[..]
+        }
+    }));
+    let batch = with_column(&records, "nonce", Arc::new(nonces));
+    assert!(matches!(
+        records_from_batch(&batch),
+        Err(ShareRecordError::WrongLength {
+            column: "nonce",
+            row: 2,
+            expected: 8,
+            actual: 7
+        })
+    ));
+
+    let challenges =
+        FixedSizeBinaryArray::try_from_iter(records.iter().map(|r| &r.challenge[..31])).unwrap();
+    let batch = with_column(&records, "challenge", Arc::new(challenges));
+    assert!(matches!(
+        records_from_batch(&batch),
+        Err(ShareRecordError::WrongLength {
+            column: "challenge",
+            row: 0,
+            expected: 32,
+            actual: 31
+        })
+    ));
+
+    let difficulties = Arc::new(arrow_array::Int64Array::from(vec![1; 4]));
+    let batch = with_column(&records, "difficulty", difficulties);
+    assert!(matches!(
+        records_from_batch(&batch),
+        Err(ShareRecordError::WrongType {
+            column: "difficulty",
+            ..
+        })
+    ));
+
+    let batch = batches(&records, 4).remove(0);
+    let batch = batch.project(&[0, 1, 2, 3, 4, 5]).unwrap();
+    assert!(matches!(
+        records_from_batch(&batch),
+        Err(ShareRecordError::MissingColumn("miner_id"))
+    ));
+}
+
+#[test]
+fn test_records_from_batch_rejects_nulls() {
+    let records = records(3);
+    let miners = arrow_array::StringArray::from(vec![Some("a"), None, Some("c")]);
+    let batch = with_column(&records, "miner_id", Arc::new(miners));
+    assert!(matches!(
+        records_from_batch(&batch),
+        Err(ShareRecordError::Null {
+            column: "miner_id",
+            row: 1
+        })
+    ));
+}
@@ -0,0 +1,163 @@
+use drillx::{
+    audit::{collect_samples, difficulty_distribution_test, uniformity_test, DEFAULT_SIGNIFICANCE},
+    DrillxError, EquixSolver, RuntimeOption, Solution, Solver,
+};
+
+/// A small deterministic generator so failures reproduce.
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn hash(&mut self) -> [u8; 32] {
+        let mut hash = [0; 32];
+        for chunk in hash.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next().to_le_bytes());
+        }
+        hash
+    }
+}
+
+/// Returns digests whose final hash has a zero first byte, by grinding the digest.
+struct Biased;
+
+impl Solver for Biased {
+    fn solve(&mut self, seed: &[u8], _runtime: RuntimeOption) -> Result<[u8; 16], DrillxError> {
+        let nonce: [u8; 8] = seed[32..40].try_into().unwrap();
+        (0u128..)
+            .map(|i| i.to_le_bytes())
+            .find(|digest| Solution::new(*digest, nonce).to_hash().h[0] == 0)
+            .ok_or(DrillxError::NoSolutions)
+    }
+}
+
+/// Fails after `left` solves.
+struct Failing {
+    left: usize,
+}
+
+impl Solver for Failing {
+    fn solve(&mut self, _seed: &[u8], _runtime: RuntimeOption) -> Result<[u8; 16], DrillxError> {
+        self.left = self.left.checked_sub(1).ok_or(DrillxError::CompileFailed)?;
+        Ok([0; 16])
+    }
+}
+
+fn hashes(challenge: &[u8; 32], n: u64, solver: &mut impl Solver) -> Vec<[u8; 32]> {
+    (0..n)
+        .filter_map(|nonce| {
+            let nonce = nonce.to_le_bytes();
+            let digest = solver
+                .solve(
+                    &drillx::seed(challenge, &nonce).data,
+                    RuntimeOption::TryCompile,
+                )
+                .ok()?;
+            Some(Solution::new(digest, nonce).to_hash().h)
+        })
+        .collect()
+}
+
+#[test]
+fn test_p_values() {
+    // Difficulty 0 and everything above split evenly, with one degree of freedom.
+    let samples: Vec<u32> = (0..10).map(|i| i % 2).collect();
+    let verdict = difficulty_distribution_test(&samples);
+    assert_eq!(verdict.degrees_of_freedom, 1);
+    assert_eq!(verdict.statistic, 0.0);
+    assert_eq!(verdict.p_value, 1.0);
+    assert!(verdict.passed);
+
+    // 20 samples bin as 0, 1 and the rest, expecting 10, 5 and 5. With two degrees
+    // of freedom the p-value is `exp(-statistic / 2)`.
+    let samples: Vec<u32> = (0..20).map(|i| (i >= 14) as u32).collect();
+    let verdict = difficulty_distribution_test(&samples);
+    assert_eq!(verdict.degrees_of_freedom, 2);
+    assert!((verdict.statistic - 6.8).abs() < 1e-12);
+    assert!(
+        (verdict.p_value - (-3.4f64).exp()).abs() < 1e-12,
+        "{}",
+        verdict.p_value
+    );
+
+    // Too few samples cannot fail.
+    let verdict = difficulty_distribution_test(&[9; 9]);
+    assert_eq!(verdict.degrees_of_freedom, 0);
+    assert!(verdict.passed);
+    assert!(uniformity_test(&[[0; 32]; 39]).passed);
+}
+
+#[test]
+fn test_significance() {
+    let samples: Vec<u32> = (0..20).map(|i| (i >= 14) as u32).collect();
+    let verdict = difficulty_distribution_test(&samples);
+    assert_eq!(verdict.significance, DEFAULT_SIGNIFICANCE);
+    assert!(verdict.passed);
+    assert!(verdict.with_significance(0.01).passed);
+    assert!(!verdict.with_significance(0.05).passed);
+    assert_eq!(verdict.with_significance(0.05).p_value, verdict.p_value);
+}
+
+#[test]
+fn test_random_hashes_pass() {
+    let mut rng = SplitMix(136);
+    let hashes: Vec<[u8; 32]> = (0..4_096).map(|_| rng.hash()).collect();
+    let verdict = uniformity_test(&hashes);
+    assert_eq!(verdict.degrees_of_freedom, 255);
+    assert!(verdict.passed, "{:?}", verdict);
+    let samples: Vec<u32> = hashes.iter().map(|h| drillx::difficulty(*h)).collect();
+    let verdict = difficulty_distribution_test(&samples);
+    assert_eq!(verdict.degrees_of_freedom, 9);
+    assert!(verdict.passed, "{:?}", verdict);
+
+    // The same bytes with one zeroed fail.
+    let zeroed: Vec<[u8; 32]> = hashes
+        .iter()
+        .map(|h| {
+            let mut h = *h;
+            h[5] = 0;
+            h
+        })
+        .collect();
+    let verdict = uniformity_test(&zeroed);
+    assert!(!verdict.passed);
+    assert!(verdict.p_value < 1e-12);
+}
+
+#[test]
+fn test_real_solver_passes() {
+    let challenge = [0xad; 32];
+    let samples = collect_samples(&challenge, 400, &mut EquixSolver::new());
+    assert_eq!(samples.len(), 400);
+    let verdict = difficulty_distribution_test(&samples);
+    assert!(verdict.passed, "{:?}", verdict);
+    assert_eq!(verdict.samples, 400);
+
+    let hashes = hashes(&challenge, 400, &mut EquixSolver::new());
+    assert!(uniformity_test(&hashes).passed);
+}
+
+#[test]
+fn test_biased_solver_fails() {
+    let challenge = [0xad; 32];
+    let samples = collect_samples(&challenge, 400, &mut Biased);
+    assert!(samples.iter().all(|d| *d >= 8));
+    let verdict = difficulty_distribution_test(&samples);
+    assert!(!verdict.passed);
+    assert!(verdict.p_value < 1e-12);
+
+    let hashes = hashes(&challenge, 400, &mut Biased);
+    assert!(!uniformity_test(&hashes).passed);
+}
+
+#[test]
+fn test_collect_samples_stops_on_error() {
+    let samples = collect_samples(&[0; 32], 10, &mut Failing { left: 3 });
+    assert_eq!(samples.len(), 3);
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "battery")]
+
+use std::{fs, path::PathBuf};
+
+use drillx::throttle::{BatteryAwareHook, PowerSource, ThrottleHook};
+
+/// A fake power_supply tree with one supply per `(name, [(attribute, value)])`.
+fn power_supply(test: &str, supplies: &[(&str, &[(&str, &str)])]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("drillx-{}-{}", test, std::process::id()));
+    fs::remove_dir_all(&root).ok();
+    fs::create_dir_all(&root).unwrap();
+    for (name, attributes) in supplies {
+        let supply = root.join(name);
+        fs::create_dir_all(&supply).unwrap();
+        for (attribute, value) in *attributes {
+            fs::write(supply.join(attribute), format!("{}\n", value)).unwrap();
+        }
+    }
+    root
+}
+
+const AC_ONLINE: (&str, &[(&str, &str)]) = ("AC", &[("type", "Mains"), ("online", "1")]);
+const AC_OFFLINE: (&str, &[(&str, &str)]) = ("AC", &[("type", "Mains"), ("online", "0")]);
+
+#[test]
+fn test_battery_plugged_in() {
+    let root = power_supply(
+        "battery-plugged",
+        &[
+            AC_ONLINE,
+            ("BAT0", &[("type", "Battery"), ("capacity", "5")]),
+        ],
+    );
+    let hook = BatteryAwareHook::new().with_root(&root);
+    assert_eq!(hook.power_source(), PowerSource::Mains);
+    assert_eq!(hook.target_intensity(), 1.0);
+    fs::remove_dir_all(root).ok();
+}
+
+#[test]
+fn test_battery_unplugged() {
+    let root = power_supply(
+        "battery-unplugged",
+        &[
+            AC_OFFLINE,
+            ("BAT0", &[("type", "Battery"), ("capacity", "80")]),
+            ("BAT1", &[("type", "Battery"), ("capacity", "55")]),
+            ("usb", &[("type", "USB"), ("online", "0")]),
+        ],
+    );
+    let hook = BatteryAwareHook::new().with_root(&root);
+    assert_eq!(
+        hook.power_source(),
+        PowerSource::Battery { capacity: Some(55) }
+    );
+    assert_eq!(hook.target_intensity(), 0.25);
+    assert_eq!(hook.clone().on_battery(0.5).target_intensity(), 0.5);
+    assert_eq!(hook.low_battery(60, 0.1).target_intensity(), 0.1);
+    fs::remove_dir_all(root).ok();
+}
+
+#[test]
+fn test_battery_low() {
+    let root = power_supply(
+        "battery-low",
+        &[("BAT0", &[("type", "Battery"), ("capacity", "20")])],
+    );
+    let hook = BatteryAwareHook::new().with_root(&root);
+    assert_eq!(hook.target_intensity(), 0.0);
+    fs::remove_dir_all(root).ok();
+}
+
+#[test]
+fn test_battery_usb_charger() {
+    let root = power_supply(
+        "battery-usb",
+        &[
+            ("BAT0", &[("type", "Battery"), ("capacity", "10")]),
+            ("usb-c", &[("type", "USB_PD"), ("online", "1")]),
+        ],
+    );
+    let hook = BatteryAwareHook::new().with_root(&root);
+    assert_eq!(hook.target_intensity(), 1.0);
+    fs::remove_dir_all(root).ok();
+}
+
+#[test]
+fn test_battery_unknown() {
+    // A desktop, or no sysfs at all, mines at full speed.
+    let root = power_supply("battery-desktop", &[AC_ONLINE]);
+    assert_eq!(
+        BatteryAwareHook::new().with_root(&root).target_intensity(),
+        1.0
+    );
+    let missing = root.join("missing");
+    assert_eq!(
+        BatteryAwareHook::new().with_root(missing).power_source(),
+        PowerSource::Mains
+    );
+    let unreadable = power_supply(
+        "battery-unreadable",
+        &[("BAT0", &[("type", "Battery"), ("capacity", "full")])],
+    );
+    assert_eq!(
+        BatteryAwareHook::new()
+            .with_root(&unreadable)
+            .power_source(),
+        PowerSource::Battery { capacity: None }
+    );
+    fs::remove_dir_all(root).ok();
+    fs::remove_dir_all(unreadable).ok();
+}
@@ -0,0 +1,119 @@
+use std::{fs, path::PathBuf, time::Duration};
+
+use drillx::bench::{
+    benchmark, energy_delta_uj, is_package_zone, parse_energy_uj, BenchReport, EnergyMeter,
+};
+
+/// A fake powercap tree with one zone per `(name, energy_uj, max_energy_range_uj)`.
+fn powercap(test: &str, zones: &[(&str, &str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("drillx-{}-{}", test, std::process::id()));
+    fs::remove_dir_all(&root).ok();
+    for (name, energy, max_range) in zones {
+        let zone = root.join(name);
+        fs::create_dir_all(&zone).unwrap();
+        fs::write(zone.join("energy_uj"), energy).unwrap();
+        fs::write(zone.join("max_energy_range_uj"), max_range).unwrap();
+        fs::write(zone.join("name"), "package-0\n").unwrap();
+    }
+    root
+}
+
+#[test]
+fn test_parse_energy_uj() {
+    assert_eq!(parse_energy_uj("68243028173\n"), Some(68_243_028_173));
+    assert_eq!(parse_energy_uj("262143328850\n"), Some(262_143_328_850));
+    assert_eq!(parse_energy_uj("0"), Some(0));
+    assert_eq!(parse_energy_uj(""), None);
+    assert_eq!(parse_energy_uj("-1\n"), None);
+    assert_eq!(parse_energy_uj("12 J"), None);
+}
+
+#[test]
+fn test_energy_delta_wraparound() {
+    let max = 262_143_328_850;
+    assert_eq!(energy_delta_uj(1_000, 5_000, max), 4_000);
+    assert_eq!(energy_delta_uj(5_000, 5_000, max), 0);
+    // The counter wrapped to zero past its range.
+    assert_eq!(energy_delta_uj(max - 1_000, 2_000, max), 3_000);
+    assert_eq!(energy_delta_uj(max, 0, max), 0);
+    // A reading above a bogus range does not underflow.
+    assert_eq!(energy_delta_uj(max + 10, 5, max), 5);
+}
+
+#[test]
+fn test_package_zones() {
+    assert!(is_package_zone("intel-rapl:0"));
+    assert!(is_package_zone("intel-rapl:12"));
+    assert!(!is_package_zone("intel-rapl:0:1"));
+    assert!(!is_package_zone("intel-rapl-mmio:0"));
+    assert!(!is_package_zone("intel-rapl:"));
+    assert!(!is_package_zone("intel-rapl"));
+}
+
+#[test]
+fn test_meter_sums_packages() {
+    let max = "262143328850\n";
+    let root = powercap(
+        "meter",
+        &[
+            ("intel-rapl:0", "262143000000\n", max),
+            ("intel-rapl:0:0", "100\n", max),
+            ("intel-rapl:1", "1000000\n", max),
+            ("intel-rapl-mmio:0", "100\n", max),
+        ],
+    );
+    let mut meter = EnergyMeter::open_at(&root).unwrap();
+    assert_eq!(meter.packages(), 2);
+    assert_eq!(meter.joules(), 0.0);
+
+    // Package 0 wraps; subzones and MMIO duplicates are not counted.
+    fs::write(root.join("intel-rapl:0/energy_uj"), "671150\n").unwrap();
+    fs::write(root.join("intel-rapl:1/energy_uj"), "3500000\n").unwrap();
+    fs::write(root.join("intel-rapl:0:0/energy_uj"), "999999999\n").unwrap();
+    assert!(meter.sample());
+    assert!((meter.joules() - 3.5).abs() < 1e-9, "{}", meter.joules());
+
+    fs::write(root.join("intel-rapl:1/energy_uj"), "4500000\n").unwrap();
+    assert!(meter.sample());
+    assert!((meter.joules() - 4.5).abs() < 1e-9);
+
+    // An unreadable counter fails the sample.
+    fs::remove_file(root.join("intel-rapl:1/energy_uj")).unwrap();
+    assert!(!meter.sample());
+    fs::remove_dir_all(&root).ok();
+}
+
+#[test]
+fn test_meter_unavailable() {
+    let root = powercap("empty", &[("intel-rapl:0:0", "100\n", "1000\n")]);
+    assert!(EnergyMeter::open_at(&root).is_none());
+    assert!(EnergyMeter::open_at(&root.join("missing")).is_none());
+
+    let unreadable = powercap("unreadable", &[("intel-rapl:0", "\n", "1000\n")]);
+    assert!(EnergyMeter::open_at(&unreadable).is_none());
+    fs::remove_dir_all(&root).ok();
+    fs::remove_dir_all(&unreadable).ok();
+}
+
+#[test]
+fn test_benchmark() {
+    let report = benchmark(1, Duration::from_millis(300));
+    assert_eq!(report.threads, 1);
+    assert!(report.hashes >= 1);
+    assert!(report.hashrate > 0.0);
+    // RAPL is usually readable only by root, and absent off Linux or in VMs.
+    match (&report.energy, EnergyMeter::open()) {
+        (Some(energy), _) => {
+            assert!(energy.packages >= 1);
+            assert!(energy.joules >= 0.0);
+            let watts = energy.joules / report.elapsed.as_secs_f64();
+            assert!((energy.watts - watts).abs() <= watts * 1e-9);
+        }
+        (None, meter) => assert!(meter.is_none()),
+    }
+
+    let json = serde_json::to_string(&report).unwrap();
+    let parsed: BenchReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed.hashes, report.hashes);
+    assert_eq!(parsed.energy.is_some(), report.energy.is_some());
+}
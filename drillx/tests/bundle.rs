@@ -0,0 +1,191 @@
+use drillx::{
+    bundle::{BundleError, Scored, SubmissionBundle, HEADER_LEN, MAX_BUNDLE_SOLUTIONS, VERSION},
+    share_weight, Solution,
+};
+
+const CHALLENGE: [u8; 32] = [42; 32];
+
+const AUTHORITY: [u8; 32] = [7; 32];
+
+/// Valid solutions to the challenge, with their difficulties.
+fn mined(count: u64) -> Vec<(Solution, u32)> {
+    (0..)
+        .filter_map(|n: u64| {
+            let nonce = n.to_le_bytes();
+            let hash = drillx::hash(&CHALLENGE, &nonce).ok()?;
+            Some((Solution::new(hash.d, nonce), hash.difficulty()))
+        })
+        .take(count as usize)
+        .collect()
+}
+
+/// A solution no challenge accepts.
+fn invalid(nonce: u64) -> Solution {
+    Solution::new([0xff; 16], nonce.to_le_bytes())
+}
+
+fn bundle(solutions: Vec<Solution>) -> SubmissionBundle {
+    SubmissionBundle::new(9, AUTHORITY, solutions).unwrap()
+}
+
+#[test]
+fn test_bundle_round_trip() {
+    for count in [0, 1, 5] {
+        let solutions: Vec<_> = mined(count).into_iter().map(|(s, _)| s).collect();
+        let bundle = bundle(solutions.clone());
+        let bytes = bundle.to_bytes();
+        assert_eq!(bytes.len(), HEADER_LEN + 24 * count as usize);
+        assert_eq!(bytes[0], VERSION);
+        let decoded = SubmissionBundle::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded, bundle);
+        assert_eq!(decoded.challenge_id(), 9);
+        assert_eq!(decoded.authority(), &AUTHORITY);
+        assert_eq!(decoded.solutions(), solutions);
+
+        let json = serde_json::to_string(&bundle).unwrap();
+        assert_eq!(
+            serde_json::from_str::<SubmissionBundle>(&json).unwrap(),
+            bundle
+        );
+    }
+}
+
+#[test]
+fn test_bundle_max() {
+    let solutions: Vec<_> = (0..MAX_BUNDLE_SOLUTIONS as u64).map(invalid).collect();
+    let full = bundle(solutions.clone());
+    assert_eq!(
+        SubmissionBundle::from_bytes(&full.to_bytes()).unwrap(),
+        full
+    );
+
+    let mut over = solutions;
+    over.push(invalid(MAX_BUNDLE_SOLUTIONS as u64));
+    assert_eq!(
+        SubmissionBundle::new(0, AUTHORITY, over.clone()),
+        Err(BundleError::TooMany {
+            count: MAX_BUNDLE_SOLUTIONS + 1
+        })
+    );
+    let json = serde_json::json!({
+        "challenge_id": 0,
+        "authority": AUTHORITY,
+        "solutions": over,
+    });
+    assert!(serde_json::from_value::<SubmissionBundle>(json).is_err());
+}
+
+#[test]
+fn test_bundle_rejects_duplicates() {
+    let (solution, _) = mined(1)[0];
+    let other = Solution::new([1; 16], solution.n);
+    assert_eq!(
+        SubmissionBundle::new(0, AUTHORITY, vec![solution, invalid(3), other]),
+        Err(BundleError::DuplicateNonce(solution.n))
+    );
+
+    // Decoding and deserializing check too.
+    let mut bytes = bundle(vec![solution, invalid(3)]).to_bytes();
+    bytes[HEADER_LEN + 24 + 16..].copy_from_slice(&solution.n);
+    assert_eq!(
+        SubmissionBundle::from_bytes(&bytes),
+        Err(BundleError::DuplicateNonce(solution.n))
+    );
+    let json = serde_json::json!({
+        "challenge_id": 0,
+        "authority": AUTHORITY,
+        "solutions": [solution, solution],
+    });
+    assert!(serde_json::from_value::<SubmissionBundle>(json).is_err());
+}
+
+#[test]
+fn test_bundle_decode_errors() {
+    let bytes = bundle(vec![invalid(1), invalid(2)]).to_bytes();
+    assert_eq!(
+        SubmissionBundle::from_bytes(&[]),
+        Err(BundleError::Truncated {
+            expected: HEADER_LEN,
+            actual: 0
+        })
+    );
+    assert_eq!(
+        SubmissionBundle::from_bytes(&bytes[..HEADER_LEN - 1]),
+        Err(BundleError::Truncated {
+            expected: HEADER_LEN,
+            actual: HEADER_LEN - 1
+        })
+    );
+    assert_eq!(
+        SubmissionBundle::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(BundleError::Truncated {
+            expected: bytes.len(),
+            actual: bytes.len() - 1
+        })
+    );
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        SubmissionBundle::from_bytes(&trailing),
+        Err(BundleError::TrailingBytes {
+            expected: bytes.len(),
+            actual: bytes.len() + 1
+        })
+    );
+    let mut version = bytes.clone();
+    version[0] = 2;
+    assert_eq!(
+        SubmissionBundle::from_bytes(&version),
+        Err(BundleError::UnknownVersion(2))
+    );
+
+    // A huge declared count fails before the input length matters.
+    let mut huge = bytes;
+    huge[HEADER_LEN - 2..HEADER_LEN].copy_from_slice(&u16::MAX.to_le_bytes());
+    assert_eq!(
+        SubmissionBundle::from_bytes(&huge),
+        Err(BundleError::TooMany {
+            count: u16::MAX as usize
+        })
+    );
+}
+
+#[test]
+fn test_bundle_scores_valid_only() {
+    let mined = mined(6);
+    let mut solutions: Vec<_> = mined.iter().map(|(s, _)| *s).collect();
+    solutions.insert(2, invalid(1000));
+    solutions.push(invalid(1001));
+    // A valid digest moved to another nonce.
+    solutions.push(Solution::new(mined[0].0.d, 1002u64.to_le_bytes()));
+    let bundle = bundle(solutions);
+
+    let best = mined.iter().map(|(_, d)| *d).max();
+    assert_eq!(
+        bundle.best_difficulty(&CHALLENGE),
+        Scored {
+            value: best,
+            skipped: 3
+        }
+    );
+    for min in [0, 2, 40] {
+        let weight = mined.iter().map(|(_, d)| share_weight(*d, min)).sum();
+        assert_eq!(
+            bundle.total_weight(&CHALLENGE, min),
+            Scored {
+                value: weight,
+                skipped: 3
+            }
+        );
+    }
+
+    // Nothing is valid for another challenge.
+    assert_eq!(
+        bundle.best_difficulty(&[0; 32]),
+        Scored {
+            value: None,
+            skipped: bundle.len()
+        }
+    );
+    assert_eq!(bundle.total_weight(&[0; 32], 0).value, 0);
+}
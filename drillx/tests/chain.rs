@@ -0,0 +1,135 @@
+use drillx::{
+    chain::{hash_chain, ChainError, ChainedSolution},
+    Solution,
+};
+
+const CHALLENGE: [u8; 32] = [164; 32];
+
+/// The first nonce whose chain of `rounds` rounds has a solution in every round.
+fn solvable_nonce(rounds: u8) -> [u8; 8] {
+    (0u64..)
+        .map(u64::to_le_bytes)
+        .find(|nonce| hash_chain(&CHALLENGE, nonce, rounds).is_ok())
+        .unwrap()
+}
+
+#[test]
+fn test_one_round_matches_hash() {
+    let nonce = solvable_nonce(1);
+    let chained = hash_chain(&CHALLENGE, &nonce, 1).unwrap();
+    let hash = drillx::hash(&CHALLENGE, &nonce).unwrap();
+    assert_eq!(chained.digests, vec![hash.d]);
+    assert_eq!(chained.h, hash.h);
+    assert_eq!(chained.difficulty(), hash.difficulty());
+
+    // Zero rounds are treated as one.
+    assert_eq!(hash_chain(&CHALLENGE, &nonce, 0).unwrap(), chained);
+
+    let solution = ChainedSolution::new(chained.digests.clone(), nonce);
+    assert_eq!(
+        solution,
+        ChainedSolution::from(Solution::new(hash.d, nonce))
+    );
+    assert_eq!(solution.is_valid(&CHALLENGE), Ok(hash.difficulty()));
+    let mut bytes = vec![1];
+    bytes.extend_from_slice(&Solution::new(hash.d, nonce).to_bytes());
+    assert_eq!(solution.to_bytes(), bytes);
+}
+
+#[test]
+fn test_rounds_chain_through_hashes() {
+    let nonce = solvable_nonce(3);
+    let chained = hash_chain(&CHALLENGE, &nonce, 3).unwrap();
+    let mut challenge = CHALLENGE;
+    for digest in &chained.digests {
+        let hash = drillx::hash(&challenge, &nonce).unwrap();
+        assert_eq!(*digest, hash.d);
+        challenge = hash.h;
+    }
+    assert_eq!(chained.digests.len(), 3);
+    assert_eq!(chained.h, challenge);
+
+    let solution = ChainedSolution::new(chained.digests.clone(), nonce);
+    assert_eq!(solution.rounds, 3);
+    assert_eq!(solution.is_valid(&CHALLENGE), Ok(chained.difficulty()));
+    assert_eq!(solution.to_hash(), chained);
+    assert!(solution.is_valid(&[0; 32]).is_err());
+}
+
+#[test]
+fn test_invalid_round_is_identified() {
+    let nonce = solvable_nonce(3);
+    let chained = hash_chain(&CHALLENGE, &nonce, 3).unwrap();
+    for round in 0..3 {
+        let mut solution = ChainedSolution::new(chained.digests.clone(), nonce);
+        solution.digests[round][0] ^= 1;
+        assert_eq!(
+            solution.is_valid(&CHALLENGE),
+            Err(ChainError::InvalidRound(round as u8))
+        );
+    }
+
+    // Swapping two rounds breaks the first of them.
+    let mut solution = ChainedSolution::new(chained.digests.clone(), nonce);
+    solution.digests.swap(1, 2);
+    assert_eq!(
+        solution.is_valid(&CHALLENGE),
+        Err(ChainError::InvalidRound(1))
+    );
+}
+
+#[test]
+fn test_round_count_is_checked() {
+    let nonce = solvable_nonce(2);
+    let chained = hash_chain(&CHALLENGE, &nonce, 2).unwrap();
+    let mut solution = ChainedSolution::new(chained.digests, nonce);
+    solution.rounds = 3;
+    assert_eq!(
+        solution.is_valid(&CHALLENGE),
+        Err(ChainError::RoundCount {
+            rounds: 3,
+            digests: 2
+        })
+    );
+
+    let empty = ChainedSolution::new(Vec::new(), nonce);
+    assert_eq!(
+        empty.is_valid(&CHALLENGE),
+        Err(ChainError::RoundCount {
+            rounds: 0,
+            digests: 0
+        })
+    );
+}
+
+#[test]
+fn test_chained_solution_round_trips() {
+    let nonce = solvable_nonce(2);
+    let chained = hash_chain(&CHALLENGE, &nonce, 2).unwrap();
+    let solution = ChainedSolution::new(chained.digests, nonce);
+
+    let bytes = solution.to_bytes();
+    assert_eq!(bytes.len(), 1 + 2 * 16 + 8);
+    assert_eq!(ChainedSolution::from_bytes(&bytes), Ok(solution.clone()));
+
+    let json = serde_json::to_string(&solution).unwrap();
+    assert_eq!(
+        serde_json::from_str::<ChainedSolution>(&json).unwrap(),
+        solution
+    );
+
+    assert_eq!(
+        ChainedSolution::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(ChainError::Length {
+            expected: 41,
+            actual: 40
+        })
+    );
+    assert_eq!(
+        ChainedSolution::from_bytes(&[]),
+        Err(ChainError::Length {
+            expected: 9,
+            actual: 0
+        })
+    );
+}
@@ -0,0 +1,92 @@
+use drillx::{
+    commit_reveal::{commit, verify_reveal, Commitment, COMMIT_TAG},
+    vectors::VECTORS,
+    Solution,
+};
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// The solution of the test vector at `index`.
+fn solution(index: usize) -> Solution {
+    let vector = &VECTORS[index];
+    Solution::new(vector.output.unwrap().digest, vector.nonce)
+}
+
+#[test]
+fn test_commit_vectors() {
+    assert_eq!(&COMMIT_TAG, b"DXCOMMIT");
+    let salts: [[u8; 32]; 3] = [[0; 32], [0x5a; 32], std::array::from_fn(|i| i as u8)];
+    let expected = [
+        "cd49bfc2e418df6463dbaef33e75a107be0ccb1269ec193d512a8950f86eb7b3",
+        "3d5394da68fda265ed00bfe7bffa24ee862aad1068c71a640e80374e296c8969",
+        "00e741350977ea1235631989e31312aac50faae756f36a708d83833e45745e85",
+    ];
+    for (salt, expected) in salts.iter().zip(expected) {
+        assert_eq!(commit(&solution(0), salt).to_vec(), hex(expected));
+    }
+    assert_eq!(
+        commit(&solution(1), &[0; 32]).to_vec(),
+        hex("4e9c6962700113e470b17e9439f2ef010df0eb116531d2b0d52d8075cbc5f7ab")
+    );
+}
+
+#[test]
+fn test_verify_reveal() {
+    let salt = [7; 32];
+    let commitment = commit(&solution(0), &salt);
+    assert!(verify_reveal(&commitment, &solution(0), &salt));
+
+    // A different solution, nonce, or salt does not open the commitment.
+    assert!(!verify_reveal(&commitment, &solution(1), &salt));
+    let mut other_nonce = solution(0);
+    other_nonce.n[7] ^= 1;
+    assert!(!verify_reveal(&commitment, &other_nonce, &salt));
+    let mut other_salt = salt;
+    other_salt[31] ^= 1;
+    assert!(!verify_reveal(&commitment, &solution(0), &other_salt));
+    let mut tampered = commitment;
+    tampered[0] ^= 0x80;
+    assert!(!verify_reveal(&tampered, &solution(0), &salt));
+}
+
+#[test]
+fn test_salt_reuse() {
+    // A reused salt lets anyone who saw it test guesses against other commitments.
+    let salt = [9; 32];
+    let first = commit(&solution(0), &salt);
+    let second = commit(&solution(1), &salt);
+    assert_ne!(first, second);
+    let guesses = [solution(0), solution(1)];
+    let opened: Vec<_> = guesses
+        .iter()
+        .filter(|guess| verify_reveal(&second, guess, &salt))
+        .collect();
+    assert_eq!(opened, [&solution(1)]);
+    // The same solution and salt always give the same commitment.
+    assert_eq!(commit(&solution(0), &salt), first);
+    assert_ne!(commit(&solution(0), &[10; 32]), first);
+}
+
+#[test]
+fn test_commitment() {
+    let salt = [3; 32];
+    let commitment = Commitment::new(&solution(0), &salt);
+    assert_eq!(commitment.to_bytes(), commit(&solution(0), &salt));
+    assert!(commitment.verify(&solution(0), &salt));
+    assert!(!commitment.verify(&solution(0), &[4; 32]));
+    assert_eq!(commitment, Commitment::from(commitment.to_bytes()));
+    let mut last = commitment.to_bytes();
+    last[31] ^= 1;
+    assert_ne!(commitment, Commitment::from(last));
+
+    let json = serde_json::to_string(&commitment).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Commitment>(&json).unwrap(),
+        commitment
+    );
+}
@@ -0,0 +1,57 @@
+use drillx::{confirm_candidates, Confirmation};
+
+const CHALLENGE: [u8; 32] = [4; 32];
+
+#[test]
+fn test_confirms_hash() {
+    let hashes: Vec<(u64, drillx::Hash)> = (0u64..6)
+        .filter_map(|nonce| Some((nonce, drillx::hash(&CHALLENGE, &nonce.to_le_bytes()).ok()?)))
+        .collect();
+    let nonces: Vec<u64> = hashes.iter().map(|(nonce, _)| *nonce).collect();
+    let confirmations = confirm_candidates(&CHALLENGE, &nonces, 0);
+    for ((nonce, hash), confirmation) in hashes.iter().zip(confirmations) {
+        let Confirmation::Confirmed(scored) = confirmation else {
+            panic!("nonce {} not confirmed: {:?}", nonce, confirmation);
+        };
+        assert_eq!(scored.solution.d, hash.d);
+        assert_eq!(scored.solution.n, nonce.to_le_bytes());
+        assert_eq!(scored.hash, hash.h);
+        assert_eq!(scored.difficulty, hash.difficulty());
+        assert!(scored.solution.is_valid(&CHALLENGE));
+    }
+}
+
+#[test]
+fn test_below_difficulty_and_no_solutions() {
+    // The vectors include a challenge and nonce without solutions.
+    let challenge = [0x5a; 32];
+    let hash = drillx::hash(&challenge, &0u64.to_le_bytes()).unwrap();
+    let confirmations = confirm_candidates(&challenge, &[0, 1], hash.difficulty() + 1);
+    assert_eq!(
+        confirmations,
+        vec![
+            Confirmation::BelowDifficulty {
+                best: hash.difficulty()
+            },
+            Confirmation::NoSolutions
+        ]
+    );
+}
+
+#[test]
+fn test_order_and_duplicates() {
+    let nonces = [9, 3, 9, 7, 3, 9];
+    let confirmations = confirm_candidates(&CHALLENGE, &nonces, 0);
+    assert_eq!(confirmations.len(), nonces.len());
+    assert_eq!(confirmations[2], Confirmation::Duplicate { first: 0 });
+    assert_eq!(confirmations[4], Confirmation::Duplicate { first: 1 });
+    assert_eq!(confirmations[5], Confirmation::Duplicate { first: 0 });
+    for (i, nonce) in [(0, 9u64), (1, 3), (3, 7)] {
+        match confirmations[i] {
+            Confirmation::Confirmed(scored) => assert_eq!(scored.solution.n, nonce.to_le_bytes()),
+            Confirmation::NoSolutions => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+    assert!(confirm_candidates(&CHALLENGE, &[], 0).is_empty());
+}
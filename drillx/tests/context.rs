@@ -0,0 +1,148 @@
+use drillx::{Context, DrillxError, EquixSolver, Interleaved, RuntimeOption, Solver};
+
+/// Fails every compile attempt while `failing` is set.
+struct FlakySolver {
+    inner: EquixSolver,
+    failing: bool,
+    compiles: usize,
+}
+
+impl Solver for FlakySolver {
+    fn solve(&mut self, seed: &[u8], runtime: RuntimeOption) -> Result<[u8; 16], DrillxError> {
+        if runtime == RuntimeOption::RequireCompile {
+            self.compiles += 1;
+            if self.failing {
+                return Err(DrillxError::CompileFailed);
+            }
+        }
+        self.inner.solve(seed, RuntimeOption::InterpretOnly)
+    }
+}
+
+fn flaky() -> FlakySolver {
+    FlakySolver {
+        inner: EquixSolver::new(),
+        failing: true,
+        compiles: 0,
+    }
+}
+
+#[test]
+fn test_context_matches_hash() {
+    let challenge = [255; 32];
+    for runtime in [
+        RuntimeOption::TryCompile,
+        RuntimeOption::InterpretOnly,
+        RuntimeOption::RequireCompile,
+    ] {
+        let mut context = Context::new(runtime);
+        for n in 0..4u64 {
+            let nonce = n.to_le_bytes();
+            let a = context.hash(&challenge, &nonce);
+            let b = drillx::hash(&challenge, &nonce);
+            match (a, b) {
+                (Ok(a), Ok(b)) => assert_eq!(a.h, b.h),
+                (Err(DrillxError::CompileFailed | DrillxError::CompilerUnsupported), _) => {
+                    assert_eq!(runtime, RuntimeOption::RequireCompile)
+                }
+                (a, b) => assert_eq!(a.err(), b.err()),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_interleaved_matches_hash() {
+    let challenge = [7; 32];
+    let mut plain = Context::new(RuntimeOption::TryCompile);
+    for interleave in [0, 1, 2, 3] {
+        let mut context = Context::interleaved(RuntimeOption::TryCompile, interleave);
+        assert_eq!(context.solver_mut().len(), interleave.max(1) as usize);
+        for n in 0..8u64 {
+            let nonce = n.to_le_bytes();
+            let a = context.hash(&challenge, &nonce).map(|h| h.h);
+            assert_eq!(a, plain.hash(&challenge, &nonce).map(|h| h.h));
+            assert_eq!(a, drillx::hash(&challenge, &nonce).map(|h| h.h));
+        }
+    }
+}
+
+#[test]
+fn test_interleaved_takes_turns() {
+    let mut interleaved = Interleaved::new(vec![flaky(), flaky()]);
+    for _ in 0..3 {
+        interleaved
+            .solve(&[0; 40], RuntimeOption::RequireCompile)
+            .unwrap_err();
+    }
+    let compiles: Vec<_> = interleaved
+        .solvers_mut()
+        .iter()
+        .map(|s| s.compiles)
+        .collect();
+    assert_eq!(compiles, [2, 1]);
+}
+
+/// The watchdog only runs where `TryCompile` is not already the interpreter.
+#[cfg(all(
+    feature = "compiler",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+#[test]
+fn test_watchdog_downgrades() {
+    let challenge = [255; 32];
+    let mut context = Context::with_solver(flaky(), RuntimeOption::TryCompile).failure_threshold(3);
+    for n in 0..3u64 {
+        assert_eq!(context.current_runtime(), RuntimeOption::TryCompile);
+        let hash = context.hash(&challenge, &n.to_le_bytes()).unwrap();
+        assert_eq!(
+            hash.h,
+            drillx::hash(&challenge, &n.to_le_bytes()).unwrap().h
+        );
+    }
+    assert_eq!(context.current_runtime(), RuntimeOption::InterpretOnly);
+    assert!(context.is_downgraded());
+    assert_eq!(context.downgrades(), 1);
+
+    // The downgrade is sticky
+    context.hash(&challenge, &7u64.to_le_bytes()).unwrap();
+    assert_eq!(context.solver_mut().compiles, 3);
+
+    // Resetting retries compilation
+    context.solver_mut().failing = false;
+    context.reset_runtime();
+    assert_eq!(context.current_runtime(), RuntimeOption::TryCompile);
+    context.hash(&challenge, &0u64.to_le_bytes()).unwrap();
+    assert_eq!(context.solver_mut().compiles, 4);
+    assert!(!context.is_downgraded());
+}
+
+/// The watchdog only runs where `TryCompile` is not already the interpreter.
+#[cfg(all(
+    feature = "compiler",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+#[test]
+fn test_watchdog_needs_consecutive_failures() {
+    let challenge = [255; 32];
+    let mut context = Context::with_solver(flaky(), RuntimeOption::TryCompile).failure_threshold(2);
+    context.hash(&challenge, &0u64.to_le_bytes()).unwrap();
+    context.solver_mut().failing = false;
+    context.hash(&challenge, &1u64.to_le_bytes()).unwrap();
+    context.solver_mut().failing = true;
+    context.hash(&challenge, &7u64.to_le_bytes()).unwrap();
+    assert_eq!(context.current_runtime(), RuntimeOption::TryCompile);
+    assert_eq!(context.downgrades(), 0);
+}
+
+#[test]
+fn test_required_compile_failures_surface() {
+    let mut context = Context::with_solver(flaky(), RuntimeOption::RequireCompile);
+    for n in 0..16u64 {
+        assert_eq!(
+            context.hash(&[255; 32], &n.to_le_bytes()).err(),
+            Some(DrillxError::CompileFailed)
+        );
+    }
+    assert_eq!(context.current_runtime(), RuntimeOption::RequireCompile);
+}
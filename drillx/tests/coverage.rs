@@ -0,0 +1,173 @@
+use drillx::work::{audit_coverage, NonceRange, Overlap, WorkUnit};
+
+fn unit(id: u64, challenge: u8, start: u64, count: u64) -> WorkUnit {
+    WorkUnit {
+        id,
+        challenge: [challenge; 32],
+        authority: [0; 32],
+        start,
+        count,
+        min_difficulty: 0,
+        expires_unix: 0,
+    }
+}
+
+fn range(start: u64, last: u64) -> NonceRange {
+    NonceRange { start, last }
+}
+
+#[test]
+fn test_clean_tiling() {
+    // Handed out out of order, as a coordinator might.
+    let units = [
+        unit(2, 1, 2000, 1000),
+        unit(0, 1, 0, 1000),
+        unit(1, 1, 1000, 1000),
+    ];
+    let report = audit_coverage(&units);
+    assert!(report.is_clean());
+    assert_eq!(report.challenges.len(), 1);
+    let coverage = &report.challenges[0];
+    assert_eq!(coverage.units, 3);
+    assert_eq!(coverage.span, Some(range(0, 2999)));
+    assert_eq!(coverage.covered, 3000);
+    assert_eq!(coverage.assigned, 3000);
+    assert!(coverage.truncated.is_empty() && coverage.empty.is_empty());
+}
+
+#[test]
+fn test_overlaps_and_gaps() {
+    let units = [
+        unit(10, 1, 0, 100),
+        unit(11, 1, 50, 100),
+        unit(12, 1, 120, 10),
+        unit(13, 1, 300, 50),
+        unit(14, 1, 1000, 1),
+        unit(15, 1, 500, 0),
+    ];
+    let report = audit_coverage(&units);
+    assert!(!report.is_clean());
+    let coverage = &report.challenges[0];
+    assert_eq!(
+        coverage.overlaps,
+        vec![
+            Overlap {
+                first_id: 10,
+                second_id: 11,
+                range: range(50, 99),
+            },
+            Overlap {
+                first_id: 11,
+                second_id: 12,
+                range: range(120, 129),
+            },
+        ]
+    );
+    assert_eq!(coverage.overlaps[0].range.size(), 50);
+    assert_eq!(coverage.gaps, vec![range(150, 299), range(350, 999)]);
+    assert_eq!(coverage.span, Some(range(0, 1000)));
+    assert_eq!(coverage.covered, 150 + 50 + 1);
+    assert_eq!(coverage.assigned, 100 + 100 + 10 + 50 + 1);
+    assert_eq!(coverage.empty, vec![15]);
+    assert_eq!(coverage.units, 6);
+}
+
+#[test]
+fn test_nested_and_repeated_units() {
+    // A unit inside another overlaps it alone, and repeats overlap pairwise.
+    let units = [
+        unit(1, 1, 0, 1000),
+        unit(2, 1, 100, 10),
+        unit(3, 1, 500, 10),
+        unit(4, 1, 500, 10),
+    ];
+    let coverage = &audit_coverage(&units).challenges[0];
+    let pairs: Vec<_> = coverage
+        .overlaps
+        .iter()
+        .map(|o| (o.first_id, o.second_id, o.range))
+        .collect();
+    assert_eq!(
+        pairs,
+        vec![
+            (1, 2, range(100, 109)),
+            (1, 3, range(500, 509)),
+            (1, 4, range(500, 509)),
+            (3, 4, range(500, 509)),
+        ]
+    );
+    assert_eq!(coverage.covered, 1000);
+    assert_eq!(coverage.assigned, 1030);
+    assert!(coverage.gaps.is_empty());
+}
+
+#[test]
+fn test_boundary_and_wraparound_ranges() {
+    let units = [
+        // Would run 10 past u64::MAX, so it ends there.
+        unit(1, 1, u64::MAX - 9, 20),
+        unit(2, 1, u64::MAX, 1),
+        unit(3, 1, 0, u64::MAX),
+    ];
+    let coverage = &audit_coverage(&units).challenges[0];
+    assert_eq!(coverage.truncated, vec![1]);
+    assert_eq!(coverage.span, Some(range(0, u64::MAX)));
+    // The whole space, which does not fit in a u64.
+    assert_eq!(coverage.covered, 1 << 64);
+    assert!(coverage.gaps.is_empty());
+    assert_eq!(
+        coverage.overlaps,
+        vec![
+            Overlap {
+                first_id: 3,
+                second_id: 1,
+                range: range(u64::MAX - 9, u64::MAX - 1),
+            },
+            Overlap {
+                first_id: 1,
+                second_id: 2,
+                range: range(u64::MAX, u64::MAX),
+            },
+        ]
+    );
+    assert_eq!(coverage.assigned, 10 + 1 + u64::MAX as u128);
+
+    // Huge units cost no more than small ones.
+    let halves = [unit(1, 2, 0, 1 << 63), unit(2, 2, (1 << 63) + 1, 1 << 62)];
+    let coverage = &audit_coverage(&halves).challenges[0];
+    assert_eq!(coverage.gaps, vec![range(1 << 63, 1 << 63)]);
+    assert_eq!(coverage.covered, (1 << 63) + (1 << 62));
+}
+
+#[test]
+fn test_mixed_challenges_and_duplicate_ids() {
+    let units = [
+        unit(1, 7, 0, 100),
+        unit(2, 3, 0, 100),
+        unit(3, 7, 100, 100),
+        unit(2, 3, 200, 100),
+        unit(1, 9, 0, 5),
+    ];
+    let report = audit_coverage(&units);
+    assert_eq!(report.duplicate_ids, vec![1, 2]);
+    let challenges: Vec<_> = report.challenges.iter().map(|c| c.challenge[0]).collect();
+    assert_eq!(challenges, vec![7, 3, 9]);
+
+    // Overlapping nonces on different challenges are not overlaps.
+    assert!(report.challenges.iter().all(|c| c.overlaps.is_empty()));
+    assert!(report.challenges[0].gaps.is_empty());
+    assert_eq!(report.challenges[1].gaps, vec![range(100, 199)]);
+    assert_eq!(report.challenges[2].covered, 5);
+
+    let json = serde_json::to_string(&report).unwrap();
+    assert_eq!(
+        serde_json::from_str::<drillx::work::CoverageReport>(&json).unwrap(),
+        report
+    );
+
+    assert!(audit_coverage(&[]).is_clean());
+    let empty = &audit_coverage(&[unit(1, 1, 5, 0)]).challenges[0];
+    assert_eq!(empty.span, None);
+    assert_eq!(empty.covered, 0);
+    assert_eq!(empty.empty, vec![1]);
+}
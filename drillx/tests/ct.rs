@@ -0,0 +1,75 @@
+use drillx::{
+    commit_reveal::{commit, verify_reveal, Commitment},
+    ct_eq_digest, ct_eq_hash,
+    vectors::VECTORS,
+    Solution, SolutionV2,
+};
+
+#[test]
+fn test_ct_eq_digest() {
+    let digest: [u8; 16] = std::array::from_fn(|i| i as u8 * 17);
+    assert!(ct_eq_digest(&digest, &digest));
+    assert!(ct_eq_digest(&[0; 16], &[0; 16]));
+    for i in 0..16 {
+        for bit in 0..8 {
+            let mut other = digest;
+            other[i] ^= 1 << bit;
+            assert!(!ct_eq_digest(&digest, &other), "byte {} bit {}", i, bit);
+            assert!(!ct_eq_digest(&other, &digest));
+        }
+    }
+    assert!(!ct_eq_digest(&[0; 16], &[0xff; 16]));
+}
+
+#[test]
+fn test_ct_eq_hash() {
+    let hash: [u8; 32] = std::array::from_fn(|i| 255 - i as u8);
+    assert!(ct_eq_hash(&hash, &hash));
+    for i in 0..32 {
+        let mut other = hash;
+        other[i] = other[i].wrapping_add(1);
+        assert!(!ct_eq_hash(&hash, &other), "byte {}", i);
+    }
+    assert!(!ct_eq_hash(&[0; 32], &[0xff; 32]));
+}
+
+#[test]
+fn test_solution_ct_eq() {
+    let solution = Solution::from_bytes(std::array::from_fn(|i| i as u8));
+    assert!(solution.ct_eq(&solution));
+    for i in 0..24 {
+        let mut bytes = solution.to_bytes();
+        bytes[i] ^= 0x40;
+        let other = Solution::from_bytes(bytes);
+        assert!(!solution.ct_eq(&other), "byte {}", i);
+        assert_eq!(solution.ct_eq(&other), solution == other);
+    }
+}
+
+#[test]
+fn test_verification_paths() {
+    // Commitment checks reject a difference at any byte.
+    let vector = &VECTORS[0];
+    let solution = Solution::new(vector.output.unwrap().digest, vector.nonce);
+    let salt = [1; 32];
+    let commitment = commit(&solution, &salt);
+    for i in 0..32 {
+        let mut tampered = commitment;
+        tampered[i] ^= 1;
+        assert!(!verify_reveal(&tampered, &solution, &salt), "byte {}", i);
+        assert_ne!(Commitment(tampered), Commitment(commitment));
+    }
+
+    // Strict checks compare the digest at the claimed index.
+    let v2 = SolutionV2::from_legacy(&vector.challenge, &solution).unwrap();
+    assert!(v2.is_valid_strict(&vector.challenge));
+    let (vector, second) = VECTORS
+        .iter()
+        .find_map(|v| {
+            let solutions = drillx::hash_all(&v.challenge, &v.nonce).ok()?;
+            Some((v, solutions.get(1)?.d))
+        })
+        .expect("a vector with two solutions");
+    assert!(!SolutionV2::new(second, vector.nonce, 0).is_valid_strict(&vector.challenge));
+    assert!(SolutionV2::new(second, vector.nonce, 1).is_valid_strict(&vector.challenge));
+}
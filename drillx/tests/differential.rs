@@ -0,0 +1,50 @@
+use drillx::DrillxMemory;
+
+/// SplitMix64, for reproducible pseudo-random inputs.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    fn challenge(&mut self) -> [u8; 32] {
+        let mut challenge = [0; 32];
+        for chunk in challenge.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next().to_le_bytes());
+        }
+        challenge
+    }
+}
+
+#[test]
+fn test_hash_paths_agree() {
+    let mut rng = Rng(0xd1);
+    let mut memory = DrillxMemory::new();
+    let mut errors = 0;
+    for _ in 0..2000 {
+        let challenge = rng.challenge();
+        let nonce = rng.next().to_le_bytes();
+        let a = drillx::hash(&challenge, &nonce);
+        let b = drillx::hash_with_memory(&mut memory, &challenge, &nonce);
+        match (a, b) {
+            (Ok(a), Ok(b)) => {
+                assert_eq!(a.d, b.d);
+                assert_eq!(a.h, b.h);
+                assert!(drillx::is_valid_digest(&challenge, &nonce, &a.d));
+            }
+            (Err(a), Err(b)) => {
+                assert_eq!(a, b);
+                errors += 1;
+            }
+            (a, b) => panic!("hash paths disagree: {:?} vs {:?}", a.err(), b.err()),
+        }
+    }
+
+    // Roughly one in eight seeds has no solutions
+    assert!(errors > 0);
+}
@@ -0,0 +1,109 @@
+use drillx::{difficulty, difficulty_fractional, Hash};
+
+/// A small deterministic generator so failures reproduce.
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn hash(&mut self) -> [u8; 32] {
+        let mut hash = [0; 32];
+        for chunk in hash.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next().to_le_bytes());
+        }
+        hash
+    }
+}
+
+/// Clears the first `zeros` bits and sets the next one.
+fn with_zeros(mut hash: [u8; 32], zeros: usize) -> [u8; 32] {
+    for bit in 0..zeros.min(256) {
+        hash[bit / 8] &= !(0x80 >> (bit % 8));
+    }
+    if zeros < 256 {
+        hash[zeros / 8] |= 0x80 >> (zeros % 8);
+    }
+    hash
+}
+
+fn check(hash: &[u8; 32]) -> f64 {
+    let fractional = difficulty_fractional(hash);
+    assert_eq!(
+        fractional.floor() as u32,
+        difficulty(*hash),
+        "hash {:02x?} fractional {}",
+        hash,
+        fractional
+    );
+    fractional
+}
+
+#[test]
+fn test_endpoints() {
+    assert_eq!(difficulty_fractional(&[0; 32]), 256.0);
+    assert_eq!(difficulty_fractional(&[0xff; 32]), 0.0);
+    let mut one = [0; 32];
+    one[31] = 1;
+    // 256 - log2(2)
+    assert_eq!(difficulty_fractional(&one), 255.0);
+    let mut half = [0; 32];
+    half[0] = 0x80;
+    assert!(check(&half) < 1.0);
+}
+
+#[test]
+fn test_floor_matches_difficulty() {
+    let mut rng = SplitMix(1);
+    for _ in 0..200 {
+        let hash = rng.hash();
+        for zeros in 0..=256 {
+            check(&with_zeros(hash, zeros));
+        }
+    }
+}
+
+#[test]
+fn test_boundaries() {
+    for zeros in 0..256 {
+        // The smallest and largest hashes with this many leading zeros.
+        let low = with_zeros([0; 32], zeros);
+        let high = with_zeros([0xff; 32], zeros);
+        let (low, high) = (check(&low), check(&high));
+        assert!(low >= high);
+        assert!(high >= zeros as f64);
+    }
+}
+
+#[test]
+fn test_monotonic() {
+    let mut rng = SplitMix(2);
+    for _ in 0..5000 {
+        let zeros = (rng.next() % 257) as usize;
+        let a = with_zeros(rng.hash(), zeros);
+        let b = with_zeros(rng.hash(), (rng.next() % 257) as usize);
+        if a < b {
+            assert!(check(&a) >= check(&b));
+        } else {
+            assert!(check(&a) <= check(&b));
+        }
+    }
+}
+
+#[test]
+fn test_hash_method() {
+    let hash = Hash {
+        d: [0; 16],
+        h: with_zeros([0x5a; 32], 13),
+    };
+    assert_eq!(
+        hash.difficulty_fractional().floor() as u32,
+        hash.difficulty()
+    );
+    assert!(hash.difficulty_fractional() > 13.0);
+}
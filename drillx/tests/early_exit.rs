@@ -0,0 +1,55 @@
+use drillx::{
+    hash, hash_first, hash_first_with_memory, is_valid_digest, DrillxError, DrillxMemory, Solution,
+    EARLY_EXIT_SUPPORTED,
+};
+
+#[test]
+fn test_early_exit_digests_verify() {
+    let mut memory = DrillxMemory::new();
+    for (i, challenge) in [[0; 32], [255; 32], [170; 32]].iter().enumerate() {
+        for nonce in 0..100u64 {
+            let nonce = nonce.to_le_bytes();
+            match (
+                hash_first_with_memory(&mut memory, challenge, &nonce),
+                hash(challenge, &nonce),
+            ) {
+                (Ok(first), Ok(full)) => {
+                    assert!(
+                        is_valid_digest(challenge, &nonce, &first.d),
+                        "{} {:?}",
+                        i,
+                        nonce
+                    );
+                    let solution = Solution::new(first.d, nonce);
+                    assert!(solution.is_valid(challenge));
+                    assert_eq!(solution.to_hash().h, first.h);
+                    if !EARLY_EXIT_SUPPORTED {
+                        assert_eq!((first.d, first.h), (full.d, full.h));
+                    }
+                }
+                (Err(first), Err(full)) => {
+                    assert_eq!(first, DrillxError::NoSolutions);
+                    assert_eq!(full, DrillxError::NoSolutions);
+                }
+                (first, full) => panic!(
+                    "hash_first ok: {}, hash ok: {}, at {:?}",
+                    first.is_ok(),
+                    full.is_ok(),
+                    nonce
+                ),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_hash_first_matches_memory_variant() {
+    let mut memory = DrillxMemory::new();
+    let challenge = [7; 32];
+    for nonce in 0..20u64 {
+        let nonce = nonce.to_le_bytes();
+        let first = hash_first(&challenge, &nonce).map(|h| (h.d, h.h));
+        let reused = hash_first_with_memory(&mut memory, &challenge, &nonce).map(|h| (h.d, h.h));
+        assert_eq!(first, reused);
+    }
+}
@@ -0,0 +1,83 @@
+use drillx::{
+    error_code::{
+        decode_error, BAD_EQUIX, BASE, COMPILER_UNSUPPORTED, COMPILE_FAILED,
+        INSUFFICIENT_DIFFICULTY, INVALID_SOLUTION, LAST, NO_SOLUTIONS,
+    },
+    DrillxError,
+};
+
+const ERRORS: [DrillxError; 4] = [
+    DrillxError::BadEquix,
+    DrillxError::NoSolutions,
+    DrillxError::CompileFailed,
+    DrillxError::CompilerUnsupported,
+];
+
+#[test]
+fn test_error_codes_are_pinned() {
+    // Released codes. Never change these.
+    assert_eq!(BASE, 0x4452_0000);
+    assert_eq!(LAST, 0x4452_ffff);
+    assert_eq!(BAD_EQUIX, 0x4452_0000);
+    assert_eq!(NO_SOLUTIONS, 0x4452_0001);
+    assert_eq!(COMPILE_FAILED, 0x4452_0002);
+    assert_eq!(COMPILER_UNSUPPORTED, 0x4452_0003);
+    assert_eq!(INVALID_SOLUTION, 0x4452_0004);
+    assert_eq!(INSUFFICIENT_DIFFICULTY, 0x4452_0005);
+    let codes: Vec<u32> = ERRORS.iter().map(|err| err.code()).collect();
+    assert_eq!(codes, [0x4452_0000, 0x4452_0001, 0x4452_0002, 0x4452_0003]);
+}
+
+#[test]
+fn test_decode_error() {
+    for err in ERRORS {
+        assert!((BASE..=LAST).contains(&err.code()));
+        assert_eq!(decode_error(err.code()), Some(err));
+    }
+    for code in [
+        0,
+        1,
+        BASE - 1,
+        INVALID_SOLUTION,
+        INSUFFICIENT_DIFFICULTY,
+        LAST,
+        u32::MAX,
+    ] {
+        assert_eq!(decode_error(code), None);
+    }
+}
+
+#[cfg(feature = "solana")]
+#[test]
+fn test_program_error_from_drillx_error() {
+    use solana_program::program_error::ProgramError;
+
+    for err in ERRORS {
+        assert_eq!(ProgramError::from(err), ProgramError::Custom(err.code()));
+    }
+}
+
+#[cfg(feature = "program")]
+#[test]
+fn test_verify_error_codes() {
+    use drillx::program::{decode_verify_error, VerifyError};
+    use solana_program::program_error::ProgramError;
+
+    for (err, code) in [
+        (VerifyError::InvalidSolution, 0x4452_0004),
+        (VerifyError::InsufficientDifficulty, 0x4452_0005),
+    ] {
+        assert_eq!(ProgramError::from(err), ProgramError::Custom(code));
+        assert_eq!(decode_verify_error(code), Some(err));
+        assert_eq!(decode_error(code), None);
+    }
+    for code in [
+        0,
+        1,
+        BAD_EQUIX,
+        COMPILER_UNSUPPORTED,
+        INSUFFICIENT_DIFFICULTY + 1,
+    ] {
+        assert_eq!(decode_verify_error(code), None);
+    }
+}
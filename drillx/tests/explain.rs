@@ -0,0 +1,91 @@
+use drillx::{vectors::VECTORS, EquixCheck, Solution};
+
+/// Deterministic randomness.
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+fn valid() -> ([u8; 32], Solution) {
+    let vector = VECTORS[0];
+    let output = vector.output.unwrap();
+    (vector.challenge, Solution::new(output.digest, vector.nonce))
+}
+
+#[test]
+fn test_explain_valid() {
+    let (challenge, solution) = valid();
+    let explanation = solution.explain(&challenge);
+    assert!(explanation.is_valid());
+    assert!(!explanation.already_sorted());
+    assert_eq!(explanation.hash, solution.to_hash().h);
+    assert_eq!(explanation.difficulty, solution.to_hash().difficulty());
+    assert_eq!(
+        explanation.to_string(),
+        "\
+seed:        00000000000000000000000000000000000000000000000000000000000000000000000000000000
+equix:       passed
+digest:      b45a828ae35b6ec80b4a898cc60bf0d5
+sorted:      c60b0b4ab45ae35b828a898c6ec8f0d5 (reordered)
+keccak in:   c60b0b4ab45ae35b828a898c6ec8f0d50000000000000000
+keccak out:  c1ca5f77bfe25845b1a08450066c5b946fdd05f9e95b18ec810f398cac4a04da
+difficulty:  0"
+    );
+}
+
+#[test]
+fn test_explain_corrupted() {
+    let (challenge, mut solution) = valid();
+    solution.d[15] ^= 1;
+    let explanation = drillx::explain(&challenge, &solution);
+    assert_eq!(explanation.equix, EquixCheck::HashSum);
+    assert_eq!(explanation.hash, solution.to_hash().h);
+    assert_eq!(
+        explanation.to_string(),
+        "\
+seed:        00000000000000000000000000000000000000000000000000000000000000000000000000000000
+equix:       failed: hash sum (digest does not solve seed)
+digest:      b45a828ae35b6ec80b4a898cc60bf0d4
+sorted:      c60b0b4ab45ae35b828a898c6ec8f0d4 (reordered)
+keccak in:   c60b0b4ab45ae35b828a898c6ec8f0d40000000000000000
+keccak out:  31d94817a6bd7723a85835f97c2eca63376b2233b85eec8df89abb99734425f5
+difficulty:  2"
+    );
+}
+
+#[test]
+fn test_explain_sorted_digest() {
+    // Sorting keeps the hash, but equix needs the digest in the solver's order.
+    let (challenge, solution) = valid();
+    let explanation = solution.explain(&challenge);
+    let sorted = Solution::new(explanation.sorted_digest, solution.n).explain(&challenge);
+    assert!(sorted.already_sorted());
+    assert!(!sorted.is_valid());
+    assert_eq!(sorted.hash, explanation.hash);
+}
+
+#[test]
+fn test_explain_garbage() {
+    let mut rng = SplitMix(152);
+    for _ in 0..256 {
+        let mut challenge = [0; 32];
+        for chunk in challenge.chunks_mut(8) {
+            chunk.copy_from_slice(&rng.next().to_le_bytes());
+        }
+        let mut digest = [0; 16];
+        digest[..8].copy_from_slice(&rng.next().to_le_bytes());
+        digest[8..].copy_from_slice(&rng.next().to_le_bytes());
+        let solution = Solution::new(digest, rng.next().to_le_bytes());
+        let explanation = solution.explain(&challenge);
+        assert_eq!(explanation.is_valid(), solution.is_valid(&challenge));
+        assert_eq!(explanation.difficulty, solution.to_hash().difficulty());
+        assert_eq!(explanation.to_string().lines().count(), 7);
+    }
+}
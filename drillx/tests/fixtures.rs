@@ -0,0 +1,51 @@
+#![cfg(feature = "test-support")]
+
+use drillx::fixtures::{find_fixture, fixture_at_least, FIXTURES, FIXTURE_CHALLENGE};
+
+#[test]
+fn test_fixtures_are_valid() {
+    for fixture in FIXTURES {
+        let solution = fixture.solution();
+        assert!(solution.is_valid(&fixture.challenge), "{:?}", fixture);
+        assert_eq!(solution.to_hash().difficulty(), fixture.difficulty);
+        // The digest is the one the solver returns, not just any valid one.
+        let hash = drillx::hash(&fixture.challenge, &fixture.nonce.to_le_bytes()).unwrap();
+        assert_eq!(hash.d, fixture.digest);
+    }
+    assert!(FIXTURES
+        .windows(2)
+        .all(|w| w[0].difficulty < w[1].difficulty));
+    let difficulties: Vec<u32> = FIXTURES.iter().map(|f| f.difficulty).collect();
+    assert_eq!(difficulties, [1, 4, 8, 12]);
+}
+
+#[test]
+fn test_fixture_at_least() {
+    assert_eq!(fixture_at_least(0).difficulty, 1);
+    assert_eq!(fixture_at_least(1).difficulty, 1);
+    assert_eq!(fixture_at_least(2).difficulty, 4);
+    assert_eq!(fixture_at_least(8).difficulty, 8);
+    assert_eq!(fixture_at_least(9).difficulty, 12);
+    assert_eq!(fixture_at_least(12).difficulty, 12);
+}
+
+#[test]
+#[should_panic(expected = "no fixture has difficulty 13")]
+fn test_fixture_at_least_too_hard() {
+    fixture_at_least(13);
+}
+
+#[test]
+fn test_find_fixture() {
+    // The difficulty 8 fixture is the first nonce at difficulty 4 or more.
+    let (nonce, solution) = find_fixture(&FIXTURE_CHALLENGE, 4, 100).unwrap();
+    assert_eq!(nonce, 5);
+    assert_eq!(solution, fixture_at_least(8).solution());
+
+    assert_eq!(find_fixture(&FIXTURE_CHALLENGE, 4, 5), None);
+    assert_eq!(find_fixture(&FIXTURE_CHALLENGE, 0, 0), None);
+    let (nonce, solution) = find_fixture(&[7; 32], 2, 1_000).unwrap();
+    assert!(solution.is_valid(&[7; 32]));
+    assert!(solution.to_hash().difficulty() >= 2);
+    assert_eq!(solution.n, nonce.to_le_bytes());
+}
@@ -0,0 +1,60 @@
+use drillx::{is_valid_digest_generic, seed_generic, Solution, MAX_CHALLENGE_LEN};
+
+#[test]
+fn test_generic_32_matches() {
+    let challenge = [0xa5; 32];
+    for n in 0..8u64 {
+        let nonce = n.to_le_bytes();
+        assert_eq!(
+            seed_generic(&challenge, &nonce).as_bytes(),
+            drillx::seed(&challenge, &nonce).data
+        );
+        let (a, b) = (
+            drillx::hash_generic(&challenge, &nonce),
+            drillx::hash(&challenge, &nonce),
+        );
+        match (a, b) {
+            (Ok(a), Ok(b)) => {
+                assert_eq!((a.d, a.h), (b.d, b.h));
+                assert!(is_valid_digest_generic(&challenge, &nonce, &a.d));
+                assert!(Solution::new(a.d, nonce).is_valid(&challenge));
+            }
+            (a, b) => assert_eq!(a.err(), b.err()),
+        }
+    }
+}
+
+#[test]
+fn test_generic_48_round_trip() {
+    let challenge: [u8; 48] = std::array::from_fn(|i| i as u8);
+    let truncated: [u8; 32] = challenge[..32].try_into().unwrap();
+    let (nonce, hash) = (0..64u64)
+        .find_map(|n| {
+            let nonce = n.to_le_bytes();
+            let hash = drillx::hash_generic(&challenge, &nonce).ok()?;
+            (hash.difficulty() >= 4).then_some((nonce, hash))
+        })
+        .unwrap();
+    let solution = Solution::new(hash.d, nonce);
+    assert!(is_valid_digest_generic(&challenge, &nonce, &hash.d));
+    assert_eq!(solution.to_hash().h, hash.h);
+    assert!(solution.to_hash().difficulty() >= 4);
+
+    // The whole challenge is bound, not just its first 32 bytes.
+    assert!(!solution.is_valid(&truncated));
+    let mut other = challenge;
+    other[47] ^= 1;
+    assert!(!is_valid_digest_generic(&other, &nonce, &hash.d));
+}
+
+#[test]
+fn test_generic_bounds() {
+    let nonce = 7u64.to_le_bytes();
+    let short = seed_generic(&[9], &nonce);
+    assert_eq!(short.as_bytes(), [&[9][..], &nonce].concat());
+    let long = seed_generic(&[3; MAX_CHALLENGE_LEN], &nonce);
+    assert_eq!(long.as_bytes().len(), MAX_CHALLENGE_LEN + 8);
+    assert_eq!(&long.as_bytes()[MAX_CHALLENGE_LEN..], nonce);
+    assert!(drillx::hash_generic(&[3; MAX_CHALLENGE_LEN], &nonce)
+        .is_ok_and(|hash| is_valid_digest_generic(&[3; MAX_CHALLENGE_LEN], &nonce, &hash.d)));
+}
@@ -0,0 +1,71 @@
+use drillx::{
+    difficulty, hash, hash_v2, hash_v2_with_memory,
+    vectors::{VECTORS, VECTORS_V2},
+    DrillxMemory, Solution,
+};
+
+#[test]
+fn test_v2_vectors() {
+    let mut memory = DrillxMemory::new();
+    for (i, vector) in VECTORS_V2.iter().enumerate() {
+        assert!(vector.check(&mut memory), "v2 vector {}", i);
+        let solution = Solution::new(vector.digest, vector.nonce);
+        assert_eq!(solution.to_hash_v2(&vector.challenge).h, vector.hash);
+    }
+
+    // Every v1 vector with solutions has a v2 counterpart with the same digest.
+    let solved: Vec<_> = VECTORS.iter().filter(|v| v.output.is_some()).collect();
+    assert_eq!(solved.len(), VECTORS_V2.len());
+    for (v1, v2) in solved.iter().zip(VECTORS_V2) {
+        assert_eq!((v1.challenge, v1.nonce), (v2.challenge, v2.nonce));
+        assert_eq!(v1.output.unwrap().digest, v2.digest);
+    }
+}
+
+#[test]
+fn test_v1_and_v2_differ() {
+    for vector in VECTORS.iter().filter(|v| v.output.is_some()) {
+        let v1 = hash(&vector.challenge, &vector.nonce).unwrap();
+        let v2 = hash_v2(&vector.challenge, &vector.nonce).unwrap();
+        assert_eq!(v1.d, v2.d);
+        assert_ne!(v1.h, v2.h);
+        // v1 is unchanged.
+        assert_eq!(v1.h, vector.output.unwrap().hash);
+        assert_eq!(v2.difficulty(), difficulty(v2.h));
+    }
+    assert!(hash_v2(&VECTORS[6].challenge, &VECTORS[6].nonce).is_err());
+}
+
+#[test]
+fn test_each_version_verifies_only_its_own_hash() {
+    let vector = &VECTORS_V2[0];
+    let solution = Solution::new(vector.digest, vector.nonce);
+    let v1 = solution.to_hash().h;
+    let v2 = solution.to_hash_v2(&vector.challenge).h;
+
+    assert!(solution.is_valid_hash(&vector.challenge, &v1));
+    assert!(!solution.is_valid_hash(&vector.challenge, &v2));
+    assert!(solution.is_valid_hash_v2(&vector.challenge, &v2));
+    assert!(!solution.is_valid_hash_v2(&vector.challenge, &v1));
+
+    // An invalid digest fails either way, even with its own hash.
+    let mut forged = solution;
+    forged.d[0] ^= 1;
+    assert!(!forged.is_valid_hash(&vector.challenge, &forged.to_hash().h));
+    assert!(!forged.is_valid_hash_v2(&vector.challenge, &forged.to_hash_v2(&vector.challenge).h));
+}
+
+#[test]
+fn test_v2_hash_is_bound_to_challenge() {
+    let vector = &VECTORS_V2[0];
+    let solution = Solution::new(vector.digest, vector.nonce);
+    let other = [1; 32];
+    assert_ne!(
+        solution.to_hash_v2(&vector.challenge).h,
+        solution.to_hash_v2(&other).h
+    );
+
+    let mut memory = DrillxMemory::new();
+    let hash = hash_v2_with_memory(&mut memory, &vector.challenge, &vector.nonce).unwrap();
+    assert_eq!(hash.h, vector.hash);
+}
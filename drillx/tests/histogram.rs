@@ -0,0 +1,190 @@
+use std::{sync::Arc, thread, time::Duration};
+
+use drillx::{difficulty, miner::MinerBuilder, DifficultyHistogram, HistogramSnapshot};
+
+/// A small deterministic generator so failures reproduce.
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn hash(&mut self) -> [u8; 32] {
+        let mut hash = [0; 32];
+        for chunk in hash.chunks_mut(8) {
+            chunk.copy_from_slice(&self.next().to_le_bytes());
+        }
+        hash
+    }
+}
+
+fn histogram(counts: &[u64]) -> DifficultyHistogram {
+    let histogram = DifficultyHistogram::new();
+    for (difficulty, &count) in counts.iter().enumerate() {
+        for _ in 0..count {
+            histogram.record(difficulty as u32);
+        }
+    }
+    histogram
+}
+
+#[test]
+fn test_concurrent_record() {
+    let histogram = Arc::new(DifficultyHistogram::new());
+    let workers: Vec<_> = (0..8u32)
+        .map(|t| {
+            let histogram = histogram.clone();
+            thread::spawn(move || {
+                for i in 0..10_000u32 {
+                    histogram.record((i + t) % 40);
+                }
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    assert_eq!(histogram.total(), 80_000);
+    assert_eq!((0..40).map(|d| histogram.count(d)).sum::<u64>(), 80_000);
+    assert_eq!(histogram.count(0), 2_000);
+    assert_eq!(histogram.snapshot().counts.len(), 40);
+}
+
+#[test]
+fn test_record_clamps() {
+    let histogram = DifficultyHistogram::new();
+    histogram.record(256);
+    histogram.record(u32::MAX);
+    assert_eq!(histogram.count(256), 2);
+    assert_eq!(histogram.count(257), 0);
+    assert_eq!(histogram.snapshot().counts.len(), 257);
+    assert_eq!(histogram.percentile(50.0), 256);
+}
+
+#[test]
+fn test_merge() {
+    let a = histogram(&[1, 2, 3]);
+    let b = histogram(&[0, 0, 0, 0, 5]);
+    a.merge(&b);
+    assert_eq!(a.snapshot().counts, vec![1, 2, 3, 0, 5]);
+    assert_eq!(b.total(), 5);
+
+    let mut snapshot = b.snapshot();
+    snapshot.merge(&histogram(&[1, 1]).snapshot());
+    assert_eq!(snapshot.counts, vec![1, 1, 0, 0, 5]);
+}
+
+#[test]
+fn test_percentile() {
+    assert_eq!(DifficultyHistogram::new().percentile(50.0), 0);
+
+    // Ten of each difficulty from 0 to 9.
+    let uniform = histogram(&[10; 10]);
+    assert_eq!(uniform.percentile(0.0), 0);
+    assert_eq!(uniform.percentile(10.0), 0);
+    assert_eq!(uniform.percentile(10.1), 1);
+    assert_eq!(uniform.percentile(50.0), 4);
+    assert_eq!(uniform.percentile(95.0), 9);
+    assert_eq!(uniform.percentile(100.0), 9);
+    assert_eq!(uniform.percentile(250.0), 9);
+    assert_eq!(uniform.percentile(f64::NAN), 0);
+
+    // Halving counts, as from honest hashing.
+    let halving = histogram(&[512, 256, 128, 64, 32, 16, 8, 4, 2, 1, 1]);
+    assert_eq!(halving.percentile(50.0), 0);
+    assert_eq!(halving.percentile(75.0), 1);
+    assert_eq!(halving.percentile(99.0), 6);
+    assert_eq!(halving.percentile(100.0), 10);
+
+    let single = histogram(&[0, 0, 0, 0, 0, 0, 0, 1]);
+    assert_eq!(single.percentile(1.0), 7);
+    assert_eq!(single.percentile(99.0), 7);
+}
+
+#[test]
+fn test_expected_count_at() {
+    assert_eq!(DifficultyHistogram::expected_count_at(0, 1024), 512.0);
+    assert_eq!(DifficultyHistogram::expected_count_at(9, 1024), 1.0);
+    assert_eq!(DifficultyHistogram::expected_count_at(300, 1024), 0.0);
+    // The probabilities of every difficulty sum to one.
+    let sum: f64 = (0..=256)
+        .map(|d| DifficultyHistogram::expected_count_at(d, 1))
+        .sum();
+    assert!((sum - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn test_geometric_sample() {
+    let total = 1 << 20;
+    let mut rng = SplitMix(0x4849_5354);
+    let histogram = DifficultyHistogram::new();
+    for _ in 0..total {
+        histogram.record(difficulty(rng.hash()));
+    }
+    assert_eq!(histogram.total(), total);
+    for d in 0..12 {
+        let expected = DifficultyHistogram::expected_count_at(d, total);
+        let actual = histogram.count(d) as f64;
+        // Five standard deviations of a binomial count.
+        let sigma = (expected * (1.0 - expected / total as f64)).sqrt();
+        assert!(
+            (actual - expected).abs() < 5.0 * sigma,
+            "difficulty {} count {} expected {}",
+            d,
+            actual,
+            expected
+        );
+    }
+    assert_eq!(histogram.percentile(45.0), 0);
+    assert_eq!(histogram.percentile(70.0), 1);
+    assert_eq!(histogram.percentile(80.0), 2);
+}
+
+#[test]
+fn test_snapshot_serde() {
+    let snapshot = histogram(&[3, 0, 1]).snapshot();
+    let json = serde_json::to_string(&snapshot).unwrap();
+    assert_eq!(json, r#"{"counts":[3,0,1]}"#);
+    let parsed: HistogramSnapshot = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, snapshot);
+    assert_eq!(parsed.percentile(100.0), 2);
+    assert_eq!(
+        DifficultyHistogram::new().snapshot(),
+        HistogramSnapshot::default()
+    );
+}
+
+#[test]
+fn test_miner_histogram() {
+    let handle = MinerBuilder::new([0x68; 32])
+        .threads(1)
+        .min_difficulty(64)
+        .deadline(Duration::from_millis(200))
+        .spawn()
+        .unwrap();
+    assert!(handle.progress().histogram.is_none());
+    handle.join().unwrap();
+
+    let handle = MinerBuilder::new([0x68; 32])
+        .threads(2)
+        .min_difficulty(64)
+        .histogram(true)
+        .spawn()
+        .unwrap();
+    thread::sleep(Duration::from_millis(200));
+    handle.pause();
+    thread::sleep(Duration::from_millis(50));
+    let progress = handle.progress();
+    handle.cancel();
+    let histogram = progress.histogram.unwrap();
+    assert!(histogram.total() > 0);
+    // Seeds without solutions are hashed but have no difficulty.
+    assert!(histogram.total() <= progress.hashes);
+    let best = progress.best.unwrap().difficulty;
+    assert_eq!(histogram.counts.len() as u32, best + 1);
+}
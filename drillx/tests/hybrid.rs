@@ -0,0 +1,165 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use drillx::{
+    miner::{Backend, MinerBuilder, StopReason},
+    DrillxError, RuntimeOption, Solution, Solver,
+};
+
+/// Nonces hashed by a backend, with the first byte of their challenge, in order.
+type Log = Arc<Mutex<Vec<(u8, u64)>>>;
+
+/// A stand-in solver that takes `delay` per hash and logs every nonce. Its digests are
+/// a cheap function of the seed, so they are reproducible but don't verify.
+struct Fake {
+    delay: Duration,
+    log: Log,
+}
+
+impl Solver for Fake {
+    fn solve(&mut self, seed: &[u8], _runtime: RuntimeOption) -> Result<[u8; 16], DrillxError> {
+        thread::sleep(self.delay);
+        let nonce = u64::from_le_bytes(seed[32..40].try_into().unwrap());
+        self.log.lock().unwrap().push((seed[0], nonce));
+        Ok(fake_digest(seed))
+    }
+}
+
+fn fake_digest(seed: &[u8]) -> [u8; 16] {
+    let mut state = 0x6a09_e667_f3bc_c908u64;
+    for byte in seed {
+        state = (state ^ *byte as u64).wrapping_mul(0x0000_0100_0000_01b3);
+    }
+    let mut digest = [0; 16];
+    for chunk in digest.chunks_mut(8) {
+        state = (state ^ (state >> 29)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        chunk.copy_from_slice(&state.to_le_bytes());
+    }
+    digest
+}
+
+fn fake(delay: Duration, log: &Log) -> impl Fn() -> Fake + Send + Sync {
+    let log = log.clone();
+    move || Fake {
+        delay,
+        log: log.clone(),
+    }
+}
+
+/// A slow CPU thread and a fast accelerator.
+fn hybrid(challenge: [u8; 32]) -> (MinerBuilder, Log, Log) {
+    let cpu = Log::default();
+    let accelerator = Log::default();
+    let builder = MinerBuilder::new(challenge)
+        .threads(1)
+        .chunk_size(4)
+        .solver(fake(Duration::from_millis(2), &cpu))
+        .accelerator(1, fake(Duration::from_micros(100), &accelerator));
+    (builder, cpu, accelerator)
+}
+
+/// Lengths of the runs of consecutive nonces in a log.
+fn runs(log: &[(u8, u64)]) -> Vec<u64> {
+    let mut runs: Vec<u64> = Vec::new();
+    for (i, (_, nonce)) in log.iter().enumerate() {
+        match i.checked_sub(1).map(|j| log[j].1) {
+            Some(last) if last + 1 == *nonce => *runs.last_mut().unwrap() += 1,
+            _ => runs.push(1),
+        }
+    }
+    runs
+}
+
+#[test]
+fn test_hybrid_chunks_follow_throughput() {
+    let (builder, cpu, accelerator) = hybrid([21; 32]);
+    let started = Instant::now();
+    let handle = builder
+        .min_difficulty(64)
+        .deadline(Duration::from_secs(2))
+        .spawn()
+        .unwrap();
+    thread::sleep(Duration::from_millis(1500));
+    let progress = handle.progress();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Deadline);
+    assert!(started.elapsed() < Duration::from_secs(4));
+
+    let cpu = cpu.lock().unwrap().clone();
+    let accelerator = accelerator.lock().unwrap().clone();
+    // The accelerator does most of the work, in chunks well beyond the CPU's.
+    assert!(
+        accelerator.len() > 3 * cpu.len(),
+        "{} vs {}",
+        accelerator.len(),
+        cpu.len()
+    );
+    let longest = runs(&accelerator).into_iter().max().unwrap();
+    assert!(longest >= 16, "longest accelerator run {}", longest);
+    // Both draw from one cursor, so no nonce is hashed twice.
+    let mut nonces = HashSet::new();
+    for (_, nonce) in cpu.iter().chain(&accelerator) {
+        assert!(nonces.insert(*nonce), "nonce {} hashed twice", nonce);
+    }
+    assert_eq!(outcome.hashes, nonces.len() as u64);
+
+    assert_eq!(progress.threads, 1);
+    let cpu_progress = progress.backend(Backend::Cpu).unwrap();
+    let accelerator_progress = progress.backend(Backend::Accelerator).unwrap();
+    assert_eq!(cpu_progress.workers, 1);
+    assert_eq!(accelerator_progress.workers, 1);
+    assert_eq!(
+        cpu_progress.hashes + accelerator_progress.hashes,
+        progress.hashes
+    );
+    assert!(accelerator_progress.hashrate > 3.0 * cpu_progress.hashrate);
+}
+
+#[test]
+fn test_hybrid_single_result() {
+    let challenge = [22; 32];
+    let (builder, cpu, accelerator) = hybrid(challenge);
+    let outcome = builder.min_difficulty(10).spawn().unwrap().join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Found);
+    let best = outcome.best.unwrap();
+    assert!(best.difficulty >= 10);
+    assert_eq!(best.solution.to_hash().h, best.hash);
+
+    // The best solution is one that a backend hashed, with its digest.
+    let nonce = u64::from_le_bytes(best.solution.n);
+    let hashed = |log: &Log| log.lock().unwrap().iter().any(|(_, n)| *n == nonce);
+    assert!(hashed(&cpu) || hashed(&accelerator));
+    let mut seed = challenge.to_vec();
+    seed.extend_from_slice(&best.solution.n);
+    assert_eq!(
+        best.solution,
+        Solution::new(fake_digest(&seed), best.solution.n)
+    );
+}
+
+#[test]
+fn test_hybrid_cancel_and_challenge_change() {
+    let (builder, _cpu, accelerator) = hybrid([23; 32]);
+    let handle = builder.min_difficulty(64).spawn().unwrap();
+    thread::sleep(Duration::from_millis(200));
+    handle.set_challenge([24; 32], 64);
+    thread::sleep(Duration::from_millis(300));
+    // The accelerator moved to the new challenge mid-chunk.
+    let switched = accelerator.lock().unwrap().len();
+    assert_eq!(accelerator.lock().unwrap().last().unwrap().0, 24);
+
+    handle.cancel();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Cancelled);
+    let log = accelerator.lock().unwrap();
+    assert!(log[..switched]
+        .iter()
+        .any(|(challenge, _)| *challenge == 23));
+    assert!(log[switched..]
+        .iter()
+        .all(|(challenge, _)| *challenge == 24));
+}
@@ -0,0 +1,202 @@
+#![cfg(feature = "process-isolation")]
+
+use std::{collections::BTreeSet, process::Command, thread, time::Duration};
+
+use drillx::{
+    isolation::{run_worker, IsolatedMiner},
+    miner::{JobState, MinerConfig, MinerError, StopReason},
+};
+
+const WORKER: &str = env!("CARGO_BIN_EXE_drillx-worker");
+
+fn config(threads: usize, start_nonce: u64, end_nonce: Option<u64>) -> MinerConfig {
+    MinerConfig {
+        threads,
+        start_nonce,
+        end_nonce,
+        ..MinerConfig::default()
+    }
+}
+
+/// The nonces in `range` with a drillx hash, which every streaming run must report.
+fn hashable(challenge: &[u8; 32], range: std::ops::RangeInclusive<u64>) -> BTreeSet<u64> {
+    range
+        .filter(|nonce| drillx::hash(challenge, &nonce.to_le_bytes()).is_ok())
+        .collect()
+}
+
+#[test]
+fn test_isolated_mine_finds_solution() {
+    let challenge = [169; 32];
+    let config = MinerConfig {
+        min_difficulty: 4,
+        ..config(2, 0, None)
+    };
+    let outcome = IsolatedMiner::new(challenge, &config)
+        .worker_command(WORKER)
+        .spawn()
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(outcome.reason, StopReason::Found);
+    assert_eq!(outcome.solutions, 1);
+    assert_eq!(outcome.jobs[0].state, JobState::Solved);
+    let best = outcome.best.unwrap();
+    assert!(best.difficulty >= 4);
+    assert!(best.solution.is_valid(&challenge));
+    assert_eq!(best.solution.to_hash().h, best.hash);
+}
+
+#[test]
+fn test_isolated_stream_exhausts_range() {
+    let challenge = [3; 32];
+    let config = MinerConfig {
+        stream: Some(1024),
+        ..config(3, 1000, Some(1099))
+    };
+    let handle = IsolatedMiner::new(challenge, &config)
+        .worker_command(WORKER)
+        .spawn()
+        .unwrap();
+    let streamed: Vec<_> = handle.solutions().unwrap().iter().collect();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Exhausted);
+    assert_eq!(outcome.hashes, 100);
+    assert_eq!(outcome.dropped, 0);
+    assert_eq!(outcome.solutions, streamed.len() as u64);
+
+    let nonces: BTreeSet<_> = streamed
+        .iter()
+        .map(|s| u64::from_le_bytes(s.scored.solution.n))
+        .collect();
+    assert_eq!(nonces.len(), streamed.len());
+    assert_eq!(nonces, hashable(&challenge, 1000..=1099));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_killed_worker_is_restarted_without_duplicates() {
+    let challenge = [11; 32];
+    let config = MinerConfig {
+        stream: Some(4096),
+        ..config(2, 0, Some(599))
+    };
+    let handle = IsolatedMiner::new(challenge, &config)
+        .worker_command(WORKER)
+        .spawn()
+        .unwrap();
+    while handle.progress().hashes < 50 {
+        thread::sleep(Duration::from_millis(5));
+    }
+    let pid = handle.worker_pids()[0];
+    let killed = Command::new("kill")
+        .args(["-9", &pid.to_string()])
+        .status()
+        .unwrap();
+    assert!(killed.success());
+
+    let streamed: Vec<_> = handle.solutions().unwrap().iter().collect();
+    assert!(handle.progress().restarts >= 1);
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Exhausted);
+    // Every nonce is counted once, however far the killed worker got.
+    assert_eq!(outcome.hashes, 600);
+    assert_eq!(outcome.solutions, streamed.len() as u64);
+    let nonces: BTreeSet<_> = streamed
+        .iter()
+        .map(|s| u64::from_le_bytes(s.scored.solution.n))
+        .collect();
+    assert_eq!(nonces.len(), streamed.len());
+    assert_eq!(nonces, hashable(&challenge, 0..=599));
+}
+
+#[test]
+fn test_progress_and_cancel() {
+    let config = MinerConfig {
+        min_difficulty: u32::MAX,
+        ..config(1, 0, None)
+    };
+    let handle = IsolatedMiner::new([5; 32], &config)
+        .worker_command(WORKER)
+        .spawn()
+        .unwrap();
+    while handle.progress().hashes < 5 {
+        thread::sleep(Duration::from_millis(5));
+    }
+    let progress = handle.progress();
+    assert_eq!(progress.workers, 1);
+    assert_eq!(progress.restarts, 0);
+    assert!(progress.best.is_some());
+    handle.cancel();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Cancelled);
+    assert!(outcome.hashes >= 5);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_crashing_worker_exceeds_restart_limit() {
+    let config = MinerConfig {
+        restart_limit: 3,
+        ..config(1, 0, None)
+    };
+    let result = IsolatedMiner::new([1; 32], &config)
+        .worker_command("false")
+        .spawn()
+        .unwrap()
+        .join();
+    assert!(matches!(
+        result,
+        Err(MinerError::TooManyRestarts { limit: 3 })
+    ));
+}
+
+#[test]
+fn test_missing_worker_fails_to_spawn() {
+    let result = IsolatedMiner::new([1; 32], &config(2, 0, None))
+        .worker_command("/nonexistent/drillx-worker")
+        .spawn();
+    assert!(matches!(result, Err(MinerError::Spawn(_))));
+}
+
+#[test]
+fn test_worker_protocol() {
+    let challenge = [169; 32];
+    let mut assign = vec![54, 0, 0, 0, 1];
+    assign.extend_from_slice(&challenge);
+    assign.extend_from_slice(&u32::MAX.to_le_bytes());
+    assign.extend_from_slice(&7u64.to_le_bytes());
+    assign.extend_from_slice(&9u64.to_le_bytes());
+    assign.push(2);
+
+    let mut output = Vec::new();
+    run_worker(&assign[..], &mut output).unwrap();
+
+    // Each nonce is hashed in order, and the first hash is always the worker's best.
+    let mut frames = Vec::new();
+    let mut rest = &output[..];
+    while !rest.is_empty() {
+        let len = u32::from_le_bytes(rest[..4].try_into().unwrap()) as usize;
+        frames.push(rest[4..4 + len].to_vec());
+        rest = &rest[4 + len..];
+    }
+    let hashed: Vec<_> = frames
+        .iter()
+        .filter(|f| f[0] == 3)
+        .map(|f| u64::from_le_bytes(f[1..].try_into().unwrap()))
+        .collect();
+    assert_eq!(hashed, vec![7, 8, 9]);
+    let first = hashable(&challenge, 7..=9).into_iter().next().unwrap();
+    let solution = frames.iter().find(|f| f[0] == 2).unwrap();
+    assert_eq!(solution.len(), 25);
+    assert_eq!(
+        u64::from_le_bytes(solution[1..9].try_into().unwrap()),
+        first
+    );
+    let hash = drillx::hash(&challenge, &first.to_le_bytes()).unwrap();
+    assert_eq!(&solution[9..], &hash.d);
+
+    // Anything but an assign frame is rejected.
+    assert!(run_worker(&[9, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0, 0][..], Vec::new()).is_err());
+    assert!(run_worker(&[][..], Vec::new()).is_err());
+}
@@ -0,0 +1,39 @@
+#![cfg(feature = "process-isolation")]
+
+//! Kept apart from the other isolation tests so that nothing else in this process
+//! compiles equix programs.
+
+use drillx::{
+    compiled_programs,
+    isolation::IsolatedMiner,
+    miner::{MinerConfig, StopReason},
+    RuntimeOption,
+};
+
+#[test]
+fn test_parent_never_compiles() {
+    let challenge = [169; 32];
+    let config = MinerConfig {
+        threads: 2,
+        end_nonce: Some(39),
+        stream: Some(1024),
+        runtime: RuntimeOption::InterpretOnly,
+        ..MinerConfig::default()
+    };
+    let handle = IsolatedMiner::new(challenge, &config)
+        .worker_command(env!("CARGO_BIN_EXE_drillx-worker"))
+        .spawn()
+        .unwrap();
+    let streamed: Vec<_> = handle.solutions().unwrap().iter().collect();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Exhausted);
+    // Every streamed solution was verified by the parent.
+    assert!(!streamed.is_empty());
+    assert_eq!(compiled_programs(), 0);
+
+    // The count does see compiled programs where there are any.
+    if drillx::runtime_info().compiler_available {
+        drillx::hash(&challenge, &0u64.to_le_bytes()).ok();
+        assert!(compiled_programs() > 0);
+    }
+}
@@ -0,0 +1,97 @@
+use drillx::{hash_iter, HashIter};
+
+#[test]
+fn test_hash_iter_find() {
+    let challenge = [7; 32];
+    let (nonce, hash) = hash_iter(&challenge, 0..)
+        .find(|(_, hash)| hash.difficulty() >= 10)
+        .unwrap();
+    assert!(hash.difficulty() >= 10);
+    assert_eq!(
+        drillx::hash(&challenge, &nonce.to_le_bytes()).unwrap().h,
+        hash.h
+    );
+}
+
+#[test]
+fn test_hash_iter_take() {
+    let challenge = [8; 32];
+    let first: Vec<(u64, [u8; 32])> = hash_iter(&challenge, 100..200)
+        .take(12)
+        .map(|(nonce, hash)| (nonce, hash.h))
+        .collect();
+    let expected: Vec<(u64, [u8; 32])> = (100..200)
+        .filter_map(|n| Some((n, drillx::hash(&challenge, &u64::to_le_bytes(n)).ok()?.h)))
+        .take(12)
+        .collect();
+    assert_eq!(first, expected);
+}
+
+#[test]
+fn test_hash_iter_errors() {
+    let challenge = [9; 32];
+    let results: Vec<_> = hash_iter(&challenge, 0..64).try_iter().collect();
+    assert_eq!(results.len(), 64);
+    let failed = results.iter().filter(|(_, hash)| hash.is_err()).count() as u64;
+    assert!(failed > 0, "no nonce without solutions in the range");
+    for (i, (nonce, result)) in results.into_iter().enumerate() {
+        assert_eq!(nonce, i as u64);
+        let expected = drillx::hash(&challenge, &nonce.to_le_bytes());
+        assert_eq!(result.map(|h| h.h), expected.map(|h| h.h));
+    }
+
+    let mut iter = hash_iter(&challenge, 0..64);
+    let hashed = iter.by_ref().count() as u64;
+    assert_eq!(hashed + iter.errors(), 64);
+    assert_eq!(iter.errors(), failed);
+}
+
+#[test]
+fn test_hash_iter_bounds() {
+    let challenge = [10; 32];
+    let nonces = |iter: HashIter| -> Vec<u64> { iter.try_iter().map(|(n, _)| n).collect() };
+    assert_eq!(nonces(hash_iter(&challenge, 3..6)), [3, 4, 5]);
+    assert_eq!(nonces(hash_iter(&challenge, 3..=6)), [3, 4, 5, 6]);
+    assert_eq!(nonces(hash_iter(&challenge, ..2)), [0, 1]);
+    let (start, end) = (6, 3);
+    assert!(nonces(hash_iter(&challenge, start..end)).is_empty());
+    assert!(nonces(hash_iter(&challenge, 0..0)).is_empty());
+    assert!(nonces(hash_iter(&challenge, ..0)).is_empty());
+    // Ranges end at `u64::MAX` rather than wrapping around.
+    assert_eq!(
+        nonces(hash_iter(&challenge, u64::MAX - 2..)),
+        [u64::MAX - 2, u64::MAX - 1, u64::MAX]
+    );
+    assert_eq!(
+        nonces(hash_iter(&challenge, u64::MAX..=u64::MAX)),
+        [u64::MAX]
+    );
+    let excluded = (
+        std::ops::Bound::Excluded(u64::MAX),
+        std::ops::Bound::Unbounded,
+    );
+    assert!(nonces(hash_iter(&challenge, excluded)).is_empty());
+
+    let mut iter = hash_iter(&challenge, u64::MAX..).try_iter();
+    assert_eq!(iter.size_hint(), (1, Some(1)));
+    assert!(iter.next().is_some());
+    assert!(iter.next().is_none());
+    assert!(iter.next().is_none());
+    assert_eq!(hash_iter(&challenge, 0..10).size_hint(), (0, Some(10)));
+}
+
+#[test]
+fn test_hash_iter_send() {
+    let challenge = [11; 32];
+    let mut iter = hash_iter(&challenge, 50..);
+    let first = iter.next().unwrap();
+    // The iterator carries on from where it was on another thread.
+    let second = std::thread::spawn(move || iter.next().unwrap())
+        .join()
+        .unwrap();
+    assert!(second.0 > first.0);
+    assert_eq!(
+        second.1.h,
+        drillx::hash(&challenge, &second.0.to_le_bytes()).unwrap().h
+    );
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "keccak-extern")]
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use drillx::{commit_reveal::commit, vectors::VECTORS, KeccakPart, Solution};
+use sha3::Digest;
+
+static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+/// The provider a program would define, here through `sha3` rather than the syscall.
+///
+/// # Safety
+///
+/// Called by drillx with the parts of one hash.
+#[no_mangle]
+pub unsafe extern "C" fn drillx_keccak(vals: *const u8, val_len: u64, hash_result: *mut u8) -> u64 {
+    CALLS.fetch_add(1, Ordering::Relaxed);
+    let mut hasher = sha3::Keccak256::new();
+    for part in KeccakPart::parts(vals, val_len) {
+        hasher.update(part.as_slice());
+    }
+    let hash: [u8; 32] = hasher.finalize().into();
+    std::ptr::copy_nonoverlapping(hash.as_ptr(), hash_result, 32);
+    0
+}
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn test_extern_provider_matches_vectors() {
+    let before = CALLS.load(Ordering::Relaxed);
+    let mut checked = 0;
+    for vector in VECTORS {
+        let Some(output) = vector.output else {
+            continue;
+        };
+        let solution = Solution::new(output.digest, vector.nonce);
+        assert_eq!(solution.to_hash().h, output.hash);
+        checked += 1;
+    }
+    assert!(checked > 0);
+    assert!(CALLS.load(Ordering::Relaxed) >= before + checked);
+
+    // Commitments hash three parts through the same provider.
+    let solution = Solution::new(VECTORS[0].output.unwrap().digest, VECTORS[0].nonce);
+    assert_eq!(
+        commit(&solution, &[0; 32]).to_vec(),
+        hex("cd49bfc2e418df6463dbaef33e75a107be0ccb1269ec193d512a8950f86eb7b3")
+    );
+}
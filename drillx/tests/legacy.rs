@@ -0,0 +1,100 @@
+use drillx::{
+    legacy::{self, verify_either, VerifyEpoch},
+    Solution,
+};
+
+/// Solutions with unsorted digests and their legacy hashes, computed with an
+/// independent keccak from the embedded test vectors' seeds.
+const LEGACY: &[(&str, u64, &str, &str)] = &[
+    (
+        "0000000000000000000000000000000000000000000000000000000000000000",
+        0,
+        "b45a828ae35b6ec80b4a898cc60bf0d5",
+        "abfb0641d9e379b74aee5edcb89bd791f44ec599390dcb17071d3634a1185299",
+    ),
+    (
+        "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        7,
+        "e5836da99914f2bf9090b6c46477ead7",
+        "140f90c3b18b457b2fae7f6d539bd5b65d47e3ddc783d5904b0a311080d92fb5",
+    ),
+    (
+        "ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        u64::MAX,
+        "4c08605638770d84a338b55344354ab0",
+        "c1ea1dc16f94b77da787ec11d714517c8545fa7b62ba017079fced72333267fb",
+    ),
+    (
+        "5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a",
+        0xdead_beef,
+        "235bb6b5063cbbd67d016f20eb6865e9",
+        "0c89c945e2e2fe4e1bb543b86b6c2aecd4d73449d2772f3009521f7fcf287599",
+    ),
+];
+
+fn hex<const N: usize>(s: &str) -> [u8; N] {
+    std::array::from_fn(|i| u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap())
+}
+
+fn fixtures() -> impl Iterator<Item = ([u8; 32], Solution, [u8; 32])> {
+    LEGACY.iter().map(|&(challenge, nonce, digest, hash)| {
+        (
+            hex(challenge),
+            Solution::new(hex(digest), nonce.to_le_bytes()),
+            hex(hash),
+        )
+    })
+}
+
+#[test]
+fn test_legacy_hash_reproduces_fixtures() {
+    for (challenge, solution, hash) in fixtures() {
+        assert!(solution.is_valid(&challenge));
+        assert_eq!(legacy::hashv(&solution.d, &solution.n), hash);
+        let legacy = legacy::to_hash(&solution);
+        assert_eq!((legacy.d, legacy.h), (solution.d, hash));
+        // The current hash sorts the digest, so it differs.
+        assert_ne!(solution.to_hash().h, hash);
+    }
+}
+
+#[test]
+fn test_verify_either() {
+    for (challenge, solution, hash) in fixtures() {
+        assert_eq!(
+            verify_either(&challenge, &solution, &hash),
+            VerifyEpoch::Legacy
+        );
+        assert_eq!(
+            verify_either(&challenge, &solution, &solution.to_hash().h),
+            VerifyEpoch::Current
+        );
+        assert_eq!(
+            verify_either(&challenge, &solution, &[0; 32]),
+            VerifyEpoch::Neither
+        );
+
+        // An invalid digest fails under either rule, even with its own hashes.
+        let mut forged = solution;
+        forged.d[0] ^= 1;
+        let legacy = legacy::to_hash(&forged).h;
+        assert_eq!(
+            verify_either(&challenge, &forged, &legacy),
+            VerifyEpoch::Neither
+        );
+        assert_eq!(
+            verify_either(&challenge, &forged, &forged.to_hash().h),
+            VerifyEpoch::Neither
+        );
+    }
+}
+
+#[test]
+fn test_sorted_digest_hashes_alike() {
+    let mut digest = [0u8; 16];
+    for (i, item) in digest.chunks_mut(2).enumerate() {
+        item.copy_from_slice(&(i as u16 * 1000).to_le_bytes());
+    }
+    let solution = Solution::new(digest, [3; 8]);
+    assert_eq!(legacy::to_hash(&solution).h, solution.to_hash().h);
+}
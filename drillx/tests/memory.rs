@@ -0,0 +1,131 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use drillx::{
+    DrillxMemory, EquixSolver, MemoryAllocator, MemoryError, MemoryPool, RuntimeOption, Solution,
+    Solver,
+};
+
+#[test]
+fn test_memory_reuse() {
+    let challenge = [255; 32];
+    let mut memory = DrillxMemory::new();
+    for nonce in 0..32u64 {
+        let nonce = nonce.to_le_bytes();
+        let a = drillx::hash_with_memory(&mut memory, &challenge, &nonce);
+        let b = drillx::hash(&challenge, &nonce);
+        match (a, b) {
+            (Ok(a), Ok(b)) => {
+                assert_eq!(a.d, b.d);
+                assert_eq!(a.h, b.h);
+                assert!(Solution::new(a.d, nonce).is_valid(&challenge));
+            }
+            (Err(_), Err(_)) => {}
+            _ => panic!("memory and transient hashes disagree"),
+        }
+    }
+}
+
+#[test]
+fn test_memory_default() {
+    let challenge = [0; 32];
+    let mut memory = DrillxMemory::default();
+    let nonce = 7u64.to_le_bytes();
+    let a = drillx::hash_with_memory(&mut memory, &challenge, &nonce).ok();
+    let b = drillx::hash_with_memory(&mut memory, &challenge, &nonce).ok();
+    assert_eq!(a.map(|h| h.h), b.map(|h| h.h));
+}
+
+#[cfg(feature = "equix-compat")]
+#[test]
+fn test_equix_compat() {
+    let challenge = [255; 32];
+    let nonce = 0u64.to_le_bytes();
+    let mut raw = drillx::equix::SolverMemory::new();
+    let a = drillx::hash_with_equix_memory(&mut raw, &challenge, &nonce).unwrap();
+    let mut memory = DrillxMemory::from(raw);
+    let b = drillx::hash_with_memory(&mut memory, &challenge, &nonce).unwrap();
+    assert_eq!(a.h, b.h);
+    let _raw: drillx::equix::SolverMemory = memory.into_inner();
+}
+
+/// Allocates while the budget lasts.
+struct Budget(Arc<AtomicUsize>);
+
+impl MemoryAllocator for Budget {
+    fn allocate(&self) -> Result<DrillxMemory, MemoryError> {
+        self.0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |left| {
+                left.checked_sub(1)
+            })
+            .map_err(|_| MemoryError {
+                size: DrillxMemory::SIZE,
+            })?;
+        DrillxMemory::try_new()
+    }
+}
+
+#[test]
+fn test_memory_try_new() {
+    let challenge = [1; 32];
+    let nonce = 3u64.to_le_bytes();
+    let mut memory = DrillxMemory::try_new().unwrap();
+    let a = drillx::hash_with_memory(&mut memory, &challenge, &nonce).ok();
+    let b = drillx::hash(&challenge, &nonce).ok();
+    assert_eq!(a.map(|h| h.h), b.map(|h| h.h));
+    assert!(EquixSolver::try_new().is_ok());
+}
+
+#[test]
+fn test_memory_pool() {
+    let budget = Arc::new(AtomicUsize::new(2));
+    let pool = MemoryPool::with_allocator(Budget(budget.clone()));
+    let a = pool.try_acquire().unwrap();
+    let _b = pool.try_acquire().unwrap();
+    let err = pool.try_acquire().unwrap_err();
+    assert_eq!(err.size, DrillxMemory::SIZE);
+    assert_eq!(
+        err.to_string(),
+        format!(
+            "Failed to allocate {} bytes of solver memory",
+            DrillxMemory::SIZE
+        )
+    );
+    assert_eq!(pool.allocated(), 2);
+
+    // Released memory is reused without allocating.
+    pool.release(a);
+    assert_eq!(pool.idle(), 1);
+    let _a = pool.try_acquire().unwrap();
+    assert_eq!(pool.idle(), 0);
+    assert_eq!(pool.allocated(), 2);
+    assert!(pool.try_acquire().is_err());
+
+    budget.store(1, Ordering::Relaxed);
+    assert!(pool.try_acquire().is_ok());
+    assert_eq!(pool.allocated(), 3);
+}
+
+#[test]
+fn test_pooled_solver_releases_memory() {
+    let pool = Arc::new(MemoryPool::with_allocator(Budget(Arc::new(
+        AtomicUsize::new(1),
+    ))));
+    let mut solver = EquixSolver::pooled(&pool).unwrap();
+    assert!(EquixSolver::pooled(&pool).is_err());
+    let seed = [5; 40];
+    let digest = solver.solve(&seed, RuntimeOption::InterpretOnly).ok();
+    drop(solver);
+    assert_eq!(pool.idle(), 1);
+
+    // The next solver reuses the memory and agrees.
+    let mut solver = EquixSolver::pooled(&pool).unwrap();
+    assert_eq!(pool.idle(), 0);
+    assert_eq!(
+        solver.solve(&seed, RuntimeOption::InterpretOnly).ok(),
+        digest
+    );
+    assert_eq!(pool.allocated(), 1);
+}
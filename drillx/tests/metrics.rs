@@ -0,0 +1,129 @@
+#![cfg(feature = "metrics")]
+
+use drillx::{
+    miner::MinerBuilder, telemetry, Context, DrillxError, EquixSolver, RuntimeOption, Solution,
+    Solver,
+};
+use metrics_util::debugging::{DebugValue, DebuggingRecorder, Snapshotter};
+
+/// Sums a counter across all of its label sets.
+fn counter(snapshotter: &Snapshotter, name: &str) -> u64 {
+    snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .filter(|(key, ..)| key.key().name() == name)
+        .map(|(.., value)| match value {
+            DebugValue::Counter(v) => v,
+            _ => panic!("{} is not a counter", name),
+        })
+        .sum()
+}
+
+fn gauge(snapshotter: &Snapshotter, name: &str) -> Option<f64> {
+    snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .find(|(key, ..)| key.key().name() == name)
+        .map(|(.., value)| match value {
+            DebugValue::Gauge(v) => v.into_inner(),
+            _ => panic!("{} is not a gauge", name),
+        })
+}
+
+/// Fails every compile attempt.
+struct FailingCompiler(EquixSolver);
+
+impl Solver for FailingCompiler {
+    fn solve(&mut self, seed: &[u8], runtime: RuntimeOption) -> Result<[u8; 16], DrillxError> {
+        match runtime {
+            RuntimeOption::InterpretOnly => self.0.solve(seed, runtime),
+            _ => Err(DrillxError::CompileFailed),
+        }
+    }
+}
+
+#[test]
+fn test_metrics() {
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    recorder.install().unwrap();
+
+    // Mine
+    let challenge = [255; 32];
+    let outcome = MinerBuilder::new(challenge)
+        .threads(2)
+        .min_difficulty(4)
+        .spawn()
+        .unwrap()
+        .join()
+        .unwrap();
+    let best = outcome.best.unwrap();
+    assert_eq!(
+        counter(&snapshotter, telemetry::HASHES_METRIC),
+        outcome.hashes
+    );
+    assert!(counter(&snapshotter, telemetry::SOLUTIONS_METRIC) >= 1);
+    assert_eq!(
+        gauge(&snapshotter, telemetry::BEST_DIFFICULTY_METRIC),
+        Some(best.difficulty as f64)
+    );
+
+    // Switch challenges. The gauge is cleared along with the best solution.
+    let handle = MinerBuilder::new([9; 32])
+        .threads(1)
+        .min_difficulty(u32::MAX)
+        .spawn()
+        .unwrap();
+    while !gauge(&snapshotter, telemetry::BEST_DIFFICULTY_METRIC).is_some_and(|d| d > 0.0) {
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    handle.pause();
+    handle.set_challenge([10; 32], u32::MAX);
+    assert!(handle.progress().best.is_none());
+    handle.cancel();
+    handle.join().unwrap();
+    assert_eq!(
+        gauge(&snapshotter, telemetry::BEST_DIFFICULTY_METRIC),
+        Some(0.0)
+    );
+
+    // Verify
+    let mut bad = best.solution;
+    bad.n = [0xff; 8];
+    let good = best.solution;
+    let verdicts = drillx::verify_batch(&challenge, &[good, bad, Solution::new([0; 16], [0; 8])]);
+    assert_eq!(verdicts, vec![true, false, false]);
+    assert_eq!(counter(&snapshotter, telemetry::VERIFY_ACCEPTED_METRIC), 1);
+    assert_eq!(counter(&snapshotter, telemetry::VERIFY_REJECTED_METRIC), 2);
+
+    // Stream into a channel nobody reads
+    let outcome = MinerBuilder::new([7; 32])
+        .threads(1)
+        .stream(1)
+        .start_nonce(u64::MAX - 15)
+        .spawn()
+        .unwrap()
+        .join()
+        .unwrap();
+    assert!(outcome.dropped > 0);
+    assert_eq!(
+        counter(&snapshotter, telemetry::STREAM_DROPPED_METRIC),
+        outcome.dropped
+    );
+
+    // Watchdog
+    let mut context = Context::with_solver(
+        FailingCompiler(EquixSolver::new()),
+        RuntimeOption::TryCompile,
+    )
+    .failure_threshold(2);
+    for n in 0..4u64 {
+        context.hash(&challenge, &n.to_le_bytes()).ok();
+    }
+    assert_eq!(
+        counter(&snapshotter, telemetry::RUNTIME_DOWNGRADES_METRIC),
+        1
+    );
+}
@@ -0,0 +1,891 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use drillx::{
+    miner::{
+        self, ChallengeJob, JobId, JobState, MinerBuilder, MinerConfig, MinerError, MinerEvent,
+        MinerWarning, NonceOrder, StopReason,
+    },
+    DrillxError, DrillxMemory, EquixSolver, MemoryAllocator, MemoryError, MemoryPool,
+    RuntimeOption, Solver,
+};
+
+#[test]
+fn test_mine_finds_solution() {
+    let challenge = [255; 32];
+    let config = MinerConfig {
+        threads: 2,
+        min_difficulty: 6,
+        ..Default::default()
+    };
+    let outcome = miner::mine(challenge, &config).unwrap();
+    assert_eq!(outcome.reason, StopReason::Found);
+    let best = outcome.best.unwrap();
+    assert!(best.difficulty >= 6);
+    assert!(best.solution.is_valid(&challenge));
+    assert_eq!(best.solution.to_hash().h, best.hash);
+}
+
+#[test]
+fn test_mine_deadline() {
+    let outcome = MinerBuilder::new([0; 32])
+        .threads(1)
+        .min_difficulty(64)
+        .deadline(Duration::from_millis(100))
+        .spawn()
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(outcome.reason, StopReason::Deadline);
+    assert!(outcome.hashes > 0);
+    assert!(outcome.best.is_some());
+}
+
+#[test]
+fn test_mine_cancel() {
+    let handle = MinerBuilder::new([1; 32])
+        .threads(2)
+        .min_difficulty(64)
+        .spawn()
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(50));
+    assert!(!handle.is_finished());
+    handle.cancel();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Cancelled);
+}
+
+#[test]
+fn test_mine_exhausted() {
+    let outcome = MinerBuilder::new([2; 32])
+        .threads(2)
+        .min_difficulty(64)
+        .start_nonce(u64::MAX - 9)
+        .chunk_size(4)
+        .spawn()
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(outcome.reason, StopReason::Exhausted);
+    assert_eq!(outcome.hashes, 10);
+}
+
+#[test]
+fn test_mine_end_nonce() {
+    let outcome = MinerBuilder::new([2; 32])
+        .threads(2)
+        .min_difficulty(64)
+        .start_nonce(100)
+        .end_nonce(109)
+        .chunk_size(4)
+        .spawn()
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(outcome.reason, StopReason::Exhausted);
+    assert_eq!(outcome.hashes, 10);
+}
+
+#[test]
+fn test_mine_stream() {
+    let challenge = [7; 32];
+    let handle = MinerBuilder::new(challenge)
+        .threads(2)
+        .min_difficulty(1)
+        .stream(1024)
+        .start_nonce(u64::MAX - 63)
+        .spawn()
+        .unwrap();
+    let streamed: Vec<_> = handle.solutions().unwrap().iter().collect();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Exhausted);
+    assert_eq!(outcome.dropped, 0);
+    assert_eq!(streamed.len() as u64, outcome.solutions);
+    for s in streamed {
+        assert_eq!(s.challenge, challenge);
+        assert!(s.scored.difficulty >= 1);
+        assert!(s.scored.solution.is_valid(&challenge));
+    }
+}
+
+#[test]
+fn test_mine_multi_solves_each() {
+    let jobs = [ChallengeJob::new([3; 32], 4), ChallengeJob::new([4; 32], 2)];
+    let config = MinerConfig {
+        threads: 2,
+        chunk_size: 4,
+        ..Default::default()
+    };
+    let outcome = miner::mine_multi(&jobs, &config).unwrap();
+    assert_eq!(outcome.reason, StopReason::Found);
+    assert_eq!(outcome.jobs.len(), 2);
+    for (job, status) in jobs.iter().zip(&outcome.jobs) {
+        assert_eq!(status.state, JobState::Solved);
+        assert_eq!(status.challenge, job.challenge);
+        let best = status.best.unwrap();
+        assert!(best.difficulty >= job.min_difficulty);
+        assert!(best.solution.is_valid(&job.challenge));
+    }
+}
+
+#[test]
+fn test_mine_multi_weights() {
+    let jobs = [
+        ChallengeJob::new([5; 32], 64).weight(3),
+        ChallengeJob::new([6; 32], 64),
+    ];
+    let outcome = MinerBuilder::multi(&jobs)
+        .threads(1)
+        .chunk_size(2)
+        .deadline(Duration::from_millis(800))
+        .spawn()
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(outcome.reason, StopReason::Deadline);
+    let heavy = outcome.jobs[0].hashes;
+    let light = outcome.jobs[1].hashes;
+    assert!(light > 0);
+    assert!(heavy >= 2 * light, "{} vs {}", heavy, light);
+    assert_eq!(heavy + light, outcome.hashes);
+}
+
+#[test]
+fn test_mine_multi_add_remove() {
+    let handle = MinerBuilder::multi(&[ChallengeJob::new([8; 32], 64)])
+        .threads(1)
+        .chunk_size(1)
+        .spawn()
+        .unwrap();
+    let added = handle.add_challenge(ChallengeJob::new([9; 32], 1));
+    std::thread::sleep(Duration::from_millis(50));
+    let removed = handle.remove_challenge(miner::JobId(0)).unwrap();
+    assert_eq!(removed.state, JobState::Removed);
+    assert!(handle.remove_challenge(miner::JobId(0)).is_none());
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Found);
+    assert_eq!(outcome.jobs.len(), 1);
+    assert_eq!(outcome.jobs[0].id, added);
+    assert_eq!(outcome.jobs[0].state, JobState::Solved);
+}
+
+#[test]
+fn test_mine_deterministic() {
+    let challenge = [11; 32];
+    let nonces: Vec<_> = [1, 2, 8]
+        .into_iter()
+        .map(|threads| {
+            let outcome = MinerBuilder::new(challenge)
+                .threads(threads)
+                .min_difficulty(5)
+                .chunk_size(3)
+                .deterministic(true)
+                .spawn()
+                .unwrap()
+                .join()
+                .unwrap();
+            assert_eq!(outcome.reason, StopReason::Found);
+            let best = outcome.best.unwrap();
+            assert!(best.difficulty >= 5);
+            u64::from_le_bytes(best.solution.n)
+        })
+        .collect();
+    assert_eq!(nonces[0], nonces[1]);
+    assert_eq!(nonces[0], nonces[2]);
+
+    // No lower nonce qualifies
+    for nonce in 0..nonces[0] {
+        let hash = drillx::hash(&challenge, &nonce.to_le_bytes());
+        assert!(hash.map_or(true, |h| h.difficulty() < 5));
+    }
+}
+
+#[test]
+fn test_mine_permuted_order() {
+    let challenge = [12; 32];
+    let order = NonceOrder::Permuted { key: [5; 16] };
+    let handle = MinerBuilder::new(challenge)
+        .threads(3)
+        .min_difficulty(1)
+        .chunk_size(5)
+        .stream(1024)
+        .end_nonce(47)
+        .nonce_order(order)
+        .spawn()
+        .unwrap();
+    let mut streamed: Vec<u64> = handle
+        .solutions()
+        .unwrap()
+        .iter()
+        .map(|s| u64::from_le_bytes(s.scored.solution.n))
+        .collect();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Exhausted);
+    assert_eq!(outcome.hashes, 48);
+
+    // Each solution from the first 48 permuted nonces, exactly once.
+    let mut expected: Vec<u64> = (0..48)
+        .map(|i| order.nonce(i))
+        .filter(|n| drillx::hash(&challenge, &n.to_le_bytes()).is_ok_and(|h| h.difficulty() >= 1))
+        .collect();
+    streamed.sort_unstable();
+    expected.sort_unstable();
+    assert_eq!(streamed, expected);
+    assert!(expected.iter().any(|&n| n >= 48));
+}
+
+#[test]
+fn test_mine_deterministic_permuted() {
+    let challenge = [11; 32];
+    let order = NonceOrder::Permuted { key: [9; 16] };
+    let indices: Vec<_> = [1, 4]
+        .into_iter()
+        .map(|threads| {
+            let outcome = MinerBuilder::new(challenge)
+                .threads(threads)
+                .min_difficulty(5)
+                .chunk_size(3)
+                .deterministic(true)
+                .nonce_order(order)
+                .spawn()
+                .unwrap()
+                .join()
+                .unwrap();
+            assert_eq!(outcome.reason, StopReason::Found);
+            order.index(u64::from_le_bytes(outcome.best.unwrap().solution.n))
+        })
+        .collect();
+    assert_eq!(indices[0], indices[1]);
+
+    // No earlier index qualifies
+    for i in 0..indices[0] {
+        let hash = drillx::hash(&challenge, &order.nonce(i).to_le_bytes());
+        assert!(hash.map_or(true, |h| h.difficulty() < 5));
+    }
+}
+
+#[test]
+fn test_mine_interleaved() {
+    let challenge = [11; 32];
+    let nonces: Vec<_> = [1, 2]
+        .into_iter()
+        .map(|interleave| {
+            let outcome = MinerBuilder::new(challenge)
+                .threads(2)
+                .min_difficulty(5)
+                .chunk_size(3)
+                .deterministic(true)
+                .interleave(interleave)
+                .spawn()
+                .unwrap()
+                .join()
+                .unwrap();
+            assert_eq!(outcome.reason, StopReason::Found);
+            let best = outcome.best.unwrap();
+            (u64::from_le_bytes(best.solution.n), best.solution.d)
+        })
+        .collect();
+    assert_eq!(nonces[0], nonces[1]);
+}
+
+/// Waits until the miner's hash count stops changing.
+fn settle(handle: &miner::MinerHandle) -> u64 {
+    let mut hashes = handle.progress().hashes;
+    loop {
+        std::thread::sleep(Duration::from_millis(100));
+        let now = handle.progress().hashes;
+        if now == hashes {
+            return now;
+        }
+        hashes = now;
+    }
+}
+
+#[test]
+fn test_mine_pause_resume() {
+    let challenge = [12; 32];
+    let handle = MinerBuilder::new(challenge)
+        .threads(1)
+        .min_difficulty(0)
+        .chunk_size(1000)
+        .stream(10_000)
+        .spawn()
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    handle.pause();
+    handle.pause();
+    let paused = settle(&handle);
+    std::thread::sleep(Duration::from_millis(200));
+    let progress = handle.progress();
+    assert!(progress.paused);
+    assert_eq!(progress.hashes, paused);
+    assert_eq!(progress.hashrate(), 0.0);
+    assert!(progress.active + Duration::from_millis(300) <= progress.elapsed);
+
+    handle.resume();
+    handle.resume();
+    while handle.progress().hashes < paused + 5 {
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(handle.progress().hashrate() > 0.0);
+    handle.cancel();
+    let solutions = handle.solutions().unwrap();
+    let nonces: Vec<u64> = std::iter::from_fn(|| solutions.recv().ok())
+        .map(|s| u64::from_le_bytes(s.scored.solution.n))
+        .collect();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Cancelled);
+
+    // The single worker picked up where it paused: no nonce was skipped or repeated.
+    let last = *nonces.last().unwrap();
+    assert!(nonces.windows(2).all(|w| w[0] < w[1]));
+    for nonce in 0..=last {
+        if nonces.binary_search(&nonce).is_err() {
+            assert!(drillx::hash(&challenge, &nonce.to_le_bytes()).is_err());
+        }
+    }
+}
+
+#[test]
+fn test_mine_pause_deadline() {
+    // A pause counts toward the deadline by default.
+    let handle = MinerBuilder::new([13; 32])
+        .threads(1)
+        .min_difficulty(64)
+        .deadline(Duration::from_millis(200))
+        .spawn()
+        .unwrap();
+    handle.pause();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Deadline);
+    assert!(outcome.elapsed < Duration::from_millis(600));
+
+    let handle = MinerBuilder::new([13; 32])
+        .threads(1)
+        .min_difficulty(64)
+        .deadline(Duration::from_millis(200))
+        .pause_extends_deadline(true)
+        .spawn()
+        .unwrap();
+    handle.pause();
+    std::thread::sleep(Duration::from_millis(400));
+    assert!(!handle.is_finished());
+    handle.resume();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Deadline);
+    assert!(outcome.elapsed >= Duration::from_millis(600));
+}
+
+#[test]
+fn test_mine_cancel_while_paused() {
+    let handle = MinerBuilder::new([14; 32])
+        .threads(2)
+        .min_difficulty(64)
+        .spawn()
+        .unwrap();
+    handle.pause();
+    handle.cancel();
+    handle.resume();
+    handle.pause();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Cancelled);
+}
+
+#[test]
+fn test_mine_set_threads() {
+    let challenge = [15; 32];
+    let handle = MinerBuilder::new(challenge)
+        .threads(1)
+        .min_difficulty(0)
+        .chunk_size(4)
+        .stream(10_000)
+        .spawn()
+        .unwrap();
+    assert_eq!(handle.progress().threads, 1);
+    std::thread::sleep(Duration::from_millis(150));
+    handle.set_threads(4).unwrap();
+    assert_eq!(handle.progress().threads, 4);
+    std::thread::sleep(Duration::from_millis(300));
+    handle.set_threads(2).unwrap();
+    assert_eq!(handle.progress().threads, 2);
+    std::thread::sleep(Duration::from_millis(300));
+    handle.set_threads(0).unwrap();
+    assert_eq!(handle.progress().threads, 1);
+    std::thread::sleep(Duration::from_millis(100));
+    handle.cancel();
+
+    let solutions = handle.solutions().unwrap();
+    let found: Vec<_> = std::iter::from_fn(|| solutions.recv().ok()).collect();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Cancelled);
+    assert!(outcome.best.unwrap().solution.is_valid(&challenge));
+
+    // Coverage stays duplicate-free across scaling.
+    let mut nonces: Vec<u64> = found
+        .iter()
+        .map(|s| u64::from_le_bytes(s.scored.solution.n))
+        .collect();
+    nonces.sort_unstable();
+    let len = nonces.len();
+    nonces.dedup();
+    assert_eq!(nonces.len(), len);
+    assert!(found.iter().all(|s| s.scored.solution.is_valid(&challenge)));
+    assert_eq!(outcome.hashes, outcome.jobs[0].hashes);
+}
+
+#[test]
+fn test_mine_set_challenge() {
+    let challenges = [[16; 32], [17; 32], [18; 32], [19; 32]];
+    let handle = MinerBuilder::new(challenges[0])
+        .threads(2)
+        .min_difficulty(0)
+        .chunk_size(4)
+        .stream(100_000)
+        .spawn()
+        .unwrap();
+    let solutions = handle.solutions().unwrap();
+    let mut found = Vec::new();
+    // Keeps every solution until one for `job` arrives.
+    let mut wait_for = |job: JobId| loop {
+        let solution = solutions.recv_timeout(Duration::from_secs(60)).unwrap();
+        found.push(solution);
+        if solution.job == job {
+            break;
+        }
+    };
+    let mut ids = vec![JobId(0)];
+    for challenge in &challenges[1..] {
+        wait_for(*ids.last().unwrap());
+        ids.push(handle.set_challenge(*challenge, 0));
+    }
+    wait_for(ids[3]);
+    let progress = handle.progress();
+    assert_eq!(progress.challenge_changes, 3);
+    assert_eq!(progress.jobs.len(), 1);
+    assert_eq!(progress.jobs[0].id, ids[3]);
+    handle.cancel();
+    found.extend(std::iter::from_fn(|| solutions.recv().ok()));
+    let events: Vec<_> = handle.events().iter().collect();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.jobs.len(), 1);
+
+    for (i, event) in events.iter().enumerate() {
+        assert_eq!(
+            *event,
+            MinerEvent::ChallengeChanged {
+                job: ids[i + 1],
+                challenge: challenges[i + 1],
+                min_difficulty: 0,
+                replaced: vec![ids[i]],
+            }
+        );
+    }
+    // Every solution is labelled with the job it was mined for.
+    for challenge in challenges {
+        assert!(found.iter().any(|s| s.challenge == challenge));
+    }
+    for s in &found {
+        let index = ids.iter().position(|id| *id == s.job).unwrap();
+        assert_eq!(s.challenge, challenges[index]);
+        assert!(s.scored.solution.is_valid(&s.challenge));
+    }
+}
+
+#[test]
+fn test_mine_set_challenge_drops_stale() {
+    let handle = MinerBuilder::new([20; 32])
+        .threads(2)
+        .min_difficulty(0)
+        .chunk_size(4)
+        .stream(100_000)
+        .deliver_stale(false)
+        .spawn()
+        .unwrap();
+    let solutions = handle.solutions().unwrap();
+    let first = solutions.recv_timeout(Duration::from_secs(60)).unwrap();
+    assert_eq!(first.job, JobId(0));
+    let id = handle.set_challenge([21; 32], 0);
+    // Anything sent for the old challenge is already queued once the swap returns.
+    solutions.try_iter().for_each(drop);
+    let mut after = vec![solutions.recv_timeout(Duration::from_secs(60)).unwrap()];
+    handle.cancel();
+    after.extend(std::iter::from_fn(|| solutions.recv().ok()));
+    handle.join().unwrap();
+
+    // Nothing for the old challenge arrives once the swap returns.
+    for s in &after {
+        assert_eq!((s.job, s.challenge), (id, [21; 32]));
+        assert!(s.scored.solution.is_valid(&s.challenge));
+    }
+}
+
+/// What a [`Faulty`] solver does on its trigger nonce.
+#[derive(Clone, Copy)]
+enum Fault {
+    Panic,
+    Hang(Duration),
+}
+
+/// Fails once on one nonce, or on every nonce if `always` is set.
+struct Faulty {
+    inner: EquixSolver,
+    nonce: u64,
+    fault: Fault,
+    tripped: Arc<AtomicBool>,
+    always: bool,
+}
+
+impl Solver for Faulty {
+    fn solve(&mut self, seed: &[u8], runtime: RuntimeOption) -> Result<[u8; 16], DrillxError> {
+        let nonce = u64::from_le_bytes(seed[32..40].try_into().unwrap());
+        if self.always || (nonce == self.nonce && !self.tripped.swap(true, Ordering::Relaxed)) {
+            match self.fault {
+                Fault::Panic => panic!("injected fault at nonce {}", nonce),
+                Fault::Hang(duration) => std::thread::sleep(duration),
+            }
+        }
+        self.inner.solve(seed, runtime)
+    }
+}
+
+fn faulty(nonce: u64, fault: Fault, always: bool) -> impl Fn() -> Faulty + Send + Sync {
+    let tripped = Arc::new(AtomicBool::new(false));
+    move || Faulty {
+        inner: EquixSolver::new(),
+        nonce,
+        fault,
+        tripped: tripped.clone(),
+        always,
+    }
+}
+
+/// Streams every solution in a small nonce range and waits for the run to end.
+fn mine_range(builder: MinerBuilder, start: u64) -> (Vec<u64>, drillx::miner::MineOutcome, u64) {
+    let handle = builder
+        .min_difficulty(0)
+        .start_nonce(start)
+        .chunk_size(4)
+        .stream(1024)
+        .spawn()
+        .unwrap();
+    let solutions = handle.solutions().unwrap();
+    let mut nonces: Vec<u64> = std::iter::from_fn(|| solutions.recv().ok())
+        .map(|s| u64::from_le_bytes(s.scored.solution.n))
+        .collect();
+    while !handle.is_finished() {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    let restarts = handle.progress().restarts;
+    let outcome = handle.join().unwrap();
+    nonces.sort_unstable();
+    (nonces, outcome, restarts)
+}
+
+#[test]
+fn test_mine_restarts_panicked_worker() {
+    let challenge = [22; 32];
+    let start = u64::MAX - 31;
+    let builder =
+        MinerBuilder::new(challenge)
+            .threads(2)
+            .solver(faulty(start + 9, Fault::Panic, false));
+    let (nonces, outcome, restarts) = mine_range(builder, start);
+    assert_eq!(outcome.reason, StopReason::Exhausted);
+    assert_eq!(restarts, 1);
+    assert_eq!(outcome.hashes, 32);
+
+    // The replacement finished the chunk: every solution, exactly once.
+    let expected: Vec<u64> = (start..=u64::MAX)
+        .filter(|n| drillx::hash(&challenge, &n.to_le_bytes()).is_ok())
+        .collect();
+    assert_eq!(nonces, expected);
+}
+
+#[test]
+fn test_mine_restarts_stalled_worker() {
+    let challenge = [23; 32];
+    let start = u64::MAX - 15;
+    let builder = MinerBuilder::new(challenge)
+        .threads(1)
+        .stall_timeout(Duration::from_millis(200))
+        .solver(faulty(
+            start + 5,
+            Fault::Hang(Duration::from_secs(3)),
+            false,
+        ));
+    let began = std::time::Instant::now();
+    let (nonces, outcome, restarts) = mine_range(builder, start);
+    assert!(began.elapsed() < Duration::from_secs(3));
+    assert_eq!(outcome.reason, StopReason::Exhausted);
+    assert_eq!(restarts, 1);
+    assert_eq!(outcome.hashes, 16);
+    let expected: Vec<u64> = (start..=u64::MAX)
+        .filter(|n| drillx::hash(&challenge, &n.to_le_bytes()).is_ok())
+        .collect();
+    assert_eq!(nonces, expected);
+}
+
+#[test]
+fn test_mine_recovers_and_solves() {
+    let challenge = [24; 32];
+    let outcome = MinerBuilder::new(challenge)
+        .threads(2)
+        .min_difficulty(6)
+        .solver(faulty(1, Fault::Panic, false))
+        .spawn()
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(outcome.reason, StopReason::Found);
+    let best = outcome.best.unwrap();
+    assert!(best.difficulty >= 6);
+    assert!(best.solution.is_valid(&challenge));
+}
+
+#[test]
+fn test_mine_restart_limit() {
+    let result = MinerBuilder::new([25; 32])
+        .threads(2)
+        .min_difficulty(64)
+        .restart_limit(3)
+        .solver(faulty(0, Fault::Panic, true))
+        .spawn()
+        .unwrap()
+        .join();
+    assert!(matches!(
+        result,
+        Err(MinerError::TooManyRestarts { limit: 3 })
+    ));
+}
+
+/// Allocates solver memory while the budget lasts.
+struct Budget(Arc<AtomicUsize>);
+
+impl MemoryAllocator for Budget {
+    fn allocate(&self) -> Result<DrillxMemory, MemoryError> {
+        self.0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |left| {
+                left.checked_sub(1)
+            })
+            .map_err(|_| MemoryError {
+                size: DrillxMemory::SIZE,
+            })?;
+        DrillxMemory::try_new()
+    }
+}
+
+fn budget_pool(budget: usize) -> (Arc<AtomicUsize>, Arc<MemoryPool>) {
+    let budget = Arc::new(AtomicUsize::new(budget));
+    let pool = Arc::new(MemoryPool::with_allocator(Budget(budget.clone())));
+    (budget, pool)
+}
+
+/// Waits until the miner runs `threads` CPU workers.
+fn wait_for_threads(handle: &miner::MinerHandle, threads: usize) -> miner::Progress {
+    let started = std::time::Instant::now();
+    loop {
+        let progress = handle.progress();
+        if progress.threads == threads || started.elapsed() > Duration::from_secs(10) {
+            return progress;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn test_mine_starts_degraded_and_scales_up() {
+    let (budget, pool) = budget_pool(2);
+    let handle = MinerBuilder::new([40; 32])
+        .threads(4)
+        .min_difficulty(64)
+        .memory_pool(pool.clone())
+        .memory_retry(Duration::from_millis(50))
+        .spawn()
+        .unwrap();
+    let progress = handle.progress();
+    assert_eq!(progress.threads, 2);
+    let error = MemoryError {
+        size: DrillxMemory::SIZE,
+    };
+    assert_eq!(
+        progress.warnings,
+        vec![MinerWarning::MemoryExhausted {
+            requested: 4,
+            running: 2,
+            error,
+        }]
+    );
+
+    // Retries keep failing until memory frees up.
+    std::thread::sleep(Duration::from_millis(200));
+    assert_eq!(handle.progress().threads, 2);
+    budget.store(2, Ordering::Relaxed);
+    let progress = wait_for_threads(&handle, 4);
+    assert_eq!(progress.threads, 4);
+    assert!(progress.warnings.is_empty());
+    handle.cancel();
+    assert_eq!(handle.join().unwrap().reason, StopReason::Cancelled);
+    // Every worker gave its memory back.
+    assert_eq!(pool.allocated(), 4);
+    assert_eq!(pool.idle(), 4);
+}
+
+#[test]
+fn test_mine_without_memory() {
+    let (_, pool) = budget_pool(0);
+    let result = MinerBuilder::new([41; 32])
+        .threads(2)
+        .memory_pool(pool)
+        .spawn();
+    assert!(matches!(
+        result,
+        Err(MinerError::OutOfMemory(MemoryError { size })) if size == DrillxMemory::SIZE
+    ));
+}
+
+#[test]
+fn test_mine_set_threads_out_of_memory() {
+    let (budget, pool) = budget_pool(1);
+    let handle = MinerBuilder::new([42; 32])
+        .threads(1)
+        .min_difficulty(64)
+        .memory_pool(pool)
+        .memory_retry(Duration::from_millis(50))
+        .spawn()
+        .unwrap();
+    assert!(handle.progress().warnings.is_empty());
+    handle.set_threads(3).unwrap();
+    let progress = handle.progress();
+    assert_eq!(progress.threads, 1);
+    assert!(matches!(
+        progress.warnings[..],
+        [MinerWarning::MemoryExhausted {
+            requested: 3,
+            running: 1,
+            ..
+        }]
+    ));
+    // Hashing goes on with the workers there are.
+    let hashes = progress.hashes;
+    std::thread::sleep(Duration::from_millis(300));
+    assert!(handle.progress().hashes > hashes);
+
+    // Scaling back down ends the shortage.
+    handle.set_threads(1).unwrap();
+    assert!(handle.progress().warnings.is_empty());
+    budget.store(5, Ordering::Relaxed);
+    std::thread::sleep(Duration::from_millis(200));
+    assert_eq!(handle.progress().threads, 1);
+    handle.cancel();
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_mine_stalled_worker_without_memory() {
+    // The stalled worker holds its memory, so its replacement cannot get any.
+    let challenge = [43; 32];
+    let start = u64::MAX - 15;
+    let (_, pool) = budget_pool(2);
+    let stalled = faulty(start + 5, Fault::Hang(Duration::from_secs(1)), false);
+    let builder = MinerBuilder::new(challenge)
+        .threads(2)
+        .stall_timeout(Duration::from_millis(200))
+        .try_solver({
+            let pool = pool.clone();
+            move || {
+                let mut solver = stalled();
+                solver.inner = EquixSolver::pooled(&pool)?;
+                Ok(solver)
+            }
+        });
+    let (nonces, outcome, restarts) = mine_range(builder, start);
+    assert_eq!(outcome.reason, StopReason::Exhausted);
+    assert_eq!(restarts, 1);
+    assert_eq!(outcome.hashes, 16);
+    assert_eq!(pool.allocated(), 2);
+
+    // The other worker took over the orphaned chunk.
+    let expected: Vec<u64> = (start..=u64::MAX)
+        .filter(|n| drillx::hash(&challenge, &n.to_le_bytes()).is_ok())
+        .collect();
+    assert_eq!(nonces, expected);
+}
+
+/// Fails every compile, as a solver on a host whose compiler is broken would.
+struct NoCompiler;
+
+impl Solver for NoCompiler {
+    fn solve(&mut self, _: &[u8], runtime: RuntimeOption) -> Result<[u8; 16], DrillxError> {
+        match runtime {
+            RuntimeOption::InterpretOnly => Ok([0; 16]),
+            _ => Err(DrillxError::CompileFailed),
+        }
+    }
+}
+
+#[test]
+fn test_mine_required_compile_failure_ends_run() {
+    let result = MinerBuilder::new([44; 32])
+        .threads(2)
+        .min_difficulty(256)
+        .runtime(RuntimeOption::RequireCompile)
+        .solver(|| NoCompiler)
+        .spawn()
+        .unwrap()
+        .join();
+    assert!(matches!(
+        result,
+        Err(MinerError::Runtime(DrillxError::CompileFailed))
+    ));
+
+    // Trying the compiler falls back to the interpreter instead.
+    let outcome = MinerBuilder::new([44; 32])
+        .threads(1)
+        .min_difficulty(256)
+        .end_nonce(15)
+        .runtime(RuntimeOption::TryCompile)
+        .solver(|| NoCompiler)
+        .spawn()
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(outcome.reason, StopReason::Exhausted);
+}
+
+#[test]
+fn test_mine_require_compile() {
+    let result = MinerBuilder::new([45; 32])
+        .threads(1)
+        .min_difficulty(1)
+        .runtime(RuntimeOption::RequireCompile)
+        .spawn();
+    if drillx::runtime_info().compiler_available {
+        let outcome = result.unwrap().join().unwrap();
+        assert_eq!(outcome.reason, StopReason::Found);
+    } else {
+        assert!(matches!(
+            result,
+            Err(MinerError::Runtime(DrillxError::CompilerUnsupported))
+        ));
+    }
+}
+
+#[cfg(not(all(
+    feature = "compiler",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+#[test]
+fn test_mine_require_compile_unsupported() {
+    let result = MinerBuilder::new([45; 32])
+        .runtime(RuntimeOption::RequireCompile)
+        .spawn();
+    assert!(matches!(
+        result,
+        Err(MinerError::Runtime(DrillxError::CompilerUnsupported))
+    ));
+}
@@ -0,0 +1,237 @@
+use std::collections::HashSet;
+
+use drillx::{
+    miner::{MinerBuilder, MinerConfig, MinerError, NonceOrder, SteppingMiner, StopReason},
+    NamespaceError, NonceNamespace,
+};
+
+/// Deterministic pseudo-random numbers.
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[test]
+fn test_compose_decompose_round_trip() {
+    let mut rng = SplitMix(165);
+    for id_bits in 0..=64 {
+        let namespace = NonceNamespace::new(id_bits);
+        assert_eq!(namespace.id_bits() + namespace.counter_bits(), 64);
+        for _ in 0..100 {
+            let nonce = rng.next();
+            let (worker_id, counter) = namespace.decompose(nonce);
+            assert!(worker_id <= namespace.max_worker_id());
+            assert!(counter <= namespace.max_counter());
+            assert_eq!(namespace.compose(worker_id, counter), Ok(nonce));
+        }
+    }
+}
+
+#[test]
+fn test_boundary_ids() {
+    let namespace = NonceNamespace::new(8);
+    assert_eq!(namespace.max_worker_id(), 255);
+    assert_eq!(namespace.max_counter(), (1 << 56) - 1);
+    assert_eq!(namespace.compose(0, 0), Ok(0));
+    assert_eq!(namespace.compose(255, 0), Ok(0xff << 56));
+    assert_eq!(namespace.compose(255, (1 << 56) - 1), Ok(u64::MAX));
+    assert_eq!(namespace.decompose(u64::MAX), (255, (1 << 56) - 1));
+    assert_eq!(namespace.decompose(1 << 56), (1, 0));
+
+    // No id bits leaves the whole nonce to the counter of worker 0.
+    let whole = NonceNamespace::new(0);
+    assert_eq!(whole.max_worker_id(), 0);
+    assert_eq!(whole.compose(0, u64::MAX), Ok(u64::MAX));
+    assert_eq!(whole.decompose(u64::MAX), (0, u64::MAX));
+
+    // All id bits leave each worker a single nonce.
+    let single = NonceNamespace::new(64);
+    assert_eq!(single.max_counter(), 0);
+    assert_eq!(single.compose(u64::MAX, 0), Ok(u64::MAX));
+    assert_eq!(single.decompose(17), (17, 0));
+    assert_eq!(NonceNamespace::new(200), single);
+}
+
+#[test]
+fn test_overflow_rejected() {
+    let namespace = NonceNamespace::new(8);
+    assert_eq!(
+        namespace.compose(256, 0),
+        Err(NamespaceError::WorkerId {
+            worker_id: 256,
+            id_bits: 8
+        })
+    );
+    assert_eq!(
+        namespace.compose(1, 1 << 56),
+        Err(NamespaceError::Counter {
+            counter: 1 << 56,
+            counter_bits: 56
+        })
+    );
+    assert_eq!(
+        NonceNamespace::new(0).compose(1, 0),
+        Err(NamespaceError::WorkerId {
+            worker_id: 1,
+            id_bits: 0
+        })
+    );
+    assert_eq!(
+        NonceNamespace::new(64).compose(0, 1),
+        Err(NamespaceError::Counter {
+            counter: 1,
+            counter_bits: 0
+        })
+    );
+}
+
+/// Every nonce a namespaced miner hashed, found by streaming every solution.
+fn mined_nonces(namespace: NonceNamespace, worker_id: u64, challenge: [u8; 32]) -> Vec<u64> {
+    let handle = MinerBuilder::new(challenge)
+        .threads(2)
+        .min_difficulty(0)
+        .chunk_size(3)
+        .stream(1024)
+        .end_nonce(39)
+        .nonce_namespace(namespace, worker_id)
+        .spawn()
+        .unwrap();
+    let nonces: Vec<u64> = handle
+        .solutions()
+        .unwrap()
+        .iter()
+        .map(|s| u64::from_le_bytes(s.scored.solution.n))
+        .collect();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Exhausted);
+    assert_eq!(outcome.hashes, 40);
+    nonces
+}
+
+#[test]
+fn test_namespaced_miners_are_disjoint() {
+    let challenge = [165; 32];
+    let namespace = NonceNamespace::new(16);
+    let first = mined_nonces(namespace, 3, challenge);
+    let second = mined_nonces(namespace, 4, challenge);
+    assert!(!first.is_empty() && !second.is_empty());
+
+    for (nonces, worker_id) in [(&first, 3), (&second, 4)] {
+        let unique: HashSet<_> = nonces.iter().collect();
+        assert_eq!(unique.len(), nonces.len());
+        for &nonce in nonces {
+            let (id, counter) = namespace.decompose(nonce);
+            assert_eq!(id, worker_id);
+            assert!(counter < 40);
+        }
+        // Each nonce's solution verifies as usual.
+        let expected: Vec<u64> = (0..40)
+            .map(|counter| namespace.compose(worker_id, counter).unwrap())
+            .filter(|n| drillx::hash(&challenge, &n.to_le_bytes()).is_ok())
+            .collect();
+        let mut sorted = nonces.clone();
+        sorted.sort_unstable();
+        assert_eq!(sorted, expected);
+    }
+    let first: HashSet<_> = first.into_iter().collect();
+    assert!(second.iter().all(|nonce| !first.contains(nonce)));
+}
+
+#[test]
+fn test_namespace_caps_counter_space() {
+    // Four counter bits leave the highest worker 16 nonces, at the top of the space.
+    let namespace = NonceNamespace::new(60);
+    let worker_id = namespace.max_worker_id();
+    let outcome = MinerBuilder::new([2; 32])
+        .threads(2)
+        .min_difficulty(64)
+        .chunk_size(4)
+        .nonce_namespace(namespace, worker_id)
+        .spawn()
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(outcome.reason, StopReason::Exhausted);
+    assert_eq!(outcome.hashes, 16);
+    if let Some(best) = outcome.best {
+        let nonce = u64::from_le_bytes(best.solution.n);
+        assert_eq!(namespace.decompose(nonce).0, worker_id);
+    }
+
+    let config = MinerConfig {
+        min_difficulty: 64,
+        nonce_namespace: Some((namespace, worker_id)),
+        ..MinerConfig::default()
+    };
+    let mut stepping = SteppingMiner::new([2; 32], &config).unwrap();
+    let step = stepping.step(100);
+    assert_eq!(step.hashes, 16);
+    assert_eq!(step.done, Some(StopReason::Exhausted));
+}
+
+#[test]
+fn test_bad_namespace_fails_to_start() {
+    let namespace = NonceNamespace::new(4);
+    let err = MinerBuilder::new([1; 32])
+        .threads(1)
+        .nonce_namespace(namespace, 16)
+        .spawn()
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err,
+        MinerError::Namespace(NamespaceError::WorkerId {
+            worker_id: 16,
+            id_bits: 4
+        })
+    ));
+
+    let err = MinerBuilder::new([1; 32])
+        .threads(1)
+        .start_nonce(1 << 60)
+        .nonce_namespace(namespace, 0)
+        .spawn()
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err,
+        MinerError::Namespace(NamespaceError::Counter { .. })
+    ));
+
+    let err = MinerBuilder::new([1; 32])
+        .threads(1)
+        .nonce_order(NonceOrder::Permuted { key: [1; 16] })
+        .nonce_namespace(namespace, 0)
+        .spawn()
+        .err()
+        .unwrap();
+    assert!(matches!(
+        err,
+        MinerError::Namespace(NamespaceError::PermutedOrder)
+    ));
+
+    // The stepping miner rejects the same configs.
+    let config = |worker_id, nonce_order| MinerConfig {
+        nonce_namespace: Some((namespace, worker_id)),
+        nonce_order,
+        ..MinerConfig::default()
+    };
+    assert!(matches!(
+        SteppingMiner::new([1; 32], &config(16, NonceOrder::Sequential)),
+        Err(NamespaceError::WorkerId {
+            worker_id: 16,
+            id_bits: 4
+        })
+    ));
+    assert!(matches!(
+        SteppingMiner::new([1; 32], &config(0, NonceOrder::Permuted { key: [1; 16] })),
+        Err(NamespaceError::PermutedOrder)
+    ));
+}
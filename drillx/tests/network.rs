@@ -0,0 +1,303 @@
+use std::time::{Duration, Instant};
+
+use drillx::{
+    estimate_hashrate, sim::ShareSimulator, DifficultyObservation, HashrateTracker, OnlineEstimator,
+};
+
+/// A small deterministic generator so failures reproduce.
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// The best difficulty of a window at `ln_work = ln(hashrate * seconds)`.
+    ///
+    /// The best is at least `d` unless no hash reaches it, which has probability
+    /// `exp(-work 2^-d)`, so it is the floor of `log2(work / E)` for an exponential `E`.
+    fn best_difficulty(&mut self, ln_work: f64) -> u32 {
+        let uniform = ((self.next() >> 11) + 1) as f64 / (1u64 << 53) as f64;
+        let exponential = -uniform.ln();
+        ((ln_work - exponential.ln()) / std::f64::consts::LN_2)
+            .floor()
+            .max(0.0) as u32
+    }
+}
+
+fn simulate(
+    rng: &mut SplitMix,
+    hashrate: f64,
+    window: f64,
+    n: usize,
+) -> Vec<DifficultyObservation> {
+    let ln_work = (hashrate * window).ln();
+    (0..n)
+        .map(|_| DifficultyObservation::new(rng.best_difficulty(ln_work), window))
+        .collect()
+}
+
+#[test]
+fn test_single_observation() {
+    // One window peaks where `x = λ T 2^-(d+1)` is ln 2.
+    let estimate = estimate_hashrate(&[DifficultyObservation::new(20, 60.0)]);
+    let expected = std::f64::consts::LN_2 * 2f64.powi(21) / 60.0;
+    assert!((estimate.hashrate / expected - 1.0).abs() < 1e-9);
+    assert!(estimate.lower < estimate.hashrate && estimate.hashrate < estimate.upper);
+    assert_eq!(estimate.observations, 1.0);
+}
+
+#[test]
+fn test_no_observations() {
+    for observations in [
+        vec![],
+        vec![DifficultyObservation::new(10, 0.0)],
+        vec![DifficultyObservation::new(10, f64::NAN)],
+    ] {
+        let estimate = estimate_hashrate(&observations);
+        assert_eq!(estimate.hashrate, 0.0);
+        assert_eq!(estimate.upper, f64::INFINITY);
+        assert_eq!(estimate.observations, 0.0);
+    }
+}
+
+#[test]
+fn test_converges() {
+    let mut rng = SplitMix(139);
+    let hashrate = 2.5e6;
+    let observations = simulate(&mut rng, hashrate, 60.0, 2_000);
+    let mut widths = vec![];
+    for n in [10, 100, 2_000] {
+        let estimate = estimate_hashrate(&observations[..n]);
+        assert!(estimate.contains(hashrate), "{} {:?}", n, estimate);
+        widths.push(estimate.upper / estimate.lower);
+    }
+    assert!(widths.windows(2).all(|w| w[1] < w[0]));
+    let estimate = estimate_hashrate(&observations);
+    assert!(
+        (estimate.hashrate / hashrate - 1.0).abs() < 0.1,
+        "{:?}",
+        estimate
+    );
+}
+
+#[test]
+fn test_interval_coverage() {
+    let mut rng = SplitMix(1);
+    let hashrate = 1e5;
+    let trials = 400;
+    let covered = (0..trials)
+        .filter(|_| estimate_hashrate(&simulate(&mut rng, hashrate, 30.0, 40)).contains(hashrate))
+        .count();
+    let coverage = covered as f64 / trials as f64;
+    assert!((0.91..=0.99).contains(&coverage), "{}", coverage);
+}
+
+#[test]
+fn test_mixed_windows() {
+    let mut rng = SplitMix(7);
+    let hashrate = 4e7;
+    let observations: Vec<_> = [1.0, 10.0, 400.0]
+        .iter()
+        .flat_map(|window| simulate(&mut rng, hashrate, *window, 300))
+        .collect();
+    let estimate = estimate_hashrate(&observations);
+    assert!(estimate.contains(hashrate), "{:?}", estimate);
+}
+
+#[test]
+fn test_high_difficulty() {
+    // About 2^200 hashes per window, far beyond exact integers in f64.
+    let mut rng = SplitMix(200);
+    let hashrate = 2f64.powi(200);
+    let observations = simulate(&mut rng, hashrate, 1.0, 500);
+    assert!(observations.iter().all(|o| o.best_difficulty > 190));
+    let estimate = estimate_hashrate(&observations);
+    assert!(estimate.contains(hashrate), "{:?}", estimate);
+    assert!(estimate.hashrate.is_finite() && estimate.lower > 0.0);
+}
+
+#[test]
+fn test_online_tracks_changes() {
+    let mut rng = SplitMix(9);
+    let mut online = OnlineEstimator::new(Duration::from_secs(60 * 100));
+    assert!(online.is_empty());
+    assert_eq!(online.estimate().hashrate, 0.0);
+    for observation in simulate(&mut rng, 1e6, 60.0, 1_000) {
+        online.push(observation);
+    }
+    assert!(online.estimate().contains(1e6), "{:?}", online.estimate());
+    for observation in simulate(&mut rng, 8e6, 60.0, 1_000) {
+        online.push(observation);
+    }
+    let estimate = online.estimate();
+    assert!(estimate.contains(8e6), "{:?}", estimate);
+    assert!(!estimate.contains(1e6));
+    // About 144 windows of weight, from a half-life of 100.
+    assert!((estimate.observations - 100.0 / std::f64::consts::LN_2).abs() < 2.0);
+    // Weights below one in a million are dropped.
+    assert!(online.len() < 2_000);
+}
+
+#[test]
+fn test_online_matches_batch_without_decay() {
+    let mut rng = SplitMix(3);
+    let observations = simulate(&mut rng, 3e4, 5.0, 50);
+    let mut online = OnlineEstimator::new(Duration::from_secs(u32::MAX as u64));
+    for observation in &observations {
+        online.push(*observation);
+    }
+    let (online, batch) = (online.estimate(), estimate_hashrate(&observations));
+    assert!((online.hashrate / batch.hashrate - 1.0).abs() < 1e-6);
+}
+
+/// Records `seconds` of a simulated miner's shares, starting `offset` into the window,
+/// and returns when the last one was found.
+fn mine(
+    tracker: &mut HashrateTracker,
+    start: Instant,
+    offset: Duration,
+    hashrate: f64,
+    share_difficulty: u32,
+    seed: u64,
+    seconds: f64,
+) -> Instant {
+    let mut end = start + offset;
+    for share in ShareSimulator::new(hashrate, share_difficulty, seed)
+        .shares()
+        .take_while(|share| share.timestamp.as_secs_f64() < seconds)
+    {
+        end = start + offset + share.timestamp;
+        tracker.record_share(share_difficulty, end);
+    }
+    end
+}
+
+#[test]
+fn test_tracker_from_simulated_shares() {
+    let start = Instant::now();
+    let hashrate = 5e3;
+    let mut tracker = HashrateTracker::new(Duration::from_secs(600)).with_start(start);
+    assert_eq!(tracker.estimate_at(start).hashrate, 0.0);
+    mine(
+        &mut tracker,
+        start,
+        Duration::ZERO,
+        hashrate,
+        14,
+        11,
+        3_600.0,
+    );
+    let estimate = tracker.estimate_at(start + Duration::from_secs(3_600));
+    assert!(estimate.contains(hashrate), "{:?}", estimate);
+    assert!(estimate.upper / estimate.lower < 1.5, "{:?}", estimate);
+}
+
+#[test]
+fn test_tracker_coverage() {
+    let hashrate = 2e4;
+    let trials = 300;
+    let covered = (0..trials)
+        .filter(|seed| {
+            let start = Instant::now();
+            let mut tracker = HashrateTracker::new(Duration::from_secs(300)).with_start(start);
+            mine(
+                &mut tracker,
+                start,
+                Duration::ZERO,
+                hashrate,
+                16,
+                *seed,
+                1_500.0,
+            );
+            tracker
+                .estimate_at(start + Duration::from_secs(1_500))
+                .contains(hashrate)
+        })
+        .count();
+    let coverage = covered as f64 / trials as f64;
+    assert!((0.9..=0.99).contains(&coverage), "{}", coverage);
+}
+
+#[test]
+fn test_tracker_across_vardiff_step() {
+    // Vardiff raises the share difficulty 16x halfway through the window.
+    let start = Instant::now();
+    let hashrate = 1e4;
+    let half_life = Duration::from_secs(900);
+    let mut tracker = HashrateTracker::new(half_life).with_start(start);
+    mine(
+        &mut tracker,
+        start,
+        Duration::ZERO,
+        hashrate,
+        10,
+        21,
+        1_800.0,
+    );
+    let step = Duration::from_secs(1_800);
+    mine(&mut tracker, start, step, hashrate, 14, 22, 1_800.0);
+    for at in [step, step * 2] {
+        let estimate = tracker.estimate_at(start + at);
+        assert!(estimate.contains(hashrate), "{:?}", estimate);
+    }
+}
+
+#[test]
+fn test_tracker_bursts_and_gaps() {
+    let start = Instant::now();
+    let half_life = Duration::from_secs(60);
+    let mut tracker = HashrateTracker::new(half_life).with_start(start);
+    // Sixteen shares of 2^10 in one instant, after 16 seconds: 1024 H/s.
+    let at = start + Duration::from_secs(16);
+    for _ in 0..16 {
+        tracker.record_share(10, at);
+    }
+    let burst = tracker.estimate_at(at);
+    assert!(burst.hashrate.is_finite());
+    assert!((burst.observations - 16.0).abs() < 1e-9);
+    // A burst's shares may arrive in any order.
+    let mut reordered = HashrateTracker::new(half_life).with_start(start);
+    reordered.record_share(10, at);
+    reordered.record_share(10, start + Duration::from_secs(8));
+    let mut ordered = HashrateTracker::new(half_life).with_start(start);
+    ordered.record_share(10, start + Duration::from_secs(8));
+    ordered.record_share(10, at);
+    let (a, b) = (reordered.estimate_at(at), ordered.estimate_at(at));
+    assert!((a.hashrate / b.hashrate - 1.0).abs() < 1e-9);
+
+    // A miner gone quiet decays toward zero, halving each half-life once the window
+    // is full.
+    let quiet = |minutes: u64| tracker.estimate_at(at + Duration::from_secs(60 * minutes));
+    assert!(quiet(1).hashrate < burst.hashrate);
+    assert!((quiet(21).hashrate / quiet(20).hashrate - 0.5).abs() < 1e-3);
+    assert!(quiet(60).hashrate < 1e-9);
+}
+
+#[test]
+fn test_tracker_merge() {
+    let start = Instant::now();
+    let half_life = Duration::from_secs(600);
+    let (a, b) = (3e3, 9e3);
+    let mut workers = [a, b].map(|_| HashrateTracker::new(half_life).with_start(start));
+    mine(&mut workers[0], start, Duration::ZERO, a, 12, 31, 3_600.0);
+    mine(&mut workers[1], start, Duration::ZERO, b, 14, 32, 3_600.0);
+    let now = start + Duration::from_secs(3_600);
+
+    let mut account = HashrateTracker::new(half_life).with_start(start);
+    for worker in &workers {
+        account.merge(worker);
+    }
+    let estimate = account.estimate_at(now);
+    assert!(estimate.contains(a + b), "{:?}", estimate);
+    let sum: f64 = workers.iter().map(|w| w.estimate_at(now).hashrate).sum();
+    assert!((estimate.hashrate / sum - 1.0).abs() < 1e-9);
+    // Merging is order independent.
+    let mut reversed = workers[1].clone();
+    reversed.merge(&workers[0]);
+    assert!((reversed.estimate_at(now).hashrate / estimate.hashrate - 1.0).abs() < 1e-9);
+}
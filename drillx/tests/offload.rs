@@ -0,0 +1,200 @@
+#![cfg(feature = "offload")]
+
+use std::sync::{Arc, Mutex};
+
+use drillx::{
+    offload::{self, DeviceError, OffloadStats, OffloadVerifier, VerifyDevice},
+    Solution,
+};
+
+/// Valid and invalid solutions for one challenge, from the test vectors.
+fn mixed_batch() -> ([u8; 32], Vec<Solution>, Vec<bool>) {
+    let vector = drillx::vectors::VECTORS
+        .iter()
+        .find(|vector| vector.output.is_some())
+        .unwrap();
+    let valid = Solution::new(vector.output.unwrap().digest, vector.nonce);
+    let mut solutions = vec![];
+    for i in 0..40u8 {
+        let mut solution = valid;
+        if i % 3 != 0 {
+            solution.d[i as usize % 16] ^= 1 + i;
+        }
+        solutions.push(solution);
+    }
+    let expected = drillx::verify_batch(&vector.challenge, &solutions);
+    assert!(expected.iter().any(|v| *v) && expected.iter().any(|v| !*v));
+    (vector.challenge, solutions, expected)
+}
+
+/// What a mock device was asked to do.
+#[derive(Default)]
+struct Calls {
+    batches: Vec<usize>,
+}
+
+/// A device that verifies on the CPU, optionally lying about some verdicts or failing
+/// some launches.
+struct MockDevice {
+    max_batch: usize,
+    /// Indices within each launch whose verdict is flipped.
+    flip: Vec<usize>,
+    /// Launch numbers that fail.
+    fail: Vec<usize>,
+    calls: Arc<Mutex<Calls>>,
+}
+
+impl MockDevice {
+    fn new(max_batch: usize) -> (Self, Arc<Mutex<Calls>>) {
+        let calls = Arc::new(Mutex::new(Calls::default()));
+        let device = MockDevice {
+            max_batch,
+            flip: vec![],
+            fail: vec![],
+            calls: calls.clone(),
+        };
+        (device, calls)
+    }
+}
+
+impl VerifyDevice for MockDevice {
+    fn name(&self) -> String {
+        "mock".to_string()
+    }
+
+    fn max_batch(&self) -> usize {
+        self.max_batch
+    }
+
+    fn verify(
+        &mut self,
+        challenge: &[u8; 32],
+        solutions: &[[u8; 24]],
+        verdicts: &mut [bool],
+    ) -> Result<(), DeviceError> {
+        assert_eq!(solutions.len(), verdicts.len());
+        let mut calls = self.calls.lock().unwrap();
+        calls.batches.push(solutions.len());
+        if self.fail.contains(&(calls.batches.len() - 1)) {
+            return Err(DeviceError("launch failed".to_string()));
+        }
+        for (i, (bytes, verdict)) in solutions.iter().zip(verdicts).enumerate() {
+            *verdict = Solution::from_bytes(*bytes).is_valid(challenge) != self.flip.contains(&i);
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn test_cpu_fallback_without_device() {
+    let (challenge, solutions, expected) = mixed_batch();
+    let mut verifier = OffloadVerifier::cpu();
+    assert_eq!(verifier.device_name(), None);
+    assert_eq!(verifier.verify_batch(&challenge, &solutions), expected);
+    assert_eq!(
+        verifier.stats(),
+        OffloadStats {
+            cpu_verified: 40,
+            ..OffloadStats::default()
+        }
+    );
+
+    // No backend is built in, so the process-wide verifier runs on the CPU.
+    assert!(offload::default_device().is_none());
+    assert_eq!(offload::verify_batch(&challenge, &solutions), expected);
+    assert!(offload::verify_batch(&challenge, &[]).is_empty());
+}
+
+#[test]
+fn test_device_batches() {
+    let (challenge, solutions, expected) = mixed_batch();
+    let (device, calls) = MockDevice::new(16);
+    let mut verifier = OffloadVerifier::with_device(Box::new(device)).cross_check(0.0);
+    assert_eq!(verifier.device_name().as_deref(), Some("mock"));
+    assert_eq!(verifier.verify_batch(&challenge, &solutions), expected);
+    assert_eq!(calls.lock().unwrap().batches, vec![16, 16, 8]);
+    let stats = verifier.stats();
+    assert_eq!(stats.device_verified, 40);
+    assert_eq!((stats.cpu_verified, stats.cross_checked), (0, 0));
+
+    // Buffers are reused for later, smaller batches.
+    assert_eq!(
+        verifier.verify_batch(&challenge, &solutions[..3]),
+        &expected[..3]
+    );
+    assert_eq!(calls.lock().unwrap().batches, vec![16, 16, 8, 3]);
+    assert!(verifier.verify_batch(&challenge, &[]).is_empty());
+    assert_eq!(calls.lock().unwrap().batches.len(), 4);
+}
+
+#[test]
+fn test_failed_launch_falls_back() {
+    let (challenge, solutions, expected) = mixed_batch();
+    let (mut device, calls) = MockDevice::new(16);
+    device.fail = vec![1];
+    let mut verifier = OffloadVerifier::with_device(Box::new(device)).cross_check(0.0);
+    assert_eq!(verifier.verify_batch(&challenge, &solutions), expected);
+    assert_eq!(calls.lock().unwrap().batches, vec![16, 16, 8]);
+    let stats = verifier.stats();
+    assert_eq!(stats.fallbacks, 1);
+    assert_eq!((stats.device_verified, stats.cpu_verified), (24, 16));
+}
+
+#[test]
+fn test_full_cross_check_corrects_device() {
+    let (challenge, solutions, expected) = mixed_batch();
+    let (mut device, _) = MockDevice::new(16);
+    device.flip = vec![0, 5];
+    let mut verifier = OffloadVerifier::with_device(Box::new(device)).cross_check(1.0);
+    assert_eq!(verifier.verify_batch(&challenge, &solutions), expected);
+    let stats = verifier.stats();
+    assert_eq!(stats.cross_checked, 40);
+    // Two flips in each of three launches.
+    assert_eq!(stats.disagreements, 6);
+}
+
+#[test]
+fn test_unchecked_lies_pass_through() {
+    let (challenge, solutions, expected) = mixed_batch();
+    let (mut device, _) = MockDevice::new(64);
+    device.flip = vec![1];
+    let mut verifier = OffloadVerifier::with_device(Box::new(device)).cross_check(0.0);
+    let verdicts = verifier.verify_batch(&challenge, &solutions);
+    assert_ne!(verdicts[1], expected[1]);
+    assert_eq!(verifier.stats().disagreements, 0);
+}
+
+#[test]
+fn test_sampled_cross_check() {
+    let (challenge, solutions, _) = mixed_batch();
+    let (device, _) = MockDevice::new(40);
+    let mut verifier = OffloadVerifier::with_device(Box::new(device)).cross_check(0.25);
+    for _ in 0..50 {
+        verifier.verify_batch(&challenge, &solutions);
+    }
+    let stats = verifier.stats();
+    assert_eq!(stats.device_verified, 2_000);
+    // 2,000 draws at one in four: a mean of 500 and a standard deviation near 19.
+    assert!((400..600).contains(&stats.cross_checked), "{:?}", stats);
+    assert_eq!(stats.disagreements, 0);
+
+    // The default checks a small fraction, and out-of-range fractions clamp.
+    let (device, _) = MockDevice::new(40);
+    let mut verifier = OffloadVerifier::with_device(Box::new(device)).cross_check(7.0);
+    verifier.verify_batch(&challenge, &solutions);
+    assert_eq!(verifier.stats().cross_checked, 40);
+    let (device, _) = MockDevice::new(40);
+    let mut verifier = OffloadVerifier::with_device(Box::new(device)).cross_check(f64::NAN);
+    verifier.verify_batch(&challenge, &solutions);
+    assert_eq!(verifier.stats().cross_checked, 0);
+}
+
+#[test]
+fn test_no_built_in_device() {
+    // No backend ships with drillx, so the default path is the CPU's.
+    assert!(offload::default_device().is_none());
+    assert!(OffloadVerifier::new().device_name().is_none());
+    let (challenge, solutions, expected) = mixed_batch();
+    assert_eq!(offload::verify_batch(&challenge, &solutions), expected);
+    assert_eq!(drillx::verify_batch(&challenge, &solutions), expected);
+}
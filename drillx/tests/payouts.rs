@@ -0,0 +1,166 @@
+use drillx::payouts::{pplns, proportional};
+
+/// A small deterministic generator so failures reproduce.
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    /// Random shares of up to `miners` miners, with difficulties in `min..min + spread`.
+    fn shares(&mut self, miners: u64, min: u32, spread: u64) -> Vec<(u64, u32)> {
+        let n = 1 + self.below(40);
+        (0..n)
+            .map(|_| (self.below(miners), min + self.below(spread) as u32))
+            .collect()
+    }
+
+    fn reward(&mut self) -> u64 {
+        match self.below(3) {
+            0 => self.below(100),
+            1 => self.next(),
+            _ => u64::MAX - self.below(3),
+        }
+    }
+}
+
+fn total(payouts: &[(u64, u64)]) -> u128 {
+    payouts.iter().map(|(_, payout)| *payout as u128).sum()
+}
+
+fn payout(payouts: &[(u64, u64)], miner: u64) -> u64 {
+    payouts
+        .iter()
+        .find(|(m, _)| *m == miner)
+        .map_or(0, |(_, payout)| *payout)
+}
+
+#[test]
+fn test_proportional() {
+    let shares = [("b", 9), ("a", 8), ("b", 8), ("c", 7)];
+    // Weights a = 1, b = 3, and c is below the minimum.
+    assert_eq!(proportional(&shares, 8, 400), vec![("a", 100), ("b", 300)]);
+    // 10 / 4 leaves two units, for the remainders 0.5 and 0.5, ties to the smaller id.
+    assert_eq!(proportional(&shares, 8, 10), vec![("a", 3), ("b", 7)]);
+    assert_eq!(proportional(&shares, 8, 0), vec![("a", 0), ("b", 0)]);
+    assert_eq!(proportional(&shares, 10, 100), vec![]);
+    assert_eq!(proportional::<&str>(&[], 0, 100), vec![]);
+}
+
+#[test]
+fn test_single_miner() {
+    for reward in [0, 1, 7, u64::MAX] {
+        assert_eq!(
+            proportional(&[(1, 12), (1, 40)], 12, reward),
+            vec![(1, reward)]
+        );
+        assert_eq!(
+            pplns(&[(1, 12), (1, 40)], 1 << 20, reward),
+            vec![(1, reward)]
+        );
+    }
+}
+
+#[test]
+fn test_equal_difficulties() {
+    // Seven equal miners split 100 as 15 each for the first two and 14 for the rest.
+    let shares: Vec<(u32, u32)> = (0..7).map(|miner| (miner, 10)).collect();
+    let expected: Vec<(u32, u64)> = (0..7).map(|m| (m, if m < 2 { 15 } else { 14 })).collect();
+    assert_eq!(proportional(&shares, 10, 100), expected);
+    assert_eq!(pplns(&shares, u128::MAX, 100), expected);
+}
+
+#[test]
+fn test_pplns_window() {
+    // Newest last: c's 2^4, b's 2^3, then a's 2^2 straddles a window of 26.
+    let shares = [("z", 10), ("a", 2), ("b", 3), ("c", 4)];
+    assert_eq!(
+        pplns(&shares, 26, 260),
+        vec![("a", 20), ("b", 80), ("c", 160)]
+    );
+    // A window within the newest share pays only its miner.
+    assert_eq!(pplns(&shares, 16, 99), vec![("c", 99)]);
+    assert_eq!(pplns(&shares, 0, 99), vec![]);
+    // A window larger than every share pays them all.
+    let all = pplns(&shares, u128::MAX, 1052);
+    assert_eq!(all, vec![("a", 4), ("b", 8), ("c", 16), ("z", 1024)]);
+}
+
+#[test]
+fn test_saturation() {
+    // Weights at u128::MAX saturate per miner, and their sum overflows, so every weight
+    // is halved and the two miners still split evenly.
+    let shares = [(1u8, 200), (2, 300), (2, 128)];
+    assert_eq!(proportional(&shares, 0, 11), vec![(1, 6), (2, 5)]);
+    // The newest share alone fills a window of u128::MAX.
+    assert_eq!(pplns(&shares, u128::MAX, 11), vec![(2, 11)]);
+    // Against a saturated miner, a small one gets nothing, but is listed.
+    assert_eq!(
+        proportional(&[(1u8, 200), (2, 0)], 0, u64::MAX),
+        vec![(1, u64::MAX), (2, 0)]
+    );
+}
+
+#[test]
+fn test_conservation() {
+    let mut rng = SplitMix(149);
+    for _ in 0..2_000 {
+        let (min, spread) = [(0, 8), (10, 40), (0, 200)][rng.below(3) as usize];
+        let miners = 1 + rng.below(10);
+        let shares = rng.shares(miners, min, spread);
+        let reward = rng.reward();
+        let window = 1 + rng.next() as u128 * rng.next() as u128;
+        for payouts in [
+            proportional(&shares, min, reward),
+            pplns(&shares, window, reward),
+        ] {
+            assert!(!payouts.is_empty());
+            assert_eq!(total(&payouts), reward as u128, "{:?} {}", shares, reward);
+            assert!(payouts.windows(2).all(|w| w[0].0 < w[1].0));
+        }
+    }
+}
+
+#[test]
+fn test_monotonicity() {
+    let mut rng = SplitMix(150);
+    for _ in 0..2_000 {
+        let (min, spread) = [(0, 8), (10, 40), (0, 200)][rng.below(3) as usize];
+        let miners = 1 + rng.below(6);
+        let shares = rng.shares(miners, min, spread);
+        let reward = rng.reward();
+        let miner = shares[rng.below(shares.len() as u64) as usize].0;
+        let before = proportional(&shares, min, reward);
+
+        // Another share, or a harder one, for one miner never pays it less.
+        let mut more = shares.clone();
+        more.push((miner, min + rng.below(spread) as u32));
+        let mut harder = shares.clone();
+        let i = harder.iter().position(|(m, _)| *m == miner).unwrap();
+        harder[i].1 += 1;
+        for weights in [more, harder] {
+            let after = proportional(&weights, min, reward);
+            assert!(
+                payout(&after, miner) >= payout(&before, miner),
+                "{:?} -> {:?}",
+                shares,
+                weights
+            );
+        }
+
+        // Likewise in PPLNS with a window covering every share.
+        let before = pplns(&shares, u128::MAX, reward);
+        let mut more = shares.clone();
+        more.insert(0, (miner, rng.below(spread) as u32));
+        assert!(payout(&pplns(&more, u128::MAX, reward), miner) >= payout(&before, miner));
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::HashSet;
+
+use drillx::NoncePermutation;
+
+/// Deterministic pseudo-random numbers.
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+fn key(count: u8) -> [u8; 16] {
+    std::array::from_fn(|i| i as u8 * count)
+}
+
+#[test]
+fn test_permutation_vectors() {
+    let cases: [([u8; 16], [u64; 4]); 2] = [
+        (
+            [0; 16],
+            [
+                0x5abb_88e1_5963_b269,
+                0xe83c_d9df_9d3f_3401,
+                0x62bf_0854_4bd8_117b,
+                0x728c_dea5_bc25_f500,
+            ],
+        ),
+        (
+            key(1),
+            [
+                0x333f_1880_ee7b_bff0,
+                0x1c55_c443_6f7b_1486,
+                0xdcd8_7a9d_57ca_6be7,
+                0x3dd7_f9fc_b8b1_5e2e,
+            ],
+        ),
+    ];
+    for (key, nonces) in cases {
+        let permutation = NoncePermutation::new(key);
+        for (i, nonce) in [0, 1, 2, u64::MAX].into_iter().zip(nonces) {
+            assert_eq!(permutation.permute(i), nonce, "key {:?}, index {}", key, i);
+        }
+    }
+}
+
+#[test]
+fn test_permutation_inverts() {
+    let mut rng = SplitMix(3);
+    for count in 0..4 {
+        let permutation = NoncePermutation::new(key(count));
+        for _ in 0..10_000 {
+            let i = rng.next();
+            assert_eq!(permutation.invert(permutation.permute(i)), i);
+            assert_eq!(permutation.permute(permutation.invert(i)), i);
+        }
+    }
+}
+
+#[test]
+fn test_permutation_is_injective_on_ranges() {
+    let mut rng = SplitMix(4);
+    let permutation = NoncePermutation::new(key(7));
+    let starts = [0, u64::MAX - 65_535, 1 << 32, rng.next(), rng.next()];
+    for start in starts {
+        let nonces: HashSet<u64> = (start..=start + 65_535)
+            .map(|i| permutation.permute(i))
+            .collect();
+        assert_eq!(nonces.len(), 65_536, "range from {}", start);
+    }
+}
+
+#[test]
+fn test_permutation_depends_on_key() {
+    let a = NoncePermutation::new(key(1));
+    let b = NoncePermutation::new(key(2));
+    let same = (0..1000).filter(|&i| a.permute(i) == b.permute(i)).count();
+    assert_eq!(same, 0);
+}
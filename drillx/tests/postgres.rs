@@ -0,0 +1,101 @@
+#![cfg(feature = "sqlx-postgres")]
+
+//! These tests need a database and skip themselves unless `DATABASE_URL` is set.
+
+use drillx::{postgres::LengthError, Hash, Solution};
+use sqlx::{PgPool, Row};
+
+async fn pool() -> Option<PgPool> {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL is not set, skipping");
+        return None;
+    };
+    Some(PgPool::connect(&url).await.unwrap())
+}
+
+fn sample() -> Solution {
+    let mut bytes = [0; 24];
+    for (i, b) in bytes.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    Solution::from_bytes(bytes)
+}
+
+#[derive(sqlx::FromRow)]
+struct Share {
+    solution: Solution,
+    hash: Hash,
+}
+
+#[test]
+fn test_try_from_slice() {
+    let solution = sample();
+    assert_eq!(Solution::try_from(&solution.to_bytes()[..]), Ok(solution));
+    assert_eq!(
+        Solution::try_from(&[0u8; 23][..]),
+        Err(LengthError {
+            expected: 24,
+            actual: 23
+        })
+    );
+    let hash = Hash {
+        d: [1; 16],
+        h: [2; 32],
+    };
+    let decoded = Hash::try_from(&hash.to_bytes()[..]).unwrap();
+    assert_eq!((decoded.d, decoded.h), (hash.d, hash.h));
+    assert!(Hash::try_from(&[0u8; 49][..]).is_err());
+}
+
+#[tokio::test]
+async fn test_round_trip() {
+    let Some(pool) = pool().await else { return };
+    let solution = sample();
+    let hash = Hash {
+        d: solution.d,
+        h: [0xab; 32],
+    };
+    let share: Share = sqlx::query_as("SELECT $1::BYTEA AS solution, $2::BYTEA AS hash")
+        .bind(solution)
+        .bind(&hash)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(share.solution, solution);
+    assert_eq!((share.hash.d, share.hash.h), (hash.d, hash.h));
+
+    let raw: Vec<u8> = sqlx::query_scalar("SELECT $1::BYTEA")
+        .bind(solution)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(raw, solution.to_bytes());
+
+    let solutions: Vec<Solution> = sqlx::query_scalar("SELECT $1::BYTEA[]")
+        .bind(vec![solution, Solution::new([9; 16], [8; 8])])
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(solutions, vec![solution, Solution::new([9; 16], [8; 8])]);
+}
+
+#[tokio::test]
+async fn test_wrong_length() {
+    let Some(pool) = pool().await else { return };
+    for len in [0, 23, 25, 48] {
+        let row = sqlx::query("SELECT $1::BYTEA AS solution")
+            .bind(vec![0u8; len])
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        let err = row.try_get::<Solution, _>("solution").unwrap_err();
+        assert!(matches!(err, sqlx::Error::ColumnDecode { .. }), "{}", err);
+        assert!(err.to_string().contains(&format!("got {}", len)), "{}", err);
+    }
+    let row = sqlx::query("SELECT $1::BYTEA AS hash")
+        .bind(vec![0u8; 24])
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert!(row.try_get::<Hash, _>("hash").is_err());
+}
@@ -0,0 +1,190 @@
+#![cfg(feature = "prost")]
+
+use drillx::{
+    miner::{ChallengeJob, JobId, JobSolution},
+    proto::{self, ProtoError},
+    ScoredSolution, Solution, SolutionV2,
+};
+use prost::Message;
+
+fn sample() -> Solution {
+    let mut bytes = [0; 24];
+    for (i, b) in bytes.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    Solution::from_bytes(bytes)
+}
+
+fn scored() -> ScoredSolution {
+    ScoredSolution {
+        solution: sample(),
+        hash: [0xee; 32],
+        difficulty: 21,
+    }
+}
+
+fn job() -> ChallengeJob {
+    ChallengeJob::new([0x42; 32], 18).weight(3)
+}
+
+fn share() -> JobSolution {
+    JobSolution {
+        job: JobId(7),
+        challenge: [0x42; 32],
+        scored: scored(),
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Encodes a value, checks it against its golden encoding, and decodes it back.
+fn round_trip<T, M>(value: T, golden: &str) -> T
+where
+    T: Into<M> + TryFrom<M, Error = ProtoError>,
+    M: Message + Default,
+{
+    let bytes = value.into().encode_to_vec();
+    assert_eq!(hex(&bytes), golden.trim());
+    M::decode(&bytes[..]).unwrap().try_into().unwrap()
+}
+
+#[test]
+fn test_round_trip_golden() {
+    let solution = sample();
+    assert_eq!(
+        round_trip::<_, proto::Solution>(solution, include_str!("proto/solution.hex")),
+        solution
+    );
+    let v2 = SolutionV2::new(solution.d, solution.n, 3);
+    assert_eq!(
+        round_trip::<_, proto::SolutionV2>(v2, include_str!("proto/solution_v2.hex")),
+        v2
+    );
+    assert_eq!(
+        round_trip::<_, proto::ScoredSolution>(scored(), include_str!("proto/scored_solution.hex")),
+        scored()
+    );
+    assert_eq!(
+        round_trip::<_, proto::ChallengeJob>(job(), include_str!("proto/challenge_job.hex")),
+        job()
+    );
+    assert_eq!(
+        round_trip::<_, proto::JobSolution>(share(), include_str!("proto/job_solution.hex")),
+        share()
+    );
+}
+
+#[test]
+fn test_rejects_wrong_lengths() {
+    let mut message = proto::Solution::from(sample());
+    message.digest.pop();
+    assert_eq!(
+        Solution::try_from(message),
+        Err(ProtoError::Length {
+            field: "Solution.digest",
+            expected: 16,
+            actual: 15
+        })
+    );
+
+    let mut message = proto::Solution::from(sample());
+    message.nonce.push(0);
+    assert_eq!(
+        Solution::try_from(message),
+        Err(ProtoError::Length {
+            field: "Solution.nonce",
+            expected: 8,
+            actual: 9
+        })
+    );
+
+    let mut message = proto::ScoredSolution::from(scored());
+    message.hash.clear();
+    assert!(matches!(
+        ScoredSolution::try_from(message),
+        Err(ProtoError::Length {
+            field: "ScoredSolution.hash",
+            ..
+        })
+    ));
+
+    let mut message = proto::ChallengeJob::from(job());
+    message.challenge.truncate(31);
+    assert!(matches!(
+        ChallengeJob::try_from(message),
+        Err(ProtoError::Length {
+            field: "ChallengeJob.challenge",
+            expected: 32,
+            actual: 31
+        })
+    ));
+
+    // Errors in nested messages keep the inner field.
+    let mut message = proto::JobSolution::from(share());
+    message
+        .scored
+        .as_mut()
+        .unwrap()
+        .solution
+        .as_mut()
+        .unwrap()
+        .nonce = vec![];
+    assert!(matches!(
+        JobSolution::try_from(message),
+        Err(ProtoError::Length {
+            field: "Solution.nonce",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn test_rejects_missing_and_out_of_range() {
+    let mut message = proto::JobSolution::from(share());
+    message.scored = None;
+    assert_eq!(
+        JobSolution::try_from(message),
+        Err(ProtoError::Missing {
+            field: "JobSolution.scored"
+        })
+    );
+    let mut message = proto::ScoredSolution::from(scored());
+    message.solution = None;
+    assert_eq!(
+        ScoredSolution::try_from(message),
+        Err(ProtoError::Missing {
+            field: "ScoredSolution.solution"
+        })
+    );
+
+    let mut message = proto::SolutionV2::from(SolutionV2::new(sample().d, sample().n, 0));
+    message.index = 256;
+    assert_eq!(
+        SolutionV2::try_from(message),
+        Err(ProtoError::OutOfRange {
+            field: "SolutionV2.index",
+            value: 256
+        })
+    );
+}
+
+#[test]
+fn test_proto_file_lists_messages() {
+    let file = include_str!("../proto/drillx.proto");
+    assert!(file.contains("package drillx.v1;"));
+    for message in [
+        "Solution",
+        "SolutionV2",
+        "ScoredSolution",
+        "ChallengeJob",
+        "JobSolution",
+    ] {
+        assert!(
+            file.contains(&format!("message {} {{", message)),
+            "{}",
+            message
+        );
+    }
+}
@@ -0,0 +1,161 @@
+use std::time::Duration;
+
+use drillx::{
+    puzzle::{
+        issue, puzzle_challenge, solve, verify, Puzzle, PuzzleError, PuzzleSolution, PUZZLE_LEN,
+        PUZZLE_SOLUTION_LEN,
+    },
+    Solution,
+};
+
+const SECRET: [u8; 32] = [166; 32];
+const CLIENT: &[u8] = b"203.0.113.7";
+const NOW: u64 = 1_700_000_000;
+const TTL: Duration = Duration::from_secs(60);
+
+#[test]
+fn test_issue_solve_verify() {
+    let puzzle = issue(&SECRET, CLIENT, 4, TTL, NOW);
+    assert_eq!(puzzle.difficulty, 4);
+    assert_eq!(puzzle.expires_unix, NOW + 60);
+    assert_eq!(
+        puzzle.challenge,
+        puzzle_challenge(&SECRET, CLIENT, 4, NOW + 60)
+    );
+
+    let solution = solve(&puzzle, 10_000).unwrap();
+    assert_eq!(solution.puzzle, puzzle);
+    assert!(solution.solution.to_hash().difficulty() >= 4);
+    assert_eq!(verify(&SECRET, CLIENT, &solution, NOW), Ok(()));
+    assert_eq!(verify(&SECRET, CLIENT, &solution, NOW + 60), Ok(()));
+
+    // The header encoding round-trips.
+    let header = solution.to_string();
+    assert_eq!(header.len(), 91);
+    assert!(header
+        .bytes()
+        .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_'));
+    assert_eq!(header.parse::<PuzzleSolution>(), Ok(solution));
+    assert_eq!(puzzle.to_string().parse::<Puzzle>(), Ok(puzzle));
+    let json = serde_json::to_string(&solution).unwrap();
+    assert_eq!(
+        serde_json::from_str::<PuzzleSolution>(&json).unwrap(),
+        solution
+    );
+}
+
+#[test]
+fn test_expired_puzzle_rejected() {
+    let puzzle = issue(&SECRET, CLIENT, 1, TTL, NOW);
+    let solution = solve(&puzzle, 1_000).unwrap();
+    assert_eq!(
+        verify(&SECRET, CLIENT, &solution, NOW + 61),
+        Err(PuzzleError::Expired {
+            expires_unix: NOW + 60,
+            now_unix: NOW + 61
+        })
+    );
+
+    // Pushing the expiry back breaks the binding.
+    let mut extended = solution;
+    extended.puzzle.expires_unix = NOW + 3600;
+    assert_eq!(
+        verify(&SECRET, CLIENT, &extended, NOW + 61),
+        Err(PuzzleError::Binding)
+    );
+}
+
+#[test]
+fn test_wrong_client_or_secret_rejected() {
+    let puzzle = issue(&SECRET, CLIENT, 1, TTL, NOW);
+    let solution = solve(&puzzle, 1_000).unwrap();
+    assert_eq!(
+        verify(&SECRET, b"203.0.113.8", &solution, NOW),
+        Err(PuzzleError::Binding)
+    );
+    assert_eq!(
+        verify(&[0; 32], CLIENT, &solution, NOW),
+        Err(PuzzleError::Binding)
+    );
+}
+
+#[test]
+fn test_difficulty_too_low_rejected() {
+    let puzzle = issue(&SECRET, CLIENT, 8, TTL, NOW);
+
+    // A real solution of the puzzle's challenge that falls short of its difficulty.
+    let solution = (0u64..)
+        .find_map(|nonce| {
+            let nonce = nonce.to_le_bytes();
+            let hash = drillx::hash(&puzzle.challenge, &nonce).ok()?;
+            (hash.difficulty() < 8).then(|| Solution::new(hash.d, nonce))
+        })
+        .unwrap();
+    let short = PuzzleSolution { puzzle, solution };
+    assert!(matches!(
+        verify(&SECRET, CLIENT, &short, NOW),
+        Err(PuzzleError::Difficulty { required: 8, actual }) if actual < 8
+    ));
+
+    // Lowering the puzzle's difficulty to match breaks the binding.
+    let mut lowered = short;
+    lowered.puzzle.difficulty = 0;
+    assert_eq!(
+        verify(&SECRET, CLIENT, &lowered, NOW),
+        Err(PuzzleError::Binding)
+    );
+}
+
+#[test]
+fn test_invalid_digest_rejected() {
+    let puzzle = issue(&SECRET, CLIENT, 0, TTL, NOW);
+    let mut solution = solve(&puzzle, 1_000).unwrap();
+    solution.solution.d[0] ^= 1;
+    assert_eq!(
+        verify(&SECRET, CLIENT, &solution, NOW),
+        Err(PuzzleError::InvalidDigest)
+    );
+}
+
+#[test]
+fn test_encoding() {
+    let puzzle = Puzzle {
+        challenge: std::array::from_fn(|i| i as u8),
+        difficulty: 0x0403_0201,
+        expires_unix: u64::MAX,
+    };
+    let bytes = puzzle.to_bytes();
+    assert_eq!(bytes.len(), PUZZLE_LEN);
+    assert_eq!(&bytes[32..36], &[1, 2, 3, 4]);
+    assert_eq!(&bytes[36..], &[0xff; 8]);
+    assert_eq!(Puzzle::from_bytes(&bytes), puzzle);
+    assert!(puzzle.to_string().starts_with("AAECAwQFBgcICQoL"));
+
+    let solution = PuzzleSolution {
+        puzzle,
+        solution: Solution::new([7; 16], [9; 8]),
+    };
+    let bytes = solution.to_bytes();
+    assert_eq!(bytes.len(), PUZZLE_SOLUTION_LEN);
+    assert_eq!(&bytes[PUZZLE_LEN..], &solution.solution.to_bytes());
+    assert_eq!(PuzzleSolution::from_bytes(&bytes), solution);
+
+    let encoded = solution.to_string();
+    assert_eq!(
+        encoded[..encoded.len() - 1].parse::<PuzzleSolution>(),
+        Err(PuzzleError::Malformed)
+    );
+    assert_eq!(
+        format!("{}=", &encoded[..encoded.len() - 1]).parse::<PuzzleSolution>(),
+        Err(PuzzleError::Malformed)
+    );
+    // Unused trailing bits must be zero.
+    let alphabet = "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let last = alphabet.find(encoded.chars().last().unwrap()).unwrap();
+    let mut flipped = encoded[..encoded.len() - 1].to_string();
+    flipped.push(alphabet.as_bytes()[last | 1] as char);
+    assert_eq!(
+        flipped.parse::<PuzzleSolution>(),
+        Err(PuzzleError::Malformed)
+    );
+}
@@ -0,0 +1,85 @@
+use drillx::{rank_solutions, rank_top_k, vectors::VECTORS, Solution};
+
+/// Deterministic pseudo-random numbers.
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A solution with a random digest and nonce, not a valid one.
+    fn solution(&mut self) -> Solution {
+        let mut digest = [0; 16];
+        digest[..8].copy_from_slice(&self.next().to_le_bytes());
+        digest[8..].copy_from_slice(&self.next().to_le_bytes());
+        Solution::new(digest, self.next().to_le_bytes())
+    }
+}
+
+fn random(count: usize) -> Vec<Solution> {
+    let mut rng = SplitMix(5);
+    (0..count).map(|_| rng.solution()).collect()
+}
+
+#[test]
+fn test_rank_solutions_order() {
+    let solutions = random(2000);
+    let ranked = rank_solutions(&solutions);
+    assert_eq!(ranked.len(), solutions.len());
+    for pair in ranked.windows(2) {
+        let ((_, d0, h0), (_, d1, h1)) = (pair[0], pair[1]);
+        assert!(d0 > d1 || (d0 == d1 && h0 <= h1));
+    }
+    let mut indices: Vec<usize> = ranked.iter().map(|r| r.0).collect();
+    indices.sort_unstable();
+    assert!(indices.into_iter().eq(0..solutions.len()));
+}
+
+#[test]
+fn test_rank_solutions_agrees_with_to_hash() {
+    let mut solutions = random(200);
+    solutions.extend(
+        VECTORS
+            .iter()
+            .filter_map(|v| Some(Solution::new(v.output?.digest, v.nonce))),
+    );
+    for (index, difficulty, hash) in rank_solutions(&solutions) {
+        let expected = solutions[index].to_hash();
+        assert_eq!(difficulty, expected.difficulty());
+        assert_eq!(hash, expected.h);
+    }
+}
+
+#[test]
+fn test_rank_solutions_breaks_ties() {
+    // The same solution ranks by index; equal difficulties rank by hash.
+    let solution = random(1)[0];
+    let ranked = rank_solutions(&[solution, solution, solution]);
+    assert_eq!(ranked.iter().map(|r| r.0).collect::<Vec<_>>(), [0, 1, 2]);
+
+    let solutions = random(500);
+    let ranked = rank_solutions(&solutions);
+    let reversed: Vec<Solution> = solutions.iter().rev().copied().collect();
+    let again = rank_solutions(&reversed);
+    let n = solutions.len();
+    assert!(ranked
+        .iter()
+        .zip(&again)
+        .all(|(a, b)| a.0 == n - 1 - b.0 && a.1 == b.1 && a.2 == b.2));
+}
+
+#[test]
+fn test_rank_top_k() {
+    let solutions = random(3000);
+    let ranked = rank_solutions(&solutions);
+    for k in [0, 1, 7, 100, 3000, 5000] {
+        assert_eq!(rank_top_k(&solutions, k), ranked[..k.min(ranked.len())]);
+    }
+    assert!(rank_solutions(&[]).is_empty());
+    assert!(rank_top_k(&[], 3).is_empty());
+}
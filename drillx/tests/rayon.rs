@@ -0,0 +1,63 @@
+#![cfg(feature = "rayon")]
+
+use drillx::{hash_iter, par_hash_range, ParallelHashExt};
+use rayon::prelude::*;
+
+fn sequential(challenge: &[u8; 32], nonces: std::ops::Range<u64>) -> Vec<(u64, [u8; 32])> {
+    hash_iter(challenge, nonces)
+        .map(|(nonce, hash)| (nonce, hash.h))
+        .collect()
+}
+
+#[test]
+fn test_par_hash_range_matches_sequential() {
+    let challenge = [30; 32];
+    let mut parallel: Vec<(u64, [u8; 32])> = par_hash_range(&challenge, 0..96u64)
+        .map(|(nonce, hash)| (nonce, hash.h))
+        .collect();
+    parallel.sort_unstable();
+    let expected = sequential(&challenge, 0..96);
+    // Nonces without a solution are skipped, not reported.
+    assert!(expected.len() < 96);
+    assert_eq!(parallel, expected);
+}
+
+#[test]
+fn test_drillx_hash_best() {
+    let challenge = [31; 32];
+    let best = (0..64u64)
+        .into_par_iter()
+        .drillx_hash(&challenge)
+        .max_by_key(|(nonce, hash)| (hash.difficulty(), std::cmp::Reverse(*nonce)))
+        .unwrap();
+    let expected = hash_iter(&challenge, 0..64)
+        .max_by_key(|(nonce, hash)| (hash.difficulty(), std::cmp::Reverse(*nonce)))
+        .unwrap();
+    assert_eq!(best.0, expected.0);
+    assert_eq!(best.1.h, expected.1.h);
+}
+
+#[test]
+fn test_drillx_hash_custom_pool() {
+    let challenge = [32; 32];
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(3)
+        .build()
+        .unwrap();
+    let nonces = vec![u64::MAX, 5, 17, 5];
+    let mut hashed: Vec<(u64, [u8; 32])> = pool.install(|| {
+        nonces
+            .clone()
+            .into_par_iter()
+            .drillx_hash(&challenge)
+            .map(|(nonce, hash)| (nonce, hash.h))
+            .collect()
+    });
+    hashed.sort_unstable();
+    let mut expected: Vec<(u64, [u8; 32])> = nonces
+        .into_iter()
+        .filter_map(|n| Some((n, drillx::hash(&challenge, &n.to_le_bytes()).ok()?.h)))
+        .collect();
+    expected.sort_unstable();
+    assert_eq!(hashed, expected);
+}
@@ -0,0 +1,201 @@
+#![cfg(feature = "redis")]
+
+//! The queue tests need a server and skip themselves unless `REDIS_URL` is set.
+
+use std::collections::HashMap;
+
+use drillx::{
+    redis::{decode_entry, DecodeError, ShareQueue, CHALLENGE_FIELD, SOLUTION_FIELD},
+    ScoredSolution, Solution,
+};
+use redis::{aio::MultiplexedConnection, AsyncCommands, FromRedisValue, ToRedisArgs, Value};
+
+async fn connection() -> Option<MultiplexedConnection> {
+    let Ok(url) = std::env::var("REDIS_URL") else {
+        eprintln!("REDIS_URL is not set, skipping");
+        return None;
+    };
+    let client = redis::Client::open(url).unwrap();
+    Some(client.get_multiplexed_async_connection().await.unwrap())
+}
+
+fn sample() -> Solution {
+    let mut bytes = [0; 24];
+    for (i, b) in bytes.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    Solution::from_bytes(bytes)
+}
+
+fn entry(fields: &[(&str, Value)]) -> HashMap<String, Value> {
+    fields
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect()
+}
+
+#[test]
+fn test_encoding() {
+    let solution = sample();
+    assert_eq!(solution.to_redis_args(), vec![solution.to_bytes().to_vec()]);
+    let value = Value::BulkString(solution.to_bytes().to_vec());
+    assert_eq!(Solution::from_redis_value(&value).unwrap(), solution);
+
+    let scored = ScoredSolution {
+        solution,
+        hash: [0xab; 32],
+        difficulty: 0x0102_0304,
+    };
+    let bytes = scored.to_bytes();
+    assert_eq!(&bytes[..24], &solution.to_bytes());
+    assert_eq!(&bytes[24..56], &[0xab; 32]);
+    assert_eq!(&bytes[56..], &[4, 3, 2, 1]);
+    assert_eq!(scored.to_redis_args(), vec![bytes.to_vec()]);
+    let value = Value::BulkString(bytes.to_vec());
+    assert_eq!(ScoredSolution::from_redis_value(&value).unwrap(), scored);
+}
+
+#[test]
+fn test_decode_errors() {
+    assert_eq!(
+        Solution::from_redis(&Value::BulkString(vec![0; 23])),
+        Err(DecodeError::Length {
+            expected: 24,
+            actual: 23
+        })
+    );
+    assert_eq!(
+        ScoredSolution::from_redis(&Value::BulkString(vec![0; 24])),
+        Err(DecodeError::Length {
+            expected: 60,
+            actual: 24
+        })
+    );
+    for value in [Value::Nil, Value::Int(24), Value::Array(vec![])] {
+        assert_eq!(Solution::from_redis(&value), Err(DecodeError::NotBytes));
+    }
+    let err = Solution::from_redis_value(&Value::BulkString(vec![0; 25])).unwrap_err();
+    assert_eq!(err.kind(), redis::ErrorKind::TypeError);
+    assert!(err.to_string().contains("Expected 24 bytes, got 25"));
+}
+
+#[test]
+fn test_decode_entry() {
+    let solution = Value::BulkString(sample().to_bytes().to_vec());
+    let challenge = Value::BulkString(b"42".to_vec());
+    assert_eq!(
+        decode_entry(&entry(&[
+            (CHALLENGE_FIELD, challenge.clone()),
+            (SOLUTION_FIELD, solution.clone())
+        ])),
+        Ok((42, sample()))
+    );
+    assert_eq!(
+        decode_entry(&entry(&[(CHALLENGE_FIELD, challenge.clone())])),
+        Err(DecodeError::MissingField(SOLUTION_FIELD))
+    );
+    assert_eq!(
+        decode_entry(&entry(&[(SOLUTION_FIELD, solution.clone())])),
+        Err(DecodeError::MissingField(CHALLENGE_FIELD))
+    );
+    for id in [&b"-1"[..], b"", b"0x2a", b"18446744073709551616", &[0xff]] {
+        assert_eq!(
+            decode_entry(&entry(&[
+                (CHALLENGE_FIELD, Value::BulkString(id.to_vec())),
+                (SOLUTION_FIELD, solution.clone())
+            ])),
+            Err(DecodeError::InvalidChallengeId)
+        );
+    }
+    assert_eq!(
+        decode_entry(&entry(&[
+            (CHALLENGE_FIELD, challenge),
+            (SOLUTION_FIELD, Value::BulkString(vec![1; 3]))
+        ])),
+        Err(DecodeError::Length {
+            expected: 24,
+            actual: 3
+        })
+    );
+}
+
+#[tokio::test]
+async fn test_queue() {
+    let Some(mut conn) = connection().await else {
+        return;
+    };
+    let stream = format!("drillx-test-{}", std::process::id());
+    let _: () = conn.del(&stream).await.unwrap();
+    let queue = ShareQueue::new(&stream);
+    queue.create_group(&mut conn, "verifiers").await.unwrap();
+    // Creating the group again is not an error.
+    queue.create_group(&mut conn, "verifiers").await.unwrap();
+
+    let solutions: Vec<Solution> = (0..5u8).map(|i| Solution::new([i; 16], [i; 8])).collect();
+    for (i, solution) in solutions.iter().enumerate() {
+        queue.push(&mut conn, i as u64, solution).await.unwrap();
+    }
+    // A poison message, written by hand.
+    let _: String = conn
+        .xadd(&stream, "*", &[(SOLUTION_FIELD, &b"garbage"[..])])
+        .await
+        .unwrap();
+
+    let first = queue
+        .pop_batch(&mut conn, "verifiers", "a", 3)
+        .await
+        .unwrap();
+    assert_eq!(first.shares.len(), 3);
+    let rest = queue
+        .pop_batch(&mut conn, "verifiers", "b", 10)
+        .await
+        .unwrap();
+    assert_eq!(rest.shares.len(), 2);
+    assert_eq!(rest.poisoned.len(), 1);
+    assert_eq!(
+        rest.poisoned[0].error,
+        DecodeError::MissingField(CHALLENGE_FIELD)
+    );
+    let popped: Vec<(u64, Solution)> = first
+        .shares
+        .iter()
+        .chain(&rest.shares)
+        .map(|share| (share.challenge_id, share.solution))
+        .collect();
+    let expected: Vec<(u64, Solution)> = solutions
+        .iter()
+        .enumerate()
+        .map(|(i, solution)| (i as u64, *solution))
+        .collect();
+    assert_eq!(popped, expected);
+
+    // Nothing is delivered twice, the poison message included.
+    assert!(queue
+        .pop_batch(&mut conn, "verifiers", "a", 10)
+        .await
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        queue
+            .ack(&mut conn, "verifiers", &first.ids())
+            .await
+            .unwrap(),
+        3
+    );
+    assert_eq!(
+        queue
+            .ack(&mut conn, "verifiers", &rest.ids())
+            .await
+            .unwrap(),
+        3
+    );
+    assert_eq!(
+        queue
+            .ack(&mut conn, "verifiers", &rest.ids())
+            .await
+            .unwrap(),
+        0
+    );
+    assert_eq!(queue.ack(&mut conn, "verifiers", &[]).await.unwrap(), 0);
+    let _: () = conn.del(&stream).await.unwrap();
+}
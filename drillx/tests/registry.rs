@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use drillx::{InsertOutcome, SolutionRegistry};
+
+const CHALLENGE: [u8; 32] = [7; 32];
+
+#[test]
+fn test_duplicates_detected() {
+    let registry = SolutionRegistry::new(1000, 0.01);
+    for nonce in 0..1000 {
+        registry.insert(&CHALLENGE, nonce);
+    }
+    for nonce in 0..1000 {
+        assert!(registry.contains(&CHALLENGE, nonce));
+        assert_eq!(
+            registry.insert(&CHALLENGE, nonce),
+            InsertOutcome::ProbablyDuplicate
+        );
+    }
+    assert!(registry.len() <= 1000);
+    assert!(registry.saturation() <= 1.0);
+}
+
+#[test]
+fn test_challenge_is_part_of_key() {
+    let registry = SolutionRegistry::new(100, 0.001);
+    assert_eq!(registry.insert(&CHALLENGE, 5), InsertOutcome::Fresh);
+    assert_eq!(registry.insert(&[8; 32], 5), InsertOutcome::Fresh);
+    assert_eq!(registry.len(), 2);
+}
+
+#[test]
+fn test_false_positive_bound() {
+    for rate in [0.01, 0.001] {
+        let capacity = 20_000;
+        let registry = SolutionRegistry::new(capacity, rate);
+        for nonce in 0..capacity as u64 {
+            registry.insert(&CHALLENGE, nonce);
+        }
+        let trials = 200_000;
+        let false_positives = (0..trials)
+            .filter(|i| registry.contains(&CHALLENGE, 1 << 40 | i))
+            .count();
+        let measured = false_positives as f64 / trials as f64;
+        // Generous headroom over the configured rate keeps this from flaking.
+        assert!(measured < rate * 2.0, "rate {} measured {}", rate, measured);
+        // Fresh inserts that were reported duplicate are false positives too.
+        assert!(registry.len() as f64 >= capacity as f64 * (1.0 - rate * 2.0));
+    }
+}
+
+#[test]
+fn test_rotate() {
+    let registry = SolutionRegistry::new(100, 0.01);
+    assert!(registry.rotate(CHALLENGE));
+    registry.insert(&CHALLENGE, 1);
+    assert!(!registry.rotate(CHALLENGE));
+    assert!(registry.contains(&CHALLENGE, 1));
+    assert!(registry.rotate([8; 32]));
+    assert_eq!(registry.challenge(), Some([8; 32]));
+    assert!(registry.is_empty());
+    assert!(!registry.contains(&CHALLENGE, 1));
+}
+
+#[test]
+fn test_concurrent_inserts_credit_once() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SolutionRegistry>();
+
+    let registry = Arc::new(SolutionRegistry::new(10_000, 0.001));
+    let threads: Vec<_> = (0..8)
+        .map(|_| {
+            let registry = registry.clone();
+            std::thread::spawn(move || {
+                (0..5000)
+                    .filter(|&nonce| registry.insert(&CHALLENGE, nonce) == InsertOutcome::Fresh)
+                    .count()
+            })
+        })
+        .collect();
+    let fresh: usize = threads.into_iter().map(|t| t.join().unwrap()).sum();
+    // Every nonce is credited at most once across all threads.
+    assert!(fresh <= 5000);
+    assert!(fresh >= 4950);
+    assert_eq!(registry.len(), fresh);
+    for nonce in 0..5000 {
+        assert!(registry.contains(&CHALLENGE, nonce));
+    }
+}
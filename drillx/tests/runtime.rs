@@ -0,0 +1,69 @@
+use drillx::{vectors::VECTORS, Context, DrillxMemory, Runtime, RuntimeOption};
+
+#[test]
+fn test_vectors_every_runtime() {
+    let mut memory = DrillxMemory::new();
+    for option in [RuntimeOption::TryCompile, RuntimeOption::InterpretOnly] {
+        for (i, vector) in VECTORS.iter().enumerate() {
+            assert!(
+                vector.check(&mut memory, option),
+                "{:?} vector {}",
+                option,
+                i
+            );
+        }
+    }
+}
+
+#[test]
+fn test_runtime_info_consistent() {
+    let info = drillx::runtime_info();
+    assert_eq!(info.arch, std::env::consts::ARCH);
+    assert_eq!(info.compiler_supported, drillx::COMPILER_SUPPORTED);
+    assert!(!info.compiler_available || info.compiler_supported);
+    assert_eq!(
+        info.default_runtime == Runtime::Compiled,
+        info.compiler_available
+    );
+}
+
+#[cfg(all(
+    feature = "compiler",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+#[test]
+fn test_compiler_supported() {
+    assert!(drillx::runtime_info().compiler_supported);
+    let context = Context::new(RuntimeOption::TryCompile);
+    assert_eq!(context.current_runtime(), RuntimeOption::TryCompile);
+}
+
+#[cfg(not(all(
+    feature = "compiler",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+#[test]
+fn test_interpreter_only() {
+    use drillx::DrillxError;
+
+    let info = drillx::runtime_info();
+    assert!(!info.compiler_supported);
+    assert!(!info.compiler_available);
+    assert_eq!(info.default_runtime, Runtime::Interpreted);
+
+    // Requiring the compiler fails with a typed error
+    let mut memory = DrillxMemory::new();
+    let result = drillx::hash_with_runtime(
+        &mut memory,
+        RuntimeOption::RequireCompile,
+        &[0; 32],
+        &[0; 8],
+    );
+    assert_eq!(result.err(), Some(DrillxError::CompilerUnsupported));
+
+    // Trying the compiler is honestly the interpreter
+    let context = Context::new(RuntimeOption::TryCompile);
+    assert_eq!(context.current_runtime(), RuntimeOption::InterpretOnly);
+    assert!(!context.is_downgraded());
+    assert!(drillx::self_test().unwrap().compiled.is_none());
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "schemars")]
+
+use drillx::{ScoredSolution, Solution, SolutionV2};
+use schemars::{schema::RootSchema, schema_for};
+
+fn sample() -> Solution {
+    let mut bytes = [0; 24];
+    for (i, b) in bytes.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    Solution::from_bytes(bytes)
+}
+
+fn check<T: serde::Serialize>(schema: RootSchema, snapshot: &str, value: &T) {
+    let schema = serde_json::to_value(schema).unwrap();
+    let pinned: serde_json::Value = serde_json::from_str(snapshot).unwrap();
+    assert_eq!(
+        schema,
+        pinned,
+        "schema changed:\n{}",
+        serde_json::to_string_pretty(&schema).unwrap()
+    );
+    let validator = jsonschema::JSONSchema::compile(&schema).unwrap();
+    assert!(validator.is_valid(&serde_json::to_value(value).unwrap()));
+}
+
+#[test]
+fn test_solution_schema() {
+    check(
+        schema_for!(Solution),
+        include_str!("schema/solution.json"),
+        &sample(),
+    );
+}
+
+#[test]
+fn test_solution_v2_schema() {
+    check(
+        schema_for!(SolutionV2),
+        include_str!("schema/solution_v2.json"),
+        &SolutionV2::new(sample().d, sample().n, 0),
+    );
+}
+
+#[test]
+fn test_scored_solution_schema() {
+    let scored = ScoredSolution {
+        solution: sample(),
+        hash: [0xff; 32],
+        difficulty: 12,
+    };
+    check(
+        schema_for!(ScoredSolution),
+        include_str!("schema/scored_solution.json"),
+        &scored,
+    );
+}
+
+#[test]
+fn test_schema_rejects_malformed() {
+    let schema = serde_json::to_value(schema_for!(Solution)).unwrap();
+    let validator = jsonschema::JSONSchema::compile(&schema).unwrap();
+    let short = serde_json::json!({ "d": ([0u8; 15]), "n": ([0u8; 8]) });
+    let hex = serde_json::json!({ "d": "000102030405060708090a0b0c0d0e0f", "n": ([0u8; 8]) });
+    assert!(!validator.is_valid(&short));
+    assert!(!validator.is_valid(&hex));
+}
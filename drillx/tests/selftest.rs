@@ -0,0 +1,53 @@
+use drillx::{miner::MinerBuilder, vectors::VECTORS, DrillxMemory, Runtime, RuntimeOption};
+
+#[test]
+fn test_vectors_interpreted() {
+    let mut memory = DrillxMemory::new();
+    for (i, vector) in VECTORS.iter().enumerate() {
+        assert!(
+            vector.check(&mut memory, RuntimeOption::InterpretOnly),
+            "vector {}",
+            i
+        );
+    }
+
+    // Vectors hold the default hash path, too
+    assert!(VECTORS.iter().any(|v| v.output.is_none()));
+    for vector in VECTORS {
+        let hash = drillx::hash(&vector.challenge, &vector.nonce).ok();
+        assert_eq!(hash.map(|h| h.h), vector.output.map(|o| o.hash));
+    }
+}
+
+#[test]
+fn test_self_test_report() {
+    let report = drillx::self_test().unwrap();
+    assert_eq!(report.vectors, VECTORS.len());
+    assert!(report.interpreted.is_ok());
+    assert_eq!(report.interpreted.passed, VECTORS.len());
+    assert!(report.interpreted.per_vector() <= report.interpreted.elapsed);
+    match report.compiled {
+        Some(ref compiled) => {
+            assert_eq!(compiled.passed + compiled.failed.len(), VECTORS.len());
+            assert_eq!(compiled.is_ok(), report.trusted == Runtime::Compiled);
+            assert_eq!(report.compiled_disagrees(), !compiled.is_ok());
+        }
+        None => {
+            assert_eq!(report.trusted, Runtime::Interpreted);
+            assert!(!report.compiled_disagrees());
+        }
+    }
+}
+
+#[test]
+fn test_miner_self_test() {
+    let outcome = MinerBuilder::new([255; 32])
+        .threads(1)
+        .min_difficulty(1)
+        .self_test(true)
+        .spawn()
+        .unwrap()
+        .join()
+        .unwrap();
+    assert!(outcome.best.unwrap().difficulty >= 1);
+}
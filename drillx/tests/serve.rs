@@ -0,0 +1,204 @@
+#![cfg(feature = "serve")]
+
+use std::{
+    io::{BufRead, BufReader},
+    process::{Child, Command, Stdio},
+};
+
+use drillx::{
+    serve::{HexSolution, VerifyRequest, VerifyResponse},
+    vectors::VECTORS,
+    Solution,
+};
+use serde_json::{json, Value};
+
+/// A `drillx-verifyd` on a free port, killed on drop.
+struct Verifyd {
+    child: Child,
+    url: String,
+}
+
+impl Verifyd {
+    fn spawn(max_batch: usize) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_drillx-verifyd"))
+            .args(["--bind", "127.0.0.1:0", "--workers", "2", "--max-batch"])
+            .arg(max_batch.to_string())
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut line = String::new();
+        BufReader::new(child.stdout.take().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let address = line.trim().strip_prefix("listening on ").unwrap();
+        Verifyd {
+            child,
+            url: format!("http://{}", address),
+        }
+    }
+
+    async fn post(&self, body: &Value) -> (u16, Value) {
+        let response = reqwest::Client::new()
+            .post(format!("{}/verify", self.url))
+            .json(body)
+            .send()
+            .await
+            .unwrap();
+        (response.status().as_u16(), response.json().await.unwrap())
+    }
+}
+
+impl Drop for Verifyd {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The test vectors with solutions, as a request, with every other digest corrupted.
+fn request() -> (VerifyRequest, Vec<Solution>) {
+    let vectors: Vec<_> = VECTORS.iter().filter(|v| v.output.is_some()).collect();
+    let challenge = vectors[0].challenge;
+    let solutions: Vec<Solution> = vectors
+        .iter()
+        .filter(|v| v.challenge == challenge)
+        .enumerate()
+        .map(|(i, v)| {
+            let mut digest = v.output.unwrap().digest;
+            if i % 2 == 1 {
+                digest[0] ^= 1;
+            }
+            Solution::new(digest, v.nonce)
+        })
+        .collect();
+    let request = VerifyRequest {
+        challenge: hex(&challenge),
+        solutions: solutions
+            .iter()
+            .map(|s| HexSolution {
+                digest: hex(&s.d),
+                nonce: hex(&s.n),
+            })
+            .collect(),
+        min_difficulty: 0,
+    };
+    (request, solutions)
+}
+
+#[tokio::test]
+async fn test_healthz() {
+    let verifyd = Verifyd::spawn(8);
+    let response = reqwest::get(format!("{}/healthz", verifyd.url))
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(
+        response.json::<Value>().await.unwrap(),
+        json!({"status": "ok"})
+    );
+}
+
+#[tokio::test]
+async fn test_verify() {
+    let verifyd = Verifyd::spawn(64);
+    let (mut request, solutions) = request();
+    assert!(solutions.len() >= 2);
+    let challenge: [u8; 32] = VECTORS
+        .iter()
+        .find(|v| v.output.is_some())
+        .unwrap()
+        .challenge;
+    let min_difficulty = solutions[0].to_hash().difficulty();
+    request.min_difficulty = min_difficulty;
+
+    let (status, body) = verifyd.post(&serde_json::to_value(&request).unwrap()).await;
+    assert_eq!(status, 200, "{}", body);
+    let response: VerifyResponse = serde_json::from_value(body).unwrap();
+    assert_eq!(response.results.len(), solutions.len());
+    for (i, (verdict, solution)) in response.results.iter().zip(&solutions).enumerate() {
+        let valid = solution.is_valid(&challenge);
+        assert_eq!(valid, i % 2 == 0);
+        assert_eq!(verdict.valid, valid);
+        let difficulty = valid.then(|| solution.to_hash().difficulty());
+        assert_eq!(verdict.difficulty, difficulty);
+        assert_eq!(
+            verdict.accepted,
+            difficulty.is_some_and(|d| d >= min_difficulty)
+        );
+    }
+    assert!(response.results[0].accepted);
+
+    // An empty batch is fine, and the minimum difficulty is optional.
+    let (status, body) = verifyd
+        .post(&json!({"challenge": request.challenge, "solutions": []}))
+        .await;
+    assert_eq!((status, body), (200, json!({"results": []})));
+}
+
+#[tokio::test]
+async fn test_malformed_hex() {
+    let verifyd = Verifyd::spawn(8);
+    let (request, _) = request();
+    let solution = &request.solutions[0];
+    for (body, field) in [
+        (json!({"challenge": "zz", "solutions": []}), "challenge"),
+        (
+            json!({"challenge": request.challenge, "solutions": [
+                {"digest": solution.digest, "nonce": solution.nonce},
+                {"digest": "0g".repeat(16), "nonce": solution.nonce},
+            ]}),
+            "solutions[1].digest",
+        ),
+        (
+            json!({"challenge": request.challenge, "solutions": [
+                {"digest": solution.digest, "nonce": "00"},
+            ]}),
+            "solutions[0].nonce",
+        ),
+    ] {
+        let (status, body) = verifyd.post(&body).await;
+        assert_eq!(status, 400, "{}", body);
+        assert_eq!(body["error"]["code"], "malformed_hex");
+        assert_eq!(body["error"]["field"], field);
+    }
+}
+
+#[tokio::test]
+async fn test_malformed_requests() {
+    let verifyd = Verifyd::spawn(2);
+    let (request, _) = request();
+    let client = reqwest::Client::new();
+    let url = format!("{}/verify", verifyd.url);
+
+    let response = client
+        .post(&url)
+        .header("content-type", "application/json")
+        .body("{not json")
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(response.status().as_u16(), 400);
+    let body: Value = response.json().await.unwrap();
+    assert_eq!(body["error"]["code"], "malformed_json");
+
+    let (status, body) = verifyd.post(&json!({"solutions": []})).await;
+    assert_eq!(status, 422);
+    assert_eq!(body["error"]["code"], "invalid_request");
+
+    // Too many solutions for the batch limit, and a body past the size limit.
+    let solutions = vec![request.solutions[0].clone(); 3];
+    let (status, body) = verifyd
+        .post(&json!({"challenge": request.challenge, "solutions": solutions}))
+        .await;
+    assert_eq!(status, 413);
+    assert_eq!(body["error"]["code"], "batch_too_large");
+    let (status, body) = verifyd
+        .post(&json!({"challenge": request.challenge, "solutions": [], "pad": "x".repeat(1 << 16)}))
+        .await;
+    assert_eq!(status, 413);
+    assert_eq!(body["error"]["code"], "body_too_large");
+}
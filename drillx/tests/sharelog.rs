@@ -0,0 +1,252 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    io::{self, Read, Write},
+    rc::Rc,
+};
+
+use drillx::{
+    sharelog::{
+        verify_stream, ChallengeId, Record, ShareLogError, ShareLogReader, ShareLogWriter,
+        FRAME_LEN,
+    },
+    Solution,
+};
+
+const CHALLENGE: [u8; 32] = [3; 32];
+const ID: ChallengeId = *b"chal0001";
+const HEADER_LEN: usize = 5;
+
+fn synthetic(i: u64) -> Solution {
+    let mut digest = [0; 16];
+    digest[..8].copy_from_slice(&i.wrapping_mul(0x9e37_79b9_7f4a_7c15).to_le_bytes());
+    Solution::new(digest, i.to_le_bytes())
+}
+
+fn write_log(solutions: &[Solution], finish: bool) -> Vec<u8> {
+    let mut writer = ShareLogWriter::new(Vec::new()).unwrap();
+    for solution in solutions {
+        writer.append(&ID, solution).unwrap();
+    }
+    let mut bytes = writer.finish().unwrap();
+    if !finish {
+        // Drop the footer, as a crashed writer would.
+        bytes.truncate(bytes.len() - FRAME_LEN);
+    }
+    bytes
+}
+
+fn read_all(bytes: &[u8]) -> (Vec<Solution>, Vec<ShareLogError>) {
+    let mut solutions = vec![];
+    let mut errors = vec![];
+    for item in ShareLogReader::new(bytes).unwrap() {
+        match item {
+            Ok(record) => solutions.push(record.solution),
+            Err(err) => errors.push(err),
+        }
+    }
+    (solutions, errors)
+}
+
+/// Real solutions for [`CHALLENGE`].
+fn mined(count: usize) -> Vec<Solution> {
+    (0u64..)
+        .filter_map(|nonce| {
+            let nonce = nonce.to_le_bytes();
+            drillx::hash(&CHALLENGE, &nonce)
+                .ok()
+                .map(|hash| Solution::new(hash.d, nonce))
+        })
+        .take(count)
+        .collect()
+}
+
+#[test]
+fn test_round_trip() {
+    let solutions: Vec<Solution> = (0..100).map(synthetic).collect();
+    let bytes = write_log(&solutions, true);
+    assert_eq!(bytes.len(), HEADER_LEN + 101 * FRAME_LEN);
+    let mut reader = ShareLogReader::new(&bytes[..]).unwrap();
+    let records: Vec<Record> = (&mut reader).map(Result::unwrap).collect();
+    assert_eq!(reader.footer(), Some(100));
+    assert_eq!(records.len(), 100);
+    assert!(records
+        .iter()
+        .enumerate()
+        .all(|(i, r)| r.index == i as u64 && r.challenge_id == ID && r.solution == solutions[i]));
+}
+
+#[test]
+fn test_rejects_header() {
+    assert!(matches!(
+        ShareLogReader::new(&b"DXS"[..]),
+        Err(ShareLogError::BadMagic)
+    ));
+    assert!(matches!(
+        ShareLogReader::new(&b"NOPE\x01"[..]),
+        Err(ShareLogError::BadMagic)
+    ));
+    assert!(matches!(
+        ShareLogReader::new(&b"DXSL\x02"[..]),
+        Err(ShareLogError::UnknownVersion(2))
+    ));
+}
+
+#[test]
+fn test_truncated_mid_record() {
+    let solutions: Vec<Solution> = (0..10).map(synthetic).collect();
+    let bytes = write_log(&solutions, false);
+    let cut = HEADER_LEN + 7 * FRAME_LEN + 20;
+    let (read, errors) = read_all(&bytes[..cut]);
+    assert_eq!(read, solutions[..7]);
+    assert!(matches!(
+        errors[..],
+        [ShareLogError::Truncated { offset, len: 20 }] if offset == (cut - 20) as u64
+    ));
+}
+
+#[test]
+fn test_torn_write_resyncs() {
+    // A crashed writer left half a record, then a new writer appended more.
+    let solutions: Vec<Solution> = (0..10).map(synthetic).collect();
+    let first = write_log(&solutions[..5], false);
+    let torn = &first[..first.len() - 11];
+    let second = write_log(&solutions[5..], true);
+    let mut bytes = torn.to_vec();
+    bytes.extend_from_slice(&second[HEADER_LEN..]);
+
+    let (read, errors) = read_all(&bytes);
+    // Only the torn record is lost; the footer count no longer matches.
+    assert_eq!(read[..4], solutions[..4]);
+    assert_eq!(read[4..], solutions[5..]);
+    assert!(matches!(
+        errors[..],
+        [
+            ShareLogError::Corrupt { len: 26, .. },
+            ShareLogError::CountMismatch {
+                declared: 5,
+                read: 9
+            }
+        ]
+    ));
+}
+
+#[test]
+fn test_bit_flips_caught() {
+    let solutions: Vec<Solution> = (0..8).map(synthetic).collect();
+    let bytes = write_log(&solutions, true);
+    for bit in 0..FRAME_LEN * 8 {
+        let mut flipped = bytes.clone();
+        flipped[HEADER_LEN + 3 * FRAME_LEN + bit / 8] ^= 1 << (bit % 8);
+        let (read, errors) = read_all(&flipped);
+        // The damaged record is dropped and reported, and nothing else is lost.
+        assert_eq!(read.len(), 7, "bit {}", bit);
+        assert!(!read.contains(&solutions[3]));
+        assert!(
+            matches!(errors[0], ShareLogError::Corrupt { len, .. } if len == FRAME_LEN as u64),
+            "bit {}: {:?}",
+            bit,
+            errors
+        );
+    }
+}
+
+/// A writer whose output can be drained while it is still being written to.
+#[derive(Clone, Default)]
+struct Pipe(Rc<RefCell<VecDeque<u8>>>);
+
+impl Write for Pipe {
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        self.0.borrow_mut().extend(bytes);
+        Ok(bytes.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Produces a valid log of `count` records without holding it in memory.
+struct Generated {
+    pipe: Pipe,
+    writer: Option<ShareLogWriter<Pipe>>,
+    count: u64,
+}
+
+impl Generated {
+    fn new(count: u64) -> Self {
+        let pipe = Pipe::default();
+        let writer = ShareLogWriter::new(pipe.clone()).unwrap();
+        Generated {
+            pipe,
+            writer: Some(writer),
+            count,
+        }
+    }
+}
+
+impl Read for Generated {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.pipe.0.borrow().is_empty() {
+            match self.writer.take() {
+                Some(mut writer) if writer.count() < self.count => {
+                    writer.append(&ID, &synthetic(writer.count()))?;
+                    self.writer = Some(writer);
+                }
+                Some(writer) => {
+                    writer.finish()?;
+                }
+                None => return Ok(0),
+            }
+        }
+        let mut buffered = self.pipe.0.borrow_mut();
+        assert!(buffered.len() <= FRAME_LEN + 5);
+        let n = out.len().min(buffered.len());
+        for (byte, b) in out.iter_mut().zip(buffered.drain(..n)) {
+            *byte = b;
+        }
+        Ok(n)
+    }
+}
+
+#[test]
+fn test_large_stream() {
+    let count = 20_000;
+    let report = verify_stream(Generated::new(count), |_| None, 0.0).unwrap();
+    assert_eq!(report.records, count);
+    assert_eq!(report.footer, Some(count));
+    assert_eq!(report.corrupt, 0);
+    assert_eq!(report.sampled, 0);
+}
+
+#[test]
+fn test_verify_stream() {
+    let mut solutions = mined(4);
+    // A forged share: right nonce, wrong digest.
+    let mut forged = solutions[1];
+    forged.d[0] ^= 1;
+    solutions.push(forged);
+    let bytes = write_log(&solutions, true);
+
+    let lookup = |id: &ChallengeId| (*id == ID).then_some(CHALLENGE);
+    let report = verify_stream(&bytes[..], lookup, 1.0).unwrap();
+    assert_eq!(report.records, 5);
+    assert_eq!(report.sampled, 5);
+    assert_eq!(report.valid, 4);
+    assert_eq!(report.invalid, vec![4]);
+    assert!(!report.is_clean());
+    // Only the shares that verified are counted.
+    let difficulties = drillx::DifficultyHistogram::new();
+    for solution in &solutions[..4] {
+        difficulties.record(solution.to_hash().difficulty());
+    }
+    assert_eq!(report.difficulties, difficulties.snapshot());
+
+    let unknown = verify_stream(&bytes[..], |_| None, 1.0).unwrap();
+    assert_eq!(unknown.unknown_challenge, 5);
+
+    // Sampling is deterministic.
+    let partial = verify_stream(&bytes[..], lookup, 0.5).unwrap();
+    assert_eq!(partial, verify_stream(&bytes[..], lookup, 0.5).unwrap());
+    assert!(partial.sampled <= 5);
+}
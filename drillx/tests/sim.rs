@@ -0,0 +1,126 @@
+use drillx::{
+    audit::difficulty_distribution_test,
+    sim::{ShareSimulator, SimMode, MAX_REAL_DIFFICULTY, SIM_CHALLENGE},
+};
+
+#[test]
+fn test_deterministic() {
+    let a: Vec<_> = ShareSimulator::new(1e6, 8, 7).shares().take(100).collect();
+    let b: Vec<_> = ShareSimulator::new(1e6, 8, 7).shares().take(100).collect();
+    let c: Vec<_> = ShareSimulator::new(1e6, 8, 8).shares().take(100).collect();
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+
+    let mut simulator = ShareSimulator::new(1e6, 8, 7);
+    for share in &a {
+        assert_eq!(simulator.next(), *share);
+    }
+}
+
+#[test]
+fn test_arrival_rate() {
+    let simulator = ShareSimulator::new(1e6, 10, 138);
+    let rate = simulator.share_rate();
+    assert_eq!(rate, 1e6 / 1024.0);
+    let n = 100_000;
+    let timestamps: Vec<f64> = simulator
+        .shares()
+        .take(n)
+        .map(|share| share.timestamp.as_secs_f64())
+        .collect();
+    assert!(timestamps.windows(2).all(|w| w[0] <= w[1]));
+
+    // The mean gap of an exponential distribution is `1 / rate`, with a relative
+    // standard error of `1 / sqrt(n)`.
+    let mean = timestamps[n - 1] / n as f64;
+    assert!((mean * rate - 1.0).abs() < 0.01, "{}", mean * rate);
+
+    // A gap exceeds the mean with probability `1 / e`, and twice the mean with
+    // `1 / e^2`.
+    let gaps: Vec<f64> = std::iter::once(timestamps[0])
+        .chain(timestamps.windows(2).map(|w| w[1] - w[0]))
+        .collect();
+    for (multiple, expected) in [(1.0, (-1.0f64).exp()), (2.0, (-2.0f64).exp())] {
+        let fraction = gaps.iter().filter(|gap| **gap * rate > multiple).count() as f64 / n as f64;
+        assert!(
+            (fraction - expected).abs() < 0.01,
+            "{} {}",
+            multiple,
+            fraction
+        );
+    }
+}
+
+#[test]
+fn test_difficulty_distribution() {
+    let threshold = 12;
+    let difficulties: Vec<u32> = ShareSimulator::new(1e9, threshold, 42)
+        .shares()
+        .take(50_000)
+        .map(|share| share.difficulty)
+        .collect();
+    assert!(difficulties.iter().all(|d| *d >= threshold));
+    // Above the threshold, difficulties are those of uniformly random hashes.
+    let excess: Vec<u32> = difficulties.iter().map(|d| d - threshold).collect();
+    let verdict = difficulty_distribution_test(&excess);
+    assert!(verdict.degrees_of_freedom >= 10);
+    assert!(verdict.passed, "{:?}", verdict);
+    let at_threshold = excess.iter().filter(|d| **d == 0).count() as f64 / 50_000.0;
+    assert!((at_threshold - 0.5).abs() < 0.01);
+}
+
+#[test]
+fn test_real_shares_verify() {
+    let threshold = 2;
+    let shares = ShareSimulator::new(1e3, threshold, 9)
+        .mode(SimMode::Real)
+        .shares()
+        .take(300);
+    let mut highest = 0;
+    for share in shares {
+        assert!(!share.synthetic);
+        assert_eq!(share.challenge, SIM_CHALLENGE);
+        assert!(share.solution.is_valid(&share.challenge));
+        assert_eq!(share.solution.to_hash().difficulty(), share.difficulty);
+        assert!(share.difficulty >= threshold);
+        assert!(share.difficulty <= MAX_REAL_DIFFICULTY);
+        highest = highest.max(share.difficulty);
+    }
+    assert!(highest > threshold + 3);
+}
+
+#[test]
+fn test_real_pool_is_complete() {
+    // Every difficulty up to the cap is reachable.
+    let mut seen = vec![false; MAX_REAL_DIFFICULTY as usize + 1];
+    for share in ShareSimulator::new(1e3, 0, 1)
+        .mode(SimMode::Real)
+        .shares()
+        .take(1 << 18)
+    {
+        seen[share.difficulty as usize] = true;
+    }
+    assert!(seen.iter().all(|seen| *seen));
+}
+
+#[test]
+fn test_synthetic_shares() {
+    let shares: Vec<_> = ShareSimulator::new(1e3, 4, 3).shares().take(50).collect();
+    for (i, share) in shares.iter().enumerate() {
+        assert!(share.synthetic);
+        assert_eq!(share.solution.n, (i as u64).to_le_bytes());
+        assert!(!share.solution.is_valid(&share.challenge));
+    }
+}
+
+#[test]
+#[should_panic(expected = "no real shares have difficulty")]
+fn test_real_difficulty_too_high() {
+    ShareSimulator::new(1e3, MAX_REAL_DIFFICULTY + 1, 0).mode(SimMode::Real);
+}
+
+#[test]
+#[should_panic(expected = "hashrate must be positive")]
+fn test_zero_hashrate() {
+    ShareSimulator::new(0.0, 8, 0);
+}
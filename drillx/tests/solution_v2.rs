@@ -0,0 +1,121 @@
+use drillx::{wire::VersionedSolution, Solution, SolutionV2, MAX_SOLUTIONS};
+
+/// Finds a nonce whose seed has more than one equix solution.
+fn multi_solution_nonce(challenge: &[u8; 32]) -> ([u8; 8], Vec<drillx::Hash>) {
+    (0u64..)
+        .find_map(|n| {
+            let nonce = n.to_le_bytes();
+            drillx::hash_all(challenge, &nonce)
+                .ok()
+                .filter(|hashes| hashes.len() > 1)
+                .map(|hashes| (nonce, hashes))
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_hash_all_canonical_order() {
+    let challenge = [255; 32];
+    let (nonce, hashes) = multi_solution_nonce(&challenge);
+    let first = drillx::hash(&challenge, &nonce).unwrap();
+    assert!(hashes
+        .iter()
+        .any(|hash| hash.d == first.d && hash.h == first.h));
+    assert!(hashes.len() <= MAX_SOLUTIONS);
+
+    // Canonical order sorts by the digest with its u16 items sorted.
+    let key = |d: &[u8; 16]| {
+        let mut items: Vec<u16> = d
+            .chunks(2)
+            .map(|c| u16::from_le_bytes([c[0], c[1]]))
+            .collect();
+        items.sort_unstable();
+        items
+            .iter()
+            .flat_map(|i| i.to_le_bytes())
+            .collect::<Vec<u8>>()
+    };
+    assert!(hashes.windows(2).all(|w| key(&w[0].d) < key(&w[1].d)));
+}
+
+#[test]
+fn test_hash_all_vector() {
+    let digests: Vec<String> = drillx::hash_all(&[255; 32], &3u64.to_le_bytes())
+        .unwrap()
+        .iter()
+        .map(|hash| hash.d.iter().map(|b| format!("{:02x}", b)).collect())
+        .collect();
+    assert_eq!(
+        digests,
+        [
+            "cc750097eaa414a7062fe735c23386dc",
+            "a7610a8bfb94eda81853457dca5efef5",
+            "41857d9a37595cc47f7babc07d20e0d0",
+            "a10f9118bea4d9b87345e971431be0c5",
+            "f301aa58e8b188d1f2207b6b308b81f1",
+        ]
+    );
+}
+
+#[test]
+fn test_strict_indices() {
+    let challenge = [255; 32];
+    let (nonce, hashes) = multi_solution_nonce(&challenge);
+    for (idx, hash) in hashes.iter().enumerate() {
+        let solution = SolutionV2::new(hash.d, nonce, idx as u8);
+        assert!(solution.is_valid(&challenge));
+        assert!(solution.is_valid_strict(&challenge));
+        assert_eq!(solution.to_hash().h, hash.h);
+    }
+
+    // A valid digest at the wrong index passes the cheap check only
+    let misplaced = SolutionV2::new(hashes[1].d, nonce, 0);
+    assert!(misplaced.is_valid(&challenge));
+    assert!(!misplaced.is_valid_strict(&challenge));
+
+    // Indices beyond the solution count are rejected
+    let beyond = SolutionV2::new(hashes[0].d, nonce, hashes.len() as u8);
+    assert!(!beyond.is_valid_strict(&challenge));
+    let out_of_range = SolutionV2::new(hashes[0].d, nonce, MAX_SOLUTIONS as u8);
+    assert!(!out_of_range.is_valid(&challenge));
+}
+
+#[test]
+fn test_legacy_conversion() {
+    // A nonce whose `hash` result is not first in canonical order.
+    let challenge = [255; 32];
+    let (nonce, hash, hashes) = (0u64..)
+        .find_map(|n| {
+            let nonce = n.to_le_bytes();
+            let hash = drillx::hash(&challenge, &nonce).ok()?;
+            let hashes = drillx::hash_all(&challenge, &nonce).ok()?;
+            (hashes[0].d != hash.d).then_some((nonce, hash, hashes))
+        })
+        .unwrap();
+    let legacy = Solution::new(hash.d, nonce);
+    let v2 = SolutionV2::from_legacy(&challenge, &legacy).unwrap();
+    assert_ne!(v2.idx, 0);
+    assert_eq!(hashes[v2.idx as usize].d, hash.d);
+    assert!(v2.is_valid_strict(&challenge));
+    assert_eq!(Solution::from(v2), legacy);
+
+    // Digests that are not among the seed's solutions have no index.
+    let forged = Solution::new([0; 16], nonce);
+    assert_eq!(SolutionV2::from_legacy(&challenge, &forged), None);
+}
+
+#[test]
+fn test_bytes_round_trip() {
+    let solution = SolutionV2::new([7; 16], 9u64.to_le_bytes(), 3);
+    let bytes = solution.to_bytes();
+    assert_eq!(bytes.len(), 25);
+    assert_eq!(bytes[24], 3);
+    assert_eq!(SolutionV2::from_bytes(bytes), solution);
+
+    let versioned = solution.to_bytes_versioned();
+    assert_eq!(versioned[0], 2);
+    assert_eq!(
+        Solution::from_bytes_versioned(&versioned),
+        Ok(VersionedSolution::V2(solution))
+    );
+}
@@ -0,0 +1,185 @@
+use std::time::Duration;
+
+use drillx::{
+    miner::{MinerBuilder, MinerConfig, NonceOrder, SteppingMiner, StopReason},
+    RuntimeOption,
+};
+
+fn config(order: NonceOrder) -> MinerConfig {
+    MinerConfig {
+        min_difficulty: 5,
+        nonce_order: order,
+        ..Default::default()
+    }
+}
+
+/// Steps until the search ends, checking each step's count.
+fn run(miner: &mut SteppingMiner, step: u32) -> StopReason {
+    loop {
+        let before = miner.hashes();
+        let outcome = miner.step(step);
+        assert!(outcome.hashes <= step);
+        assert_eq!(miner.hashes(), before + outcome.hashes as u64);
+        assert_eq!(outcome.best, miner.best());
+        if let Some(reason) = outcome.done {
+            return reason;
+        }
+        assert_eq!(outcome.hashes, step);
+    }
+}
+
+#[test]
+fn test_stepping_matches_single_run() {
+    let challenge = [11; 32];
+    for order in [
+        NonceOrder::Sequential,
+        NonceOrder::Permuted { key: [9; 16] },
+    ] {
+        let mut small = SteppingMiner::new(challenge, &config(order)).unwrap();
+        assert_eq!(run(&mut small, 3), StopReason::Found);
+        let mut big = SteppingMiner::new(challenge, &config(order)).unwrap();
+        assert_eq!(run(&mut big, u32::MAX), StopReason::Found);
+        assert_eq!(small.best(), big.best());
+        assert_eq!(small.hashes(), big.hashes());
+
+        // The threaded miner agrees on the lowest qualifying index.
+        let threaded = MinerBuilder::new(challenge)
+            .threads(2)
+            .min_difficulty(5)
+            .deterministic(true)
+            .nonce_order(order)
+            .spawn()
+            .unwrap()
+            .join()
+            .unwrap();
+        let best = small.best().unwrap();
+        assert!(best.difficulty >= 5 && best.solution.is_valid(&challenge));
+        assert_eq!(threaded.best.unwrap().solution, best.solution);
+        let index = order.index(u64::from_le_bytes(best.solution.n));
+        assert_eq!(small.hashes(), index + 1);
+
+        // A finished search does no more work.
+        let after = small.step(10);
+        assert_eq!((after.hashes, after.done), (0, Some(StopReason::Found)));
+    }
+}
+
+#[test]
+fn test_stepping_resumes_after_serialization() {
+    let challenge = [11; 32];
+    let order = NonceOrder::Permuted { key: [3; 16] };
+    let mut reference = SteppingMiner::new(challenge, &config(order)).unwrap();
+    run(&mut reference, 64);
+
+    let mut miner = SteppingMiner::new(challenge, &config(order)).unwrap();
+    let first = miner.step(4);
+    assert_eq!((first.hashes, first.done), (4, None));
+    let saved = serde_json::to_string(&miner).unwrap();
+    drop(miner);
+    let mut resumed: SteppingMiner = serde_json::from_str(&saved).unwrap();
+    assert_eq!(resumed.hashes(), 4);
+    assert_eq!(resumed.best(), first.best);
+    assert_eq!(run(&mut resumed, 2), StopReason::Found);
+    assert_eq!(resumed.best(), reference.best());
+    assert_eq!(resumed.hashes(), reference.hashes());
+}
+
+#[test]
+fn test_stepping_exhausts_range() {
+    let config = MinerConfig {
+        min_difficulty: 64,
+        start_nonce: 10,
+        end_nonce: Some(14),
+        ..Default::default()
+    };
+    let mut miner = SteppingMiner::new([1; 32], &config).unwrap();
+    let outcome = miner.step(3);
+    assert_eq!((outcome.hashes, outcome.done), (3, None));
+    let outcome = miner.step(3);
+    assert_eq!(
+        (outcome.hashes, outcome.done),
+        (2, Some(StopReason::Exhausted))
+    );
+    let nonce = u64::from_le_bytes(outcome.best.unwrap().solution.n);
+    assert!((10..=14).contains(&nonce));
+    assert_eq!(miner.step(3).hashes, 0);
+
+    let empty = MinerConfig {
+        start_nonce: 5,
+        end_nonce: Some(4),
+        ..Default::default()
+    };
+    let outcome = SteppingMiner::new([1; 32], &empty).unwrap().step(3);
+    assert_eq!(
+        (outcome.hashes, outcome.done),
+        (0, Some(StopReason::Exhausted))
+    );
+}
+
+#[test]
+fn test_stepping_deadline() {
+    let config = MinerConfig {
+        min_difficulty: 64,
+        deadline: Some(Duration::ZERO),
+        ..Default::default()
+    };
+    let mut miner = SteppingMiner::new([1; 32], &config).unwrap();
+    let outcome = miner.step(100);
+    assert_eq!(
+        (outcome.hashes, outcome.done),
+        (0, Some(StopReason::Deadline))
+    );
+    assert_eq!(miner.done(), Some(StopReason::Deadline));
+}
+
+#[test]
+fn test_stepping_require_compile() {
+    let config = MinerConfig {
+        runtime: RuntimeOption::RequireCompile,
+        end_nonce: Some(9),
+        min_difficulty: 64,
+        ..Default::default()
+    };
+    let mut miner = SteppingMiner::new([45; 32], &config).unwrap();
+    let outcome = miner.step(100);
+    if drillx::runtime_info().compiler_available {
+        assert_eq!(
+            (outcome.hashes, outcome.done, outcome.failure),
+            (10, Some(StopReason::Exhausted), None)
+        );
+    } else {
+        // The first hash ends the search, as it ends a threaded run.
+        assert_eq!(outcome.hashes, 1);
+        assert_eq!(outcome.done, Some(StopReason::Cancelled));
+        assert!(outcome.failure.is_some());
+        assert_eq!(miner.failure(), outcome.failure);
+        assert_eq!(miner.step(100).hashes, 0);
+    }
+}
+
+#[cfg(not(all(
+    feature = "compiler",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+#[test]
+fn test_stepping_require_compile_unsupported() {
+    let config = MinerConfig {
+        runtime: RuntimeOption::RequireCompile,
+        ..Default::default()
+    };
+    let mut miner = SteppingMiner::new([45; 32], &config).unwrap();
+    let outcome = miner.step(100);
+    assert_eq!(
+        outcome.failure,
+        Some(drillx::DrillxError::CompilerUnsupported)
+    );
+    assert_eq!(outcome.done, Some(StopReason::Cancelled));
+
+    // It survives a save and resume.
+    let resumed: SteppingMiner =
+        serde_json::from_str(&serde_json::to_string(&miner).unwrap()).unwrap();
+    assert_eq!(
+        resumed.failure(),
+        Some(drillx::DrillxError::CompilerUnsupported)
+    );
+}
@@ -0,0 +1,61 @@
+use drillx::{Solution, TAG_DEFAULT};
+
+const TAG_FAUCET: [u8; 8] = *b"FAUCET01";
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// Mines the first nonce with a solution under the tag.
+fn mine(tag: &[u8; 8], challenge: &[u8; 32]) -> (Solution, [u8; 32]) {
+    (0u64..)
+        .find_map(|n| {
+            let nonce = n.to_le_bytes();
+            drillx::hash_tagged(tag, challenge, &nonce)
+                .ok()
+                .map(|hash| (Solution::new(hash.d, nonce), hash.h))
+        })
+        .unwrap()
+}
+
+#[test]
+fn test_untagged_unchanged() {
+    let hash = drillx::hash(&[255; 32], &0u64.to_le_bytes()).unwrap();
+    assert_eq!(hash.d.to_vec(), hex("583c118f4911af9e2e07b5be682bbbfd"));
+    assert_eq!(
+        hash.h.to_vec(),
+        hex("c4486c02a210988f98e217a992945538fe99fa09a4f9f6596006575ebd070f31")
+    );
+}
+
+#[test]
+fn test_same_tag_valid() {
+    let challenge = [255; 32];
+    let (solution, hash) = mine(&TAG_FAUCET, &challenge);
+    assert!(solution.is_valid_tagged(&TAG_FAUCET, &challenge));
+    assert!(drillx::is_valid_digest_tagged(
+        &TAG_FAUCET,
+        &challenge,
+        &solution.n,
+        &solution.d
+    ));
+    assert_eq!(solution.to_hash_tagged(&TAG_FAUCET).h, hash);
+}
+
+#[test]
+fn test_cross_tag_invalid() {
+    let challenge = [255; 32];
+    let (solution, hash) = mine(&TAG_FAUCET, &challenge);
+    assert!(!solution.is_valid_tagged(&TAG_DEFAULT, &challenge));
+    assert!(!solution.is_valid(&challenge));
+    assert_ne!(solution.to_hash_tagged(&TAG_DEFAULT).h, hash);
+    assert_ne!(solution.to_hash().h, hash);
+
+    // Untagged solutions are not valid under a tag either
+    let nonce = 0u64.to_le_bytes();
+    let untagged = drillx::hash(&challenge, &nonce).unwrap();
+    assert!(!Solution::new(untagged.d, nonce).is_valid_tagged(&TAG_DEFAULT, &challenge));
+}
@@ -0,0 +1,169 @@
+use std::{
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use drillx::{
+    miner::{MinerBuilder, MinerHandle, StopReason},
+    throttle::{ThrottleHook, MIN_INTENSITY},
+    DrillxError, RuntimeOption, Solver,
+};
+
+/// How long each solve takes, so that the duty cycle is easy to measure.
+const SOLVE: Duration = Duration::from_millis(4);
+
+const POLL: Duration = Duration::from_millis(20);
+
+/// Takes a fixed time per solve.
+struct Sleeper;
+
+impl Solver for Sleeper {
+    fn solve(&mut self, _: &[u8], _: RuntimeOption) -> Result<[u8; 16], DrillxError> {
+        std::thread::sleep(SOLVE);
+        Ok([0; 16])
+    }
+}
+
+/// A hook whose intensity the test scripts.
+#[derive(Clone)]
+struct Scripted(Arc<AtomicU32>);
+
+impl Scripted {
+    fn new(intensity: f32) -> Self {
+        Scripted(Arc::new(AtomicU32::new(intensity.to_bits())))
+    }
+
+    fn set(&self, intensity: f32) {
+        self.0.store(intensity.to_bits(), Ordering::Relaxed);
+    }
+}
+
+impl ThrottleHook for Scripted {
+    fn target_intensity(&self) -> f32 {
+        f32::from_bits(self.0.load(Ordering::Relaxed))
+    }
+}
+
+fn spawn(hook: impl ThrottleHook + 'static) -> MinerHandle {
+    MinerBuilder::new([0; 32])
+        .threads(1)
+        .min_difficulty(256)
+        .solver(|| Sleeper)
+        .throttle(hook)
+        .throttle_poll(POLL)
+        .spawn()
+        .unwrap()
+}
+
+/// Hashes per second over `window`, after letting a change of intensity settle.
+fn rate(handle: &MinerHandle, window: Duration) -> f64 {
+    std::thread::sleep(POLL * 3);
+    let before = handle.progress().hashes;
+    std::thread::sleep(window);
+    (handle.progress().hashes - before) as f64 / window.as_secs_f64()
+}
+
+#[test]
+fn test_throttle_follows_hook() {
+    let hook = Scripted::new(1.0);
+    let handle = spawn(hook.clone());
+    let window = Duration::from_millis(400);
+    let full = rate(&handle, window);
+    assert!(full > 0.0);
+    assert_eq!(handle.progress().intensity, 1.0);
+
+    for intensity in [0.5, 0.25] {
+        hook.set(intensity);
+        let duty = rate(&handle, window) / full;
+        assert!(
+            (duty - intensity as f64).abs() < 0.12,
+            "duty {} at intensity {}",
+            duty,
+            intensity
+        );
+        assert_eq!(handle.progress().intensity, intensity);
+    }
+
+    hook.set(0.0);
+    assert_eq!(rate(&handle, window), 0.0);
+    let progress = handle.progress();
+    assert_eq!(progress.intensity, 0.0);
+    assert!(!progress.paused);
+    assert!(!handle.is_finished());
+
+    hook.set(1.0);
+    assert!(rate(&handle, window) > full / 2.0);
+    handle.cancel();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Cancelled);
+}
+
+#[test]
+fn test_throttle_clamps() {
+    let hook = Scripted::new(7.0);
+    let handle = spawn(hook.clone());
+    std::thread::sleep(POLL * 3);
+    assert_eq!(handle.progress().intensity, 1.0);
+    hook.set(f32::NAN);
+    std::thread::sleep(POLL * 3);
+    assert_eq!(handle.progress().intensity, 1.0);
+    hook.set(-1.0);
+    std::thread::sleep(POLL * 3);
+    assert_eq!(handle.progress().intensity, 0.0);
+    handle.cancel();
+    handle.join().unwrap();
+}
+
+#[test]
+fn test_throttle_tiny_intensity_parks() {
+    let hook = Scripted::new(f32::MIN_POSITIVE);
+    let handle = MinerBuilder::new([0; 32])
+        .threads(1)
+        .min_difficulty(256)
+        .solver(|| Sleeper)
+        .throttle(hook.clone())
+        .throttle_poll(POLL)
+        .stall_timeout(POLL)
+        .spawn()
+        .unwrap();
+    // Rests this long would overflow a Duration, so the workers park instead.
+    assert_eq!(rate(&handle, Duration::from_millis(200)), 0.0);
+    assert_eq!(handle.progress().intensity, 0.0);
+    hook.set(MIN_INTENSITY / 2.0);
+    std::thread::sleep(POLL * 3);
+    assert_eq!(handle.progress().intensity, 0.0);
+
+    hook.set(MIN_INTENSITY);
+    std::thread::sleep(POLL * 3);
+    assert_eq!(handle.progress().intensity, MIN_INTENSITY);
+    handle.cancel();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Cancelled);
+}
+
+#[test]
+fn test_throttle_parked_at_start() {
+    let handle = spawn(|| 0.0);
+    assert_eq!(rate(&handle, Duration::from_millis(200)), 0.0);
+    // Parked workers still stop.
+    handle.cancel();
+    let outcome = handle.join().unwrap();
+    assert_eq!(outcome.reason, StopReason::Cancelled);
+}
+
+#[test]
+fn test_throttle_slow_hook() {
+    let handle = spawn(|| {
+        std::thread::sleep(Duration::from_secs(5));
+        0.5
+    });
+    std::thread::sleep(Duration::from_millis(200));
+    assert!(handle.progress().hashes > 0);
+    let cancelled = Instant::now();
+    handle.cancel();
+    handle.join().unwrap();
+    assert!(cancelled.elapsed() < Duration::from_secs(2));
+}
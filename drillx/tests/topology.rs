@@ -0,0 +1,187 @@
+use drillx::{
+    miner::{accelerator_chunk_size, scaled_chunk_size, MinerBuilder, Prefer},
+    topology::{self, parse_capacities, parse_cpulist, parse_pmu_cpus, parse_sysctl, Topology},
+};
+
+fn cpus(topology: &Topology) -> (Vec<usize>, Vec<usize>) {
+    match topology {
+        Topology::Hybrid {
+            performance,
+            efficiency,
+        } => (performance.cpus.clone(), efficiency.cpus.clone()),
+        other => panic!("not hybrid: {:?}", other),
+    }
+}
+
+/// `cpu_capacity` of each CPU, as read from sysfs.
+fn capacities(values: &[&'static str]) -> Vec<(usize, &'static str)> {
+    values.iter().copied().enumerate().collect()
+}
+
+#[test]
+fn test_parse_capacities() {
+    // RK3588: four Cortex-A55 and four Cortex-A76.
+    let rk3588 = capacities(&[
+        "414\n", "414\n", "414\n", "414\n", "1024\n", "1024\n", "1024\n", "1024\n",
+    ]);
+    assert_eq!(
+        cpus(&parse_capacities(&rk3588)),
+        (vec![4, 5, 6, 7], vec![0, 1, 2, 3])
+    );
+
+    // Snapdragon 8 Gen 2: the mid cores are close enough to the prime core to count as
+    // performance cores.
+    let sm8550 = capacities(&[
+        "325\n", "325\n", "325\n", "828\n", "828\n", "828\n", "828\n", "1024\n",
+    ]);
+    assert_eq!(
+        cpus(&parse_capacities(&sm8550)),
+        (vec![3, 4, 5, 6, 7], vec![0, 1, 2])
+    );
+
+    let uniform = capacities(&["1024\n"; 4]);
+    assert_eq!(parse_capacities(&uniform), Topology::Uniform { cpus: 4 });
+
+    assert_eq!(parse_capacities(&[]), Topology::Unknown);
+    assert_eq!(
+        parse_capacities(&capacities(&["1024\n", "\n"])),
+        Topology::Unknown
+    );
+}
+
+#[test]
+fn test_parse_cpulist() {
+    assert_eq!(parse_cpulist("0\n"), Some(vec![0]));
+    assert_eq!(
+        parse_cpulist("0-3,8,10-11\n"),
+        Some(vec![0, 1, 2, 3, 8, 10, 11])
+    );
+    assert_eq!(parse_cpulist("\n"), Some(vec![]));
+    assert_eq!(parse_cpulist("3-1"), None);
+    assert_eq!(parse_cpulist("0-x"), None);
+}
+
+#[test]
+fn test_parse_pmu_cpus() {
+    // Core i7-12700: eight hyperthreaded P-cores and four E-cores.
+    let topology = parse_pmu_cpus("0-15\n", "16-19\n");
+    assert_eq!(
+        cpus(&topology),
+        ((0..16).collect::<Vec<_>>(), vec![16, 17, 18, 19])
+    );
+    assert_eq!(parse_pmu_cpus("0-7\n", "\n"), Topology::Uniform { cpus: 8 });
+    assert_eq!(parse_pmu_cpus("garbage", "16-19"), Topology::Unknown);
+}
+
+#[test]
+fn test_parse_sysctl() {
+    // M1 Pro.
+    let m1_pro = "hw.logicalcpu: 10\nhw.nperflevels: 2\nhw.perflevel0.logicalcpu: 8\nhw.perflevel1.logicalcpu: 2\n";
+    match parse_sysctl(m1_pro) {
+        Topology::Hybrid {
+            performance,
+            efficiency,
+        } => {
+            assert_eq!((performance.count, efficiency.count), (8, 2));
+            assert!(performance.cpus.is_empty() && efficiency.cpus.is_empty());
+        }
+        other => panic!("not hybrid: {:?}", other),
+    }
+    // Intel Macs have no performance levels; sysctl reports the unknown keys on stderr.
+    assert_eq!(
+        parse_sysctl("hw.logicalcpu: 16\n"),
+        Topology::Uniform { cpus: 16 }
+    );
+    assert_eq!(
+        parse_sysctl("hw.logicalcpu: 8\nhw.nperflevels: 1\nhw.perflevel0.logicalcpu: 8\n"),
+        Topology::Uniform { cpus: 8 }
+    );
+    assert_eq!(parse_sysctl(""), Topology::Unknown);
+}
+
+#[test]
+fn test_scaled_chunk_size() {
+    assert_eq!(scaled_chunk_size(64, 3.0, 3.0), 64);
+    assert_eq!(scaled_chunk_size(64, 2.8, 3.0), 64);
+    assert_eq!(scaled_chunk_size(64, 1.0, 3.0), 21);
+    assert_eq!(scaled_chunk_size(64, 0.001, 3.0), 1);
+    // Until speeds are measured.
+    assert_eq!(scaled_chunk_size(64, 0.0, 0.0), 64);
+    assert_eq!(scaled_chunk_size(64, 1.0, 0.0), 64);
+}
+
+#[test]
+fn test_accelerator_chunk_size() {
+    assert_eq!(accelerator_chunk_size(64, 300.0, 3.0), 6400);
+    assert_eq!(accelerator_chunk_size(64, 4.5, 3.0), 96);
+    // Never below a CPU chunk, nor above the cap.
+    assert_eq!(accelerator_chunk_size(64, 1.0, 3.0), 64);
+    assert_eq!(accelerator_chunk_size(64, 1e30, 1.0), 64 << 16);
+    assert_eq!(accelerator_chunk_size(u64::MAX, 1e30, 1.0), u64::MAX);
+    // Until speeds are measured.
+    assert_eq!(accelerator_chunk_size(64, 0.0, 3.0), 64);
+    assert_eq!(accelerator_chunk_size(64, 300.0, 0.0), 64);
+}
+
+/// Simulates workers of the given speeds draining `nonces` nonces by claiming chunks,
+/// returning how long the last worker finishes after the ideal completion time.
+fn tail(speeds: &[f64], nonces: u64, chunk_size: u64, scaled: bool) -> f64 {
+    let fastest = speeds.iter().copied().fold(0.0, f64::max);
+    let mut free = vec![0.0f64; speeds.len()];
+    let mut next = 0;
+    while next < nonces {
+        let (worker, _) = free
+            .iter()
+            .enumerate()
+            .min_by(|a, b| a.1.total_cmp(b.1))
+            .unwrap();
+        let speed = speeds[worker];
+        let chunk = if scaled {
+            scaled_chunk_size(chunk_size, speed, fastest)
+        } else {
+            chunk_size
+        };
+        let chunk = chunk.min(nonces - next);
+        next += chunk;
+        free[worker] += chunk as f64 / speed;
+    }
+    let makespan = free.iter().copied().fold(0.0, f64::max);
+    makespan - nonces as f64 / speeds.iter().sum::<f64>()
+}
+
+#[test]
+fn test_chunks_bound_stall() {
+    // Four performance cores and four efficiency cores at a third of their speed.
+    let speeds = [3.0, 3.0, 3.0, 3.0, 1.0, 1.0, 1.0, 1.0];
+    let chunk_size = 64;
+    let worst = |scaled| {
+        (256..2048)
+            .map(|nonces| tail(&speeds, nonces, chunk_size, scaled))
+            .fold(0.0, f64::max)
+    };
+    let fixed = worst(false);
+    let scaled = worst(true);
+    // A full chunk on the fastest worker takes about 21 time units, and one on the
+    // slowest 64.
+    assert!(scaled <= chunk_size as f64 / 3.0 * 1.5, "{}", scaled);
+    assert!(fixed > chunk_size as f64 / 2.0, "{}", fixed);
+}
+
+#[test]
+fn test_core_preference() {
+    for prefer in [
+        Prefer::PerformanceOnly,
+        Prefer::PerformanceFirst,
+        Prefer::All,
+    ] {
+        let handle = MinerBuilder::new([1; 32])
+            .threads(2)
+            .min_difficulty(1)
+            .core_preference(prefer)
+            .spawn()
+            .unwrap();
+        assert_eq!(handle.progress().topology, topology::detect());
+        let outcome = handle.join().unwrap();
+        assert!(outcome.best.unwrap().solution.is_valid(&[1; 32]));
+    }
+}
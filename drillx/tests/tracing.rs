@@ -0,0 +1,115 @@
+#![cfg(feature = "tracing")]
+
+use std::sync::{Arc, Mutex};
+
+use drillx::{miner::MinerBuilder, telemetry, DrillxError, EquixSolver, RuntimeOption, Solver};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Subscriber,
+};
+use tracing_subscriber::{layer::Context, prelude::*, registry::LookupSpan, Layer};
+
+/// A span or event name and its field names.
+type Record = (String, Vec<String>);
+
+/// Records the names and fields of every drillx event and span.
+#[derive(Clone, Default)]
+struct Capture {
+    records: Arc<Mutex<Vec<Record>>>,
+}
+
+impl Capture {
+    fn find(&self, name: &str) -> Option<Vec<String>> {
+        let records = self.records.lock().unwrap();
+        records
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, f)| f.clone())
+    }
+}
+
+struct FieldNames(Vec<String>);
+
+impl Visit for FieldNames {
+    fn record_debug(&mut self, field: &Field, _value: &dyn std::fmt::Debug) {
+        self.0.push(field.name().to_string());
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for Capture {
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, _id: &span::Id, _ctx: Context<'_, S>) {
+        if attrs.metadata().target() == "drillx" {
+            let mut fields = FieldNames(vec![]);
+            attrs.record(&mut fields);
+            self.records
+                .lock()
+                .unwrap()
+                .push((attrs.metadata().name().to_string(), fields.0));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if event.metadata().target() == "drillx" {
+            let mut fields = FieldNames(vec![]);
+            event.record(&mut fields);
+            self.records
+                .lock()
+                .unwrap()
+                .push((event.metadata().name().to_string(), fields.0));
+        }
+    }
+}
+
+/// Fails every compile attempt.
+struct FailingCompiler(EquixSolver);
+
+impl Solver for FailingCompiler {
+    fn solve(&mut self, seed: &[u8], runtime: RuntimeOption) -> Result<[u8; 16], DrillxError> {
+        match runtime {
+            RuntimeOption::InterpretOnly => self.0.solve(seed, runtime),
+            _ => Err(DrillxError::CompileFailed),
+        }
+    }
+}
+
+#[test]
+fn test_miner_events() {
+    let capture = Capture::default();
+    tracing_subscriber::registry().with(capture.clone()).init();
+
+    let outcome = MinerBuilder::new([255; 32])
+        .threads(2)
+        .min_difficulty(4)
+        .spawn()
+        .unwrap()
+        .join()
+        .unwrap();
+    assert!(outcome.best.unwrap().difficulty >= 4);
+
+    let challenge = capture.find(telemetry::CHALLENGE_EVENT).unwrap();
+    assert!(challenge.contains(&"challenge".to_string()));
+    assert!(challenge.contains(&"min_difficulty".to_string()));
+
+    let solution = capture.find(telemetry::SOLUTION_EVENT).unwrap();
+    assert!(solution.contains(&"nonce".to_string()));
+    assert!(solution.contains(&"difficulty".to_string()));
+
+    let topology = capture.find(telemetry::TOPOLOGY_EVENT).unwrap();
+    assert!(topology.contains(&"topology".to_string()));
+    assert!(topology.contains(&"preference".to_string()));
+
+    // Nonce 0 is always sampled
+    let solve = capture.find(telemetry::SOLVE_SPAN).unwrap();
+    assert!(solve.contains(&"nonce".to_string()));
+
+    // Watchdog
+    let mut context = drillx::Context::with_solver(
+        FailingCompiler(EquixSolver::new()),
+        RuntimeOption::TryCompile,
+    )
+    .failure_threshold(1);
+    context.hash(&[255; 32], &0u64.to_le_bytes()).unwrap();
+    let downgrade = capture.find(telemetry::RUNTIME_DOWNGRADE_EVENT).unwrap();
+    assert!(downgrade.contains(&"failures".to_string()));
+    assert!(capture.find(telemetry::RUNTIME_FALLBACK_EVENT).is_some());
+}
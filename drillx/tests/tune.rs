@@ -0,0 +1,57 @@
+use std::time::Duration;
+
+use drillx::{autotune, autotune_within, miner::MinerBuilder};
+
+#[test]
+fn test_autotune_smoke() {
+    let budget = Duration::from_millis(600);
+    let report = autotune_within(budget);
+    assert!(!report.trials.is_empty());
+    assert!(report.trial(1).is_some());
+    assert!(report.trial(report.logical_cores).is_some());
+    assert!(report.trial(report.recommended).is_some());
+    assert!(report
+        .trials
+        .windows(2)
+        .all(|w| w[0].threads < w[1].threads));
+    for trial in &report.trials {
+        assert!(trial.hashes >= trial.threads as u64);
+        assert!(trial.hashrate > 0.0);
+    }
+    let best = report.trials.iter().map(|t| t.hashrate).fold(0.0, f64::max);
+    assert!(report.trial(report.recommended).unwrap().hashrate >= best * 0.98);
+    // Allow for the last hash of each trial running over.
+    assert!(report.elapsed < budget + Duration::from_millis(500));
+
+    // serde_json may parse a float one ulp off, so hashrates are compared loosely.
+    let json = serde_json::to_string(&report).unwrap();
+    let mut parsed: drillx::TuneReport = serde_json::from_str(&json).unwrap();
+    for (parsed, trial) in parsed.trials.iter_mut().zip(&report.trials) {
+        assert!((parsed.hashrate - trial.hashrate).abs() <= trial.hashrate * 1e-12);
+        parsed.hashrate = trial.hashrate;
+    }
+    assert_eq!(parsed, report);
+}
+
+#[test]
+fn test_autotune_zero_seconds() {
+    // Every trial still hashes once per thread.
+    let report = autotune(0);
+    assert!(report.trial(report.recommended).is_some());
+    for trial in &report.trials {
+        assert!(trial.hashes >= trial.threads as u64);
+    }
+}
+
+#[test]
+fn test_threads_auto() {
+    let handle = MinerBuilder::new([16; 32])
+        .threads_auto()
+        .min_difficulty(64)
+        .spawn()
+        .unwrap();
+    let threads = handle.progress().threads;
+    assert!(threads >= 1);
+    handle.cancel();
+    handle.join().unwrap();
+}
@@ -0,0 +1,245 @@
+use std::time::Duration;
+
+use drillx::{VardiffController, VARDIFF_IDLE_INTERVALS, VARDIFF_MIN_SHARES};
+
+const TARGET: Duration = Duration::from_secs(10);
+
+/// A small deterministic generator so failures reproduce.
+struct SplitMix(u64);
+
+impl SplitMix {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// An exponential draw of mean `mean`.
+    fn exponential(&mut self, mean: f64) -> f64 {
+        let uniform = ((self.next() >> 11) + 1) as f64 / (1u64 << 53) as f64;
+        -uniform.ln() * mean
+    }
+}
+
+/// A miner submitting shares to a controller, which is checked for idleness once per
+/// target interval.
+struct Miner {
+    rng: SplitMix,
+    now: f64,
+    /// Difficulty after each retarget, with its time.
+    retargets: Vec<(f64, u32)>,
+    shares: usize,
+}
+
+impl Miner {
+    fn new(seed: u64) -> Self {
+        Miner {
+            rng: SplitMix(seed),
+            now: 0.0,
+            retargets: Vec::new(),
+            shares: 0,
+        }
+    }
+
+    /// Mines at `hashrate` until `shares` more shares are in or `seconds` pass.
+    fn run(&mut self, vardiff: &mut VardiffController, hashrate: f64, shares: usize, seconds: f64) {
+        let end = self.now + seconds;
+        let target = TARGET.as_secs_f64();
+        let mut left = shares;
+        while left > 0 && self.now < end {
+            // Shares are memoryless, so a draw is as good after a retarget as before.
+            let mean = 2f64.powi(vardiff.current() as i32) / hashrate;
+            let share = self.now + self.rng.exponential(mean);
+            let check = (self.now / target).floor() * target + target;
+            let retarget = if share <= check {
+                self.now = share;
+                self.shares += 1;
+                left -= 1;
+                vardiff.on_share(Duration::from_secs_f64(share))
+            } else {
+                self.now = check;
+                vardiff.on_idle(Duration::from_secs_f64(check))
+            };
+            if let Some(difficulty) = retarget {
+                assert_eq!(difficulty, vardiff.current());
+                self.retargets.push((self.now, difficulty));
+            }
+        }
+    }
+
+    fn retargets_after(&self, time: f64) -> usize {
+        self.retargets.iter().filter(|(t, _)| *t > time).count()
+    }
+}
+
+/// The difficulty at which `hashrate` finds a share every target interval.
+fn ideal(hashrate: f64) -> f64 {
+    (hashrate * TARGET.as_secs_f64()).log2()
+}
+
+fn assert_near(difficulty: u32, hashrate: f64) {
+    let error = difficulty as f64 - ideal(hashrate);
+    assert!(
+        error.abs() <= 1.0,
+        "difficulty {} for an ideal of {}",
+        difficulty,
+        ideal(hashrate)
+    );
+}
+
+#[test]
+fn test_fast_miner_converges() {
+    // A large server, starting at the bottom.
+    let hashrate = 6000.0 * 64.0;
+    let mut vardiff = VardiffController::new(TARGET, 1, 40);
+    let mut miner = Miner::new(1);
+    vardiff.resume(Duration::ZERO);
+    miner.run(&mut vardiff, hashrate, 200, f64::INFINITY);
+    assert_near(vardiff.current(), hashrate);
+    // The climb is in steps of at most four, each after a handful of quick shares.
+    assert!(miner.retargets.len() <= 8, "{:?}", miner.retargets);
+    assert!(miner.retargets[4].0 < 60.0, "{:?}", miner.retargets);
+}
+
+#[test]
+fn test_slow_miner_converges() {
+    // A phone, starting far too high, so it reaches the right difficulty on idle checks.
+    let hashrate = 5.0;
+    let mut vardiff = VardiffController::new(TARGET, 1, 40).with_difficulty(24);
+    let mut miner = Miner::new(2);
+    vardiff.resume(Duration::ZERO);
+    miner.run(&mut vardiff, hashrate, 100, f64::INFINITY);
+    assert_near(vardiff.current(), hashrate);
+    let idle = (VARDIFF_IDLE_INTERVALS as f64 * TARGET.as_secs_f64()) as u32;
+    assert!(miner.retargets[0].0 >= idle as f64);
+    assert_eq!(miner.shares, 100);
+}
+
+#[test]
+fn test_steady_hashrate_is_stable() {
+    for (seed, hashrate) in [(3, 1e4), (4, 1e4 * 2f64.sqrt()), (5, 3e5)] {
+        let mut vardiff =
+            VardiffController::new(TARGET, 1, 40).with_difficulty(ideal(hashrate).round() as u32);
+        let mut miner = Miner::new(seed);
+        vardiff.resume(Duration::ZERO);
+        miner.run(&mut vardiff, hashrate, 1000, f64::INFINITY);
+        // Even halfway between difficulties, noise moves it at most a few times.
+        assert!(miner.retargets.len() <= 4, "{:?}", miner.retargets);
+        assert_near(vardiff.current(), hashrate);
+    }
+}
+
+#[test]
+fn test_hashrate_steps() {
+    for (seed, factor) in [(6, 4.0), (7, 0.25)] {
+        let hashrate = 1e4;
+        let mut vardiff =
+            VardiffController::new(TARGET, 1, 40).with_difficulty(ideal(hashrate).round() as u32);
+        let mut miner = Miner::new(seed);
+        vardiff.resume(Duration::ZERO);
+        miner.run(&mut vardiff, hashrate, 300, f64::INFINITY);
+        assert_near(vardiff.current(), hashrate);
+
+        let step = miner.now;
+        let before = vardiff.current();
+        miner.run(&mut vardiff, hashrate * factor, 1000, f64::INFINITY);
+        assert_near(vardiff.current(), hashrate * factor);
+        // It follows a 4x step in one or two retargets, within a few dozen intervals,
+        // then holds.
+        let followed: Vec<_> = miner.retargets.iter().filter(|(t, _)| *t > step).collect();
+        assert!(!followed.is_empty());
+        assert!(followed[0].0 - step < 60.0 * TARGET.as_secs_f64());
+        assert_ne!(followed[0].1, before);
+        assert!(miner.retargets_after(step) <= 5, "{:?}", followed);
+    }
+}
+
+#[test]
+fn test_bounds() {
+    // Hashrates beyond either bound pin the difficulty there.
+    let mut vardiff = VardiffController::new(TARGET, 8, 12);
+    let mut miner = Miner::new(8);
+    vardiff.resume(Duration::ZERO);
+    miner.run(&mut vardiff, 1e9, 500, f64::INFINITY);
+    assert_eq!(vardiff.current(), 12);
+    assert!(miner.retargets.iter().all(|(_, d)| (8..=12).contains(d)));
+
+    miner.run(&mut vardiff, 0.01, usize::MAX, 1e6);
+    assert_eq!(vardiff.current(), 8);
+    assert!(miner.retargets.iter().all(|(_, d)| (8..=12).contains(d)));
+
+    assert_eq!(
+        VardiffController::new(TARGET, 8, 12)
+            .with_difficulty(20)
+            .current(),
+        12
+    );
+    assert_eq!(VardiffController::new(TARGET, 8, 12).current(), 8);
+}
+
+#[test]
+fn test_hysteresis() {
+    let mut vardiff = VardiffController::new(TARGET, 1, 40).with_difficulty(10);
+    let secs = Duration::from_secs;
+    vardiff.resume(secs(0));
+    // Too few shares to retarget, however fast.
+    for i in 1..VARDIFF_MIN_SHARES as u64 {
+        assert_eq!(vardiff.on_share(secs(i)), None);
+    }
+    // Shares twice as slow as the target are inside the deadband.
+    let mut vardiff = VardiffController::new(TARGET, 1, 40).with_difficulty(10);
+    vardiff.resume(secs(0));
+    for i in 1..=100 {
+        assert_eq!(vardiff.on_share(secs(20 * i)), None);
+    }
+    assert_eq!(vardiff.average_interval(), Some(secs(20)));
+    // Shares four times as fast are not; the average starts over after the retarget.
+    let mut vardiff = VardiffController::new(TARGET, 1, 40).with_difficulty(10);
+    vardiff.resume(secs(0));
+    for i in 1..VARDIFF_MIN_SHARES as u64 {
+        assert_eq!(vardiff.on_share(Duration::from_millis(2500 * i)), None);
+    }
+    let now = Duration::from_millis(2500 * VARDIFF_MIN_SHARES as u64);
+    assert_eq!(vardiff.on_share(now), Some(12));
+    assert_eq!(vardiff.average_interval(), None);
+}
+
+#[test]
+fn test_idle() {
+    let secs = Duration::from_secs;
+    let idle = VARDIFF_IDLE_INTERVALS as u64 * TARGET.as_secs();
+    let mut vardiff = VardiffController::new(TARGET, 1, 40).with_difficulty(20);
+    // Nothing to measure before the clock starts.
+    assert_eq!(vardiff.on_idle(secs(1000)), None);
+    vardiff.resume(secs(1000));
+    assert_eq!(vardiff.on_idle(secs(1000 + idle - 1)), None);
+    assert_eq!(vardiff.on_idle(secs(1000 + idle)), Some(18));
+    // The next drop waits for another idle period, and a longer gap drops further.
+    assert_eq!(vardiff.on_idle(secs(1000 + idle + 1)), None);
+    assert_eq!(vardiff.on_idle(secs(1000 + idle + 4 * idle)), Some(14));
+    // A share resets the gap.
+    vardiff.on_share(secs(1000 + 6 * idle));
+    assert_eq!(vardiff.on_idle(secs(1000 + 6 * idle + idle / 2)), None);
+}
+
+#[test]
+fn test_serde_roundtrip() {
+    let mut vardiff = VardiffController::new(TARGET, 1, 40);
+    let mut miner = Miner::new(9);
+    vardiff.resume(Duration::ZERO);
+    miner.run(&mut vardiff, 1e5, 50, f64::INFINITY);
+    let json = serde_json::to_string(&vardiff).unwrap();
+    let mut restored: VardiffController = serde_json::from_str(&json).unwrap();
+    // serde_json may round the average in its last bit.
+    assert_eq!(restored.current(), vardiff.current());
+    assert_eq!(restored.average_interval(), vardiff.average_interval());
+
+    // After a reconnect the time away is not an interval.
+    let before = restored.current();
+    let later = Duration::from_secs_f64(miner.now) + Duration::from_secs(3600);
+    restored.resume(later);
+    assert_eq!(restored.on_share(later + TARGET), None);
+    assert_eq!(restored.current(), before);
+}
@@ -0,0 +1,124 @@
+use std::sync::Arc;
+
+use drillx::{vectors::VECTORS, CachedVerdict, Solution, VerifyCache};
+
+/// Valid solutions from the test vectors, with their challenges.
+fn solutions() -> Vec<([u8; 32], Solution)> {
+    VECTORS
+        .iter()
+        .filter_map(|v| Some((v.challenge, Solution::new(v.output?.digest, v.nonce))))
+        .collect()
+}
+
+#[test]
+fn test_verify_cache_hits_repeats() {
+    let cache = VerifyCache::new(64);
+    let (challenge, solution) = solutions()[0];
+    let difficulty = Some(solution.to_hash().difficulty());
+    let first = cache.get_or_verify(&challenge, &solution);
+    assert_eq!(
+        first,
+        CachedVerdict {
+            valid: true,
+            difficulty,
+            hit: false
+        }
+    );
+    let second = cache.get_or_verify(&challenge, &solution);
+    assert_eq!(second, CachedVerdict { hit: true, ..first });
+    assert_eq!((cache.hits(), cache.misses(), cache.len()), (1, 1, 1));
+}
+
+#[test]
+fn test_verify_cache_keys_on_challenge_and_digest() {
+    let cache = VerifyCache::new(64);
+    let (challenge, solution) = solutions()[0];
+    assert!(cache.get_or_verify(&challenge, &solution).valid);
+
+    let other = cache.get_or_verify(&[0xaa; 32], &solution);
+    assert!(!other.valid && !other.hit);
+
+    let mut digest = solution.d;
+    digest[0] ^= 1;
+    let forged = cache.get_or_verify(&challenge, &Solution::new(digest, solution.n));
+    assert!(!forged.valid && !forged.hit);
+
+    // Invalid verdicts are cached too.
+    let again = cache.get_or_verify(&[0xaa; 32], &solution);
+    assert!(!again.valid && again.hit);
+    assert_eq!(again.difficulty, None);
+}
+
+#[test]
+fn test_verify_cache_evicts_least_recently_used() {
+    // One shard, so the order is global.
+    let cache = VerifyCache::new(1);
+    let (challenge, solution) = solutions()[0];
+    let invalid = Solution::new([0; 16], [1; 8]);
+    cache.get_or_verify(&challenge, &solution);
+    cache.get_or_verify(&challenge, &invalid);
+    assert_eq!(cache.len(), 1);
+    assert!(!cache.get_or_verify(&challenge, &solution).hit);
+    assert!(cache.get_or_verify(&challenge, &solution).hit);
+
+    // Sixteen shards of two.
+    let cache = VerifyCache::new(32);
+    let shares: Vec<Solution> = (0..64)
+        .map(|n: u64| Solution::new([0; 16], n.to_le_bytes()))
+        .collect();
+    for share in &shares {
+        cache.get_or_verify(&challenge, share);
+        // Touching the first share keeps it cached in whichever shard it is in.
+        assert!(cache.get_or_verify(&challenge, &shares[0]).hit || share == &shares[0]);
+    }
+    assert!(cache.len() <= 32);
+}
+
+#[test]
+fn test_verify_cache_is_bounded() {
+    for capacity in [0, 1, 5, 16, 100] {
+        let cache = VerifyCache::new(capacity);
+        for n in 0..300u64 {
+            cache.get_or_verify(&[1; 32], &Solution::new([0; 16], n.to_le_bytes()));
+        }
+        assert_eq!(cache.len(), capacity.max(1));
+        assert_eq!(cache.capacity(), capacity.max(1));
+    }
+
+    let cache = VerifyCache::new(8);
+    cache.get_or_verify(&[1; 32], &Solution::new([0; 16], [0; 8]));
+    cache.clear();
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn test_verify_cache_concurrent() {
+    let cache = Arc::new(VerifyCache::new(1024));
+    let shares = Arc::new(solutions());
+    let threads = 8;
+    let rounds = 50;
+    let handles: Vec<_> = (0..threads)
+        .map(|_| {
+            let cache = cache.clone();
+            let shares = shares.clone();
+            std::thread::spawn(move || {
+                for _ in 0..rounds {
+                    for (challenge, solution) in shares.iter() {
+                        let verdict = cache.get_or_verify(challenge, solution);
+                        assert!(verdict.valid);
+                        assert_eq!(verdict.difficulty, Some(solution.to_hash().difficulty()));
+                    }
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    let requests = (threads * rounds * shares.len()) as u64;
+    assert_eq!(cache.hits() + cache.misses(), requests);
+    // Each share is verified at most once per thread that missed it concurrently.
+    assert!(cache.misses() <= (threads * shares.len()) as u64);
+    assert!(cache.misses() * 20 <= requests);
+    assert_eq!(cache.len(), shares.len());
+}
@@ -0,0 +1,46 @@
+//! Builds against the verification API alone, with
+//! `cargo test -p drillx --no-default-features --features verify --test verify_only`.
+#![cfg(not(feature = "solve"))]
+
+use drillx::{
+    vectors::{VECTORS, VECTORS_V2},
+    Solution, SolutionV2,
+};
+
+#[test]
+fn test_verify_vectors() {
+    let mut solutions = Vec::new();
+    for vector in VECTORS {
+        let Some(output) = vector.output else {
+            continue;
+        };
+        assert!(drillx::is_valid_digest(
+            &vector.challenge,
+            &vector.nonce,
+            &output.digest
+        ));
+        let solution = Solution::new(output.digest, vector.nonce);
+        assert!(solution.is_valid(&vector.challenge));
+        assert_eq!(solution.to_hash().h, output.hash);
+        assert_eq!(
+            solution.to_hash().difficulty(),
+            drillx::difficulty(output.hash)
+        );
+        assert!(SolutionV2::new(solution.d, solution.n, 0).is_valid(&vector.challenge));
+        if vector.challenge == VECTORS[0].challenge {
+            solutions.push(solution);
+        }
+    }
+
+    for vector in VECTORS_V2 {
+        let solution = Solution::new(vector.digest, vector.nonce);
+        assert!(solution.is_valid_hash_v2(&vector.challenge, &vector.hash));
+    }
+
+    let mut forged = solutions[0];
+    forged.d[0] ^= 1;
+    solutions.push(forged);
+    let verdicts = drillx::verify_batch(&VECTORS[0].challenge, &solutions);
+    assert_eq!(verdicts.iter().filter(|v| !**v).count(), 1);
+    assert!(!verdicts[verdicts.len() - 1]);
+}
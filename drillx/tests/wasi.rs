@@ -0,0 +1,73 @@
+//! Runs under a WASI runtime with
+//! `cargo test -p drillx --target wasm32-wasip1 --test wasi`, which uses the runner in
+//! `.cargo/config.toml`.
+#![cfg(target_os = "wasi")]
+
+use drillx::{
+    runtime_info, vectors::VECTORS, DrillxMemory, Runtime, RuntimeOption, Solution,
+    COMPILER_SUPPORTED,
+};
+
+#[test]
+fn test_interpreter_only() {
+    const { assert!(!COMPILER_SUPPORTED) };
+    let info = runtime_info();
+    assert!(!info.compiler_available);
+    assert_eq!(info.default_runtime, Runtime::Interpreted);
+}
+
+#[test]
+fn test_vectors() {
+    let mut memory = DrillxMemory::new();
+    for (i, vector) in VECTORS.iter().enumerate() {
+        assert!(
+            vector.check(&mut memory, RuntimeOption::InterpretOnly),
+            "vector {}",
+            i
+        );
+        assert!(
+            vector.check(&mut memory, RuntimeOption::TryCompile),
+            "vector {}",
+            i
+        );
+    }
+}
+
+#[test]
+fn test_verify_vectors() {
+    for vector in VECTORS {
+        let Some(output) = vector.output else {
+            continue;
+        };
+        assert!(drillx::is_valid_digest(
+            &vector.challenge,
+            &vector.nonce,
+            &output.digest
+        ));
+        let solution = Solution::new(output.digest, vector.nonce);
+        assert!(solution.is_valid(&vector.challenge));
+        assert_eq!(solution.to_hash().h, output.hash);
+        assert_eq!(
+            solution.to_hash().difficulty(),
+            drillx::difficulty(output.hash)
+        );
+
+        let mut forged = output.digest;
+        forged[0] ^= 1;
+        assert!(!Solution::new(forged, vector.nonce).is_valid(&vector.challenge));
+    }
+}
+
+#[test]
+fn test_require_compile() {
+    let vector = &VECTORS[0];
+    assert!(matches!(
+        drillx::hash_with_runtime(
+            &mut DrillxMemory::new(),
+            RuntimeOption::RequireCompile,
+            &vector.challenge,
+            &vector.nonce
+        ),
+        Err(drillx::DrillxError::CompilerUnsupported)
+    ));
+}
@@ -0,0 +1,87 @@
+//! Runs under `wasm-bindgen-test-runner`, in Node by default:
+//!
+//! ```sh
+//! cargo test -p drillx --target wasm32-unknown-unknown --no-default-features --features web --test web
+//! ```
+//!
+//! Node is not cross-origin isolated, so these cover the main-thread fallback.
+#![cfg(all(feature = "web", target_arch = "wasm32"))]
+
+use std::{cell::Cell, rc::Rc};
+
+use drillx::{web::WebMiner, Solution};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn get(object: &JsValue, key: &str) -> JsValue {
+    js_sys::Reflect::get(object, &JsValue::from_str(key)).unwrap()
+}
+
+fn bytes<const N: usize>(value: JsValue) -> [u8; N] {
+    js_sys::Uint8Array::new(&value).to_vec().try_into().unwrap()
+}
+
+#[wasm_bindgen_test]
+async fn test_web_miner_falls_back_to_main_thread() {
+    assert!(!WebMiner::supports_threads());
+    let challenge = [255; 32];
+    let miner = WebMiner::new(&challenge, 1, 4, "worker.js".to_string())
+        .ok()
+        .unwrap();
+    let calls = Rc::new(Cell::new(0));
+    let counter = calls.clone();
+    let on_progress = Closure::<dyn FnMut(JsValue)>::new(move |progress: JsValue| {
+        assert_eq!(get(&progress, "workers"), JsValue::from(0));
+        counter.set(counter.get() + 1);
+    });
+    let best = JsFuture::from(
+        miner.run(
+            on_progress
+                .as_ref()
+                .unchecked_ref::<js_sys::Function>()
+                .clone(),
+        ),
+    )
+    .await
+    .unwrap();
+
+    let solution = Solution::new(bytes(get(&best, "digest")), bytes(get(&best, "nonce")));
+    assert!(solution.is_valid(&challenge));
+    let difficulty = get(&best, "difficulty").as_f64().unwrap() as u32;
+    assert!(difficulty >= 1);
+    assert_eq!(solution.to_hash().difficulty(), difficulty);
+    assert_eq!(get(&best, "hashes").as_f64(), Some(miner.hashes()));
+    assert!(calls.get() as f64 >= miner.hashes());
+}
+
+#[wasm_bindgen_test]
+async fn test_web_miner_cancels() {
+    let miner = Rc::new(
+        WebMiner::new(&[7; 32], 64, 2, "worker.js".to_string())
+            .ok()
+            .unwrap(),
+    );
+    let canceller = miner.clone();
+    let on_progress = Closure::<dyn FnMut(JsValue)>::new(move |progress: JsValue| {
+        if get(&progress, "hashes").as_f64().unwrap() >= 3.0 {
+            canceller.cancel();
+        }
+    });
+    JsFuture::from(
+        miner.run(
+            on_progress
+                .as_ref()
+                .unchecked_ref::<js_sys::Function>()
+                .clone(),
+        ),
+    )
+    .await
+    .unwrap();
+    assert_eq!(miner.hashes(), 3.0);
+}
+
+#[wasm_bindgen_test]
+fn test_web_miner_rejects_short_challenge() {
+    assert!(WebMiner::new(&[0; 31], 1, 1, String::new()).is_err());
+}
@@ -0,0 +1,44 @@
+use drillx::{apply_weight, share_weight, sum_weights};
+
+#[test]
+fn test_share_weight() {
+    assert_eq!(share_weight(7, 8), 0);
+    assert_eq!(share_weight(8, 8), 1);
+    assert_eq!(share_weight(9, 8), 2);
+    assert_eq!(share_weight(8 + 127, 8), 1 << 127);
+    assert_eq!(share_weight(8 + 128, 8), u128::MAX);
+    assert_eq!(share_weight(u32::MAX, 0), u128::MAX);
+    assert_eq!(share_weight(0, u32::MAX), 0);
+}
+
+#[test]
+fn test_apply_weight() {
+    assert_eq!(apply_weight(100, 7, 8), 0);
+    assert_eq!(apply_weight(100, 8, 8), 100);
+    assert_eq!(apply_weight(100, 10, 8), 400);
+    assert_eq!(apply_weight(0, 200, 8), 0);
+    assert_eq!(apply_weight(1, 8 + 63, 8), 1 << 63);
+    assert_eq!(apply_weight(2, 8 + 63, 8), u64::MAX);
+    assert_eq!(apply_weight(1, 8 + 64, 8), u64::MAX);
+    assert_eq!(apply_weight(u64::MAX, 8, 8), u64::MAX);
+    assert_eq!(apply_weight(1, 8 + 128, 8), u64::MAX);
+}
+
+#[test]
+fn test_sum_weights() {
+    assert_eq!(sum_weights(&[], 8), 0);
+    assert_eq!(sum_weights(&[8, 8], 8), 2);
+    assert_eq!(sum_weights(&[7, 8, 9, 10], 8), 7);
+    assert_eq!(sum_weights(&[8 + 127, 8 + 127], 8), u128::MAX);
+    assert_eq!(sum_weights(&[8 + 128, 8], 8), u128::MAX);
+}
+
+#[test]
+fn test_const_evaluation() {
+    const WEIGHT: u128 = share_weight(12, 10);
+    const REWARD: u64 = apply_weight(5, 12, 10);
+    const TOTAL: u128 = sum_weights(&[10, 11, 12], 10);
+    assert_eq!(WEIGHT, 4);
+    assert_eq!(REWARD, 20);
+    assert_eq!(TOTAL, 7);
+}
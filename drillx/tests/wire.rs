@@ -0,0 +1,101 @@
+use drillx::{
+    wire::{DecodeError, VersionedSolution, MAX_VERSIONED_LEN, VERSION_1, VERSION_2},
+    Solution,
+};
+
+const V1_HEX: &str = "01000102030405060708090a0b0c0d0e0f1011121314151617";
+const V2_HEX: &str = "02000102030405060708090a0b0c0d0e0f101112131415161718";
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+fn sample() -> Solution {
+    let mut bytes = [0; 24];
+    for (i, b) in bytes.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    Solution::from_bytes(bytes)
+}
+
+#[test]
+fn test_v1_vector() {
+    let solution = sample();
+    assert_eq!(solution.to_bytes_versioned(), hex(V1_HEX));
+    assert_eq!(
+        Solution::from_bytes_versioned(&hex(V1_HEX)),
+        Ok(VersionedSolution::V1(solution))
+    );
+}
+
+#[test]
+fn test_v1_round_trip() {
+    let solution = Solution::new([0xab; 16], 42u64.to_le_bytes());
+    let bytes = solution.to_bytes_versioned();
+    assert_eq!(bytes.len(), 25);
+    assert_eq!(bytes[0], VERSION_1);
+    assert_eq!(&bytes[1..], &solution.to_bytes());
+    let decoded = Solution::from_bytes_versioned(&bytes).unwrap();
+    assert_eq!(decoded.version(), VERSION_1);
+    assert_eq!(decoded, VersionedSolution::from(solution));
+    assert_eq!(decoded.to_bytes(), bytes);
+}
+
+#[test]
+fn test_decode_rejects() {
+    let bytes = hex(V1_HEX);
+    assert_eq!(Solution::from_bytes_versioned(&[]), Err(DecodeError::Empty));
+    assert_eq!(
+        Solution::from_bytes_versioned(&[0]),
+        Err(DecodeError::UnknownVersion(0))
+    );
+    let mut unknown = bytes.clone();
+    unknown[0] = 0xff;
+    assert_eq!(
+        Solution::from_bytes_versioned(&unknown),
+        Err(DecodeError::UnknownVersion(0xff))
+    );
+    assert_eq!(
+        Solution::from_bytes_versioned(&bytes[..24]),
+        Err(DecodeError::Truncated {
+            version: VERSION_1,
+            expected: 24,
+            actual: 23
+        })
+    );
+    let mut long = bytes.clone();
+    long.push(0);
+    assert_eq!(
+        Solution::from_bytes_versioned(&long),
+        Err(DecodeError::TrailingBytes {
+            version: VERSION_1,
+            expected: 24,
+            actual: 25
+        })
+    );
+    let huge = vec![VERSION_1; MAX_VERSIONED_LEN + 1];
+    assert_eq!(
+        Solution::from_bytes_versioned(&huge),
+        Err(DecodeError::TooLong {
+            len: MAX_VERSIONED_LEN + 1
+        })
+    );
+}
+
+#[test]
+fn test_v2_vector() {
+    let v2 = drillx::SolutionV2::from_bytes(hex(V2_HEX)[1..].try_into().unwrap());
+    assert_eq!(v2.idx, 0x18);
+    assert_eq!(v2.to_bytes_versioned(), hex(V2_HEX));
+    assert_eq!(
+        Solution::from_bytes_versioned(&hex(V2_HEX)[..25]),
+        Err(DecodeError::Truncated {
+            version: VERSION_2,
+            expected: 25,
+            actual: 24
+        })
+    );
+}
@@ -0,0 +1,210 @@
+#![cfg(feature = "signing")]
+
+use drillx::{
+    miner::{MinerBuilder, MinerError, StopReason},
+    work::{
+        sign_work, verify_work, verify_work_at, Keypair, SignedWorkUnit, WorkAuthError, WorkUnit,
+        SIGNING_MESSAGE_LEN, WORK_TAG,
+    },
+};
+use ed25519_dalek::{PublicKey, SecretKey};
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// The pool's keypair, from a fixed secret key.
+fn pool() -> Keypair {
+    keypair(9)
+}
+
+fn keypair(seed: u8) -> Keypair {
+    let secret = SecretKey::from_bytes(&[seed; 32]).unwrap();
+    let public = PublicKey::from(&secret);
+    Keypair { secret, public }
+}
+
+fn unit() -> WorkUnit {
+    WorkUnit {
+        id: 7,
+        challenge: [1; 32],
+        authority: [2; 32],
+        start: 1 << 40,
+        count: 1 << 20,
+        min_difficulty: 12,
+        expires_unix: 1_700_000_000,
+    }
+}
+
+#[test]
+fn test_signing_message_layout() {
+    let message = unit().signing_message();
+    assert_eq!(message.len(), SIGNING_MESSAGE_LEN);
+    assert_eq!(&message[..15], WORK_TAG);
+    assert_eq!(&message[15..23], &7u64.to_le_bytes());
+    assert_eq!(&message[23..55], &[1; 32]);
+    assert_eq!(&message[55..87], &[2; 32]);
+    assert_eq!(&message[87..95], &(1u64 << 40).to_le_bytes());
+    assert_eq!(&message[95..103], &(1u64 << 20).to_le_bytes());
+    assert_eq!(&message[103..107], &12u32.to_le_bytes());
+    assert_eq!(&message[107..], &1_700_000_000u64.to_le_bytes());
+}
+
+#[test]
+fn test_sign_work_vector() {
+    let signed = sign_work(&unit(), &pool());
+    assert_eq!(
+        signed.pool_pubkey.to_vec(),
+        hex("fd1724385aa0c75b64fb78cd602fa1d991fdebf76b13c58ed702eac835e9f618")
+    );
+    assert_eq!(
+        signed.signature.to_vec(),
+        hex(concat!(
+            "0ebb2023385b0f91a35715f8461d4301729ac000b978be8cce926cfe3df086c2",
+            "f35a8a026e1493a8ff7170ed684c384a57931a97cfc1116e4b8e9da5c7cf3906"
+        ))
+    );
+    assert_eq!(verify_work(&signed, &signed.pool_pubkey), Ok(()));
+}
+
+#[test]
+fn test_verify_work_rejects_tampered_fields() {
+    let signed = sign_work(&unit(), &pool());
+    let key = signed.pool_pubkey;
+    let tampered: [fn(&mut WorkUnit); 7] = [
+        |w| w.id += 1,
+        |w| w.challenge[31] ^= 1,
+        |w| w.authority[0] ^= 1,
+        |w| w.start -= 1,
+        |w| w.count += 1,
+        |w| w.min_difficulty -= 1,
+        |w| w.expires_unix += 1,
+    ];
+    for tamper in tampered {
+        let mut forged = signed;
+        tamper(&mut forged.work);
+        assert_eq!(verify_work(&forged, &key), Err(WorkAuthError::BadSignature));
+    }
+    let mut forged = signed;
+    forged.signature[0] ^= 1;
+    assert_eq!(verify_work(&forged, &key), Err(WorkAuthError::BadSignature));
+}
+
+#[test]
+fn test_verify_work_rejects_wrong_key() {
+    let signed = sign_work(&unit(), &pool());
+    let other = keypair(10).public.to_bytes();
+    assert_eq!(verify_work(&signed, &other), Err(WorkAuthError::WrongPool));
+
+    // Signed by another key but claiming to be the pool's.
+    let mut forged = sign_work(&unit(), &keypair(10));
+    forged.pool_pubkey = signed.pool_pubkey;
+    assert_eq!(
+        verify_work(&forged, &signed.pool_pubkey),
+        Err(WorkAuthError::BadSignature)
+    );
+}
+
+#[test]
+fn test_verify_work_at_expiry() {
+    let signed = sign_work(&unit(), &pool());
+    let key = signed.pool_pubkey;
+    let expires = unit().expires_unix;
+    assert_eq!(verify_work_at(&signed, &key, expires - 1), Ok(()));
+    assert_eq!(verify_work_at(&signed, &key, expires), Ok(()));
+    let err = verify_work_at(&signed, &key, expires + 30).unwrap_err();
+    assert_eq!(
+        err,
+        WorkAuthError::Expired {
+            expires_unix: expires,
+            now_unix: expires + 30
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "Work unit expired at 1700000000, 30 seconds ago"
+    );
+}
+
+#[test]
+fn test_verify_work_rejects_empty() {
+    let empty = WorkUnit { count: 0, ..unit() };
+    let signed = sign_work(&empty, &pool());
+    assert_eq!(
+        verify_work(&signed, &signed.pool_pubkey),
+        Err(WorkAuthError::Empty)
+    );
+}
+
+#[test]
+fn test_signed_work_serde_round_trip() {
+    let signed = sign_work(&unit(), &pool());
+    let json = serde_json::to_string(&signed).unwrap();
+    assert_eq!(
+        serde_json::from_str::<SignedWorkUnit>(&json).unwrap(),
+        signed
+    );
+}
+
+/// Work that expires long after the tests run.
+fn live_work(keypair: &Keypair) -> SignedWorkUnit {
+    let work = WorkUnit {
+        challenge: [3; 32],
+        start: 500,
+        count: 12,
+        min_difficulty: 64,
+        expires_unix: u64::MAX,
+        ..unit()
+    };
+    sign_work(&work, keypair)
+}
+
+#[test]
+fn test_mine_signed_work() {
+    let signed = live_work(&pool());
+    let outcome = MinerBuilder::new([0; 32])
+        .work(signed)
+        .threads(2)
+        .chunk_size(4)
+        .expected_pool_pubkey(signed.pool_pubkey)
+        .spawn()
+        .unwrap()
+        .join()
+        .unwrap();
+    assert_eq!(outcome.reason, StopReason::Exhausted);
+    assert_eq!(outcome.hashes, 12);
+    assert_eq!(outcome.jobs[0].challenge, [3; 32]);
+}
+
+#[test]
+fn test_miner_refuses_unverifiable_work() {
+    let key = pool().public.to_bytes();
+    let start = |builder: MinerBuilder| match builder.threads(1).spawn() {
+        Err(MinerError::WorkAuth(err)) => err,
+        Ok(_) => panic!("miner started"),
+        Err(err) => panic!("{}", err),
+    };
+
+    let unsigned = MinerBuilder::new([3; 32]).expected_pool_pubkey(key);
+    assert_eq!(start(unsigned), WorkAuthError::Unsigned);
+
+    let foreign = MinerBuilder::new([0; 32])
+        .work(live_work(&keypair(10)))
+        .expected_pool_pubkey(key);
+    assert_eq!(start(foreign), WorkAuthError::WrongPool);
+
+    let mut tampered = live_work(&pool());
+    tampered.work.authority = [6; 32];
+    let tampered = MinerBuilder::new([0; 32])
+        .work(tampered)
+        .expected_pool_pubkey(key);
+    assert_eq!(start(tampered), WorkAuthError::BadSignature);
+
+    let expired = MinerBuilder::new([0; 32])
+        .work(sign_work(&unit(), &pool()))
+        .expected_pool_pubkey(key);
+    assert!(matches!(start(expired), WorkAuthError::Expired { .. }));
+}
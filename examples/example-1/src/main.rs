@@ -1,6 +1,6 @@
 use std::time::Instant;
 
-use drillx::{equix::SolverMemory, Solution};
+use drillx::{DrillxMemory, Solution};
 
 const TARGET_DIFFICULTY: u32 = 8; // 12; // 8; //10;
 
@@ -25,7 +25,7 @@ fn main() {
 
 // Parallelize
 fn do_work(challenge: [u8; 32]) -> (drillx::Hash, u64) {
-    let mut memory = SolverMemory::new();
+    let mut memory = DrillxMemory::new();
     let mut nonce: u64 = 0;
     loop {
         // Calculate hash
@@ -1,11 +1,11 @@
 use std::{collections::HashMap, time::Instant};
 
-use drillx::equix::SolverMemory;
+use drillx::DrillxMemory;
 
 fn main() {
     let timer = Instant::now();
     let challenge = [255; 32];
-    let mut memory = SolverMemory::new();
+    let mut memory = DrillxMemory::new();
     let mut hash_count = HashMap::<u32, u64>::new();
     let mut nonce: u64 = 0;
     loop {
@@ -13,7 +13,7 @@ fn main() {
         if let Ok(hx) = drillx::hash_with_memory(&mut memory, &challenge, &nonce.to_le_bytes()) {
             let diff = hx.difficulty();
             hash_count.insert(diff, hash_count.get(&diff).unwrap_or(&0).saturating_add(1));
-            if nonce % 100 == 0 {
+            if nonce.is_multiple_of(100) {
                 print(&hash_count, &timer);
             }
         }
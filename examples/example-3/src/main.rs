@@ -4,7 +4,7 @@ const TEST_SIZE: u64 = 1000;
 
 fn main() {
     println!("Benchmarking...");
-    let mut memory = drillx::equix::SolverMemory::new();
+    let mut memory = drillx::DrillxMemory::new();
     let challenge = [255; 32];
     let timer = Instant::now();
     for nonce in 0..TEST_SIZE {
@@ -0,0 +1,66 @@
+//! A verifier program built on pinocchio, without `solana-program`.
+//!
+//! Drillx hashes through the `sol_keccak256` syscall when built for `target_os =
+//! "solana"`, so a verify-only build needs nothing from the Solana SDK:
+//!
+//! ```text
+//! cargo build-sbf --manifest-path examples/pinocchio-verify/Cargo.toml
+//! ```
+//!
+//! The instruction data is the minimum difficulty as a little-endian `u64`, then the
+//! digest and the nonce, as in `drillx-program`.
+
+use drillx::Solution;
+use pinocchio::{error::ProgramError, AccountView, Address, ProgramResult};
+
+/// Length of the instruction data.
+pub const DATA_LEN: usize = 32;
+
+/// The challenge every solution must solve.
+pub const CHALLENGE: [u8; 32] = [255; 32];
+
+#[cfg(not(feature = "no-entrypoint"))]
+pinocchio::entrypoint!(process_instruction);
+
+pub fn process_instruction(
+    _program_id: &Address,
+    accounts: &mut [AccountView],
+    data: &[u8],
+) -> ProgramResult {
+    let [_signer] = accounts else {
+        return Err(ProgramError::NotEnoughAccountKeys);
+    };
+    let (difficulty, solution) = parse(data)?;
+
+    // Prove that the solution is valid.
+    if !solution.is_valid(&CHALLENGE) {
+        return Err(ProgramError::Custom(0));
+    }
+
+    if (solution.to_hash().difficulty() as u64) < difficulty {
+        return Err(ProgramError::Custom(1));
+    }
+
+    Ok(())
+}
+
+/// Encodes the instruction data.
+pub fn data(difficulty: u64, digest: [u8; 16], nonce: [u8; 8]) -> [u8; DATA_LEN] {
+    let mut data = [0; DATA_LEN];
+    data[..8].copy_from_slice(&difficulty.to_le_bytes());
+    data[8..24].copy_from_slice(&digest);
+    data[24..].copy_from_slice(&nonce);
+    data
+}
+
+fn parse(data: &[u8]) -> Result<(u64, Solution), ProgramError> {
+    let data: &[u8; DATA_LEN] = data
+        .try_into()
+        .map_err(|_| ProgramError::InvalidInstructionData)?;
+    let difficulty = u64::from_le_bytes(data[..8].try_into().unwrap());
+    let solution = Solution::new(
+        data[8..24].try_into().unwrap(),
+        data[24..].try_into().unwrap(),
+    );
+    Ok((difficulty, solution))
+}
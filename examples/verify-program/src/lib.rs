@@ -0,0 +1,12 @@
+//! The reference verification program in [`drillx::program`], as a deployable program.
+//!
+//! Its entrypoint comes from drillx's `program-entrypoint` feature, so this crate only
+//! gives the program a shared object to build:
+//!
+//! ```text
+//! cargo build-sbf --manifest-path examples/verify-program/Cargo.toml --sbf-out-dir program/tests/fixtures
+//! ```
+//!
+//! `drillx-program`'s tests load the build from there to measure its compute units.
+
+pub use drillx::program::*;
@@ -0,0 +1,115 @@
+//! A verification harness for WASI runtimes.
+//!
+//! ```text
+//! cargo build -p wasi-verify --release --target wasm32-wasip1
+//! wasmtime run target/wasm32-wasip1/release/wasi-verify.wasm vectors
+//! wasmtime run target/wasm32-wasip1/release/wasi-verify.wasm solve <challenge> 4 \
+//!     | wasmtime run target/wasm32-wasip1/release/wasi-verify.wasm verify <challenge>
+//! ```
+//!
+//! `verify` reads one `<digest hex> <nonce>` pair per line on stdin and prints
+//! `valid <difficulty>` or `invalid` for each.
+
+use std::{
+    io::{self, BufRead},
+    process::ExitCode,
+};
+
+use drillx::{vectors::VECTORS, DrillxMemory, RuntimeOption, Solution};
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let result = match args[..] {
+        ["vectors"] => vectors(),
+        ["verify", challenge] => parse(challenge).and_then(verify),
+        ["solve", challenge, difficulty] => parse(challenge).and_then(|challenge| {
+            let difficulty = difficulty.parse().map_err(|_| "bad difficulty")?;
+            solve(challenge, difficulty)
+        }),
+        _ => {
+            Err("usage: wasi-verify vectors | verify <challenge> | solve <challenge> <difficulty>")
+        }
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("{}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Checks the embedded vectors with the interpreter and the verifier.
+fn vectors() -> Result<(), &'static str> {
+    let mut memory = DrillxMemory::new();
+    for (i, vector) in VECTORS.iter().enumerate() {
+        if !vector.check(&mut memory, RuntimeOption::InterpretOnly) {
+            println!("vector {} failed", i);
+            return Err("vectors failed");
+        }
+        if let Some(output) = vector.output {
+            let solution = Solution::new(output.digest, vector.nonce);
+            if !solution.is_valid(&vector.challenge) || solution.to_hash().h != output.hash {
+                println!("vector {} failed verification", i);
+                return Err("vectors failed");
+            }
+        }
+    }
+    println!("{} vectors ok", VECTORS.len());
+    Ok(())
+}
+
+fn verify(challenge: [u8; 32]) -> Result<(), &'static str> {
+    for line in io::stdin().lock().lines() {
+        let line = line.map_err(|_| "cannot read stdin")?;
+        let Some((digest, nonce)) = line.trim().split_once(' ') else {
+            continue;
+        };
+        let digest = parse(digest)?;
+        let nonce: u64 = nonce.trim().parse().map_err(|_| "bad nonce")?;
+        let solution = Solution::new(digest, nonce.to_le_bytes());
+        if solution.is_valid(&challenge) {
+            println!("valid {}", solution.to_hash().difficulty());
+        } else {
+            println!("invalid");
+        }
+    }
+    Ok(())
+}
+
+/// Searches nonces from zero with the interpreter and prints the first solution.
+fn solve(challenge: [u8; 32], difficulty: u32) -> Result<(), &'static str> {
+    let mut memory = DrillxMemory::new();
+    for nonce in 0u64.. {
+        let hash = drillx::hash_with_runtime(
+            &mut memory,
+            RuntimeOption::InterpretOnly,
+            &challenge,
+            &nonce.to_le_bytes(),
+        );
+        if let Ok(hash) = hash {
+            if hash.difficulty() >= difficulty {
+                println!("{} {}", hex(&hash.d), nonce);
+                return Ok(());
+            }
+        }
+    }
+    Err("no solution")
+}
+
+fn parse<const N: usize>(hex: &str) -> Result<[u8; N], &'static str> {
+    if hex.len() != N * 2 || !hex.is_ascii() {
+        return Err("bad hex length");
+    }
+    let mut bytes = [0; N];
+    for (byte, pair) in bytes.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        let pair = std::str::from_utf8(pair).map_err(|_| "bad hex")?;
+        *byte = u8::from_str_radix(pair, 16).map_err(|_| "bad hex")?;
+    }
+    Ok(bytes)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
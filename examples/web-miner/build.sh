@@ -0,0 +1,10 @@
+#!/bin/sh
+# Builds the page's module with threads into www/pkg.
+set -e
+cd "$(dirname "$0")/../.."
+export RUSTFLAGS="-C target-feature=+atomics,+bulk-memory,+mutable-globals \
+-C link-arg=--shared-memory -C link-arg=--import-memory -C link-arg=--max-memory=1073741824 \
+-C link-arg=--export=__wasm_init_tls -C link-arg=--export=__tls_size \
+-C link-arg=--export=__tls_align -C link-arg=--export=__tls_base -C link-arg=--export=__heap_base"
+cargo +nightly build -p web-miner --release --target wasm32-unknown-unknown -Z build-std=std,panic_abort
+wasm-bindgen --target web --out-dir examples/web-miner/www/pkg target/wasm32-unknown-unknown/release/web_miner.wasm
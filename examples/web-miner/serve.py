@@ -0,0 +1,25 @@
+"""Serves www with the headers that allow SharedArrayBuffer."""
+
+import functools
+import http.server
+import os
+
+ROOT = os.path.join(os.path.dirname(os.path.abspath(__file__)), "www")
+
+
+class Handler(http.server.SimpleHTTPRequestHandler):
+    extensions_map = {
+        **http.server.SimpleHTTPRequestHandler.extensions_map,
+        ".js": "text/javascript",
+        ".wasm": "application/wasm",
+    }
+
+    def end_headers(self):
+        self.send_header("Cross-Origin-Opener-Policy", "same-origin")
+        self.send_header("Cross-Origin-Embedder-Policy", "require-corp")
+        super().end_headers()
+
+
+if __name__ == "__main__":
+    handler = functools.partial(Handler, directory=ROOT)
+    http.server.ThreadingHTTPServer(("localhost", 8000), handler).serve_forever()
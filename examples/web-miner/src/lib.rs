@@ -0,0 +1,14 @@
+//! A browser page mining on Web Workers.
+//!
+//! ```text
+//! examples/web-miner/build.sh
+//! python3 examples/web-miner/serve.py
+//! ```
+//!
+//! `build.sh` builds the module with threads, which takes nightly, into `www/pkg`.
+//! `serve.py` serves `www` on http://localhost:8000 with the headers that make the page
+//! cross-origin isolated. Built without the target features, or served without the
+//! headers, the page still mines, on the main thread only.
+
+#[cfg(target_arch = "wasm32")]
+pub use drillx::web::{drillx_worker_entry, WebMiner};
@@ -0,0 +1,31 @@
+import init, { WebMiner } from "./pkg/web_miner.js";
+
+const $ = (id) => document.getElementById(id);
+const hex = (bytes) => Array.from(bytes, (b) => b.toString(16).padStart(2, "0")).join("");
+
+await init();
+
+$("start").onclick = async () => {
+  const challenge = crypto.getRandomValues(new Uint8Array(32));
+  const difficulty = Number($("difficulty").value);
+  const threads = navigator.hardwareConcurrency || 4;
+  const miner = new WebMiner(challenge, difficulty, threads, new URL("worker.js", import.meta.url).href);
+  const mode = WebMiner.supportsThreads() ? `${threads} workers` : "main thread only";
+  const started = performance.now();
+
+  $("start").disabled = true;
+  $("cancel").disabled = false;
+  $("cancel").onclick = () => miner.cancel();
+  const best = await miner.run((progress) => {
+    const rate = progress.hashes / ((performance.now() - started) / 1000);
+    $("status").textContent =
+      `${mode}\n${progress.hashes} hashes, ${rate.toFixed(1)} H/s\n` +
+      `best difficulty ${progress.bestDifficulty ?? "-"}`;
+  });
+  $("status").textContent += best
+    ? `\n\nchallenge ${hex(challenge)}\ndigest ${hex(best.digest)}\nnonce ${hex(best.nonce)}\ndifficulty ${best.difficulty}`
+    : "\n\nno solution";
+  miner.free();
+  $("start").disabled = false;
+  $("cancel").disabled = true;
+};
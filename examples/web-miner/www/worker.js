@@ -0,0 +1,9 @@
+import init, { drillx_worker_entry } from "./pkg/web_miner.js";
+
+self.onmessage = async ({ data: [module, memory, task] }) => {
+  const wasm = await init(module, memory);
+  drillx_worker_entry(task);
+  // Frees this thread's stack and thread locals in the shared memory.
+  wasm.__wbindgen_thread_destroy?.();
+  close();
+};
@@ -0,0 +1,47 @@
+use drillx::{error_code::decode_error, DrillxError};
+use solana_program::{
+    account_info::AccountInfo,
+    entrypoint::ProgramResult,
+    instruction::{Instruction, InstructionError},
+    pubkey::Pubkey,
+};
+use solana_program_test::{processor, ProgramTest};
+use solana_sdk::{
+    signature::Signer,
+    transaction::{Transaction, TransactionError},
+};
+
+const ERRORS: [DrillxError; 4] = [
+    DrillxError::BadEquix,
+    DrillxError::NoSolutions,
+    DrillxError::CompileFailed,
+    DrillxError::CompilerUnsupported,
+];
+
+/// Fails with the drillx error indexed by the instruction's only byte.
+fn process_instruction(_: &Pubkey, _: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    Err(ERRORS[data[0] as usize].into())
+}
+
+#[tokio::test]
+async fn test_client_sees_drillx_error_codes() {
+    let program_id = Pubkey::new_unique();
+    let program_test =
+        ProgramTest::new("drillx_errors", program_id, processor!(process_instruction));
+    let (mut banks, payer, blockhash) = program_test.start().await;
+    for (index, err) in ERRORS.into_iter().enumerate() {
+        let ix = Instruction {
+            program_id,
+            accounts: vec![],
+            data: vec![index as u8],
+        };
+        let tx =
+            Transaction::new_signed_with_payer(&[ix], Some(&payer.pubkey()), &[&payer], blockhash);
+        let result = banks.process_transaction(tx).await.unwrap_err().unwrap();
+        let TransactionError::InstructionError(0, InstructionError::Custom(code)) = result else {
+            panic!("unexpected error {:?}", result);
+        };
+        assert_eq!(code, 0x4452_0000 + index as u32);
+        assert_eq!(decode_error(code), Some(err));
+    }
+}
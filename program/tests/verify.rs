@@ -0,0 +1,226 @@
+use drillx::{
+    program::{
+        decode_verify_error, ChallengeAccount, VerifyError, VerifyResult, RESULT_ACCOUNT_LEN,
+    },
+    Solution,
+};
+use solana_program::{hash::Hash, instruction::InstructionError, pubkey::Pubkey};
+use solana_program_test::{processor, BanksClient, ProgramTest};
+use solana_sdk::{
+    account::Account,
+    compute_budget::ComputeBudgetInstruction,
+    signature::{Keypair, Signer},
+    transaction::{Transaction, TransactionError},
+};
+
+/// The transaction's compute unit limit, the most a transaction may request.
+const CU_LIMIT: u32 = 1_400_000;
+
+/// The most compute units a successful verify may consume on-chain.
+const CU_BUDGET: u64 = 500_000;
+
+#[tokio::test]
+async fn test_verify_success() {
+    let (challenge, solution, difficulty) = solve([255; 32]);
+    let mut env = setup(challenge, difficulty).await;
+    let tx = env.build_tx(solution.to_bytes().to_vec());
+    env.banks.process_transaction(tx).await.unwrap();
+
+    // Result account holds the derived hash and difficulty
+    let account = env.banks.get_account(env.result).await.unwrap().unwrap();
+    let result = VerifyResult::try_from_bytes(&account.data).unwrap();
+    assert_eq!(result.hash, solution.to_hash().h);
+    assert_eq!(result.difficulty, difficulty);
+}
+
+/// Runs the SBF build of the program, which `processor!` tests cannot measure.
+#[tokio::test]
+#[ignore = "needs the SBF build: cargo build-sbf --manifest-path examples/verify-program/Cargo.toml --sbf-out-dir program/tests/fixtures"]
+async fn test_verify_compute_units() {
+    let (challenge, solution, difficulty) = solve([255; 32]);
+    let program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(true);
+    program_test.add_program("drillx_verify", program_id, None);
+    let mut env = start(program_test, program_id, challenge, difficulty).await;
+    let tx = env.build_tx(solution.to_bytes().to_vec());
+    let res = env
+        .banks
+        .process_transaction_with_metadata(tx)
+        .await
+        .unwrap();
+    assert!(res.result.is_ok(), "{:?}", res.result);
+    let consumed = res.metadata.unwrap().compute_units_consumed;
+    assert!(
+        consumed <= CU_BUDGET,
+        "verify consumed {} compute units",
+        consumed
+    );
+}
+
+#[tokio::test]
+async fn test_verify_wrong_challenge() {
+    let (_, solution, difficulty) = solve([255; 32]);
+    let mut env = setup([254; 32], difficulty).await;
+    let tx = env.build_tx(solution.to_bytes().to_vec());
+    let err = env
+        .banks
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom_error(VerifyError::InvalidSolution));
+}
+
+#[tokio::test]
+async fn test_verify_low_difficulty() {
+    let (challenge, solution, difficulty) = solve([255; 32]);
+    let mut env = setup(challenge, difficulty + 1).await;
+    let tx = env.build_tx(solution.to_bytes().to_vec());
+    let err = env
+        .banks
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(err, custom_error(VerifyError::InsufficientDifficulty));
+}
+
+#[tokio::test]
+async fn test_verify_malformed_data() {
+    let (challenge, solution, difficulty) = solve([255; 32]);
+    let mut env = setup(challenge, difficulty).await;
+    let tx = env.build_tx(solution.to_bytes()[..23].to_vec());
+    let err = env
+        .banks
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(1, InstructionError::InvalidInstructionData)
+    );
+}
+
+#[tokio::test]
+async fn test_verify_aliased_accounts() {
+    let (challenge, solution, difficulty) = solve([255; 32]);
+    let mut env = setup(challenge, difficulty).await;
+    env.result = env.challenge;
+    let tx = env.build_tx(solution.to_bytes().to_vec());
+    let err = env
+        .banks
+        .process_transaction(tx)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        err,
+        TransactionError::InstructionError(1, InstructionError::InvalidArgument)
+    );
+
+    // The challenge account is unchanged.
+    let account = env.banks.get_account(env.challenge).await.unwrap().unwrap();
+    let stored = ChallengeAccount::try_from_bytes(&account.data).unwrap();
+    assert_eq!(stored.challenge, challenge);
+    assert_eq!(stored.min_difficulty, difficulty);
+}
+
+fn solve(challenge: [u8; 32]) -> ([u8; 32], Solution, u64) {
+    let nonce = 0u64.to_le_bytes();
+    let hash = drillx::hash(&challenge, &nonce).unwrap();
+    let difficulty = hash.difficulty() as u64;
+    (challenge, Solution::new(hash.d, nonce), difficulty)
+}
+
+fn custom_error(e: VerifyError) -> TransactionError {
+    let code = match e {
+        VerifyError::InvalidSolution => 0x4452_0004,
+        VerifyError::InsufficientDifficulty => 0x4452_0005,
+    };
+    assert_eq!(decode_verify_error(code), Some(e));
+    TransactionError::InstructionError(1, InstructionError::Custom(code))
+}
+
+struct TestEnv {
+    banks: BanksClient,
+    payer: Keypair,
+    blockhash: Hash,
+    program_id: Pubkey,
+    challenge: Pubkey,
+    result: Pubkey,
+}
+
+impl TestEnv {
+    fn build_tx(&self, data: Vec<u8>) -> Transaction {
+        let cu_budget_ix = ComputeBudgetInstruction::set_compute_unit_limit(CU_LIMIT);
+        let mut ix = drillx::program::verify(
+            self.program_id,
+            self.payer.pubkey(),
+            self.challenge,
+            self.result,
+            Solution::from_bytes([0; 24]),
+        );
+        ix.data = data;
+        Transaction::new_signed_with_payer(
+            &[cu_budget_ix, ix],
+            Some(&self.payer.pubkey()),
+            &[&self.payer],
+            self.blockhash,
+        )
+    }
+}
+
+async fn setup(challenge: [u8; 32], min_difficulty: u64) -> TestEnv {
+    let program_id = Pubkey::new_unique();
+    let program_test = ProgramTest::new(
+        "drillx_verify",
+        program_id,
+        processor!(drillx::program::process_instruction),
+    );
+    start(program_test, program_id, challenge, min_difficulty).await
+}
+
+async fn start(
+    mut program_test: ProgramTest,
+    program_id: Pubkey,
+    challenge: [u8; 32],
+    min_difficulty: u64,
+) -> TestEnv {
+    let challenge_address = Pubkey::new_unique();
+    let result_address = Pubkey::new_unique();
+    let challenge_account = ChallengeAccount {
+        challenge,
+        min_difficulty,
+    };
+    program_test.add_account(
+        challenge_address,
+        Account {
+            lamports: 1_000_000_000,
+            data: challenge_account.to_bytes().to_vec(),
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    program_test.add_account(
+        result_address,
+        Account {
+            lamports: 1_000_000_000,
+            data: vec![0; RESULT_ACCOUNT_LEN],
+            owner: program_id,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    let (banks, payer, blockhash) = program_test.start().await;
+    TestEnv {
+        banks,
+        payer,
+        blockhash,
+        program_id,
+        challenge: challenge_address,
+        result: result_address,
+    }
+}